@@ -0,0 +1,37 @@
+#![no_std]
+
+use read_primitives::embedded::{ReadFrom, ReadU16};
+use read_primitives::{Endian, SliceReader};
+
+#[derive(read_primitives::ReadFrom)]
+#[read(no_std, embedded, little)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub len: u16,
+}
+
+/// Reads through the allocation-free APIs, so that they are linked
+///
+/// # Safety
+/// `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn check(data: *const u8, len: usize) -> u32 {
+    // SAFETY: guaranteed by the caller
+    let bytes = unsafe { core::slice::from_raw_parts(data, len) };
+    let mut reader = SliceReader::new(bytes);
+    let first = reader.read_le_u32().unwrap_or(0);
+    let pair = reader
+        .read_le_u16_heapless::<2>(2)
+        .map_or(0, |pair| pair.iter().fold(0, |sum, &half| sum ^ half));
+    let mut rest = reader.remaining_slice();
+    let header = Header::read_from(&mut rest, Endian::Little).map_or(0, |header| header.len);
+    let extra = rest
+        .read_le_u16_arrayvec::<4>(1)
+        .map_or(0, |values| values.first().copied().unwrap_or(0));
+    first ^ u32::from(header ^ pair ^ extra)
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
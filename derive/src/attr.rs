@@ -0,0 +1,441 @@
+use proc_macro2::{Group, Ident, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, ExprLit, Lit, LitByteStr, LitStr, Path, Result, Type};
+
+/// Byte order selected by `#[read(little)]`, `#[read(big)]` or `#[read(native)]`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ByteOrder {
+    Little,
+    Big,
+    Native,
+}
+
+impl ByteOrder {
+    fn from_meta(meta: &ParseNestedMeta) -> Option<Self> {
+        if meta.path.is_ident("little") {
+            Some(ByteOrder::Little)
+        } else if meta.path.is_ident("big") {
+            Some(ByteOrder::Big)
+        } else if meta.path.is_ident("native") {
+            Some(ByteOrder::Native)
+        } else {
+            None
+        }
+    }
+}
+
+impl ToTokens for ByteOrder {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            ByteOrder::Little => quote!(::read_primitives::Endian::Little),
+            ByteOrder::Big => quote!(::read_primitives::Endian::Big),
+            ByteOrder::Native => quote!(::read_primitives::Endian::NATIVE),
+        });
+    }
+}
+
+/// Async backend selected by `#[read(tokio)]` or `#[read(futures)]` on a struct or enum, for which
+/// the async read traits of the backend's module are derived as well
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AsyncBackend {
+    Tokio,
+    Futures,
+}
+
+impl ToTokens for AsyncBackend {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            AsyncBackend::Tokio => quote!(::read_primitives::tokio),
+            AsyncBackend::Futures => quote!(::read_primitives::futures),
+        });
+    }
+}
+
+/// Call `f` for every item inside the `#[read(...)]` attributes in `attrs`
+fn parse_read_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> Result<()>,
+) -> Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("read")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
+
+fn set_byte_order(slot: &mut Option<ByteOrder>, meta: &ParseNestedMeta) -> Result<bool> {
+    let Some(order) = ByteOrder::from_meta(meta) else {
+        return Ok(false);
+    };
+    if slot.is_some() {
+        return Err(meta.error("conflicting byte order attributes"));
+    }
+    *slot = Some(order);
+    Ok(true)
+}
+
+/// Parse the string value of `name = "expr"` as an expression
+fn parse_expr(meta: &ParseNestedMeta) -> Result<Expr> {
+    meta.value()?.parse::<LitStr>()?.parse()
+}
+
+/// Rename `self` to `__self`, which the generated code binds to the value read so far
+fn replace_self(tokens: TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|tree| match tree {
+            TokenTree::Ident(ident) if ident == "self" => {
+                TokenTree::Ident(Ident::new("__self", ident.span()))
+            }
+            TokenTree::Group(group) => {
+                let mut replaced = Group::new(group.delimiter(), replace_self(group.stream()));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            tree => tree,
+        })
+        .collect()
+}
+
+/// Version of a `vN = Layout` attribute
+fn layout_version(meta: &ParseNestedMeta) -> Option<u64> {
+    let ident = meta.path.get_ident()?.to_string();
+    let digits = ident.strip_prefix('v')?;
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// A `#[read(assert = "...")]` condition
+pub(crate) struct Assert {
+    pub(crate) expr: Expr,
+    /// The condition as written, for the error message
+    pub(crate) text: String,
+}
+
+/// Attributes allowed on both the struct and its fields
+#[derive(Default)]
+pub(crate) struct Checks {
+    /// `#[read(magic = b"...")]`: bytes that must come first
+    pub(crate) magic: Option<LitByteStr>,
+    /// Conditions checked after reading
+    pub(crate) asserts: Vec<Assert>,
+}
+
+impl Checks {
+    /// Parse `meta` if it is a check; `self` in conditions is renamed if `bind_self` is set
+    fn parse(&mut self, meta: &ParseNestedMeta, bind_self: bool) -> Result<bool> {
+        if meta.path.is_ident("magic") {
+            if self.magic.is_some() {
+                return Err(meta.error("duplicate magic attribute"));
+            }
+            self.magic = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("assert") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let mut tokens: TokenStream = lit.parse()?;
+            if bind_self {
+                tokens = replace_self(tokens);
+            }
+            self.asserts.push(Assert {
+                expr: syn::parse2(tokens)?,
+                text: lit.value(),
+            });
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+/// How a field is read if not with its `ReadFrom` implementation, with an expression evaluated
+/// over the fields read before it
+pub(crate) enum Mode {
+    /// `#[read(count = "...")]`: number of elements of a `Vec<T>`
+    Count(Expr),
+    /// `#[read(len = "...")]`: byte length of a `String`, or of a borrowed `&str` or `&[u8]`
+    Len(Expr),
+    /// `#[read(tag = "...")]`: tag selecting the variant of a `ReadTagged` enum
+    Tag(Expr),
+    /// `#[read(until_eof)]`: elements of a `Vec<T>` up to the end of the input
+    UntilEof,
+    /// `#[read(default_on_eof)]`: the default value if the input ends before the field
+    DefaultOnEof,
+    /// `#[read(version_from = "...", v1 = LayoutV1, v2 = LayoutV2)]`: version selecting the
+    /// layout type read, which is converted into the field type with `Into`
+    Version {
+        version: Expr,
+        /// Version numbers and their layouts, in the order written
+        layouts: Vec<(u64, Type)>,
+    },
+}
+
+/// A check run on a field after reading it
+pub(crate) enum Validator {
+    /// `#[read(check = "range ...")]`: range the value must lie in
+    Range {
+        range: Expr,
+        /// The range as written, for the error message
+        text: String,
+    },
+    /// `#[read(check_fn = "...")]`: function taking the field by reference and returning a
+    /// `Result<(), E>`
+    Fn(Path),
+}
+
+impl Validator {
+    fn parse_check(meta: &ParseNestedMeta) -> Result<Self> {
+        let lit: LitStr = meta.value()?.parse()?;
+        let value = lit.value();
+        match value.strip_prefix("range ") {
+            Some(range) => Ok(Validator::Range {
+                range: LitStr::new(range, lit.span()).parse()?,
+                text: range.trim().to_owned(),
+            }),
+            None => Err(syn::Error::new_spanned(
+                lit,
+                "unknown check, expected \"range <range>\"",
+            )),
+        }
+    }
+}
+
+/// Attributes on the struct or enum
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) endian: Option<ByteOrder>,
+    pub(crate) checks: Checks,
+    /// `#[read(tag_type = "...")]` on an enum
+    pub(crate) tag_type: Option<Type>,
+    pub(crate) async_backends: Vec<AsyncBackend>,
+    /// `#[read(embedded)]`: also derive the `embedded::ReadFrom` of `embedded-io` readers
+    pub(crate) embedded: bool,
+    /// `#[read(no_std)]`: derive only the `embedded-io` read, for builds without `std`
+    pub(crate) no_std: bool,
+    /// `#[read(fixed_size)]`: also implement `FixedSize` from the sizes of the fields
+    pub(crate) fixed_size: bool,
+    /// `#[read(incremental)]`: also implement `ReadIncremental`, decoding field by field
+    pub(crate) incremental: bool,
+    /// `#[read(serde)]`: with `incremental`, derive serde's traits for the decoding state
+    pub(crate) serde: bool,
+    /// `#[read(bump)]`: implement `ReadFromIn` for the lifetime instead of `ReadFromSlice`
+    pub(crate) bump: bool,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut container = ContainerAttrs::default();
+        parse_read_attrs(attrs, |meta| {
+            if set_byte_order(&mut container.endian, &meta)?
+                || container.checks.parse(&meta, true)?
+            {
+                return Ok(());
+            }
+            if meta.path.is_ident("tag_type") {
+                container.tag_type = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                return Ok(());
+            }
+            let flag = if meta.path.is_ident("embedded") {
+                Some(&mut container.embedded)
+            } else if meta.path.is_ident("no_std") {
+                Some(&mut container.no_std)
+            } else if meta.path.is_ident("fixed_size") {
+                Some(&mut container.fixed_size)
+            } else if meta.path.is_ident("incremental") {
+                Some(&mut container.incremental)
+            } else if meta.path.is_ident("serde") {
+                Some(&mut container.serde)
+            } else if meta.path.is_ident("bump") {
+                Some(&mut container.bump)
+            } else {
+                None
+            };
+            if let Some(flag) = flag {
+                if *flag {
+                    return Err(meta.error("duplicate read attribute"));
+                }
+                *flag = true;
+                return Ok(());
+            }
+            let backend = if meta.path.is_ident("tokio") {
+                AsyncBackend::Tokio
+            } else if meta.path.is_ident("futures") {
+                AsyncBackend::Futures
+            } else {
+                return Err(meta.error("unknown read attribute"));
+            };
+            if container.async_backends.contains(&backend) {
+                return Err(meta.error("duplicate async backend attribute"));
+            }
+            container.async_backends.push(backend);
+            Ok(())
+        })?;
+        Ok(container)
+    }
+}
+
+/// Attributes on a field
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) endian: Option<ByteOrder>,
+    pub(crate) mode: Option<Mode>,
+    pub(crate) checks: Checks,
+    /// Checks run in order after the assertions
+    pub(crate) validators: Vec<Validator>,
+    pub(crate) padding: Padding,
+    /// `#[read(if = "...")]`: condition for reading an `Option<T>` field, which is `None` otherwise
+    pub(crate) condition: Option<Expr>,
+}
+
+/// Padding around a field, skipped on read and written as zeros
+#[derive(Default)]
+pub(crate) struct Padding {
+    /// `#[read(pad_before = ...)]`: bytes before the field
+    pub(crate) before: Option<Expr>,
+    /// `#[read(align = ...)]`: alignment of the field's offset from the start of the value, after
+    /// `pad_before`
+    pub(crate) align: Option<Expr>,
+    /// `#[read(pad_to = ...)]`: size the field is padded to at the end
+    pub(crate) to: Option<Expr>,
+}
+
+impl Padding {
+    fn parse(&mut self, meta: &ParseNestedMeta) -> Result<bool> {
+        let slot = if meta.path.is_ident("pad_before") {
+            &mut self.before
+        } else if meta.path.is_ident("align") {
+            &mut self.align
+        } else if meta.path.is_ident("pad_to") {
+            &mut self.to
+        } else {
+            return Ok(false);
+        };
+        if slot.is_some() {
+            return Err(meta.error("duplicate padding attribute"));
+        }
+        // a literal size, or a string holding an expression as for `count` and `len`
+        *slot = Some(match meta.value()?.parse()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => lit.parse()?,
+            expr => expr,
+        });
+        Ok(true)
+    }
+
+    /// Whether the code needs the offset from the start of the value
+    pub(crate) fn uses_position(&self) -> bool {
+        self.align.is_some() || self.to.is_some()
+    }
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut field = FieldAttrs::default();
+        let mut layouts: Vec<(u64, Type)> = Vec::new();
+        let mut layouts_span = None;
+        parse_read_attrs(attrs, |meta| {
+            if set_byte_order(&mut field.endian, &meta)?
+                || field.checks.parse(&meta, false)?
+                || field.padding.parse(&meta)?
+            {
+                return Ok(());
+            }
+            if meta.path.is_ident("check") {
+                field.validators.push(Validator::parse_check(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("if") {
+                if field.condition.is_some() {
+                    return Err(meta.error("duplicate if attribute"));
+                }
+                field.condition = Some(parse_expr(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("check_fn") {
+                let path = meta.value()?.parse::<LitStr>()?.parse()?;
+                field.validators.push(Validator::Fn(path));
+                return Ok(());
+            }
+            if let Some(version) = layout_version(&meta) {
+                if layouts.iter().any(|(known, _)| *known == version) {
+                    return Err(meta.error(format!("duplicate layout for version {version}")));
+                }
+                layouts_span.get_or_insert(meta.path.clone());
+                layouts.push((version, meta.value()?.parse()?));
+                return Ok(());
+            }
+            let mode = if meta.path.is_ident("count") {
+                Mode::Count(parse_expr(&meta)?)
+            } else if meta.path.is_ident("len") {
+                Mode::Len(parse_expr(&meta)?)
+            } else if meta.path.is_ident("tag") {
+                Mode::Tag(parse_expr(&meta)?)
+            } else if meta.path.is_ident("until_eof") {
+                Mode::UntilEof
+            } else if meta.path.is_ident("default_on_eof") {
+                Mode::DefaultOnEof
+            } else if meta.path.is_ident("version_from") {
+                Mode::Version {
+                    version: parse_expr(&meta)?,
+                    layouts: Vec::new(),
+                }
+            } else {
+                return Err(meta.error("unknown read attribute"));
+            };
+            if field.mode.is_some() {
+                return Err(meta
+                    .error("conflicting count, len, tag, until_eof and version_from attributes"));
+            }
+            field.mode = Some(mode);
+            Ok(())
+        })?;
+        match (&mut field.mode, layouts_span) {
+            (Some(Mode::Version { layouts: slot, .. }), Some(_)) => *slot = layouts,
+            (Some(Mode::Version { version, .. }), None) => {
+                return Err(syn::Error::new_spanned(
+                    version,
+                    "version_from needs layouts like `v1 = LayoutV1`",
+                ))
+            }
+            (_, Some(path)) => {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "layouts like `v1 = LayoutV1` need a version_from attribute",
+                ))
+            }
+            (_, None) => {}
+        }
+        Ok(field)
+    }
+}
+
+/// Attributes on an enum variant
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[read(id = ...)]`: tag value selecting the variant
+    pub(crate) id: Option<Expr>,
+    /// `#[read(other)]`: variant holding the raw tag of every tag no other variant has
+    pub(crate) other: bool,
+}
+
+impl VariantAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut variant = VariantAttrs::default();
+        parse_read_attrs(attrs, |meta| {
+            if meta.path.is_ident("id") {
+                variant.id = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("other") {
+                if variant.other {
+                    return Err(meta.error("duplicate other attribute"));
+                }
+                variant.other = true;
+                return Ok(());
+            }
+            Err(meta.error("unknown read attribute"))
+        })?;
+        Ok(variant)
+    }
+}
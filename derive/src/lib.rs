@@ -0,0 +1,239 @@
+//! Derive macros for [read-primitives](https://docs.rs/read-primitives)
+//!
+//! Use them through the `derive` feature of `read-primitives`, which re-exports them.
+
+mod attr;
+mod read;
+mod write;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derive `ReadFrom` for a struct by reading its fields in declaration order
+///
+/// Fields are read with their own `ReadFrom` implementations. The byte order passed to
+/// `read_from` is handed down to every field unless an attribute overrides it:
+///
+/// - `#[read(little)]`, `#[read(big)]` or `#[read(native)]` on the struct fixes the byte order
+///   for all of its fields,
+/// - the same attributes on a field override the struct's byte order for that field.
+///
+/// Collections are sized by expressions over the fields read before them, given as strings:
+///
+/// - `#[read(count = "expr")]` on a `Vec<T>` field reads `expr` elements,
+/// - `#[read(len = "expr")]` on a `String` field reads `expr` bytes of UTF-8,
+/// - `#[read(until_eof)]` on the last field, a `Vec<T>`, reads elements until the reader ends,
+///   such as the end of a slice or of a [`Read::take`](std::io::Read::take) limit.
+///
+/// The expression can have any integer type; a value that does not fit in `usize` is an
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) error.
+///
+/// A field present only under a condition over the fields before it is an `Option<T>` with
+/// `#[read(if = "flags & 0x1 != 0")]`: it is read as a `T` if the condition holds, and is `None`
+/// otherwise. Other attributes of the field apply to the `T`.
+///
+/// Trailing fields that older producers leave out take `#[read(default_on_eof)]`: if the reader
+/// ends right before such a field, it gets its `Default` value, while an end inside it is still an
+/// error. Only such fields may follow it.
+///
+/// A version read earlier can select among layouts for a field: with
+/// `#[read(version_from = "header.version", v1 = LayoutV1, v2 = LayoutV2)]`, version 1 reads a
+/// `LayoutV1` and version 2 a `LayoutV2`, each converted into the field's type with `Into`, and
+/// other versions are an [`InvalidData`](std::io::ErrorKind::InvalidData) error. The field's type
+/// is typically the current layout, with `From` impls upgrading the older ones, or an enum with a
+/// variant per layout.
+///
+/// Validation attributes work on both the struct and its fields, and fail with
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) errors naming what was expected:
+///
+/// - `#[read(magic = b"...")]` reads the given bytes before the struct or field and compares them,
+/// - `#[read(assert = "expr")]` checks a condition after reading. On a field it can refer to that
+///   field and the ones before it; on the struct, `self` refers to the complete value,
+/// - `#[read(check = "range 1..=64")]` on a field checks that the value lies in the range,
+/// - `#[read(check_fn = "path")]` on a field calls `path(&field)`, which returns a
+///   `Result<(), E>`; an error `E` becomes the source of the `InvalidData` error.
+///
+/// Padding between fields is skipped, and written as zeros by the `WriteTo` derive:
+///
+/// - `#[read(pad_before = n)]` on a field skips `n` bytes before it,
+/// - `#[read(align = n)]` skips to the next multiple of `n` bytes from the start of the struct,
+/// - `#[read(pad_to = n)]` skips after the field until it took `n` bytes, failing if it took more.
+///
+/// Errors are wrapped in a `FieldError` naming the innermost struct and field that failed and the
+/// offset of that field from the start of the outermost value, as in
+/// `Header.section_count at offset 0x14: failed to fill whole buffer`.
+///
+/// Generic structs get a `ReadFrom` bound on each type parameter. A struct with a lifetime
+/// parameter instead implements `ReadFromSlice` for its first lifetime and is read from a byte
+/// slice, so that `&'a str` and `&'a [u8]` fields with a `len` attribute borrow from the input
+/// instead of being copied; its type parameters are bound by `ReadFromSlice`.
+///
+/// `#[read(bump)]` on a struct with a lifetime parameter, with the `bumpalo` feature of
+/// read-primitives, implements `ReadFromIn` for that lifetime instead, reading from any reader and
+/// allocating in a `bumpalo::Bump`: `&'bump str` and `&'bump [u8]` fields with a `len` attribute
+/// and `bumpalo::collections::Vec<'bump, T>` fields with a `count` attribute are allocated in the
+/// arena, and the type parameters are bound by `ReadFromIn`. It is not supported together with
+/// the async, embedded, `fixed_size` and `incremental` reads.
+///
+/// `#[read(tokio)]` or `#[read(futures)]` on the struct also implements `ReadFromAsync` from the
+/// `tokio` or `futures` module, which need the features of the same name, to read the same layout
+/// from an async reader. The fields' types must implement it as well, and structs with a lifetime
+/// parameter are not supported. `Vec` fields yield to the executor after every
+/// `DEFAULT_YIELD_INTERVAL` elements, so that long decodes don't starve other tasks.
+///
+/// `#[read(embedded)]` also implements `embedded::ReadFrom`, which reads from `embedded-io`
+/// readers and needs the `embedded-io` feature but not `std`; adding `#[read(no_std)]` leaves out
+/// the `std` implementation, so that the same definition builds for firmware, for example with
+/// `#[cfg_attr(not(feature = "std"), read(no_std))]`. Its errors are `embedded::DecodeError`s
+/// naming the field without allocating. Embedded reads support the byte order, validation,
+/// padding, `if` and `version_from` attributes, but not the ones needing `std` collections.
+///
+/// `#[read(fixed_size)]` also implements `FixedSize`, whose `SIZE` sums the sizes of the fields
+/// with the magic and padding, for `RecordTable::fixed` and `FixedSize::read_batch`. Every field
+/// must implement `FixedSize`, padding must be constant, and the attributes sizing collections,
+/// `if` and `version_from` are not allowed. Type parameters are bound by `FixedSize` as well.
+///
+/// `#[read(incremental)]` also implements `ReadIncremental`, to decode the struct field by field
+/// with an `IncrementalDecoder` as its bytes arrive: fields read so far are kept, and only the
+/// bytes of the field being read wait for the rest of them. Expressions over earlier fields may
+/// copy them but not move them, and `until_eof` and `default_on_eof` are not supported, as the
+/// end of the input only means more bytes are needed. With the `serde` feature of
+/// read-primitives, `#[read(incremental, serde)]` also derives serde's `Serialize` and
+/// `Deserialize` for the decoding state, whose fields must implement them, so that an
+/// `IncrementalDecoder` can be checkpointed and resumed.
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom};
+///
+/// #[derive(ReadFrom, Debug, PartialEq)]
+/// #[read(little)]
+/// struct Header {
+///     version: u16,
+///     #[read(big)]
+///     length: u32,
+/// }
+///
+/// #[derive(ReadFrom, Debug, PartialEq)]
+/// #[read(little)]
+/// struct Entry {
+///     header: Header,
+///     name_len: u8,
+///     #[read(len = "name_len")]
+///     name: String,
+///     #[read(count = "header.length / 2")]
+///     samples: Vec<i16>,
+/// }
+///
+/// let mut bytes: &[u8] = &[1, 0, 0, 0, 0, 4, 2, b'h', b'i', 1, 0, 2, 0];
+/// let entry = Entry::read_from(&mut bytes, Endian::Big).unwrap();
+/// assert_eq!(Header { version: 1, length: 4 }, entry.header);
+/// assert_eq!(("hi", vec![1, 2]), (entry.name.as_str(), entry.samples));
+/// ```
+#[proc_macro_derive(ReadFrom, attributes(read))]
+pub fn derive_read_from(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    read::expand(input, false)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ReadTagged` for an enum whose variant is selected by a tag read before it
+///
+/// Every variant needs a `#[read(id = ...)]` attribute giving the tag value that selects it; its
+/// fields are then read like those of a struct deriving `ReadFrom`, with the same attributes.
+/// Other tags are an [`InvalidData`](std::io::ErrorKind::InvalidData) error that includes the
+/// value, unless a variant with a single unnamed field of the tag type is marked
+/// `#[read(other)]`: it then holds every tag no other variant has, so that data from newer
+/// producers still reads. The tag type is `u64` unless the enum sets `#[read(tag_type = "...")]`. As for
+/// `ReadFrom`, `#[read(tokio)]` or `#[read(futures)]` also implements `ReadTaggedAsync`.
+///
+/// A struct deriving `ReadFrom` passes the tag with `#[read(tag = "expr")]` on the enum field,
+/// an expression over the fields before it that is converted into the tag type with `Into`.
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom, ReadTagged};
+///
+/// #[derive(ReadTagged, Debug, PartialEq)]
+/// #[read(tag_type = "u8")]
+/// enum Shape {
+///     #[read(id = 1)]
+///     Circle { radius: u16 },
+///     #[read(id = 2)]
+///     Rect(u16, u16),
+/// }
+///
+/// #[derive(ReadFrom, Debug, PartialEq)]
+/// #[read(big)]
+/// struct Message {
+///     kind: u8,
+///     #[read(tag = "kind")]
+///     shape: Shape,
+/// }
+///
+/// let mut bytes: &[u8] = &[2, 0, 3, 0, 4];
+/// let message = Message::read_from(&mut bytes, Endian::Big).unwrap();
+/// assert_eq!(Shape::Rect(3, 4), message.shape);
+/// let err = Message::read_from(&mut &[9u8][..], Endian::Big).unwrap_err();
+/// assert_eq!("Shape at offset 0x1: unknown tag 9", err.to_string());
+/// ```
+#[proc_macro_derive(ReadTagged, attributes(read))]
+pub fn derive_read_tagged(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    read::expand(input, true)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `WriteTo` for a struct or enum, writing what `#[derive(ReadFrom)]` or
+/// `#[derive(ReadTagged)]` reads
+///
+/// The derive takes the same `#[read(...)]` attributes, so a type deriving both round-trips:
+///
+/// - byte order attributes apply to the same fields,
+/// - `magic` bytes are written where they are checked on read,
+/// - fields with `count` or `len` are written without a prefix, after checking that their length
+///   matches the expression, which here sees the other fields by reference; a mismatch is an
+///   [`InvalidInput`](std::io::ErrorKind::InvalidInput) error,
+/// - a field with an `if` condition is written if it is `Some`, after checking likewise that it is
+///   `Some` exactly when the condition holds,
+/// - an enum writes the fields of its variant, but not the tag, which belongs to the field that
+///   `#[read(tag = "...")]` refers to; the `#[read(other)]` variant writes nothing.
+///
+/// `assert` conditions are only checked on read. `version_from` fields are a compile error, as the
+/// layout of the version can't be recovered from the field's type.
+///
+/// ```compile_fail
+/// use read_primitives::WriteTo;
+///
+/// #[derive(WriteTo)]
+/// struct File {
+///     version: u8,
+///     #[read(version_from = "version", v1 = u16)]
+///     entry: u32,
+/// }
+/// ```
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom, WriteTo};
+///
+/// #[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+/// #[read(big, magic = b"ID")]
+/// struct Name {
+///     len: u8,
+///     #[read(len = "len")]
+///     name: String,
+/// }
+///
+/// let name = Name { len: 2, name: "ok".into() };
+/// let mut bytes = Vec::new();
+/// name.write_to(&mut bytes, Endian::Little).unwrap();
+/// assert_eq!(b"ID\x02ok", bytes.as_slice());
+/// assert_eq!(name, Name::read_from(&mut bytes.as_slice(), Endian::Little).unwrap());
+/// ```
+#[proc_macro_derive(WriteTo, attributes(read))]
+pub fn derive_write_to(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    write::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
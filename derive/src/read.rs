@@ -0,0 +1,1114 @@
+use crate::attr::{
+    AsyncBackend, Checks, ContainerAttrs, FieldAttrs, Mode, Validator, VariantAttrs,
+};
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, Lifetime, Path, PathArguments,
+    Result, Type,
+};
+
+/// Where a derived read takes its bytes from
+enum Source {
+    /// Any reader, for `ReadFrom` and `ReadTagged`
+    Reader,
+    /// A byte slice that fields can borrow from for the lifetime, for `ReadFromSlice`
+    Slice(Lifetime),
+    /// Any reader, with an arena that fields allocate in for the lifetime, for `ReadFromIn`
+    Bump(Lifetime),
+    /// An async reader of the backend, for its `ReadFromAsync` and `ReadTaggedAsync`
+    Async(AsyncBackend),
+    /// An `embedded-io` reader, for `embedded::ReadFrom`
+    Embedded,
+}
+
+impl Source {
+    /// Expression for the offset of the next byte from the start of the value
+    fn position(&self) -> TokenStream {
+        match self {
+            Source::Reader | Source::Bump(_) => {
+                quote!(::read_primitives::__private::Counter::position(__reader))
+            }
+            Source::Slice(_) => {
+                quote!(::read_primitives::__private::slice_position(
+                    __base, __reader
+                ))
+            }
+            Source::Async(backend) => {
+                quote!(#backend::derive_support::Counter::position(__reader))
+            }
+            Source::Embedded => {
+                quote!(::read_primitives::embedded::derive_support::Counter::position(__reader))
+            }
+        }
+    }
+
+    /// Module of the helpers that build and check errors of the read's error type
+    fn support(&self) -> TokenStream {
+        match self {
+            Source::Embedded => quote!(::read_primitives::embedded::derive_support),
+            _ => quote!(::read_primitives::__private),
+        }
+    }
+
+    /// Call of the reading helper `name`, awaited if async
+    fn helper(&self, name: &str, args: TokenStream) -> TokenStream {
+        let name = format_ident!("{}", name);
+        match self {
+            Source::Reader | Source::Slice(_) | Source::Bump(_) => {
+                quote!(::read_primitives::__private::#name(#args))
+            }
+            Source::Async(backend) => quote!(#backend::derive_support::#name(#args).await),
+            Source::Embedded => quote!(::read_primitives::embedded::derive_support::#name(#args)),
+        }
+    }
+
+    /// Call of a helper that needs the `usize` in `size`, an `io::Result`, bound to `binding`
+    fn with_size(&self, size: TokenStream, binding: &str, call: TokenStream) -> TokenStream {
+        let binding = format_ident!("{}", binding);
+        match self {
+            Source::Reader | Source::Slice(_) | Source::Bump(_) | Source::Embedded => {
+                quote!(#size.and_then(|#binding| #call))
+            }
+            Source::Async(_) => quote! {
+                match #size {
+                    ::core::result::Result::Ok(#binding) => #call,
+                    ::core::result::Result::Err(__err) => ::core::result::Result::Err(__err),
+                }
+            },
+        }
+    }
+}
+
+/// Code checking the magic before a value is read, and the conditions after, attributing
+/// failures to `path` starting at `start`
+fn checks(
+    checks: &Checks,
+    path: &str,
+    start: &TokenStream,
+    source: &Source,
+) -> (TokenStream, TokenStream) {
+    let support = source.support();
+    let magic = match &checks.magic {
+        Some(magic) => {
+            let check = source.helper("check_magic", quote!(__reader, #magic));
+            quote!(#support::with_field(#check, #path, #start)?;)
+        }
+        None => quote!(),
+    };
+    let asserts = checks.asserts.iter().map(|assert| {
+        let expr = &assert.expr;
+        let text = &assert.text;
+        quote! {
+            if !(#expr) {
+                return ::core::result::Result::Err(#support::field_error(
+                    #support::assertion_failed(#text),
+                    #path,
+                    #start,
+                ));
+            }
+        }
+    });
+    (magic, quote!(#(#asserts)*))
+}
+
+/// The `T` of a type written as `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Code reading a struct's or variant's fields in order
+struct FieldReads {
+    /// Statements binding each field to a local, one entry per field
+    reads: Vec<TokenStream>,
+    /// The locals, in the order of the fields
+    locals: Vec<Ident>,
+    /// Expression building the value from the locals
+    construct: TokenStream,
+    /// Whether a field inherits the byte order
+    uses_endian: bool,
+}
+
+fn read_fields(fields: &Fields, path: &Path, context: &str, source: &Source) -> Result<FieldReads> {
+    let support = source.support();
+    let mut reads = Vec::new();
+    let mut locals = Vec::new();
+    let mut uses_endian = false;
+    let mut after_optional = false;
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let (local, context) = match &field.ident {
+            Some(ident) => (ident.clone(), format!("{context}.{ident}")),
+            None => (
+                format_ident!("__field{}", index),
+                format!("{context}.{index}"),
+            ),
+        };
+        let endian = match attrs.endian {
+            Some(order) => quote!(#order),
+            None => {
+                uses_endian = true;
+                quote!(__endian)
+            }
+        };
+        if matches!(attrs.mode, Some(Mode::UntilEof)) && index + 1 != fields.len() {
+            return Err(Error::new_spanned(
+                field,
+                "until_eof is only allowed on the last field",
+            ));
+        }
+        let optional = matches!(attrs.mode, Some(Mode::DefaultOnEof));
+        if after_optional && !optional {
+            return Err(Error::new_spanned(
+                field,
+                "fields after a default_on_eof field need default_on_eof as well",
+            ));
+        }
+        after_optional |= optional;
+        let ty = &field.ty;
+        let size = |expr: &Expr| quote!(#support::size(#expr));
+        // the type read, the `T` of the `Option<T>` of a conditional field
+        let read_ty = match &attrs.condition {
+            Some(condition) => match option_inner(ty) {
+                Some(inner) => quote!(#inner),
+                None => {
+                    return Err(Error::new_spanned(
+                        condition,
+                        "the if attribute needs an Option<T> field",
+                    ))
+                }
+            },
+            None => quote!(#ty),
+        };
+        // read of a `ty` with its own implementation
+        let read_as = |ty: TokenStream| match source {
+            Source::Reader => {
+                quote!(<#ty as ::read_primitives::ReadFrom>::read_from(__reader, #endian))
+            }
+            Source::Slice(lifetime) => quote! {
+                <#ty as ::read_primitives::ReadFromSlice<#lifetime>>::read_from_slice(__reader, #endian)
+            },
+            Source::Bump(lifetime) => quote! {
+                <#ty as ::read_primitives::ReadFromIn<#lifetime>>::read_from_in(__reader, #endian, __bump)
+            },
+            Source::Async(backend) => quote! {
+                <#ty as #backend::ReadFromAsync>::read_from_async(__reader, #endian).await
+            },
+            Source::Embedded => quote! {
+                <#ty as ::read_primitives::embedded::ReadFrom>::read_from(__reader, #endian)
+            },
+        };
+        if matches!(source, Source::Embedded)
+            && matches!(
+                attrs.mode,
+                Some(
+                    Mode::Count(_)
+                        | Mode::Len(_)
+                        | Mode::UntilEof
+                        | Mode::Tag(_)
+                        | Mode::DefaultOnEof
+                )
+            )
+        {
+            return Err(Error::new_spanned(
+                field,
+                "count, len, until_eof, tag and default_on_eof are not supported for embedded reads",
+            ));
+        }
+        let read = match (&attrs.mode, source) {
+            (None, _) => read_as(read_ty.clone()),
+            (Some(Mode::Version { version, layouts }), _) => {
+                let arms = layouts.iter().map(|(number, layout)| {
+                    let number = Literal::u64_unsuffixed(*number);
+                    let read = read_as(quote!(#layout));
+                    quote! {
+                        if __version == #number {
+                            ::core::result::Result::map(#read, ::core::convert::Into::into)
+                        } else
+                    }
+                });
+                quote! {
+                    {
+                        let __version = #version;
+                        #(#arms)* {
+                            ::core::result::Result::Err(
+                                #support::unsupported_version(&__version),
+                            )
+                        }
+                    }
+                }
+            }
+            (Some(Mode::Count(count)), Source::Slice(_)) => source.with_size(
+                size(count),
+                "__count",
+                quote!(::read_primitives::__private::read_vec_from_slice(__reader, #endian, __count)),
+            ),
+            (Some(Mode::Count(count)), Source::Bump(lifetime)) => source.with_size(
+                size(count),
+                "__count",
+                quote! {
+                    <#read_ty as ::read_primitives::__private::ReadCountIn<#lifetime>>::read_count_in(
+                        __reader, #endian, __count, __bump,
+                    )
+                },
+            ),
+            (Some(Mode::Count(count)), _) => source.with_size(
+                size(count),
+                "__count",
+                source.helper("read_vec", quote!(__reader, #endian, __count)),
+            ),
+            (Some(Mode::Len(len)), Source::Slice(lifetime)) => source.with_size(
+                size(len),
+                "__len",
+                quote!(<#read_ty as ::read_primitives::__private::ReadLen<#lifetime>>::read_len(__reader, __len)),
+            ),
+            (Some(Mode::Len(len)), Source::Bump(lifetime)) => source.with_size(
+                size(len),
+                "__len",
+                quote! {
+                    <#read_ty as ::read_primitives::__private::ReadLenIn<#lifetime>>::read_len_in(
+                        __reader, __len, __bump,
+                    )
+                },
+            ),
+            (Some(Mode::Len(len)), _) => source.with_size(
+                size(len),
+                "__len",
+                source.helper("read_string", quote!(__reader, __len)),
+            ),
+            (Some(Mode::UntilEof), Source::Slice(_)) => quote! {
+                ::read_primitives::__private::read_until_eof_from_slice(__reader, #endian)
+            },
+            (Some(Mode::UntilEof), _) => source.helper("read_until_eof", quote!(__reader, #endian)),
+            (Some(Mode::DefaultOnEof), Source::Slice(_)) => quote! {
+                ::read_primitives::__private::read_optional_from_slice(__reader, #endian)
+            },
+            (Some(Mode::DefaultOnEof), _) => source.helper("read_optional", quote!(__reader, #endian)),
+            (Some(Mode::Tag(tag)), Source::Async(backend)) => quote! {
+                <#read_ty as #backend::ReadTaggedAsync>::read_tagged_async(
+                    __reader,
+                    #endian,
+                    ::core::convert::Into::into(#tag),
+                )
+                .await
+            },
+            (Some(Mode::Tag(tag)), _) => quote! {
+                <#read_ty as ::read_primitives::ReadTagged>::read_tagged(
+                    __reader,
+                    #endian,
+                    ::core::convert::Into::into(#tag),
+                )
+            },
+        };
+        let read = match &attrs.condition {
+            Some(condition) => quote! {
+                if #condition {
+                    ::core::result::Result::map(#read, ::core::option::Option::Some)
+                } else {
+                    ::core::result::Result::Ok(::core::option::Option::None)
+                }
+            },
+            None => read,
+        };
+        let (magic, asserts) = checks(&attrs.checks, &context, &quote!(__start), source);
+        let magic = match &attrs.condition {
+            Some(condition) if attrs.checks.magic.is_some() => quote!(if #condition { #magic }),
+            _ => magic,
+        };
+        // validators check the `T` of a conditional field if it is present
+        let value = match attrs.condition {
+            Some(_) => quote!(__value),
+            None => quote!(&#local),
+        };
+        let validators = attrs.validators.iter().map(|validator| {
+            let check = match validator {
+                Validator::Range { range, text } => quote! {
+                    #support::check_range(#value, #range, #text)
+                },
+                Validator::Fn(path) => quote! {
+                    #support::check_fn(#path(#value))
+                },
+            };
+            quote!(#support::with_field(#check, #context, __start)?;)
+        });
+        let validators = match attrs.condition {
+            Some(_) if !attrs.validators.is_empty() => quote! {
+                if let ::core::option::Option::Some(__value) = &#local {
+                    #(#validators)*
+                }
+            },
+            _ => quote!(#(#validators)*),
+        };
+        let position = source.position();
+        // `len` evaluates to the `io::Result<usize>` of the number of bytes to skip
+        let skip = |len: TokenStream| {
+            let skip =
+                source.with_size(len, "__len", source.helper("skip", quote!(__reader, __len)));
+            quote!(#support::with_field(#skip, #context, __start)?;)
+        };
+        let pad_before =
+            (attrs.padding.before.as_ref()).map(|len| skip(quote!(#support::size(#len))));
+        let align = attrs.padding.align.as_ref().map(|align| {
+            skip(quote! {
+                #support::size(#align)
+                    .and_then(|__align| #support::align(#position, __align))
+            })
+        });
+        let pad_to = attrs.padding.to.as_ref().map(|size| {
+            skip(quote! {
+                #support::size(#size).and_then(|__size| {
+                    #support::pad_to(#position - __start, __size)
+                })
+            })
+        });
+        // only reads from a `Read` are traced, by the reader the trace passes in
+        let (mark, finish) = match source {
+            Source::Reader | Source::Bump(_) => (
+                quote!(let __mark = ::read_primitives::__private::trace_mark();),
+                quote! {
+                    ::read_primitives::TraceMark::finish_with(__mark, #context, || {
+                        use ::read_primitives::__private::{DescribeDebug as _, DescribeOther as _};
+                        (&::read_primitives::__private::TraceValue(&#local)).describe()
+                    });
+                },
+            ),
+            _ => (quote!(), quote!()),
+        };
+        reads.push(quote! {
+            let __start = #position;
+            #pad_before
+            #align
+            let __start = #position;
+            #mark
+            #magic
+            let #local: #ty = #support::with_field(#read, #context, __start)?;
+            #finish
+            #asserts
+            #validators
+            #pad_to
+        });
+        locals.push(local);
+    }
+    let construct = match fields {
+        Fields::Named(_) => quote!(#path { #(#locals),* }),
+        Fields::Unnamed(_) => quote!(#path(#(#locals),*)),
+        Fields::Unit => quote!(#path),
+    };
+    Ok(FieldReads {
+        reads,
+        locals,
+        construct,
+        uses_endian,
+    })
+}
+
+/// Expand `#[derive(ReadFrom)]` for a struct, or `#[derive(ReadTagged)]` for an enum if `tagged`
+pub(crate) fn expand(input: DeriveInput, tagged: bool) -> Result<TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let source = match input.generics.lifetimes().next() {
+        Some(param) if !tagged && container.bump => Source::Bump(param.lifetime.clone()),
+        Some(param) if !tagged => Source::Slice(param.lifetime.clone()),
+        _ => Source::Reader,
+    };
+    if container.bump {
+        if tagged || !matches!(source, Source::Bump(_)) {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "bump is only supported for structs with a lifetime parameter",
+            ));
+        }
+        if container.embedded
+            || container.no_std
+            || container.fixed_size
+            || container.incremental
+            || !container.async_backends.is_empty()
+        {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "bump is not supported with async, embedded, fixed_size or incremental reads",
+            ));
+        }
+    }
+    if (container.embedded || container.no_std) && tagged {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "embedded reads are not supported for enums",
+        ));
+    }
+    if container.no_std && (!container.embedded || !container.async_backends.is_empty()) {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "no_std needs #[read(embedded)] and no async backend",
+        ));
+    }
+    let mut impls = Vec::new();
+    if container.fixed_size {
+        impls.push(fixed_size(&input, &container, tagged, &source)?);
+    }
+    if container.incremental {
+        impls.push(incremental(&input, &container, tagged, &source)?);
+    } else if container.serde {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "serde is only supported with incremental",
+        ));
+    }
+    if !container.no_std {
+        impls.push(expand_source(&input, &container, tagged, &source)?);
+    }
+    if container.embedded {
+        if let Source::Slice(lifetime) = &source {
+            return Err(Error::new_spanned(
+                lifetime,
+                "embedded reads are not supported for structs borrowing from a slice",
+            ));
+        }
+        impls.push(expand_source(
+            &input,
+            &container,
+            tagged,
+            &Source::Embedded,
+        )?);
+    }
+    for backend in &container.async_backends {
+        if let Source::Slice(lifetime) = &source {
+            return Err(Error::new_spanned(
+                lifetime,
+                "async reads are not supported for structs borrowing from a slice",
+            ));
+        }
+        let source = Source::Async(*backend);
+        impls.push(expand_source(&input, &container, tagged, &source)?);
+    }
+    Ok(quote!(#(#impls)*))
+}
+
+/// Implementation of `FixedSize` for a struct whose fields all have a fixed size, summing them
+/// with the magic and the padding
+fn fixed_size(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    tagged: bool,
+    source: &Source,
+) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) if !tagged => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "fixed_size is only supported for structs",
+            ))
+        }
+    };
+    if let Source::Slice(lifetime) = source {
+        return Err(Error::new_spanned(
+            lifetime,
+            "fixed_size is not supported for structs borrowing from a slice",
+        ));
+    }
+    if container.no_std {
+        return Err(Error::new_spanned(
+            name,
+            "fixed_size needs the std ReadFrom and is not supported with no_std",
+        ));
+    }
+    let magic_len = |checks: &Checks| {
+        let len = checks.magic.as_ref().map_or(0, |magic| magic.value().len());
+        quote!(__size += #len;)
+    };
+    let mut sizes = vec![magic_len(&container.checks)];
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.mode.is_some() || attrs.condition.is_some() {
+            return Err(Error::new_spanned(
+                field,
+                "fixed_size fields can't have count, len, until_eof, tag, default_on_eof, \
+                 version_from or if",
+            ));
+        }
+        let ty = &field.ty;
+        let before = attrs
+            .padding
+            .before
+            .as_ref()
+            .map(|len| quote!(__size += (#len) as usize;));
+        let align = attrs.padding.align.as_ref().map(|align| {
+            quote! {
+                let __align = (#align) as usize;
+                __size += (__align - __size % __align) % __align;
+            }
+        });
+        let magic = magic_len(&attrs.checks);
+        let start = attrs
+            .padding
+            .to
+            .as_ref()
+            .map(|_| quote!(let __start = __size;));
+        let pad_to = attrs.padding.to.as_ref().map(|size| {
+            quote! {
+                let __padded = (#size) as usize;
+                assert!(__size - __start <= __padded, "field is longer than its padded size");
+                __size = __start + __padded;
+            }
+        });
+        sizes.push(quote! {
+            #before
+            #align
+            #start
+            #magic
+            __size += <#ty as ::read_primitives::FixedSize>::SIZE;
+            #pad_to
+        });
+    }
+
+    let mut generics = input.generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: ::read_primitives::FixedSize));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::read_primitives::FixedSize for #name #ty_generics #where_clause {
+            const SIZE: usize = {
+                let mut __size = 0usize;
+                #(#sizes)*
+                __size
+            };
+        }
+    })
+}
+
+/// Implementation of `ReadIncremental` for a struct, with a state keeping the fields decoded so
+/// far and a step per field that reads it with the same code as `ReadFrom`
+fn incremental(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    tagged: bool,
+    source: &Source,
+) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) if !tagged => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "incremental is only supported for structs",
+            ))
+        }
+    };
+    if let Source::Slice(lifetime) = source {
+        return Err(Error::new_spanned(
+            lifetime,
+            "incremental is not supported for structs borrowing from a slice",
+        ));
+    }
+    if container.no_std {
+        return Err(Error::new_spanned(
+            name,
+            "incremental needs the std ReadFrom and is not supported with no_std",
+        ));
+    }
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if matches!(attrs.mode, Some(Mode::UntilEof | Mode::DefaultOnEof)) {
+            return Err(Error::new_spanned(
+                field,
+                "until_eof and default_on_eof fields can't be decoded incrementally",
+            ));
+        }
+    }
+    let self_path: Path = syn::parse_quote!(Self);
+    let FieldReads {
+        reads,
+        locals,
+        construct,
+        uses_endian,
+    } = read_fields(fields, &self_path, &name.to_string(), source)?;
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+
+    let (magic, asserts) = checks(&container.checks, &name.to_string(), &quote!(0), source);
+    // the container magic is step 0, field `i` step `i + 1`
+    let mut steps = vec![quote!({
+        #[allow(clippy::redundant_closure_call)]
+        let __result = (|| -> ::std::io::Result<()> {
+            #magic
+            ::core::result::Result::Ok(())
+        })();
+        __result
+    })];
+    for (index, read) in reads.iter().enumerate() {
+        let local = &locals[index];
+        let ty = types[index];
+        let before = &locals[..index];
+        steps.push(quote!({
+            #(
+                let #before = ::core::option::Option::take(&mut __state.#before)
+                    .ok_or_else(::read_primitives::__private::corrupted_state)?;
+            )*
+            #[allow(clippy::redundant_closure_call)]
+            let __result = (|| -> ::std::io::Result<#ty> {
+                #read
+                ::core::result::Result::Ok(#local)
+            })();
+            #(__state.#before = ::core::option::Option::Some(#before);)*
+            __result.map(|__value| __state.#local = ::core::option::Option::Some(__value))
+        }));
+    }
+    let step_count = steps.len();
+    let step_numbers = 0..step_count;
+    let take_locals = locals.iter().map(|local| {
+        quote! {
+            let #local = ::core::option::Option::take(&mut __state.#local)
+                .ok_or_else(::read_primitives::__private::corrupted_state)?;
+        }
+    });
+    let value = if container.checks.asserts.is_empty() {
+        quote!({ #construct })
+    } else {
+        quote! {{
+            let __value = { #construct };
+            let __self = &__value;
+            #asserts
+            __value
+        }}
+    };
+    let fixed_endian = match (uses_endian, container.endian) {
+        (true, Some(order)) => quote!(let __endian = #order;),
+        _ => quote!(),
+    };
+
+    let mut generics = input.generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: ::read_primitives::ReadFrom));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // field `i` is decoded once step `i + 1` is done
+    let decoded_after = (2usize..).take(locals.len());
+    let (serde, raw_state) = if container.serde {
+        // a checkpoint is checked as it is deserialized, so a corrupted one can't be resumed
+        let raw = quote!(__RawState #ty_generics).to_string();
+        (
+            quote! {
+                #[derive(
+                    ::read_primitives::__private::serde::Serialize,
+                    ::read_primitives::__private::serde::Deserialize,
+                )]
+                #[serde(crate = "::read_primitives::__private::serde", try_from = #raw)]
+            },
+            quote! {
+                #[derive(::read_primitives::__private::serde::Deserialize)]
+                #[serde(crate = "::read_primitives::__private::serde")]
+                pub struct __RawState #impl_generics #where_clause {
+                    __step: usize,
+                    __offset: u64,
+                    #(#locals: ::core::option::Option<#types>,)*
+                }
+
+                impl #impl_generics __State #ty_generics #where_clause {
+                    /// Whether exactly the fields before the step are decoded
+                    fn is_consistent(&self) -> bool {
+                        self.__step <= #step_count
+                            #(&& self.#locals.is_some() == (self.__step >= #decoded_after))*
+                    }
+                }
+
+                impl #impl_generics ::core::convert::TryFrom<__RawState #ty_generics>
+                    for __State #ty_generics #where_clause
+                {
+                    type Error = &'static str;
+
+                    fn try_from(__raw: __RawState #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                        let __state = __State {
+                            __step: __raw.__step,
+                            __offset: __raw.__offset,
+                            #(#locals: __raw.#locals,)*
+                        };
+                        if !__state.is_consistent() {
+                            return ::core::result::Result::Err("incremental state is corrupted");
+                        }
+                        ::core::result::Result::Ok(__state)
+                    }
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+    Ok(quote! {
+        const _: () = {
+            #[doc(hidden)]
+            #serde
+            pub struct __State #impl_generics #where_clause {
+                __step: usize,
+                __offset: u64,
+                #(#locals: ::core::option::Option<#types>,)*
+            }
+
+            impl #impl_generics ::core::default::Default for __State #ty_generics #where_clause {
+                fn default() -> Self {
+                    __State {
+                        __step: 0,
+                        __offset: 0,
+                        #(#locals: ::core::option::Option::None,)*
+                    }
+                }
+            }
+
+            #raw_state
+
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::ReadIncremental for #name #ty_generics #where_clause {
+                type State = __State #ty_generics;
+
+                fn resume(
+                    __state: &mut Self::State,
+                    __input: &mut &[u8],
+                    __endian: ::read_primitives::Endian,
+                ) -> ::std::io::Result<::core::option::Option<Self>> {
+                    let __nested = ::read_primitives::__private::nested()?;
+                    #fixed_endian
+                    while __state.__step < #step_count {
+                        let mut __bytes: &[u8] = __input;
+                        let mut __counter =
+                            ::read_primitives::__private::Counter::at(&mut __bytes, __state.__offset);
+                        #[allow(unused_variables)]
+                        let __reader = &mut __counter;
+                        let __result: ::std::io::Result<()> = match __state.__step {
+                            #(#step_numbers => #steps)*
+                            _ => ::core::unreachable!(),
+                        };
+                        match __result {
+                            ::core::result::Result::Ok(()) => {
+                                __state.__offset = __counter.position();
+                                *__input = __bytes;
+                                __state.__step += 1;
+                            }
+                            ::core::result::Result::Err(__err)
+                                if __err.kind() == ::std::io::ErrorKind::UnexpectedEof =>
+                            {
+                                return ::core::result::Result::Ok(::core::option::Option::None);
+                            }
+                            ::core::result::Result::Err(__err) => {
+                                return ::core::result::Result::Err(__err);
+                            }
+                        }
+                    }
+                    #(#take_locals)*
+                    *__state = ::core::default::Default::default();
+                    ::core::result::Result::Ok(::core::option::Option::Some(#value))
+                }
+            }
+        };
+    })
+}
+
+/// Implementation of the read trait for `source`
+fn expand_source(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    tagged: bool,
+    source: &Source,
+) -> Result<TokenStream> {
+    let name = &input.ident;
+    let self_path: Path = syn::parse_quote!(Self);
+
+    // reads of the value after the container magic, and whether they use reader and byte order
+    let (body, uses_reader, uses_endian) = match &input.data {
+        Data::Struct(_) if tagged => {
+            return Err(Error::new_spanned(
+                name,
+                "ReadTagged can only be derived for enums, use ReadFrom for structs",
+            ))
+        }
+        Data::Enum(_) if !tagged => {
+            return Err(Error::new_spanned(
+                name,
+                "ReadFrom can only be derived for structs, use ReadTagged for enums",
+            ))
+        }
+        Data::Struct(data) => {
+            if let Some(tag_type) = &container.tag_type {
+                return Err(Error::new_spanned(
+                    tag_type,
+                    "tag_type is only allowed on enums",
+                ));
+            }
+            let fields = read_fields(&data.fields, &self_path, &name.to_string(), source)?;
+            let FieldReads {
+                reads, construct, ..
+            } = &fields;
+            (
+                quote!(#(#reads)* #construct),
+                !data.fields.is_empty(),
+                fields.uses_endian,
+            )
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut uses_reader = false;
+            let mut uses_endian = false;
+            let mut fallback = None;
+            for variant in &data.variants {
+                let attrs = VariantAttrs::parse(&variant.attrs)?;
+                let variant_name = &variant.ident;
+                if attrs.other {
+                    if let Some(id) = &attrs.id {
+                        return Err(Error::new_spanned(
+                            id,
+                            "the other variant is selected by every unknown tag and takes no id",
+                        ));
+                    }
+                    if !matches!(&variant.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+                    {
+                        return Err(Error::new_spanned(
+                            variant,
+                            "the other variant needs a single unnamed field for the tag",
+                        ));
+                    }
+                    if fallback.is_some() {
+                        return Err(Error::new_spanned(
+                            variant_name,
+                            "only one variant can be #[read(other)]",
+                        ));
+                    }
+                    fallback = Some(quote!({ Self::#variant_name(__tag) }));
+                    continue;
+                }
+                let Some(id) = attrs.id else {
+                    return Err(Error::new_spanned(
+                        &variant.ident,
+                        "variant needs a #[read(id = ...)] attribute",
+                    ));
+                };
+                let path = syn::parse_quote!(Self::#variant_name);
+                let fields = read_fields(
+                    &variant.fields,
+                    &path,
+                    &format!("{name}::{variant_name}"),
+                    source,
+                )?;
+                uses_reader |= !variant.fields.is_empty();
+                uses_endian |= fields.uses_endian;
+                let FieldReads {
+                    reads, construct, ..
+                } = fields;
+                arms.push(quote!(if __tag == #id { #(#reads)* #construct } else));
+            }
+            let path = name.to_string();
+            let fallback = fallback.unwrap_or_else(|| {
+                quote! {
+                    {
+                        return ::core::result::Result::Err(::read_primitives::__private::field_error(
+                            ::read_primitives::__private::unknown_tag(&__tag),
+                            #path,
+                            0,
+                        ));
+                    }
+                }
+            });
+            (quote!(#(#arms)* #fallback), uses_reader, uses_endian)
+        }
+        Data::Union(_) => return Err(Error::new_spanned(name, "unions are not supported")),
+    };
+
+    let (magic, asserts) = checks(&container.checks, &name.to_string(), &quote!(0), source);
+    let (reader, counter) = match (uses_reader || container.checks.magic.is_some(), source) {
+        (false, _) => (quote!(_), quote!()),
+        (true, Source::Reader | Source::Bump(_)) => (
+            quote!(__reader),
+            quote! {
+                let mut __counter = ::read_primitives::__private::Counter::new(__reader);
+                let __reader = &mut __counter;
+            },
+        ),
+        (true, Source::Slice(_)) => (quote!(__reader), quote!(let __base = __reader.len();)),
+        (true, Source::Async(backend)) => (
+            quote!(__reader),
+            quote! {
+                let mut __counter = #backend::derive_support::Counter::new(__reader);
+                let __reader = &mut __counter;
+            },
+        ),
+        (true, Source::Embedded) => (
+            quote!(__reader),
+            quote! {
+                let mut __counter = ::read_primitives::embedded::derive_support::Counter::new(__reader);
+                let __reader = &mut __counter;
+            },
+        ),
+    };
+    let (endian, fixed_endian) = match (uses_endian, container.endian) {
+        (false, _) => (quote!(_), quote!()),
+        (true, Some(order)) => (quote!(_), quote!(let __endian = #order;)),
+        (true, None) => (quote!(__endian), quote!()),
+    };
+    let value = if container.checks.asserts.is_empty() {
+        quote!({ #body })
+    } else {
+        quote! {{
+            let __value = { #body };
+            let __self = &__value;
+            #asserts
+            __value
+        }}
+    };
+    let nested = match source {
+        Source::Async(_) | Source::Embedded => quote!(),
+        _ => quote!(let __nested = ::read_primitives::__private::nested()?;),
+    };
+    let body = quote! {
+        #nested
+        #fixed_endian
+        #counter
+        #magic
+        ::core::result::Result::Ok(#value)
+    };
+
+    let mut generics = input.generics.clone();
+    let bound: TokenStream = match source {
+        Source::Reader => quote!(::read_primitives::ReadFrom),
+        Source::Slice(lifetime) => quote!(::read_primitives::ReadFromSlice<#lifetime>),
+        Source::Bump(lifetime) => quote!(::read_primitives::ReadFromIn<#lifetime>),
+        Source::Async(backend) => quote!(#backend::ReadFromAsync),
+        Source::Embedded => quote!(::read_primitives::embedded::ReadFrom),
+    };
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: #bound));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let tag_type = match &container.tag_type {
+        Some(tag_type) => quote!(#tag_type),
+        None => quote!(u64),
+    };
+    Ok(match (tagged, source) {
+        (true, Source::Async(backend)) => quote! {
+            #[automatically_derived]
+            impl #impl_generics #backend::ReadTaggedAsync for #name #ty_generics #where_clause {
+                type Tag = #tag_type;
+
+                fn read_tagged_async<
+                    __R: #backend::derive_support::AsyncRead
+                        + ::core::marker::Unpin
+                        + ?::core::marker::Sized,
+                >(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                    __tag: Self::Tag,
+                ) -> impl ::core::future::Future<Output = ::std::io::Result<Self>> {
+                    async move { #body }
+                }
+            }
+        },
+        (true, _) => quote! {
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::ReadTagged for #name #ty_generics #where_clause {
+                type Tag = #tag_type;
+
+                fn read_tagged<__R: ::std::io::Read + ?::core::marker::Sized>(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                    __tag: Self::Tag,
+                ) -> ::std::io::Result<Self> {
+                    #body
+                }
+            }
+        },
+        (false, Source::Async(backend)) => quote! {
+            #[automatically_derived]
+            impl #impl_generics #backend::ReadFromAsync for #name #ty_generics #where_clause {
+                fn read_from_async<
+                    __R: #backend::derive_support::AsyncRead
+                        + ::core::marker::Unpin
+                        + ?::core::marker::Sized,
+                >(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                ) -> impl ::core::future::Future<Output = ::std::io::Result<Self>> {
+                    async move { #body }
+                }
+            }
+        },
+        (false, Source::Slice(lifetime)) => quote! {
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::ReadFromSlice<#lifetime> for #name #ty_generics #where_clause {
+                fn read_from_slice(
+                    #reader: &mut &#lifetime [u8],
+                    #endian: ::read_primitives::Endian,
+                ) -> ::std::io::Result<Self> {
+                    #body
+                }
+            }
+        },
+        (false, Source::Bump(lifetime)) => quote! {
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::ReadFromIn<#lifetime> for #name #ty_generics #where_clause {
+                fn read_from_in<__R: ::std::io::Read + ?::core::marker::Sized>(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                    __bump: &#lifetime ::read_primitives::__private::bumpalo::Bump,
+                ) -> ::std::io::Result<Self> {
+                    #body
+                }
+            }
+        },
+        (false, Source::Embedded) => quote! {
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::embedded::ReadFrom for #name #ty_generics #where_clause {
+                fn read_from<
+                    __R: ::read_primitives::embedded::derive_support::Read + ?::core::marker::Sized,
+                >(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                ) -> ::core::result::Result<Self, ::read_primitives::embedded::DecodeError<__R::Error>> {
+                    #body
+                }
+            }
+        },
+        (false, Source::Reader) => quote! {
+            #[automatically_derived]
+            impl #impl_generics ::read_primitives::ReadFrom for #name #ty_generics #where_clause {
+                fn read_from<__R: ::std::io::Read + ?::core::marker::Sized>(
+                    #reader: &mut __R,
+                    #endian: ::read_primitives::Endian,
+                ) -> ::std::io::Result<Self> {
+                    #body
+                }
+            }
+        },
+    })
+}
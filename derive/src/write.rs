@@ -0,0 +1,239 @@
+use crate::attr::{Checks, ContainerAttrs, FieldAttrs, Mode, VariantAttrs};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Path, Result};
+
+/// Code writing the magic that a read checks; conditions are not checked on write
+fn magic(checks: &Checks) -> TokenStream {
+    match &checks.magic {
+        Some(magic) => quote!(::std::io::Write::write_all(__writer, #magic)?;),
+        None => quote!(),
+    }
+}
+
+/// Code writing a struct's or variant's fields in order
+struct FieldWrites {
+    /// Pattern binding each field to a local reference
+    pattern: TokenStream,
+    /// Statements writing the locals
+    writes: TokenStream,
+    /// Whether a field inherits the byte order
+    uses_endian: bool,
+    /// Whether the writes need the offset from the start of the value
+    uses_position: bool,
+}
+
+fn write_fields(fields: &Fields, path: &Path, context: &str) -> Result<FieldWrites> {
+    let mut writes = Vec::new();
+    let mut locals = Vec::new();
+    let mut uses_endian = false;
+    let mut uses_position = false;
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        uses_position |= attrs.padding.uses_position();
+        let (local, context) = match &field.ident {
+            Some(ident) => (ident.clone(), format!("{context}.{ident}")),
+            None => (
+                format_ident!("__field{}", index),
+                format!("{context}.{index}"),
+            ),
+        };
+        let endian = match attrs.endian {
+            Some(order) => quote!(#order),
+            None => {
+                uses_endian = true;
+                quote!(__endian)
+            }
+        };
+        let write =
+            match &attrs.mode {
+                Some(Mode::Version { .. }) => return Err(Error::new_spanned(
+                    field,
+                    "version_from is not supported by WriteTo, which can't tell which layout to \
+                     write",
+                )),
+                None | Some(Mode::Tag(_) | Mode::DefaultOnEof) => {
+                    quote!(::read_primitives::WriteTo::write_to(#local, __writer, #endian)?;)
+                }
+                Some(Mode::Count(count)) => quote! {
+                    ::read_primitives::__private::check_size(
+                        #context,
+                        ::read_primitives::__private::size(#count)?,
+                        #local.len(),
+                    )?;
+                    ::read_primitives::__private::write_slice(__writer, #endian, #local)?;
+                },
+                Some(Mode::UntilEof) => {
+                    quote!(::read_primitives::__private::write_slice(__writer, #endian, #local)?;)
+                }
+                Some(Mode::Len(len)) => quote! {
+                    ::read_primitives::__private::check_size(
+                        #context,
+                        ::read_primitives::__private::size(#len)?,
+                        #local.len(),
+                    )?;
+                    ::std::io::Write::write_all(
+                        __writer,
+                        ::core::convert::AsRef::<[u8]>::as_ref(#local),
+                    )?;
+                },
+            };
+        let magic = magic(&attrs.checks);
+        let write = match &attrs.condition {
+            Some(condition) => quote! {
+                ::read_primitives::__private::check_present(#context, #condition, #local.is_some())?;
+                if let ::core::option::Option::Some(#local) = #local {
+                    #magic
+                    #write
+                }
+            },
+            None => quote!(#magic #write),
+        };
+        let position = quote!(::read_primitives::__private::Counter::position(__writer));
+        let pad_before = attrs.padding.before.as_ref().map(|len| {
+            quote! {
+                ::read_primitives::__private::write_zeros(
+                    __writer,
+                    ::read_primitives::__private::size(#len)?,
+                )?;
+            }
+        });
+        let align = attrs.padding.align.as_ref().map(|align| {
+            quote! {
+                ::read_primitives::__private::write_zeros(
+                    __writer,
+                    ::read_primitives::__private::align(
+                        #position,
+                        ::read_primitives::__private::size(#align)?,
+                    )?,
+                )?;
+            }
+        });
+        let pad_to = attrs.padding.to.as_ref().map(|size| {
+            quote! {
+                ::read_primitives::__private::write_zeros(
+                    __writer,
+                    ::read_primitives::__private::check_pad_to(
+                        #context,
+                        #position - __start,
+                        ::read_primitives::__private::size(#size)?,
+                    )?,
+                )?;
+            }
+        });
+        let start = pad_to.as_ref().map(|_| quote!(let __start = #position;));
+        writes.push(quote!(#pad_before #align #start #write #pad_to));
+        locals.push(local);
+    }
+    let pattern = match fields {
+        Fields::Named(_) => quote!(#path { #(#locals),* }),
+        Fields::Unnamed(_) => quote!(#path(#(#locals),*)),
+        Fields::Unit => quote!(#path),
+    };
+    Ok(FieldWrites {
+        pattern,
+        writes: quote!(#(#writes)*),
+        uses_endian,
+        uses_position,
+    })
+}
+
+/// Expand `#[derive(WriteTo)]` for a struct or enum
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let name = &input.ident;
+
+    // writes of the value after the container magic, and whether they use writer and byte order
+    let mut uses_position = false;
+    let (body, uses_writer, uses_endian) = match &input.data {
+        Data::Struct(data) => {
+            let fields = write_fields(&data.fields, &syn::parse_quote!(Self), &name.to_string())?;
+            let FieldWrites {
+                pattern, writes, ..
+            } = &fields;
+            uses_position = fields.uses_position;
+            let body = if data.fields.is_empty() {
+                quote!()
+            } else {
+                quote!(let #pattern = self; #writes)
+            };
+            (body, !data.fields.is_empty(), fields.uses_endian)
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut uses_writer = false;
+            let mut uses_endian = false;
+            for variant in &data.variants {
+                let variant_name = &variant.ident;
+                // the raw tag of the other variant belongs to the tag field, like any tag
+                if VariantAttrs::parse(&variant.attrs)?.other {
+                    arms.push(quote!(Self::#variant_name(..) => {}));
+                    continue;
+                }
+                let fields = write_fields(
+                    &variant.fields,
+                    &syn::parse_quote!(Self::#variant_name),
+                    &format!("{name}::{variant_name}"),
+                )?;
+                uses_writer |= !variant.fields.is_empty();
+                uses_endian |= fields.uses_endian;
+                uses_position |= fields.uses_position;
+                let FieldWrites {
+                    pattern, writes, ..
+                } = fields;
+                arms.push(quote!(#pattern => { #writes }));
+            }
+            (quote!(match self { #(#arms)* }), uses_writer, uses_endian)
+        }
+        Data::Union(_) => return Err(Error::new_spanned(name, "unions are not supported")),
+    };
+
+    let magic = magic(&container.checks);
+    let counter = if uses_position {
+        quote! {
+            let mut __counter = ::read_primitives::__private::Counter::new(__writer);
+            let __writer = &mut __counter;
+        }
+    } else {
+        quote!()
+    };
+    let writer = if uses_writer || container.checks.magic.is_some() {
+        quote!(__writer)
+    } else {
+        quote!(_)
+    };
+    let (endian, fixed_endian) = match (uses_endian, container.endian) {
+        (false, _) => (quote!(_), quote!()),
+        (true, Some(order)) => (quote!(_), quote!(let __endian = #order;)),
+        (true, None) => (quote!(__endian), quote!()),
+    };
+
+    let mut generics = input.generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: ::read_primitives::WriteTo));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::read_primitives::WriteTo for #name #ty_generics #where_clause {
+            fn write_to<__W: ::std::io::Write + ?::core::marker::Sized>(
+                &self,
+                #writer: &mut __W,
+                #endian: ::read_primitives::Endian,
+            ) -> ::std::io::Result<()> {
+                #fixed_endian
+                #counter
+                #magic
+                #body
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
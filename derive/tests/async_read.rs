@@ -0,0 +1,127 @@
+use read_primitives::tokio::{ReadFromAsync, ReadTaggedAsync};
+use read_primitives::{Endian, ReadFrom, ReadTagged};
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(
+    little,
+    tokio,
+    futures,
+    magic = b"PK",
+    assert = "self.names.len() <= 4"
+)]
+struct Archive<T> {
+    count: u8,
+    #[read(align = 2, check = "range 1..")]
+    version: u16,
+    #[read(count = "count")]
+    names: Vec<Name>,
+    kind: u8,
+    #[read(tag = "kind")]
+    body: Body,
+    trailer: T,
+    #[read(until_eof)]
+    rest: Vec<u8>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(tokio, futures)]
+struct Name {
+    len: u8,
+    #[read(len = "len")]
+    name: String,
+}
+
+#[derive(ReadTagged, Debug, PartialEq)]
+#[read(tag_type = "u8", tokio, futures)]
+enum Body {
+    #[read(id = 1)]
+    Empty,
+    #[read(id = 2)]
+    Value(#[read(big)] u16),
+}
+
+const BYTES: &[u8] = b"PK\x02\0\x03\0\x01a\x02bc\x02\x01\x02\x7f\x0a\x0b";
+
+fn expected() -> Archive<i8> {
+    Archive {
+        count: 2,
+        version: 3,
+        names: vec![
+            Name {
+                len: 1,
+                name: "a".into(),
+            },
+            Name {
+                len: 2,
+                name: "bc".into(),
+            },
+        ],
+        kind: 2,
+        body: Body::Value(0x0102),
+        trailer: 0x7f,
+        rest: vec![0x0a, 0x0b],
+    }
+}
+
+#[::tokio::test]
+async fn tokio_matches_sync() {
+    let sync = Archive::<i8>::read_from(&mut &BYTES[..], Endian::Big).unwrap();
+    assert_eq!(expected(), sync);
+    let value = <Archive<i8> as ReadFromAsync>::read_from_async(&mut &BYTES[..], Endian::Big)
+        .await
+        .unwrap();
+    assert_eq!(expected(), value);
+    let body = Body::read_tagged_async(&mut &[][..], Endian::Big, 1).await;
+    assert_eq!(Body::Empty, body.unwrap());
+}
+
+#[test]
+fn futures_matches_sync() {
+    let value = ::futures::executor::block_on(
+        <Archive<i8> as read_primitives::futures::ReadFromAsync>::read_from_async(
+            &mut &BYTES[..],
+            Endian::Big,
+        ),
+    );
+    assert_eq!(expected(), value.unwrap());
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(tokio)]
+struct Optional {
+    kind: u8,
+    #[read(if = "kind == 2")]
+    extra: Option<u8>,
+    #[read(default_on_eof)]
+    size: u16,
+}
+
+#[::tokio::test]
+async fn default_on_eof() {
+    for bytes in [&[1][..], &[1, 0, 5], &[2, 7, 0, 5]] {
+        let sync = Optional::read_from(&mut &bytes[..], Endian::Little).unwrap();
+        let value = Optional::read_from_async(&mut &bytes[..], Endian::Little)
+            .await
+            .unwrap();
+        assert_eq!(sync, value);
+    }
+    let err = Optional::read_from_async(&mut &[1, 0][..], Endian::Little)
+        .await
+        .unwrap_err();
+    assert_eq!(std::io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[::tokio::test]
+async fn async_errors_match_sync() {
+    let inputs: [&[u8]; 4] = [b"PX", &BYTES[..9], b"PK\x02\0\0\0", b"PK\x00\0\x01\0\x05"];
+    for bytes in inputs {
+        let sync = Archive::<i8>::read_from(&mut &bytes[..], Endian::Big).unwrap_err();
+        let err = <Archive<i8> as ReadFromAsync>::read_from_async(&mut &bytes[..], Endian::Big)
+            .await
+            .unwrap_err();
+        assert_eq!(sync.kind(), err.kind());
+        assert_eq!(sync.to_string(), err.to_string());
+    }
+    let err = Body::read_tagged(&mut &[][..], Endian::Big, 3).unwrap_err();
+    assert_eq!("Body at offset 0x0: unknown tag 3", err.to_string());
+}
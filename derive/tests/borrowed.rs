@@ -0,0 +1,101 @@
+use read_primitives::{Endian, ReadFrom, ReadFromSlice, WriteTo};
+use std::io;
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+#[read(little)]
+struct Entry<'a> {
+    name_len: u8,
+    #[read(len = "name_len")]
+    name: &'a str,
+    data_len: u16,
+    #[read(len = "data_len")]
+    data: &'a [u8],
+    #[read(len = "1")]
+    owned: String,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Archive<'a, T> {
+    count: u8,
+    #[read(count = "count")]
+    entries: Vec<Entry<'a>>,
+    trailer: T,
+}
+
+#[test]
+fn generic_struct() {
+    let mut bytes: &[u8] = &[0, 1, 0, 2];
+    let pair = Pair::<u16>::read_from(&mut bytes, Endian::Big).unwrap();
+    assert_eq!(
+        Pair {
+            first: 1,
+            second: 2
+        },
+        pair
+    );
+    let mut written = Vec::new();
+    pair.write_to(&mut written, Endian::Little).unwrap();
+    assert_eq!([1, 0, 2, 0], written.as_slice());
+}
+
+#[test]
+fn borrowed_fields() {
+    let bytes = b"\x02id\x03\x00abcz";
+    let mut input = &bytes[..];
+    let entry = Entry::read_from_slice(&mut input, Endian::Big).unwrap();
+    assert_eq!("id", entry.name);
+    assert_eq!(b"abc", entry.data);
+    assert_eq!("z", entry.owned);
+    assert!(std::ptr::eq(&bytes[1], entry.name.as_ptr()));
+    assert!(input.is_empty());
+    let mut written = Vec::new();
+    entry.write_to(&mut written, Endian::Big).unwrap();
+    assert_eq!(&bytes[..], written.as_slice());
+}
+
+#[test]
+fn nested_borrowed_and_generic() {
+    let bytes = b"\x02\x01a\x00\x00b\x00\x00\x00c\x09\x08";
+    let archive = Archive::<Pair<u8>>::read_from_slice(&mut &bytes[..], Endian::Little).unwrap();
+    assert_eq!(
+        ["a", ""],
+        [archive.entries[0].name, archive.entries[1].name]
+    );
+    assert_eq!(
+        Pair {
+            first: 9,
+            second: 8
+        },
+        archive.trailer
+    );
+
+    let err = Archive::<Pair<u8>>::read_from_slice(&mut &bytes[..11], Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!(
+        "Pair.second at offset 0xb: failed to fill whole buffer",
+        err.to_string()
+    );
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Note<'a> {
+    len: u8,
+    #[read(len = "len")]
+    text: &'a str,
+    #[read(default_on_eof)]
+    color: u8,
+}
+
+#[test]
+fn default_on_eof_from_slice() {
+    let note = Note::read_from_slice(&mut &b"\x02hi"[..], Endian::Big).unwrap();
+    assert_eq!(("hi", 0), (note.text, note.color));
+    let note = Note::read_from_slice(&mut &b"\x02hi\x05"[..], Endian::Big).unwrap();
+    assert_eq!(5, note.color);
+}
@@ -0,0 +1,53 @@
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+use read_primitives::{Endian, FieldError, ReadFrom, ReadFromIn};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(bump, little)]
+struct Record<'bump> {
+    tag: u16,
+    name_len: u8,
+    #[read(len = "name_len")]
+    name: &'bump str,
+    #[read(len = "2")]
+    data: &'bump [u8],
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(bump, magic = b"RC")]
+struct File<'bump, T> {
+    count: u8,
+    #[read(count = "count")]
+    records: BumpVec<'bump, Record<'bump>>,
+    #[read(count = "2")]
+    trailer: Vec<T>,
+}
+
+#[test]
+fn read_in_bump() {
+    let bytes: &[u8] = b"RC\x02\x01\x00\x02ab\xff\xfe\x02\x00\x00\x01\x02\x00\x03\x00\x04";
+    let bump = Bump::new();
+    let file = File::<u16>::read_from_in(&mut &bytes[..], Endian::Big, &bump).unwrap();
+    assert_eq!(2, file.records.len());
+    assert_eq!((1, "ab"), (file.records[0].tag, file.records[0].name));
+    assert_eq!([0xff, 0xfe], file.records[0].data);
+    assert_eq!(
+        ("", &[1, 2][..]),
+        (file.records[1].name, file.records[1].data)
+    );
+    assert_eq!([3, 4], *file.trailer);
+    assert!(bump.allocated_bytes() > 0);
+}
+
+#[test]
+fn errors() {
+    let bump = Bump::new();
+    let bytes: &[u8] = b"RC\x01\x01\x00\x02a\xff";
+    let err = File::<u8>::read_from_in(&mut &bytes[..], Endian::Big, &bump).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let field = err.get_ref().unwrap().downcast_ref::<FieldError>().unwrap();
+    assert_eq!(("Record.name", 6), (field.path(), field.offset()));
+    let err = Record::read_from_in(&mut &[1, 0, 5][..], Endian::Big, &bump).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
@@ -0,0 +1,137 @@
+use read_primitives::{Endian, ReadFrom};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(little, magic = b"RIFF", assert = "self.version <= 3")]
+struct Riff {
+    version: u16,
+    #[read(magic = b"\0fmt", assert = "channels > 0", assert = "channels <= 8")]
+    channels: u8,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(magic = b"END")]
+struct Trailer;
+
+fn read(bytes: &[u8]) -> io::Result<Riff> {
+    Riff::read_from(&mut &bytes[..], Endian::Big)
+}
+
+#[test]
+fn matching_checks() {
+    assert_eq!(
+        Riff {
+            version: 3,
+            channels: 2
+        },
+        read(b"RIFF\x03\0\0fmt\x02").unwrap()
+    );
+    assert_eq!(
+        Trailer,
+        Trailer::read_from(&mut &b"END"[..], Endian::Big).unwrap()
+    );
+}
+
+#[test]
+fn magic_mismatch() {
+    let err = read(b"RIFX\x03\0\0fmt\x02").unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        r#"Riff at offset 0x0: expected magic b"RIFF", found b"RIFX""#,
+        err.to_string()
+    );
+    let err = read(b"RIFF\x03\0\xfffmt\x02").unwrap_err();
+    assert_eq!(
+        r#"Riff.channels at offset 0x6: expected magic b"\x00fmt", found b"\xfffmt""#,
+        err.to_string()
+    );
+    let err = read(b"RI").unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn assertion_failures() {
+    let err = read(b"RIFF\x04\0\0fmt\x02").unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        "Riff at offset 0x0: assertion `self.version <= 3` failed",
+        err.to_string()
+    );
+    let err = read(b"RIFF\x03\0\0fmt\x09").unwrap_err();
+    assert_eq!(
+        "Riff.channels at offset 0x6: assertion `channels <= 8` failed",
+        err.to_string()
+    );
+}
+
+#[derive(Debug)]
+struct ChecksumError(u8);
+
+impl std::fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bad checksum {:#x}", self.0)
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+fn validate_checksum(checksum: &u8) -> Result<(), ChecksumError> {
+    match checksum {
+        0xa5 => Ok(()),
+        &other => Err(ChecksumError(other)),
+    }
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Block {
+    #[read(check = "range 1..=64")]
+    size: u8,
+    #[read(check = "range ..0x8000", check_fn = "validate_offset")]
+    offset: u16,
+    #[read(check_fn = "validate_checksum")]
+    checksum: u8,
+}
+
+fn validate_offset(offset: &u16) -> Result<(), String> {
+    if offset % 2 == 0 {
+        Ok(())
+    } else {
+        Err(format!("odd offset {offset}"))
+    }
+}
+
+#[test]
+fn validators() {
+    let read = |bytes: &[u8]| Block::read_from(&mut &bytes[..], Endian::Big);
+    assert_eq!(
+        Block {
+            size: 64,
+            offset: 2,
+            checksum: 0xa5
+        },
+        read(b"\x40\0\x02\xa5").unwrap()
+    );
+    let err = read(b"\0\0\x02\xa5").unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        "Block.size at offset 0x0: 0 is out of range 1..=64",
+        err.to_string()
+    );
+    let err = read(b"\x01\x80\x00\xa5").unwrap_err();
+    assert_eq!(
+        "Block.offset at offset 0x1: 32768 is out of range ..0x8000",
+        err.to_string()
+    );
+    let err = read(b"\x01\0\x03\xa5").unwrap_err();
+    assert_eq!("Block.offset at offset 0x1: odd offset 3", err.to_string());
+    let err = read(b"\x01\0\x02\x00").unwrap_err();
+    assert_eq!(
+        "Block.checksum at offset 0x3: bad checksum 0x0",
+        err.to_string()
+    );
+    let source = std::error::Error::source(err.get_ref().unwrap())
+        .and_then(|source| source.downcast_ref::<io::Error>())
+        .and_then(|source| source.get_ref())
+        .and_then(|source| source.downcast_ref::<ChecksumError>());
+    assert!(matches!(source, Some(ChecksumError(0))));
+}
@@ -0,0 +1,63 @@
+use read_primitives::{Endian, FieldError, ReadFrom};
+use std::io;
+
+#[derive(ReadFrom, Debug)]
+#[read(big)]
+struct Section {
+    offset: u32,
+    #[read(assert = "size < 0x1000")]
+    size: u32,
+}
+
+#[derive(ReadFrom, Debug)]
+#[read(big)]
+struct Header {
+    version: u16,
+    section_count: u8,
+    #[read(count = "section_count")]
+    sections: Vec<Section>,
+}
+
+#[derive(ReadFrom, Debug)]
+struct File {
+    magic: [u8; 4],
+    header: Header,
+}
+
+fn field_error(err: &io::Error) -> &FieldError {
+    err.get_ref().unwrap().downcast_ref().unwrap()
+}
+
+#[test]
+fn valid_file() {
+    let bytes = b"FILE\x00\x01\x01\x00\x00\x00\x0b\x00\x00\x00\x04";
+    let file = File::read_from(&mut &bytes[..], Endian::Little).unwrap();
+    assert_eq!((*b"FILE", 1), (file.magic, file.header.version));
+    assert_eq!(1, file.header.section_count);
+    let section = &file.header.sections[0];
+    assert_eq!((11, 4), (section.offset, section.size));
+}
+
+#[test]
+fn truncated_field() {
+    let err = Header::read_from(&mut &[0u8, 1][..], Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!("Header.section_count", field_error(&err).path());
+    assert_eq!(2, field_error(&err).offset());
+    assert!(err
+        .to_string()
+        .starts_with("Header.section_count at offset 0x2: "));
+}
+
+#[test]
+fn nested_offsets_are_absolute() {
+    let mut bytes = b"FILE\x00\x01\x02".to_vec();
+    bytes.extend([0, 0, 0, 0, 0, 0, 0, 8]);
+    bytes.extend([0, 0, 0, 0x10, 0, 0, 0x10, 0]);
+    let err = File::read_from(&mut bytes.as_slice(), Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        "Section.size at offset 0x13: assertion `size < 0x1000` failed",
+        err.to_string()
+    );
+}
@@ -0,0 +1,72 @@
+use read_primitives::embedded::{self, DecodeError, DecodeErrorKind};
+use read_primitives::{Endian, ReadFrom};
+use std::convert::Infallible;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(embedded)]
+struct Sample {
+    channel: u8,
+    #[read(little, check = "range ..=1000")]
+    value: i16,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(
+    big,
+    embedded,
+    no_std,
+    magic = b"SN",
+    assert = "self.samples[0].channel == 0"
+)]
+struct Frame {
+    flags: u8,
+    #[read(if = "flags & 1 != 0", pad_before = 1)]
+    seq: Option<u16>,
+    #[read(align = 4)]
+    samples: [Sample; 2],
+}
+
+fn read(bytes: &[u8]) -> Result<Frame, DecodeError<Infallible>> {
+    <Frame as embedded::ReadFrom>::read_from(&mut &bytes[..], Endian::Little)
+}
+
+#[test]
+fn embedded_read() {
+    let frame = read(b"SN\x01\x00\x00\x07\x00\x00\x00\x05\x00\x01\x06\x00").unwrap();
+    assert_eq!(Some(7), frame.seq);
+    assert_eq!(
+        [
+            Sample {
+                channel: 0,
+                value: 5
+            },
+            Sample {
+                channel: 1,
+                value: 6
+            }
+        ],
+        frame.samples
+    );
+    let std = <Sample as ReadFrom>::read_from(&mut &[2u8, 1, 0][..], Endian::Big).unwrap();
+    assert_eq!(1, std.value);
+}
+
+#[test]
+fn embedded_errors() {
+    let err = read(b"SX").unwrap_err();
+    assert_eq!(&DecodeErrorKind::Magic, err.kind());
+    assert_eq!(
+        "Frame at offset 0x0: magic bytes do not match",
+        err.to_string()
+    );
+    let err = read(b"SN\x00\x00\x00\xe9\x03").unwrap_err();
+    assert_eq!(&DecodeErrorKind::OutOfRange("..=1000"), err.kind());
+    assert_eq!((Some("Sample.value"), 5), (err.path(), err.offset()));
+    let err = read(b"SN\x00\x00\x01\x05\x00\x01\x06\x00").unwrap_err();
+    assert_eq!(
+        &DecodeErrorKind::Assertion("self.samples[0].channel == 0"),
+        err.kind()
+    );
+    let err = read(b"SN\x00\x00\x00\x05").unwrap_err();
+    assert_eq!(&DecodeErrorKind::UnexpectedEof, err.kind());
+}
@@ -0,0 +1,45 @@
+use read_primitives::{Endian, FixedSize, ReadFrom, RecordTable};
+use std::io::Cursor;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(big, fixed_size)]
+struct Entry {
+    id: u16,
+    #[read(align = 4)]
+    offset: u32,
+    #[read(magic = b"E", pad_to = 4)]
+    flags: [u8; 2],
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(magic = b"PAIR", fixed_size)]
+struct Pair<T> {
+    first: T,
+    #[read(pad_before = 1)]
+    second: (T, bool),
+}
+
+const ENTRIES: &[u8] = &[
+    0, 1, 0xee, 0xee, 0, 0, 0, 8, b'E', 3, 4, 0, 0, 2, 0, 0, 0, 0, 0, 9, b'E', 5, 6, 0,
+];
+
+#[test]
+fn sizes() {
+    assert_eq!(12, Entry::SIZE);
+    assert_eq!(10, Pair::<u16>::SIZE);
+    assert_eq!(8, Pair::<u8>::SIZE);
+}
+
+#[test]
+fn batch_and_table() {
+    let entries = Entry::read_batch(&mut &ENTRIES[..], Endian::Little, 2).unwrap();
+    let second = Entry {
+        id: 2,
+        offset: 9,
+        flags: [5, 6],
+    };
+    assert_eq!(8, entries[0].offset);
+    assert_eq!(second, entries[1]);
+    let mut table = RecordTable::<_, Entry>::fixed(Cursor::new(ENTRIES), 0, 2, Endian::Little);
+    assert_eq!(second, table.get(1).unwrap());
+}
@@ -0,0 +1,109 @@
+use read_primitives::{Endian, IncrementalDecoder, ReadFrom};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(magic = b"MSG", incremental, assert = "self.name.len() < 8")]
+struct Message<T> {
+    kind: T,
+    #[read(align = 4, check = "range 1..=4")]
+    name_len: u8,
+    #[read(len = "name_len")]
+    name: String,
+    #[read(big, count = "name_len as usize / 2")]
+    values: Vec<u16>,
+}
+
+const BYTES: &[u8] = b"MSG\x07\x04abcd\0\x01\0\x02";
+
+fn message() -> Message<u8> {
+    Message {
+        kind: 7,
+        name_len: 4,
+        name: "abcd".into(),
+        values: vec![1, 2],
+    }
+}
+
+#[test]
+fn byte_at_a_time() {
+    let mut decoder = IncrementalDecoder::<Message<u8>>::new(Endian::Little);
+    let mut values = Vec::new();
+    for _ in 0..2 {
+        for byte in BYTES {
+            decoder.push(&[*byte]);
+            if let Some(value) = decoder.poll_value().unwrap() {
+                values.push(value);
+            }
+        }
+    }
+    assert_eq!(vec![message(), message()], values);
+    assert_eq!(0, decoder.buffered());
+    let whole = Message::<u8>::read_from(&mut &BYTES[..], Endian::Little).unwrap();
+    assert_eq!(message(), whole);
+}
+
+#[test]
+fn buffers_only_the_current_field() {
+    let mut decoder = IncrementalDecoder::<Message<u8>>::new(Endian::Little);
+    decoder.push(&BYTES[..10]);
+    assert_eq!(None, decoder.poll_value().unwrap());
+    assert_eq!(1, decoder.buffered());
+    decoder.push(&BYTES[10..]);
+    assert_eq!(Some(message()), decoder.poll_value().unwrap());
+}
+
+#[test]
+fn errors_name_the_field() {
+    let mut decoder = IncrementalDecoder::<Message<u8>>::new(Endian::Little);
+    decoder.push(b"MSG\x07\x04");
+    decoder.push(b"ab\xffd");
+    let err = decoder.poll_value().unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert!(err.to_string().starts_with("Message.name at offset 0x5"));
+    decoder.clear();
+    decoder.push(b"MSH");
+    let err = decoder.poll_value().unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(big, incremental, serde)]
+struct Record {
+    id: u32,
+    len: u16,
+    #[read(count = "len")]
+    samples: Vec<i16>,
+}
+
+#[test]
+fn checkpoint_and_resume() {
+    let bytes = [0, 0, 0, 9, 0, 2, 0, 1, 0xff, 0xff];
+    let mut decoder = IncrementalDecoder::<Record>::new(Endian::Big);
+    decoder.push(&bytes[..8]);
+    assert_eq!(None, decoder.poll_value().unwrap());
+    let checkpoint = bincode::serialize(&decoder).unwrap();
+    drop(decoder);
+
+    let mut resumed: IncrementalDecoder<Record> = bincode::deserialize(&checkpoint).unwrap();
+    assert_eq!(2, resumed.buffered());
+    resumed.push(&bytes[8..]);
+    let record = resumed.poll_value().unwrap().unwrap();
+    assert_eq!((9, vec![1, -1]), (record.id, record.samples));
+    assert_eq!(2, record.len);
+}
+
+#[test]
+fn corrupted_checkpoint() {
+    let mut decoder = IncrementalDecoder::<Record>::new(Endian::Big);
+    decoder.push(&[0, 0, 0, 9, 0, 2, 0, 1]);
+    assert_eq!(None, decoder.poll_value().unwrap());
+    let checkpoint = bincode::serialize(&decoder).unwrap();
+    // the step is the first field, after which `id` and `len` are decoded
+    assert_eq!(3, checkpoint[0]);
+    for step in [2, 4, 9] {
+        let mut corrupted = checkpoint.clone();
+        corrupted[0] = step;
+        let err = bincode::deserialize::<IncrementalDecoder<Record>>(&corrupted).unwrap_err();
+        assert_eq!("incremental state is corrupted", err.to_string());
+    }
+}
@@ -0,0 +1,87 @@
+use read_primitives::{Endian, LimitExceeded, Limits, ReadFrom, ReadFromSlice};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Leaf {
+    value: u8,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Branch {
+    count: u8,
+    #[read(count = "count")]
+    leaves: Vec<Leaf>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Tree {
+    branch: Branch,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Named {
+    len: u8,
+    #[read(len = "len")]
+    name: String,
+    #[read(until_eof)]
+    rest: Vec<u8>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Borrowed<'a> {
+    len: u8,
+    #[read(len = "len")]
+    name: &'a str,
+}
+
+fn limit(err: &io::Error) -> LimitExceeded {
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let inner = err.get_ref().unwrap();
+    if let Some(limit) = inner.downcast_ref::<LimitExceeded>() {
+        return *limit;
+    }
+    limit(inner.source().unwrap().downcast_ref::<io::Error>().unwrap())
+}
+
+#[test]
+fn depth() {
+    let limits = Limits {
+        max_depth: 2,
+        ..Limits::UNLIMITED
+    };
+    let mut shallow: &[u8] = &[1, 7];
+    assert!(Branch::read_from_with_limits(&mut shallow, Endian::Big, limits).is_ok());
+    let mut deep: &[u8] = &[1, 7];
+    let err = Tree::read_from_with_limits(&mut deep, Endian::Big, limits).unwrap_err();
+    assert_eq!(LimitExceeded::Depth { max: 2 }, limit(&err));
+    let mut deep: &[u8] = &[1, 7];
+    assert!(Tree::read_from(&mut deep, Endian::Big).is_ok());
+}
+
+#[test]
+fn elements_and_bytes() {
+    let limits = Limits {
+        max_elements: 2,
+        max_bytes_per_field: 2,
+        ..Limits::UNLIMITED
+    };
+    let mut wide: &[u8] = &[3, 0, 0, 0];
+    let err = Branch::read_from_with_limits(&mut wide, Endian::Big, limits).unwrap_err();
+    assert_eq!(LimitExceeded::Elements { count: 3, max: 2 }, limit(&err));
+
+    let mut name: &[u8] = &[2, b'a', b'b', 1, 2];
+    let named = Named::read_from_with_limits(&mut name, Endian::Big, limits).unwrap();
+    assert_eq!("ab", named.name);
+    let mut long: &[u8] = &[3, b'a', b'b', b'c'];
+    let err = Named::read_from_with_limits(&mut long, Endian::Big, limits).unwrap_err();
+    assert_eq!(LimitExceeded::FieldBytes { len: 3, max: 2 }, limit(&err));
+    let mut trailing: &[u8] = &[0, 1, 2, 3];
+    let err = Named::read_from_with_limits(&mut trailing, Endian::Big, limits).unwrap_err();
+    assert_eq!(LimitExceeded::Elements { count: 3, max: 2 }, limit(&err));
+
+    let mut borrowed: &[u8] = &[3, b'a', b'b', b'c'];
+    let err = limits
+        .apply(|| Borrowed::read_from_slice(&mut borrowed, Endian::Big))
+        .unwrap_err();
+    assert_eq!(LimitExceeded::FieldBytes { len: 3, max: 2 }, limit(&err));
+}
@@ -0,0 +1,63 @@
+use read_primitives::{Endian, ReadFrom, WriteTo};
+use std::io;
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+#[read(little)]
+struct Record {
+    kind: u8,
+    #[read(align = 4)]
+    length: u32,
+    #[read(pad_before = 2, pad_to = 8)]
+    name: [u8; 3],
+    #[read(pad_to = "length")]
+    flags: u16,
+}
+
+const BYTES: &[u8] = b"\x07\0\0\0\x04\0\0\0\0\0abc\0\0\0\0\0\x01\x02\0\0";
+
+#[test]
+fn skip_padding() {
+    let record = Record::read_from(&mut &BYTES[..], Endian::Big).unwrap();
+    assert_eq!(
+        Record {
+            kind: 7,
+            length: 4,
+            name: *b"abc",
+            flags: 0x0201,
+        },
+        record
+    );
+    let mut written = Vec::new();
+    record.write_to(&mut written, Endian::Big).unwrap();
+    assert_eq!(BYTES, written.as_slice());
+}
+
+#[test]
+fn padding_errors() {
+    let err = Record::read_from(&mut &BYTES[..21], Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!(
+        "Record.flags at offset 0x12: unexpected end of file",
+        err.to_string()
+    );
+    let mut bytes = BYTES.to_vec();
+    bytes[4] = 1;
+    let err = Record::read_from(&mut bytes.as_slice(), Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        "Record.flags at offset 0x12: field of 2 bytes is longer than its padded size 1",
+        err.to_string()
+    );
+    let record = Record {
+        kind: 0,
+        length: 1,
+        name: *b"abc",
+        flags: 0,
+    };
+    let err = record.write_to(&mut Vec::new(), Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    assert_eq!(
+        "Record.flags: field of 2 bytes is longer than its padded size 1",
+        err.to_string()
+    );
+}
@@ -0,0 +1,86 @@
+use read_primitives::{Endian, ReadFrom};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Point {
+    x: i16,
+    y: i16,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(big)]
+struct Record {
+    id: u32,
+    #[read(little)]
+    flags: u16,
+    origin: Point,
+    tag: [u8; 2],
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Pair(#[read(big)] u16, u16);
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Empty;
+
+#[test]
+fn inherited_byte_order() {
+    let mut bytes: &[u8] = &[1, 0, 0xff, 0xff];
+    let point = Point::read_from(&mut bytes, Endian::Little).unwrap();
+    assert_eq!(Point { x: 1, y: -1 }, point);
+}
+
+#[test]
+fn container_and_field_byte_order() {
+    let mut bytes: &[u8] = &[0, 0, 0, 7, 1, 0, 0, 2, 0, 3, b'o', b'k'];
+    let record = Record::read_from(&mut bytes, Endian::Little).unwrap();
+    assert_eq!(
+        Record {
+            id: 7,
+            flags: 1,
+            origin: Point { x: 2, y: 3 },
+            tag: *b"ok",
+        },
+        record
+    );
+}
+
+#[test]
+fn tuple_and_unit_structs() {
+    let mut bytes: &[u8] = &[0, 1, 1, 0];
+    assert_eq!(
+        Pair(1, 1),
+        Pair::read_from(&mut bytes, Endian::Little).unwrap()
+    );
+    assert_eq!(Empty, Empty::read_from(&mut bytes, Endian::Little).unwrap());
+}
+
+#[test]
+fn short_input() {
+    let mut bytes: &[u8] = &[0, 0, 0, 7, 1];
+    let err = Record::read_from(&mut bytes, Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(little)]
+struct Extended {
+    size: u16,
+    #[read(default_on_eof)]
+    flags: u16,
+    #[read(default_on_eof)]
+    extra: [u8; 2],
+}
+
+#[test]
+fn default_on_eof() {
+    let read = |bytes: &[u8]| Extended::read_from(&mut &bytes[..], Endian::Big);
+    let old = read(&[3, 0]).unwrap();
+    assert_eq!((3, 0, [0; 2]), (old.size, old.flags, old.extra));
+    let newer = read(&[3, 0, 1, 0]).unwrap();
+    assert_eq!((1, [0; 2]), (newer.flags, newer.extra));
+    assert_eq!([7, 8], read(&[3, 0, 1, 0, 7, 8]).unwrap().extra);
+    let err = read(&[3, 0, 1]).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    assert!(err.to_string().starts_with("Extended.flags at offset 0x2"));
+}
@@ -0,0 +1,133 @@
+use read_primitives::{Endian, ReadFrom, ReadFromSlice, WriteTo};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Header {
+    num_entries: u16,
+    name_len: u8,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(big)]
+struct Table {
+    header: Header,
+    #[read(len = "header.name_len")]
+    name: String,
+    #[read(count = "header.num_entries")]
+    entries: Vec<[u8; 2]>,
+    #[read(little, count = "entries.len() * 2")]
+    values: Vec<u16>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Signed {
+    count: i8,
+    #[read(count = "count")]
+    items: Vec<u8>,
+}
+
+#[test]
+fn count_and_len() {
+    let mut bytes: &[u8] = &[0, 1, 3, b'a', b'b', b'c', 9, 8, 1, 0, 2, 0];
+    let table = Table::read_from(&mut bytes, Endian::Little).unwrap();
+    assert_eq!(
+        Table {
+            header: Header {
+                num_entries: 1,
+                name_len: 3,
+            },
+            name: "abc".into(),
+            entries: vec![[9, 8]],
+            values: vec![1, 2],
+        },
+        table
+    );
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn invalid_sizes() {
+    let err = Signed::read_from(&mut &[0xff_u8][..], Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let mut bytes: &[u8] = &[0, 0, 2, 0xff, 0xff];
+    let err = Table::read_from(&mut bytes, Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let mut bytes: &[u8] = &[0, 9, 0];
+    let err = Table::read_from(&mut bytes, Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+#[read(little)]
+struct Chunk {
+    id: u8,
+    #[read(until_eof)]
+    records: Vec<[u16; 2]>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Names<'a> {
+    #[read(until_eof)]
+    names: Vec<Name<'a>>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Name<'a> {
+    len: u8,
+    #[read(len = "len")]
+    name: &'a str,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Empty {
+    #[read(until_eof)]
+    arrays: Vec<[u8; 0]>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Unnamed<'a> {
+    #[read(until_eof)]
+    names: Vec<EmptyName<'a>>,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct EmptyName<'a> {
+    #[read(len = "0")]
+    name: &'a str,
+}
+
+#[test]
+fn until_eof() {
+    let bytes = [7, 1, 0, 2, 0, 3, 0, 4, 0];
+    let chunk = Chunk::read_from(&mut &bytes[..], Endian::Big).unwrap();
+    assert_eq!([[1, 2], [3, 4]], chunk.records.as_slice());
+    let mut written = Vec::new();
+    chunk.write_to(&mut written, Endian::Big).unwrap();
+    assert_eq!(bytes, written.as_slice());
+
+    let mut limited = io::Read::take(&bytes[..], 5);
+    let chunk = Chunk::read_from(&mut limited, Endian::Big).unwrap();
+    assert_eq!([[1, 2]], chunk.records.as_slice());
+    let empty = Chunk::read_from(&mut &bytes[..1], Endian::Big).unwrap();
+    assert!(empty.records.is_empty());
+
+    let err = Chunk::read_from(&mut &bytes[..7], Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!(
+        "Chunk.records at offset 0x1: failed to fill whole buffer",
+        err.to_string()
+    );
+
+    let names = Names::read_from_slice(&mut &b"\x02ab\x00\x01c"[..], Endian::Big).unwrap();
+    let names: Vec<_> = names.names.iter().map(|name| name.name).collect();
+    assert_eq!(["ab", "", "c"], names.as_slice());
+
+    let err = Empty::read_from(&mut &b"\x01"[..], Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    assert_eq!(
+        "Empty.arrays at offset 0x0: [u8; 0] read until the end consumes no bytes",
+        err.to_string()
+    );
+    let err = Unnamed::read_from_slice(&mut &b"\x01"[..], Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+}
@@ -0,0 +1,83 @@
+use read_primitives::{Endian, ReadFrom, ReadTagged};
+use std::io;
+
+const PING: u64 = 3;
+
+#[derive(ReadTagged, Debug, PartialEq)]
+#[read(little)]
+enum Payload {
+    #[read(id = 1)]
+    Data(#[read(big)] u16, u8),
+    #[read(id = 2)]
+    Text {
+        len: u8,
+        #[read(len = "len")]
+        text: String,
+    },
+    #[read(id = PING)]
+    Ping,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Packet {
+    kind: u16,
+    #[read(tag = "kind")]
+    payload: Payload,
+}
+
+fn read(bytes: &[u8]) -> io::Result<Packet> {
+    Packet::read_from(&mut &bytes[..], Endian::Little)
+}
+
+#[test]
+fn dispatch_on_tag() {
+    assert_eq!(
+        Payload::Data(0x102, 3),
+        read(&[1, 0, 1, 2, 3]).unwrap().payload
+    );
+    assert_eq!(
+        Payload::Text {
+            len: 2,
+            text: "hi".into()
+        },
+        read(&[2, 0, 2, b'h', b'i']).unwrap().payload
+    );
+    assert_eq!(Payload::Ping, read(&[3, 0]).unwrap().payload);
+}
+
+#[test]
+fn read_tagged_directly() {
+    let mut bytes: &[u8] = &[0, 7, 9];
+    let payload = Payload::read_tagged(&mut bytes, Endian::Big, 1).unwrap();
+    assert_eq!(Payload::Data(7, 9), payload);
+}
+
+#[test]
+fn unknown_tag() {
+    let err = read(&[0x34, 0x12]).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!("Payload at offset 0x2: unknown tag 4660", err.to_string());
+}
+
+#[derive(ReadTagged, Debug, PartialEq)]
+#[read(tag_type = "u8")]
+enum Record {
+    #[read(id = 1)]
+    Known(u8),
+    #[read(other)]
+    Other(u8),
+}
+
+#[test]
+fn unknown_tag_to_other() {
+    let mut bytes: &[u8] = &[5];
+    assert_eq!(
+        Record::Known(5),
+        Record::read_tagged(&mut bytes, Endian::Big, 1).unwrap()
+    );
+    assert_eq!(
+        Record::Other(0x42),
+        Record::read_tagged(&mut bytes, Endian::Big, 0x42).unwrap()
+    );
+    assert!(bytes.is_empty());
+}
@@ -0,0 +1,94 @@
+use read_primitives::{Endian, ReadFrom, Trace};
+use std::io;
+
+#[derive(ReadFrom)]
+struct Point {
+    x: i16,
+    y: i16,
+}
+
+impl std::fmt::Debug for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(ReadFrom, Debug)]
+#[read(magic = b"PT")]
+struct Header {
+    #[read(pad_before = 1)]
+    count: u8,
+    origin: Point,
+    #[read(count = "count")]
+    flags: Vec<u8>,
+}
+
+/// Without `Debug`, so it and `Inner` are traced without values
+#[derive(ReadFrom)]
+struct Opaque {
+    point: Point,
+    inner: Inner,
+}
+
+#[derive(ReadFrom)]
+struct Inner {
+    value: u8,
+}
+
+#[test]
+fn derived_fields() {
+    let data = b"PT\xee\x02\x00\x01\xff\xfe\x0a\x0b";
+    let (header, trace) = Trace::read_from::<Header, _>(&mut &data[..], Endian::Big);
+    let header = header.unwrap();
+    assert_eq!((2, -2), (header.count, header.origin.y));
+    assert_eq!([10, 11], *header.flags);
+    let entries: Vec<_> = trace
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(), entry.range(), entry.depth(), entry.value()))
+        .collect();
+    assert_eq!(
+        [
+            ("Header.count", 3..4, 0, Some("2")),
+            ("Header.origin", 4..8, 0, Some("(1, -2)")),
+            ("Point.x", 4..6, 1, Some("1")),
+            ("Point.y", 6..8, 1, Some("-2")),
+            ("Header.flags", 8..10, 0, Some("[10, 11]")),
+        ],
+        *entries
+    );
+    let text = trace.to_string();
+    let lines: Vec<_> = text.lines().map(str::trim_end).collect();
+    assert!(lines[0].starts_with("00000000  50 54 ee"));
+    assert!(lines[1].ends_with(" Header.count = 2"));
+    assert!(lines[2].ends_with(" Header.origin (4 bytes)"));
+    assert!(lines[3].starts_with("00000004  00 01 "));
+    assert!(lines[3].ends_with("   Point.x = 1"));
+    assert!(lines[5].starts_with("00000008  0a 0b "));
+}
+
+#[test]
+fn without_debug() {
+    let (opaque, trace) = Trace::read_from::<Opaque, _>(&mut &[0, 1, 0, 2, 3][..], Endian::Big);
+    let opaque = opaque.unwrap();
+    assert_eq!((2, 3), (opaque.point.y, opaque.inner.value));
+    assert_eq!(Some("(1, 2)"), trace.entries()[0].value());
+    let inner = trace
+        .entries()
+        .iter()
+        .find(|entry| entry.path() == "Opaque.inner");
+    assert_eq!(None, inner.unwrap().value());
+}
+
+#[test]
+fn failed_field() {
+    let (header, trace) =
+        Trace::read_from::<Header, _>(&mut &b"PT\0\x05\0\0\0\0\x01"[..], Endian::Big);
+    assert_eq!(io::ErrorKind::UnexpectedEof, header.unwrap_err().kind());
+    let paths: Vec<_> = trace.entries().iter().map(|entry| entry.path()).collect();
+    assert_eq!(
+        ["Header.count", "Header.origin", "Point.x", "Point.y"],
+        *paths
+    );
+    assert!(trace.to_string().trim_end().ends_with("00000008  01"));
+}
@@ -0,0 +1,75 @@
+use read_primitives::{Endian, ReadFrom};
+use std::io;
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct EntryV1 {
+    size: u16,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct EntryV2 {
+    size: u32,
+    flags: u8,
+}
+
+/// The current layout, which the older ones are upgraded to
+#[derive(Debug, PartialEq)]
+struct Entry {
+    size: u32,
+    flags: u8,
+}
+
+impl From<EntryV1> for Entry {
+    fn from(entry: EntryV1) -> Self {
+        Entry {
+            size: entry.size.into(),
+            flags: 0,
+        }
+    }
+}
+
+impl From<EntryV2> for Entry {
+    fn from(entry: EntryV2) -> Self {
+        Entry {
+            size: entry.size,
+            flags: entry.flags,
+        }
+    }
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+struct Header {
+    version: u8,
+}
+
+#[derive(ReadFrom, Debug, PartialEq)]
+#[read(big)]
+struct File {
+    header: Header,
+    #[read(version_from = "header.version", v1 = EntryV1, v2 = EntryV2)]
+    entry: Entry,
+}
+
+#[test]
+fn dispatch_on_version() {
+    let v1 = File::read_from(&mut &[1, 0, 9][..], Endian::Little).unwrap();
+    assert_eq!(Entry { size: 9, flags: 0 }, v1.entry);
+    let v2 = File::read_from(&mut &[2, 0, 0, 1, 0, 3][..], Endian::Little).unwrap();
+    assert_eq!(
+        Entry {
+            size: 0x100,
+            flags: 3
+        },
+        v2.entry
+    );
+}
+
+#[test]
+fn unsupported_version() {
+    let err = File::read_from(&mut &[3, 0, 9][..], Endian::Little).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        "File.entry at offset 0x1: unsupported version 3",
+        err.to_string()
+    );
+}
@@ -0,0 +1,131 @@
+use read_primitives::{Endian, ReadFrom, ReadTagged, WriteTo};
+use std::io;
+
+#[derive(ReadTagged, WriteTo, Debug, PartialEq)]
+enum Body {
+    #[read(id = 0)]
+    Empty,
+    #[read(id = 1)]
+    Samples {
+        count: u16,
+        #[read(little, count = "count")]
+        samples: Vec<i16>,
+    },
+    #[read(other)]
+    Unknown(u64),
+}
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+#[read(big, magic = b"CHNK")]
+struct Chunk {
+    kind: u8,
+    id: [u8; 2],
+    #[read(tag = "kind")]
+    body: Body,
+    flag: bool,
+}
+
+fn round_trip(chunk: &Chunk) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    chunk.write_to(&mut bytes, Endian::Little).unwrap();
+    let read = Chunk::read_from(&mut bytes.as_slice(), Endian::Little).unwrap();
+    assert_eq!(chunk, &read);
+    bytes
+}
+
+#[test]
+fn round_trips() {
+    let chunk = Chunk {
+        kind: 1,
+        id: *b"ab",
+        body: Body::Samples {
+            count: 2,
+            samples: vec![1, -1],
+        },
+        flag: true,
+    };
+    assert_eq!(
+        b"CHNK\x01ab\x00\x02\x01\x00\xff\xff\x01",
+        round_trip(&chunk).as_slice()
+    );
+    let chunk = Chunk {
+        kind: 0,
+        id: *b"cd",
+        body: Body::Empty,
+        flag: false,
+    };
+    assert_eq!(b"CHNK\x00cd\x00", round_trip(&chunk).as_slice());
+    let chunk = Chunk {
+        kind: 9,
+        id: *b"ef",
+        body: Body::Unknown(9),
+        flag: true,
+    };
+    assert_eq!(b"CHNK\x09ef\x01", round_trip(&chunk).as_slice());
+}
+
+#[test]
+fn size_mismatch() {
+    let chunk = Chunk {
+        kind: 1,
+        id: *b"ab",
+        body: Body::Samples {
+            count: 3,
+            samples: vec![1],
+        },
+        flag: true,
+    };
+    let err = chunk.write_to(&mut Vec::new(), Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    assert_eq!(
+        "Body::Samples.samples: length 1 does not match the declared size 3",
+        err.to_string()
+    );
+}
+
+#[derive(ReadFrom, WriteTo, Debug, PartialEq)]
+#[read(little)]
+struct Flagged {
+    flags: u8,
+    #[read(if = "flags & 0x1 != 0", check = "range 0x100..")]
+    extra: Option<u16>,
+    #[read(if = "flags & 0x2 != 0", len = "2")]
+    name: Option<String>,
+    end: u8,
+}
+
+#[test]
+fn conditional_fields() {
+    let read = |bytes: &[u8]| Flagged::read_from(&mut &bytes[..], Endian::Big).unwrap();
+    let flagged = Flagged {
+        flags: 3,
+        extra: Some(0x102),
+        name: Some("ab".into()),
+        end: 9,
+    };
+    let mut bytes = Vec::new();
+    flagged.write_to(&mut bytes, Endian::Big).unwrap();
+    assert_eq!(b"\x03\x02\x01ab\x09", bytes.as_slice());
+    assert_eq!(flagged, read(&bytes));
+    let plain = read(&[0, 9]);
+    assert_eq!(
+        (None, None, 9),
+        (plain.extra, plain.name.as_deref(), plain.end)
+    );
+    let mut bytes = Vec::new();
+    plain.write_to(&mut bytes, Endian::Big).unwrap();
+    assert_eq!([0, 9], *bytes);
+
+    let missing = Flagged {
+        flags: 1,
+        extra: None,
+        name: None,
+        end: 0,
+    };
+    let err = missing.write_to(&mut Vec::new(), Endian::Big).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    assert_eq!(
+        "Flagged.extra: value is None but its condition is true",
+        err.to_string()
+    );
+}
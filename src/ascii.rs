@@ -0,0 +1,135 @@
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+/// Longest number text accepted, so that input without delimiters can't grow a token forever
+const MAX_TOKEN_LEN: usize = 128;
+
+/// Next byte without consuming it, `None` at the end
+fn peek<R: BufRead + ?Sized>(reader: &mut R) -> io::Result<Option<u8>> {
+    Ok(reader.fill_buf()?.first().copied())
+}
+
+/// Read the bytes before the first one matching `stop`, consuming that one too
+///
+/// The end of the reader ends the token only if `eof_ends`.
+fn token<R: BufRead + ?Sized>(
+    reader: &mut R,
+    stop: impl Fn(u8) -> bool,
+    eof_ends: bool,
+) -> io::Result<Vec<u8>> {
+    let mut token = Vec::new();
+    loop {
+        match peek(reader)? {
+            Some(byte) => {
+                reader.consume(1);
+                if stop(byte) {
+                    return Ok(token);
+                }
+                if token.len() == MAX_TOKEN_LEN {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("number longer than {MAX_TOKEN_LEN} bytes"),
+                    ));
+                }
+                token.push(byte);
+            }
+            None if eof_ends => return Ok(token),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+fn parse<T: FromStr>(token: &[u8], type_name: &str) -> io::Result<T> {
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {type_name} \"{}\"", token.escape_ascii()),
+            )
+        })
+}
+
+macro_rules! impl_ascii_methods {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste! {
+                #[doc = "Read " $type " as ASCII text ending at `delim`, consuming the delimiter"]
+                #[doc = "# Errors"]
+                #[doc = "errors with [`io::ErrorKind::InvalidData`] if the text is not a " $type ","]
+                #[doc = "with [`io::ErrorKind::UnexpectedEof`] if the reader ends before `delim`,"]
+                #[doc = "or when [`BufRead::fill_buf`](https://doc.rust-lang.org/std/io/trait.BufRead.html#tymethod.fill_buf) errors"]
+                fn [<read_ascii_ $type _until>](&mut self, delim: u8) -> io::Result<$type> {
+                    parse(&token(self, |byte| byte == delim, false)?, stringify!($type))
+                }
+                #[doc = "Read " $type " as ASCII text after skipping whitespace, consuming the one whitespace byte after it"]
+                #[doc = ""]
+                #[doc = "The text also ends at the end of the reader. Consuming exactly one byte after it leaves the reader"]
+                #[doc = "at the binary body that follows headers like those of PNM."]
+                #[doc = "# Errors"]
+                #[doc = "errors with [`io::ErrorKind::InvalidData`] if the text is not a " $type ","]
+                #[doc = "with [`io::ErrorKind::UnexpectedEof`] if only whitespace is left,"]
+                #[doc = "or when [`BufRead::fill_buf`](https://doc.rust-lang.org/std/io/trait.BufRead.html#tymethod.fill_buf) errors"]
+                fn [<read_ascii_ $type _ws>](&mut self) -> io::Result<$type> {
+                    loop {
+                        match peek(self)? {
+                            Some(byte) if byte.is_ascii_whitespace() => self.consume(1),
+                            Some(_) => break,
+                            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                        }
+                    }
+                    parse(&token(self, |byte| byte.is_ascii_whitespace(), true)?, stringify!($type))
+                }
+            }
+        )+
+    };
+}
+
+/// Trait to read numbers written as ASCII text, for text headers in front of binary data
+///
+/// ```
+/// use read_primitives::{ReadAscii, ReadU8};
+/// let mut pgm: &[u8] = b"P5\n2 1\n255\n\x07\x09";
+/// assert_eq!(b"P5", &*pgm.read_bytes(2).unwrap());
+/// let width = pgm.read_ascii_u32_ws().unwrap();
+/// let height = pgm.read_ascii_u32_ws().unwrap();
+/// let max = pgm.read_ascii_u16_ws().unwrap();
+/// assert_eq!((2, 1, 255), (width, height, max));
+/// assert_eq!(7, pgm.read_u8().unwrap());
+/// ```
+pub trait ReadAscii: BufRead {
+    impl_ascii_methods!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+}
+impl<R> ReadAscii for R where R: BufRead {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, BufReader};
+
+    #[test]
+    fn until() {
+        let mut reader: &[u8] = b"12,-3;";
+        assert_eq!(12, reader.read_ascii_u32_until(b',').unwrap());
+        assert_eq!(-3, reader.read_ascii_i8_until(b';').unwrap());
+        let err = reader.read_ascii_u32_until(b',').unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut invalid: &[u8] = b"-3,";
+        let err = invalid.read_ascii_u32_until(b',').unwrap_err();
+        assert_eq!("invalid u32 \"-3\"", err.to_string());
+    }
+
+    #[test]
+    fn whitespace() {
+        let mut reader = BufReader::with_capacity(2, &b"  1.5\t\n-2e3"[..]);
+        assert_eq!(1.5, reader.read_ascii_f64_ws().unwrap());
+        assert_eq!(b'\n', reader.read_u8().unwrap());
+        assert_eq!(-2000.0, reader.read_ascii_f32_ws().unwrap());
+        let err = reader.read_ascii_f32_ws().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let long = vec![b'1'; 200];
+        let err = long.as_slice().read_ascii_u64_ws().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,194 @@
+//! Reads of ASN.1 BER and DER tag and length headers
+//!
+//! Each BER or DER element is a TLV: identifier octets giving its tag, length octets, then the
+//! value. [`ReadDer::read_der_tlv_header`] decodes the first two, including tags numbered 31 and
+//! above and long-form lengths, leaving the value to the caller, as certificate and SNMP tooling
+//! needs.
+//!
+//! ```
+//! use read_primitives::asn1::{Class, Length, ReadDer};
+//! // the start of an X.509 certificate: a SEQUENCE of 1000 bytes
+//! let mut der: &[u8] = &[0x30, 0x82, 0x03, 0xe8];
+//! let header = der.read_der_tlv_header().unwrap();
+//! assert_eq!((Class::Universal, true, 16), (header.tag.class, header.tag.constructed, header.tag.number));
+//! assert_eq!(Length::Definite(1000), header.length);
+//! ```
+
+use std::io::{self, Read};
+
+/// Class of a tag, the top 2 bits of its first identifier octet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// Types defined by ASN.1 itself, like `INTEGER` and `SEQUENCE`
+    Universal,
+    /// Types of an application
+    Application,
+    /// Tags whose meaning depends on where they appear, like `[0]` in a `SEQUENCE`
+    ContextSpecific,
+    /// Types of a private agreement
+    Private,
+}
+
+/// Tag of an element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag {
+    /// Class
+    pub class: Class,
+    /// Whether the value is a sequence of elements rather than primitive contents
+    pub constructed: bool,
+    /// Number within the class
+    pub number: u32,
+}
+
+/// Length of an element's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Length {
+    /// Value of this many bytes
+    Definite(u64),
+    /// Value of elements ending at an end-of-contents element, two zero bytes; BER only
+    Indefinite,
+}
+
+/// Tag and length of an element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TlvHeader {
+    /// Tag
+    pub tag: Tag,
+    /// Length of the value
+    pub length: Length,
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_byte<R: Read + ?Sized>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Trait to read ASN.1 BER and DER headers
+pub trait ReadDer: Read {
+    /// Read the identifier and length octets of an element
+    ///
+    /// This accepts BER, which DER restricts: DER also requires definite lengths in the fewest
+    /// octets, which callers checking DER can verify on the result.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if a tag number has a leading zero or
+    /// overflows u32, a length overflows u64 or uses the reserved `0xff`, or a primitive element
+    /// has an indefinite length, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_der_tlv_header(&mut self) -> io::Result<TlvHeader> {
+        let identifier = read_byte(self)?;
+        let class = match identifier >> 6 {
+            0 => Class::Universal,
+            1 => Class::Application,
+            2 => Class::ContextSpecific,
+            _ => Class::Private,
+        };
+        let constructed = identifier & 0x20 != 0;
+        let mut number = u32::from(identifier & 0x1f);
+        if number == 0x1f {
+            number = 0;
+            loop {
+                let byte = read_byte(self)?;
+                if number == 0 && byte == 0x80 {
+                    return Err(invalid_data("tag number has a leading zero"));
+                }
+                if number >> 25 != 0 {
+                    return Err(invalid_data("tag number overflows u32"));
+                }
+                number = number << 7 | u32::from(byte & 0x7f);
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+        }
+        let tag = Tag {
+            class,
+            constructed,
+            number,
+        };
+
+        let first = read_byte(self)?;
+        let length = match first {
+            0x00..=0x7f => Length::Definite(first.into()),
+            0x80 if constructed => Length::Indefinite,
+            0x80 => return Err(invalid_data("primitive element has an indefinite length")),
+            0xff => return Err(invalid_data("reserved length octet 0xff")),
+            _ => {
+                let len = usize::from(first & 0x7f);
+                if len > 8 {
+                    return Err(invalid_data("length overflows u64"));
+                }
+                let mut bytes = [0u8; 8];
+                self.read_exact(&mut bytes[8 - len..])?;
+                Length::Definite(u64::from_be_bytes(bytes))
+            }
+        };
+        Ok(TlvHeader { tag, length })
+    }
+}
+impl<R> ReadDer for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::asn1::*;
+    use std::io;
+
+    fn header(bytes: &[u8]) -> TlvHeader {
+        let mut reader = bytes;
+        let header = reader.read_der_tlv_header().unwrap();
+        assert!(reader.is_empty(), "{bytes:x?} left bytes unread");
+        header
+    }
+
+    #[test]
+    fn tags() {
+        let integer = header(&[0x02, 0x01]);
+        assert_eq!(
+            Tag {
+                class: Class::Universal,
+                constructed: false,
+                number: 2
+            },
+            integer.tag
+        );
+        let explicit = header(&[0xa0, 0x03]).tag;
+        assert_eq!(
+            (Class::ContextSpecific, true, 0),
+            (explicit.class, explicit.constructed, explicit.number)
+        );
+        let high = header(&[0x5f, 0x81, 0x00, 0x00]).tag;
+        assert_eq!((Class::Application, 128), (high.class, high.number));
+        let max = header(&[0xdf, 0x8f, 0xff, 0xff, 0xff, 0x7f, 0x00]).tag;
+        assert_eq!((Class::Private, u32::MAX), (max.class, max.number));
+        let err = (&[0x1f, 0x80, 0x01, 0x00][..])
+            .read_der_tlv_header()
+            .unwrap_err();
+        assert_eq!("tag number has a leading zero", err.to_string());
+        let err = (&[0x1f, 0x90, 0x80, 0x80, 0x80, 0x80, 0x00][..])
+            .read_der_tlv_header()
+            .unwrap_err();
+        assert_eq!("tag number overflows u32", err.to_string());
+    }
+
+    #[test]
+    fn lengths() {
+        assert_eq!(Length::Definite(0x7f), header(&[0x04, 0x7f]).length);
+        assert_eq!(Length::Definite(0x80), header(&[0x04, 0x81, 0x80]).length);
+        assert_eq!(
+            Length::Definite(u64::MAX),
+            header(&[0x04, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).length
+        );
+        assert_eq!(Length::Indefinite, header(&[0x30, 0x80]).length);
+        let err = (&[0x04, 0x80][..]).read_der_tlv_header().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0x04, 0x89][..]).read_der_tlv_header().unwrap_err();
+        assert_eq!("length overflows u64", err.to_string());
+        let err = (&[0x04, 0xff][..]).read_der_tlv_header().unwrap_err();
+        assert_eq!("reserved length octet 0xff", err.to_string());
+        let err = (&[0x04, 0x82, 0x01][..]).read_der_tlv_header().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
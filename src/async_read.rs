@@ -0,0 +1,642 @@
+// Shared implementation of the async read traits, instantiated once per async I/O backend next
+// to `impl_async_write!`, so it names the items it uses by their full paths.
+macro_rules! impl_async_peek_methods {
+    ($order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Peek at a `T` in " $order_doc " without consuming it"]
+            #[doc = "# Errors"]
+            #[doc = "the future errors with [`io::ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the buffered data after a fill is shorter than `T`,"]
+            #[doc = "or when filling the buffer errors"]
+            fn [<peek_ $order>]<T: $crate::Primitive>(&mut self) -> PeekPrimitive<'_, Self, T> {
+                PeekPrimitive {
+                    reader: self,
+                    decode: T::[<from_ $order _bytes>],
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_async_read_methods {
+    ($order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read a `T` in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "the future errors with [`io::ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the reader ends early,"]
+            #[doc = "or when reading from the underlying reader errors"]
+            pub fn [<read_ $order>]<T: $crate::Primitive>(&mut self) -> ReadValue<'_, R, T> {
+                ReadValue {
+                    reader: self,
+                    decode: T::[<from_ $order _bytes>],
+                }
+            }
+
+            #[doc = "Peek at a `T` in " $order_doc " without consuming it"]
+            #[doc = "# Errors"]
+            #[doc = "errors like [`" [<read_ $order>] "`](Self::" [<read_ $order>] ")"]
+            pub fn [<peek_ $order>]<T: $crate::Primitive>(&mut self) -> PeekValue<'_, R, T> {
+                PeekValue {
+                    reader: self,
+                    decode: T::[<from_ $order _bytes>],
+                }
+            }
+        }
+    };
+}
+
+// `$poll_read` adapts the backend's `poll_read` to
+// `fn(&mut R, &mut Context, &mut [u8]) -> Poll<io::Result<usize>>`
+macro_rules! impl_async_read {
+    ($async_buf_read:path, $async_read:path, $poll_read:path) => {
+        use $async_buf_read as AsyncBufRead;
+        use $async_read as AsyncRead;
+
+        /// Async reader of primitives whose reads are cancellation safe
+        ///
+        /// A future that reads a value with `read_exact` loses the bytes
+        /// it already read when it is dropped, for example when it loses a `select!`, and the
+        /// stream is then out of step. This reader keeps those bytes instead: they are the start
+        /// of the next value read, whatever its type, so reads can be dropped and retried at any
+        /// point without losing data. Peeks keep the bytes they read the same way, so unlike those
+        /// of [`AsyncPeekPrimitive`] they see values across the buffer boundaries of the
+        /// underlying reader.
+        #[derive(Debug)]
+        pub struct AsyncPrimitiveReader<R> {
+            inner: R,
+            /// Bytes read but not decoded yet, in `pending[..filled]`
+            pending: Vec<u8>,
+            filled: usize,
+        }
+
+        impl<R> AsyncPrimitiveReader<R> {
+            /// Wrap `inner`
+            pub fn new(inner: R) -> Self {
+                AsyncPrimitiveReader {
+                    inner,
+                    pending: Vec::new(),
+                    filled: 0,
+                }
+            }
+
+            /// Bytes read from the underlying reader by a cancelled read and not decoded yet
+            pub fn pending(&self) -> &[u8] {
+                &self.pending[..self.filled]
+            }
+
+            /// Borrow the wrapped reader
+            pub fn get_ref(&self) -> &R {
+                &self.inner
+            }
+
+            /// Mutably borrow the wrapped reader
+            ///
+            /// Reading from it directly skips the [`pending`](Self::pending) bytes.
+            pub fn get_mut(&mut self) -> &mut R {
+                &mut self.inner
+            }
+
+            /// Unwrap the reader, returning it with the [`pending`](Self::pending) bytes
+            pub fn into_parts(mut self) -> (R, Vec<u8>) {
+                self.pending.truncate(self.filled);
+                (self.inner, self.pending)
+            }
+        }
+
+        impl<R: AsyncRead + Unpin> AsyncPrimitiveReader<R> {
+            impl_async_read_methods!(ne, "native byte order");
+            impl_async_read_methods!(le, "little endian byte order");
+            impl_async_read_methods!(be, "big endian byte order");
+
+            /// Read a `T` in `endian` byte order
+            /// # Errors
+            /// the future errors with [`io::ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof)
+            /// if the reader ends early, or when reading from the underlying reader errors
+            pub fn read_endian<T: $crate::Primitive>(
+                &mut self,
+                endian: $crate::Endian,
+            ) -> ReadValue<'_, R, T> {
+                ReadValue {
+                    reader: self,
+                    decode: match endian {
+                        $crate::Endian::Little => T::from_le_bytes,
+                        $crate::Endian::Big => T::from_be_bytes,
+                    },
+                }
+            }
+
+            /// Peek at a `T` in `endian` byte order without consuming it
+            /// # Errors
+            /// errors like [`read_endian`](Self::read_endian)
+            pub fn peek_endian<T: $crate::Primitive>(
+                &mut self,
+                endian: $crate::Endian,
+            ) -> PeekValue<'_, R, T> {
+                PeekValue {
+                    reader: self,
+                    decode: match endian {
+                        $crate::Endian::Little => T::from_le_bytes,
+                        $crate::Endian::Big => T::from_be_bytes,
+                    },
+                }
+            }
+
+            /// Read from the underlying reader until `len` bytes are pending
+            fn poll_fill(
+                &mut self,
+                cx: &mut ::std::task::Context<'_>,
+                len: usize,
+            ) -> ::std::task::Poll<::std::io::Result<()>> {
+                if self.pending.len() < len {
+                    self.pending.resize(len, 0);
+                }
+                while self.filled < len {
+                    let buf = &mut self.pending[self.filled..len];
+                    match ::std::task::ready!($poll_read(&mut self.inner, cx, buf)) {
+                        Ok(0) => {
+                            return ::std::task::Poll::Ready(Err(
+                                ::std::io::ErrorKind::UnexpectedEof.into(),
+                            ))
+                        }
+                        Ok(read) => self.filled += read,
+                        Err(err) if err.kind() == ::std::io::ErrorKind::Interrupted => {}
+                        Err(err) => return ::std::task::Poll::Ready(Err(err)),
+                    }
+                }
+                ::std::task::Poll::Ready(Ok(()))
+            }
+        }
+
+        /// Future returned by the reads of [`AsyncPrimitiveReader`]
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct ReadValue<'a, R, T: $crate::Primitive> {
+            reader: &'a mut AsyncPrimitiveReader<R>,
+            decode: fn(T::Bytes) -> T,
+        }
+
+        impl<R: AsyncRead + Unpin, T: $crate::Primitive> ::std::future::Future
+            for ReadValue<'_, R, T>
+        {
+            type Output = ::std::io::Result<T>;
+
+            fn poll(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context<'_>,
+            ) -> ::std::task::Poll<Self::Output> {
+                let decode = self.decode;
+                let reader = &mut *self.reader;
+                ::std::task::ready!(reader.poll_fill(cx, T::SIZE))?;
+                let mut bytes = T::Bytes::default();
+                bytes.as_mut().copy_from_slice(&reader.pending[..T::SIZE]);
+                // bytes left by a cancelled read of a larger value start the next one
+                reader.pending.copy_within(T::SIZE..reader.filled, 0);
+                reader.filled -= T::SIZE;
+                ::std::task::Poll::Ready(Ok(decode(bytes)))
+            }
+        }
+
+        /// Future returned by the peeks of [`AsyncPrimitiveReader`]
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct PeekValue<'a, R, T: $crate::Primitive> {
+            reader: &'a mut AsyncPrimitiveReader<R>,
+            decode: fn(T::Bytes) -> T,
+        }
+
+        impl<R: AsyncRead + Unpin, T: $crate::Primitive> ::std::future::Future
+            for PeekValue<'_, R, T>
+        {
+            type Output = ::std::io::Result<T>;
+
+            fn poll(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context<'_>,
+            ) -> ::std::task::Poll<Self::Output> {
+                let decode = self.decode;
+                let reader = &mut *self.reader;
+                ::std::task::ready!(reader.poll_fill(cx, T::SIZE))?;
+                let mut bytes = T::Bytes::default();
+                bytes.as_mut().copy_from_slice(&reader.pending[..T::SIZE]);
+                ::std::task::Poll::Ready(Ok(decode(bytes)))
+            }
+        }
+
+        /// Future returned by the peek methods of [`AsyncPeekPrimitive`]
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct PeekPrimitive<'a, R: ?Sized, T: $crate::Primitive> {
+            reader: &'a mut R,
+            decode: fn(T::Bytes) -> T,
+        }
+
+        impl<R: AsyncBufRead + Unpin + ?Sized, T: $crate::Primitive> ::std::future::Future
+            for PeekPrimitive<'_, R, T>
+        {
+            type Output = ::std::io::Result<T>;
+
+            fn poll(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context<'_>,
+            ) -> ::std::task::Poll<Self::Output> {
+                let this = &mut *self;
+                let buf =
+                    ::std::task::ready!(::std::pin::Pin::new(&mut *this.reader).poll_fill_buf(cx))?;
+                let mut bytes = T::Bytes::default();
+                let Some(prefix) = buf.get(..T::SIZE) else {
+                    return ::std::task::Poll::Ready(Err(
+                        ::std::io::ErrorKind::UnexpectedEof.into()
+                    ));
+                };
+                bytes.as_mut().copy_from_slice(prefix);
+                ::std::task::Poll::Ready(Ok((this.decode)(bytes)))
+            }
+        }
+
+        /// Trait to look ahead at any [`Primitive`](crate::Primitive) in the buffer of an async
+        /// reader, such as a tag that selects how to parse what follows
+        ///
+        /// Peeking only fills the buffer when it is empty, so the value must lie within the
+        /// buffered data: a shorter buffer is reported like the end of the input, as more can't be
+        /// buffered without consuming it. Peek with an [`AsyncPrimitiveReader`] at values that may
+        /// cross a buffer boundary. Peeks consume nothing, so they can be cancelled at any point.
+        pub trait AsyncPeekPrimitive: AsyncBufRead + Unpin {
+            impl_async_peek_methods!(ne, "native byte order");
+            impl_async_peek_methods!(le, "little endian byte order");
+            impl_async_peek_methods!(be, "big endian byte order");
+
+            /// Peek at a `T` in `endian` byte order without consuming it
+            /// # Errors
+            /// the future errors with [`io::ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof)
+            /// if the buffered data after a fill is shorter than `T`, or when filling the buffer errors
+            fn peek_endian<T: $crate::Primitive>(
+                &mut self,
+                endian: $crate::Endian,
+            ) -> PeekPrimitive<'_, Self, T> {
+                PeekPrimitive {
+                    reader: self,
+                    decode: match endian {
+                        $crate::Endian::Little => T::from_le_bytes,
+                        $crate::Endian::Big => T::from_be_bytes,
+                    },
+                }
+            }
+        }
+        impl<R: AsyncBufRead + Unpin + ?Sized> AsyncPeekPrimitive for R {}
+    };
+}
+
+/// Future that is pending once, waking its task right away, so the executor can run other tasks
+/// before resuming it
+pub(crate) struct YieldNow {
+    yielded: bool,
+}
+
+impl std::future::Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<()> {
+        if self.yielded {
+            return std::task::Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        std::task::Poll::Pending
+    }
+}
+
+pub(crate) fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+macro_rules! impl_async_read_from_primitive {
+    ($($type:ty),+) => {
+        $(
+            impl ReadFromAsync for $type {
+                fn read_from_async<R: AsyncRead + Unpin + ?Sized>(
+                    reader: &mut R,
+                    endian: $crate::Endian,
+                ) -> impl ::std::future::Future<Output = ::std::io::Result<Self>> {
+                    async move {
+                        let mut bytes = <$type as $crate::Primitive>::Bytes::default();
+                        read_exact(reader, &mut bytes).await?;
+                        Ok(<$type as $crate::Primitive>::from_endian_bytes(bytes, endian))
+                    }
+                }
+            }
+        )+
+    };
+}
+
+// Async counterparts of `ReadFrom` and `ReadTagged`, expanded after `impl_async_read!` in the same
+// module, whose `AsyncRead` import they use; `$poll_read` is the same adapter
+macro_rules! impl_async_read_from {
+    ($module:ident, $async_read:path, $poll_read:ident) => {
+        /// Read into `buf`, retrying interrupted reads
+        async fn read_some<R: AsyncRead + Unpin + ?Sized>(
+            reader: &mut R,
+            buf: &mut [u8],
+        ) -> ::std::io::Result<usize> {
+            loop {
+                match ::std::future::poll_fn(|cx| $poll_read(reader, cx, buf)).await {
+                    Err(err) if err.kind() == ::std::io::ErrorKind::Interrupted => {}
+                    result => return result,
+                }
+            }
+        }
+
+        async fn read_exact<R: AsyncRead + Unpin + ?Sized>(
+            reader: &mut R,
+            buf: &mut [u8],
+        ) -> ::std::io::Result<()> {
+            let mut filled = 0;
+            while filled < buf.len() {
+                match read_some(reader, &mut buf[filled..]).await? {
+                    0 => {
+                        return Err(::std::io::Error::new(
+                            ::std::io::ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    read => filled += read,
+                }
+            }
+            Ok(())
+        }
+
+        /// A type that can be read from an async reader, the async counterpart of
+        /// [`ReadFrom`](crate::ReadFrom)
+        ///
+        /// Implemented for the same primitive types. With the `derive` feature,
+        /// `#[derive(ReadFrom)]` implements it as well when the struct is marked with the
+        /// attribute of this module's backend. Unlike the reads of [`AsyncPrimitiveReader`], the
+        /// futures are not cancellation safe.
+        pub trait ReadFromAsync: Sized {
+            /// Read a value, using `endian` for the parts that do not specify their own byte order
+            /// # Errors
+            /// the future errors when reading from `reader` errors, or if the data is invalid for
+            /// the type
+            fn read_from_async<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: $crate::Endian,
+            ) -> impl ::std::future::Future<Output = ::std::io::Result<Self>>;
+
+            /// Read `count` values into a `Vec`, yielding to the executor after every
+            /// `yield_every` values, or never if it is 0
+            ///
+            /// Reads of buffered data complete without waiting, so without the yields a large
+            /// decode would keep its task running and starve the others on its thread.
+            ///
+            /// ```
+            /// # futures::executor::block_on(async {
+            #[doc = concat!("use read_primitives::", stringify!($module), "::{ReadFromAsync, DEFAULT_YIELD_INTERVAL};")]
+            /// let bytes = vec![1u8; 10_000];
+            /// let values = u8::read_vec_async(&mut bytes.as_slice(), read_primitives::Endian::Big, 10_000, DEFAULT_YIELD_INTERVAL)
+            ///     .await?;
+            /// assert_eq!(bytes, values);
+            /// # std::io::Result::Ok(())
+            /// # }).unwrap();
+            /// ```
+            /// # Errors
+            /// the future errors like [`ReadFromAsync::read_from_async`]
+            fn read_vec_async<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: $crate::Endian,
+                count: usize,
+                yield_every: usize,
+            ) -> impl ::std::future::Future<Output = ::std::io::Result<Vec<Self>>> {
+                async move {
+                    let mut vec = Vec::with_capacity(
+                        count.min($crate::PREALLOC_LIMIT / size_of::<Self>().max(1)),
+                    );
+                    for read in 1..=count {
+                        vec.push(Self::read_from_async(reader, endian).await?);
+                        if yield_every != 0 && read % yield_every == 0 && read < count {
+                            $crate::async_read::yield_now().await;
+                        }
+                    }
+                    Ok(vec)
+                }
+            }
+        }
+
+        /// Values read between yields to the executor by derived `Vec` fields, and a reasonable
+        /// interval for [`ReadFromAsync::read_vec_async`]
+        pub const DEFAULT_YIELD_INTERVAL: usize = 1024;
+
+        impl_async_read_from_primitive!(
+            u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool
+        );
+
+        impl<T: ReadFromAsync, const N: usize> ReadFromAsync for [T; N] {
+            fn read_from_async<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: $crate::Endian,
+            ) -> impl ::std::future::Future<Output = ::std::io::Result<Self>> {
+                async move {
+                    let mut vec = Vec::with_capacity(N);
+                    for _ in 0..N {
+                        vec.push(T::read_from_async(reader, endian).await?);
+                    }
+                    match vec.try_into() {
+                        Ok(array) => Ok(array),
+                        Err(_) => unreachable!("exactly N elements were read"),
+                    }
+                }
+            }
+        }
+
+        /// An enum read from an async reader whose variant is selected by a tag read before it,
+        /// the async counterpart of [`ReadTagged`](crate::ReadTagged)
+        pub trait ReadTaggedAsync: Sized {
+            /// Type of the tag
+            type Tag;
+
+            /// Read the variant selected by `tag`, using `endian` for the parts that do not
+            /// specify their own byte order
+            /// # Errors
+            /// the future errors with [`io::ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+            /// if no variant has the tag, when reading from `reader` errors, or if the data is
+            /// invalid for the variant
+            fn read_tagged_async<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: $crate::Endian,
+                tag: Self::Tag,
+            ) -> impl ::std::future::Future<Output = ::std::io::Result<Self>>;
+        }
+
+        /// Helpers called by the code generated by the derive, not public API
+        #[cfg(feature = "derive")]
+        #[doc(hidden)]
+        pub mod derive_support {
+            use super::{read_some, ReadFromAsync};
+            use std::io;
+            use std::task::{ready, Context, Poll};
+            use $crate::__private::{compare_magic, shift_element_error};
+            use $crate::Endian;
+
+            pub use $async_read as AsyncRead;
+
+            /// Reader counting the bytes read through it, with one byte of lookahead for
+            /// `until_eof`
+            pub struct Counter<'r, R: ?Sized> {
+                inner: &'r mut R,
+                position: u64,
+                peeked: Option<u8>,
+            }
+
+            impl<'r, R: AsyncRead + Unpin + ?Sized> Counter<'r, R> {
+                pub fn new(inner: &'r mut R) -> Self {
+                    Counter {
+                        inner,
+                        position: 0,
+                        peeked: None,
+                    }
+                }
+
+                pub fn position(&self) -> u64 {
+                    self.position
+                }
+
+                pub(super) fn poll_read_slice(
+                    &mut self,
+                    cx: &mut Context<'_>,
+                    buf: &mut [u8],
+                ) -> Poll<io::Result<usize>> {
+                    if buf.is_empty() {
+                        return Poll::Ready(Ok(0));
+                    }
+                    let read = match self.peeked.take() {
+                        Some(byte) => {
+                            buf[0] = byte;
+                            1
+                        }
+                        None => ready!(super::$poll_read(self.inner, cx, buf))?,
+                    };
+                    self.position += read as u64;
+                    Poll::Ready(Ok(read))
+                }
+
+                /// Whether the reader has ended, reading a byte ahead if not
+                async fn at_eof(&mut self) -> io::Result<bool> {
+                    if self.peeked.is_some() {
+                        return Ok(false);
+                    }
+                    let mut byte = [0u8; 1];
+                    if read_some(self.inner, &mut byte).await? == 0 {
+                        return Ok(true);
+                    }
+                    self.peeked = Some(byte[0]);
+                    Ok(false)
+                }
+            }
+
+            pub async fn read_vec<T: ReadFromAsync, R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: Endian,
+                count: usize,
+            ) -> io::Result<Vec<T>> {
+                let mut vec =
+                    Vec::with_capacity(count.min($crate::PREALLOC_LIMIT / size_of::<T>().max(1)));
+                let mut counter = Counter::new(reader);
+                for read in 1..=count {
+                    let start = counter.position();
+                    let element = T::read_from_async(&mut counter, endian)
+                        .await
+                        .map_err(|err| shift_element_error(err, start))?;
+                    vec.push(element);
+                    if read % super::DEFAULT_YIELD_INTERVAL == 0 && read < count {
+                        $crate::async_read::yield_now().await;
+                    }
+                }
+                Ok(vec)
+            }
+
+            pub async fn read_until_eof<T: ReadFromAsync, R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                endian: Endian,
+            ) -> io::Result<Vec<T>> {
+                let mut vec = Vec::new();
+                let mut counter = Counter::new(reader);
+                loop {
+                    let start = counter.position();
+                    if counter.at_eof().await? {
+                        return Ok(vec);
+                    }
+                    let element = T::read_from_async(&mut counter, endian)
+                        .await
+                        .map_err(|err| shift_element_error(err, start))?;
+                    vec.push(element);
+                    if vec.len() % super::DEFAULT_YIELD_INTERVAL == 0 {
+                        $crate::async_read::yield_now().await;
+                    }
+                }
+            }
+
+            pub async fn read_optional<
+                T: ReadFromAsync + Default,
+                R: AsyncRead + Unpin + ?Sized,
+            >(
+                reader: &mut R,
+                endian: Endian,
+            ) -> io::Result<T> {
+                let mut counter = Counter::new(reader);
+                if counter.at_eof().await? {
+                    return Ok(T::default());
+                }
+                T::read_from_async(&mut counter, endian).await
+            }
+
+            /// Read `len` bytes, growing the buffer as data arrives instead of trusting `len`
+            async fn read_exact_vec<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                len: usize,
+            ) -> io::Result<Vec<u8>> {
+                let mut bytes = Vec::new();
+                let mut filled = 0;
+                while filled < len {
+                    if filled == bytes.len() {
+                        bytes.resize(len.min(filled + $crate::PREALLOC_LIMIT), 0);
+                    }
+                    match read_some(reader, &mut bytes[filled..]).await? {
+                        0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                        read => filled += read,
+                    }
+                }
+                Ok(bytes)
+            }
+
+            pub async fn read_string<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                len: usize,
+            ) -> io::Result<String> {
+                String::from_utf8(read_exact_vec(reader, len).await?)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            }
+
+            pub async fn check_magic<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                expected: &[u8],
+            ) -> io::Result<()> {
+                compare_magic(expected, &read_exact_vec(reader, expected.len()).await?)
+            }
+
+            pub async fn skip<R: AsyncRead + Unpin + ?Sized>(
+                reader: &mut R,
+                mut len: usize,
+            ) -> io::Result<()> {
+                let mut buf = [0u8; 256];
+                while len > 0 {
+                    let chunk = len.min(buf.len());
+                    match read_some(reader, &mut buf[..chunk]).await? {
+                        0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                        read => len -= read,
+                    }
+                }
+                Ok(())
+            }
+        }
+    };
+}
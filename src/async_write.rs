@@ -0,0 +1,116 @@
+// Shared implementation of the async write traits, instantiated once per async I/O backend.
+// The helper macros expect `AsyncWrite` and `WriteBytes` to be in scope where they expand.
+macro_rules! impl_async_write_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Write " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "the future errors when writing to the underlying writer errors"]
+            fn [<write_ $order _ $type>](&mut self, value: $type) -> WriteBytes<'_, Self> {
+                WriteBytes::new(self, &value.[<to_ $order _bytes>]())
+            }
+        }
+    };
+}
+
+macro_rules! impl_async_write_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to asynchronously write " $type "."]
+                pub trait [<AsyncWrite $type:camel>]: AsyncWrite + Unpin {
+                    impl_async_write_methods!($type, ne, "native byte order");
+                    impl_async_write_methods!($type, le, "little endian byte order");
+                    impl_async_write_methods!($type, be, "big endian byte order");
+                }
+                impl<T: AsyncWrite + Unpin + ?Sized> [<AsyncWrite $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+macro_rules! impl_async_write {
+    ($async_write:path) => {
+        use std::future::Future;
+        use std::io;
+        use std::pin::Pin;
+        use std::task::{ready, Context, Poll};
+        use $async_write as AsyncWrite;
+
+        /// Future returned by the async write methods
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct WriteBytes<'a, W: ?Sized> {
+            writer: &'a mut W,
+            bytes: [u8; 16],
+            pos: usize,
+            len: usize,
+        }
+
+        impl<'a, W: ?Sized> WriteBytes<'a, W> {
+            fn new(writer: &'a mut W, value: &[u8]) -> Self {
+                let mut bytes = [0u8; 16];
+                bytes[..value.len()].copy_from_slice(value);
+                WriteBytes {
+                    writer,
+                    bytes,
+                    pos: 0,
+                    len: value.len(),
+                }
+            }
+        }
+
+        impl<W: AsyncWrite + Unpin + ?Sized> Future for WriteBytes<'_, W> {
+            type Output = io::Result<()>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = &mut *self;
+                while this.pos < this.len {
+                    let written =
+                        ready!(Pin::new(&mut *this.writer)
+                            .poll_write(cx, &this.bytes[this.pos..this.len]))?;
+                    if written == 0 {
+                        return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+                    }
+                    this.pos += written;
+                }
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        impl_async_write_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+        /// Trait to asynchronously write u8
+        pub trait AsyncWriteU8: AsyncWrite + Unpin {
+            /// Write a u8
+            /// # Errors
+            /// the future errors when writing to the underlying writer errors
+            fn write_u8(&mut self, value: u8) -> WriteBytes<'_, Self> {
+                WriteBytes::new(self, &[value])
+            }
+        }
+        impl<W: AsyncWrite + Unpin + ?Sized> AsyncWriteU8 for W {}
+
+        /// Trait to asynchronously write char
+        pub trait AsyncWriteChar: AsyncWrite + Unpin {
+            /// Write a char as a u32 in native byte order
+            /// # Errors
+            /// the future errors when writing to the underlying writer errors
+            fn write_char(&mut self, value: char) -> WriteBytes<'_, Self> {
+                WriteBytes::new(self, &u32::from(value).to_ne_bytes())
+            }
+        }
+        impl<W: AsyncWrite + Unpin + ?Sized> AsyncWriteChar for W {}
+
+        /// Trait to asynchronously write bool
+        pub trait AsyncWriteBool: AsyncWrite + Unpin {
+            /// Write a bool as a single byte
+            /// # Errors
+            /// the future errors when writing to the underlying writer errors
+            fn write_bool(&mut self, value: bool) -> WriteBytes<'_, Self> {
+                WriteBytes::new(self, &[u8::from(value)])
+            }
+        }
+        impl<W: AsyncWrite + Unpin + ?Sized> AsyncWriteBool for W {}
+    };
+}
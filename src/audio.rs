@@ -0,0 +1,250 @@
+//! Reads of interleaved multi-channel PCM samples
+//!
+//! Frames are read in large blocks and split into one buffer per channel in a single pass.
+//! 24-bit samples are sign-extended into `i32`. [`ReadNormalized`] reads integer samples as
+//! `f32` scaled to `-1.0..1.0` instead.
+//!
+//! ```
+//! use read_primitives::audio::ReadInterleaved;
+//! // two stereo frames: (1, -1), (2, -2)
+//! let mut bytes: &[u8] = &[1, 0, 0xff, 0xff, 2, 0, 0xfe, 0xff];
+//! let mut channels = [Vec::new(), Vec::new()];
+//! bytes.read_le_i16_deinterleaved(2, &mut channels).unwrap();
+//! assert_eq!([vec![1, 2], vec![-1, -2]], channels);
+//! ```
+
+use std::io::{self, Read};
+
+/// Number of bytes decoded per block
+const BLOCK_LEN: usize = 16 * 1024;
+
+pub(crate) fn i24_from_le_bytes([b0, b1, b2]: [u8; 3]) -> i32 {
+    i32::from_le_bytes([0, b0, b1, b2]) >> 8
+}
+
+pub(crate) fn i24_from_be_bytes([b0, b1, b2]: [u8; 3]) -> i32 {
+    i32::from_be_bytes([b0, b1, b2, 0]) >> 8
+}
+
+macro_rules! impl_deinterleaved {
+    ($name:ident, $out:ty, $size:literal, $decode:expr, $doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read `frames` interleaved " $doc " frames and append each channel's samples to its buffer in `channels`"]
+            #[doc = ""]
+            #[doc = "The number of channels is the length of `channels`."]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidInput`] if `channels` is empty,"]
+            #[doc = "or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn $name(&mut self, frames: usize, channels: &mut [Vec<$out>]) -> io::Result<()> {
+                if channels.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, "no channels"));
+                }
+                let frame_len = $size * channels.len();
+                let frames_per_block = (BLOCK_LEN / frame_len).max(1);
+                let mut block = vec![0u8; frames_per_block.min(frames) * frame_len];
+                let mut remaining = frames;
+                while remaining > 0 {
+                    let len = remaining.min(frames_per_block);
+                    let bytes = &mut block[..len * frame_len];
+                    self.read_exact(bytes)?;
+                    for frame in bytes.chunks_exact(frame_len) {
+                        for (channel, sample) in channels.iter_mut().zip(frame.chunks_exact($size)) {
+                            let mut array = [0u8; $size];
+                            array.copy_from_slice(sample);
+                            channel.push($decode(array));
+                        }
+                    }
+                    remaining -= len;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+/// Trait to read interleaved PCM frames into per-channel buffers
+pub trait ReadInterleaved: Read {
+    impl_deinterleaved!(
+        read_le_i16_deinterleaved,
+        i16,
+        2,
+        i16::from_le_bytes,
+        "little endian i16"
+    );
+    impl_deinterleaved!(
+        read_be_i16_deinterleaved,
+        i16,
+        2,
+        i16::from_be_bytes,
+        "big endian i16"
+    );
+    impl_deinterleaved!(
+        read_le_i24_deinterleaved,
+        i32,
+        3,
+        i24_from_le_bytes,
+        "little endian 24-bit"
+    );
+    impl_deinterleaved!(
+        read_be_i24_deinterleaved,
+        i32,
+        3,
+        i24_from_be_bytes,
+        "big endian 24-bit"
+    );
+    impl_deinterleaved!(
+        read_le_f32_deinterleaved,
+        f32,
+        4,
+        f32::from_le_bytes,
+        "little endian f32"
+    );
+    impl_deinterleaved!(
+        read_be_f32_deinterleaved,
+        f32,
+        4,
+        f32::from_be_bytes,
+        "big endian f32"
+    );
+}
+impl<R> ReadInterleaved for R where R: Read {}
+
+macro_rules! impl_normalized {
+    ($order:ident, $type:ident, $size:literal, $decode:expr, $doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read a " $doc " sample as `f32` in `-1.0..1.0`, dividing by the magnitude of its most negative value"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _norm_f32>](&mut self) -> io::Result<f32> {
+                let mut array = [0u8; $size];
+                self.read_exact(&mut array)?;
+                Ok(normalize($decode(array) as f64, $size))
+            }
+            #[doc = "Read `samples.len()` " $doc " samples into `samples` as `f32` in `-1.0..1.0`"]
+            #[doc = ""]
+            #[doc = "Bytes are read in large blocks and converted in one pass."]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _norm_f32_into>](&mut self, samples: &mut [f32]) -> io::Result<()> {
+                let mut block = vec![0u8; samples.len().min(BLOCK_LEN / $size) * $size];
+                for chunk in samples.chunks_mut(BLOCK_LEN / $size) {
+                    let bytes = &mut block[..chunk.len() * $size];
+                    self.read_exact(bytes)?;
+                    for (sample, bytes) in chunk.iter_mut().zip(bytes.chunks_exact($size)) {
+                        let mut array = [0u8; $size];
+                        array.copy_from_slice(bytes);
+                        *sample = normalize($decode(array) as f64, $size);
+                    }
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+/// Scale a signed integer sample of `size` bytes to `-1.0..1.0`
+fn normalize(sample: f64, size: i32) -> f32 {
+    (sample / 2f64.powi(size * 8 - 1)) as f32
+}
+
+/// Trait to read integer PCM samples as normalized `f32`, as audio pipelines process them
+///
+/// ```
+/// use read_primitives::audio::ReadNormalized;
+/// let mut bytes: &[u8] = &[0, 0x80, 0, 0x40, 0, 0, 0, 0xc0];
+/// assert_eq!(-1.0, bytes.read_le_i16_norm_f32().unwrap());
+/// let mut samples = [0.0; 3];
+/// bytes.read_le_i16_norm_f32_into(&mut samples).unwrap();
+/// assert_eq!([0.5, 0.0, -0.5], samples);
+/// ```
+pub trait ReadNormalized: Read {
+    impl_normalized!(le, i16, 2, i16::from_le_bytes, "little endian i16");
+    impl_normalized!(be, i16, 2, i16::from_be_bytes, "big endian i16");
+    impl_normalized!(le, i24, 3, i24_from_le_bytes, "little endian 24-bit");
+    impl_normalized!(be, i24, 3, i24_from_be_bytes, "big endian 24-bit");
+    impl_normalized!(le, i32, 4, i32::from_le_bytes, "little endian i32");
+    impl_normalized!(be, i32, 4, i32::from_be_bytes, "big endian i32");
+}
+impl<R> ReadNormalized for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::audio::*;
+
+    #[test]
+    fn i24() {
+        assert_eq!(-1, i24_from_le_bytes([0xff, 0xff, 0xff]));
+        assert_eq!(0x7f_ff_fe, i24_from_le_bytes([0xfe, 0xff, 0x7f]));
+        assert_eq!(-0x80_00_00, i24_from_be_bytes([0x80, 0, 0]));
+        assert_eq!(0x01_02_03, i24_from_be_bytes([1, 2, 3]));
+    }
+
+    #[test]
+    fn deinterleave_i24() {
+        let mut bytes: &[u8] = &[
+            1, 0, 0, 0xff, 0xff, 0xff, 0, 0, 0x80, 2, 0, 0, 3, 0, 0, 4, 0, 0,
+        ];
+        let mut channels = [Vec::new(), Vec::new(), Vec::new()];
+        bytes.read_le_i24_deinterleaved(2, &mut channels).unwrap();
+        assert_eq!([vec![1, 2], vec![-1, 3], vec![-0x80_00_00, 4]], channels);
+    }
+
+    #[test]
+    fn deinterleave_many_blocks() {
+        let frames = 10_000;
+        let bytes: Vec<u8> = (0..frames)
+            .flat_map(|i| [(i as f32).to_be_bytes(), (-(i as f32)).to_be_bytes()])
+            .flatten()
+            .collect();
+        let mut channels = [Vec::new(), Vec::new()];
+        bytes
+            .as_slice()
+            .read_be_f32_deinterleaved(frames, &mut channels)
+            .unwrap();
+        assert_eq!(frames, channels[0].len());
+        assert_eq!(9999.0, channels[0][9999]);
+        assert_eq!(-9999.0, channels[1][9999]);
+    }
+
+    #[test]
+    fn normalized() {
+        let mut bytes: &[u8] = &[0x7f, 0xff, 0xff, 0, 0, 0x80, 0x80, 0, 0, 0, 1];
+        let max = bytes.read_be_i24_norm_f32().unwrap();
+        assert!(max < 1.0 && max > 0.999_999);
+        assert_eq!(-1.0, bytes.read_le_i24_norm_f32().unwrap());
+        assert_eq!(-1.0, bytes.read_be_i32_norm_f32().unwrap());
+        let err = bytes.read_le_i16_norm_f32().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn normalized_many_blocks() {
+        let bytes: Vec<u8> = (0..20_000i32)
+            .flat_map(|i| ((i - 10_000) as i16).to_be_bytes())
+            .collect();
+        let mut samples = vec![0.0; 20_000];
+        bytes
+            .as_slice()
+            .read_be_i16_norm_f32_into(&mut samples)
+            .unwrap();
+        assert_eq!(-10_000.0 / 32_768.0, samples[0]);
+        assert_eq!(9_999.0 / 32_768.0, samples[19_999]);
+        let err = bytes[1..]
+            .as_ref()
+            .read_be_i16_norm_f32_into(&mut samples)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn deinterleave_errors() {
+        let mut bytes: &[u8] = &[1, 0, 2];
+        let err = bytes.read_le_i16_deinterleaved(1, &mut []).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let mut channels = [Vec::new(), Vec::new()];
+        let err = bytes
+            .read_le_i16_deinterleaved(1, &mut channels)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,95 @@
+//! Decoding from the consumer of a [`bbqueue`](::bbqueue) queue
+//!
+//! Values are decoded from both parts of the committed bytes, so they may span the wrap point, and
+//! their bytes are only released once a value is complete: a producer, such as an interrupt
+//! handler, can keep committing bytes while the main loop retries.
+//!
+//! ```
+//! use bbqueue::BBBuffer;
+//! use read_primitives::bbqueue::ReadConsumer;
+//! use read_primitives::{Endian, Needed};
+//!
+//! static QUEUE: BBBuffer<8> = BBBuffer::new();
+//! let (mut producer, mut consumer) = QUEUE.try_split().unwrap();
+//! let mut grant = producer.grant_exact(3).unwrap();
+//! grant.buf().copy_from_slice(&[0x12, 0x34, 0x56]);
+//! grant.commit(3);
+//! assert_eq!(Ok(0x1234), consumer.try_decode::<u16>(Endian::Big).unwrap());
+//! assert_eq!(Err(Needed::Unknown), consumer.try_decode::<u16>(Endian::Big).unwrap());
+//! ```
+
+use crate::embedded::{decode_split, DecodeError, ReadFrom};
+use crate::{Endian, Needed};
+use ::bbqueue::{Consumer, Error};
+
+/// Trait to decode [`embedded::ReadFrom`](ReadFrom) values from a [`Consumer`]
+pub trait ReadConsumer {
+    /// Decode a `T` from the front, releasing its bytes, or tell that more bytes are needed and
+    /// release nothing
+    /// # Errors
+    /// errors if the data is invalid for the type, or with [`Error::GrantInProgress`] as the
+    /// reader error if a read grant is still held
+    fn try_decode<T: ReadFrom>(
+        &mut self,
+        endian: Endian,
+    ) -> Result<Result<T, Needed>, DecodeError<Error>>;
+}
+
+impl<const N: usize> ReadConsumer for Consumer<'_, N> {
+    fn try_decode<T: ReadFrom>(
+        &mut self,
+        endian: Endian,
+    ) -> Result<Result<T, Needed>, DecodeError<Error>> {
+        let grant = match self.split_read() {
+            Ok(grant) => grant,
+            Err(Error::InsufficientSize) => return Ok(Err(Needed::Unknown)),
+            Err(err) => return Err(crate::embedded::DecodeErrorKind::Io(err).into()),
+        };
+        let (front, back) = grant.bufs();
+        match decode_split(front, back, endian).map_err(|err| err.map_io(|never| match never {}))? {
+            Ok((value, len)) => {
+                grant.release(len);
+                Ok(Ok(value))
+            }
+            Err(needed) => {
+                grant.release(0);
+                Ok(Err(needed))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bbqueue::*;
+    use crate::embedded::DecodeErrorKind;
+    use ::bbqueue::BBBuffer;
+
+    #[test]
+    fn across_wrap_point() {
+        static QUEUE: BBBuffer<6> = BBBuffer::new();
+        let (mut producer, mut consumer) = QUEUE.try_split().unwrap();
+        let grant = producer.grant_exact(4).unwrap();
+        grant.commit(4);
+        consumer.read().unwrap().release(4);
+        let mut grant = producer.grant_exact(2).unwrap();
+        grant.buf().copy_from_slice(&[1, 2]);
+        grant.commit(2);
+        let mut grant = producer.grant_exact(2).unwrap();
+        grant.buf().copy_from_slice(&[3, 4]);
+        grant.commit(2);
+        assert_eq!(
+            Ok(0x0403_0201),
+            consumer.try_decode::<u32>(Endian::Little).unwrap()
+        );
+        assert_eq!(
+            Err(Needed::Unknown),
+            consumer.try_decode::<u8>(Endian::Little).unwrap()
+        );
+
+        producer.grant_exact(1).unwrap().commit(1);
+        let _held = consumer.read().unwrap();
+        let err = consumer.try_decode::<u8>(Endian::Little).unwrap_err();
+        assert_eq!(&DecodeErrorKind::Io(Error::GrantInProgress), err.kind());
+    }
+}
@@ -0,0 +1,108 @@
+/// Unsigned integer that [`bitfields!`](crate::bitfields) can split into fields
+pub trait Bitfield: Copy {
+    /// Signed integer of the same width, for signed fields
+    type Signed;
+    /// Width in bits
+    const BITS: u32;
+
+    /// The `width` bits starting `shift` bits from the least significant one
+    fn bits(self, shift: u32, width: u32) -> Self;
+
+    /// [`Bitfield::bits`] read as two's complement, sign-extended
+    fn signed_bits(self, shift: u32, width: u32) -> Self::Signed;
+}
+
+macro_rules! impl_bitfield {
+    ($($type:ty, $signed:ty);+) => {
+        $(
+            impl Bitfield for $type {
+                type Signed = $signed;
+                const BITS: u32 = <$type>::BITS;
+
+                fn bits(self, shift: u32, width: u32) -> Self {
+                    (self >> shift) & (<$type>::MAX >> (Self::BITS - width))
+                }
+
+                fn signed_bits(self, shift: u32, width: u32) -> Self::Signed {
+                    ((self << (Self::BITS - shift - width)) as $signed) >> (Self::BITS - width)
+                }
+            }
+        )+
+    };
+}
+
+impl_bitfield!(u8, i8; u16, i16; u32, i32; u64, i64; u128, i128; usize, isize);
+
+/// Split an unsigned integer into named bitfields, listed from the most significant bit
+///
+/// Evaluates to a value with one field per name. Fields are of the integer's type, or of the
+/// signed type of the same width when their width is preceded by `signed`. The widths must add
+/// up to the width of the integer; name unused bits too.
+///
+/// ```
+/// use read_primitives::{bitfields, ReadU32};
+/// let mut bytes: &[u8] = &[0x4f, 0xff, 0x00, 0x2a];
+/// let header = bitfields!(bytes.read_be_u32().unwrap(), {
+///     version: 4,
+///     delta: signed 12,
+///     length: 16,
+/// });
+/// assert_eq!((4, -1, 42), (header.version, header.delta, header.length));
+/// ```
+/// # Panics
+/// panics if the widths don't add up to the width of the integer
+#[macro_export]
+macro_rules! bitfields {
+    ($value:expr, { $($name:ident : $($signed:ident)? $width:literal),+ $(,)? }) => {{
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        struct Bitfields<$($name),+> {
+            $($name: $name),+
+        }
+        $(const _: () = assert!($width > 0, "bitfields must be at least one bit wide");)+
+        let value = $value;
+        let mut shift = $crate::__bitfield_width(&value);
+        assert!(
+            shift == 0 $(+ $width)+,
+            "bitfield widths do not add up to the width of the integer"
+        );
+        Bitfields {
+            $($name: {
+                shift -= $width;
+                $crate::bitfields!(@bits value, shift, $width $(, $signed)?)
+            }),+
+        }
+    }};
+    (@bits $value:ident, $shift:ident, $width:literal) => {
+        $crate::Bitfield::bits($value, $shift, $width)
+    };
+    (@bits $value:ident, $shift:ident, $width:literal, signed) => {
+        $crate::Bitfield::signed_bits($value, $shift, $width)
+    };
+}
+
+/// Width of the integer passed to [`bitfields!`](crate::bitfields), not public API
+#[doc(hidden)]
+pub fn __bitfield_width<T: Bitfield>(_: &T) -> u32 {
+    T::BITS
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn fields() {
+        let fields = bitfields!(0b1010_0110u8, { high: 3, low: signed 5 });
+        assert_eq!((0b101, 0b00110), (fields.high, fields.low));
+        let fields = bitfields!(0b1011_1111u8, { high: signed 2, low: signed 6 });
+        assert_eq!((-2, -1), (fields.high, fields.low));
+        let whole = bitfields!(u64::MAX, { all: signed 64 });
+        assert_eq!(-1, whole.all);
+        assert_eq!(u128::MAX, bitfields!(u128::MAX, { all: 128 }).all);
+    }
+
+    #[test]
+    #[should_panic = "bitfield widths"]
+    fn widths_must_add_up() {
+        let _ = bitfields!(0u16, { short: 15 });
+    }
+}
@@ -0,0 +1,241 @@
+use crate::BitOrder;
+use std::io::{self, Read};
+
+/// Reader of single bits and bit fields from the bytes of the inner reader
+///
+/// Bits are taken from each byte in the [`BitOrder`] given: [`BitOrder::MsbFirst`] for video
+/// bitstreams like H.264's, where a field's first bit is its most significant, and
+/// [`BitOrder::LsbFirst`] for formats like DEFLATE, where it's the least significant.
+///
+/// ```
+/// use read_primitives::{BitOrder, BitReader};
+/// // the codes 1, 011 and 011, then a 2 bit field 01
+/// let mut bits = BitReader::new(&[0b1011_0110, 0b1000_0000][..], BitOrder::MsbFirst);
+/// assert_eq!(0, bits.read_ue().unwrap());
+/// assert_eq!(2, bits.read_ue().unwrap());
+/// assert_eq!(-1, bits.read_se().unwrap());
+/// assert_eq!(0b01, bits.read_bits(2).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct BitReader<R> {
+    inner: R,
+    order: BitOrder,
+    byte: u8,
+    /// Bits of `byte` not yet read
+    left: u32,
+}
+
+impl<R> BitReader<R> {
+    /// Read the bits of `inner` in `order`
+    pub fn new(inner: R, order: BitOrder) -> Self {
+        BitReader {
+            inner,
+            order,
+            byte: 0,
+            left: 0,
+        }
+    }
+
+    /// Whether the next bit starts a byte
+    pub fn is_aligned(&self) -> bool {
+        self.left == 0
+    }
+
+    /// Skip the rest of the current byte
+    pub fn align(&mut self) {
+        self.left = 0;
+    }
+
+    /// Unwrap the reader, dropping the unread bits of the current byte
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> BitReader<R> {
+    fn fill(&mut self) -> io::Result<()> {
+        if self.left == 0 {
+            let mut byte = [0];
+            self.inner.read_exact(&mut byte)?;
+            self.byte = byte[0];
+            self.left = 8;
+        }
+        Ok(())
+    }
+
+    /// Read one bit
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    pub fn read_bit(&mut self) -> io::Result<bool> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    /// Read a field of `count` bits, at most 64
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `count` is over 64,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    pub fn read_bits(&mut self, count: u32) -> io::Result<u64> {
+        if count > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can't read {count} bits into a u64"),
+            ));
+        }
+        let mut value = 0u64;
+        let mut read = 0;
+        while read < count {
+            self.fill()?;
+            let take = (count - read).min(self.left);
+            let mask = (1u16 << take) - 1;
+            let bits = match self.order {
+                BitOrder::MsbFirst => (u16::from(self.byte) >> (self.left - take)) & mask,
+                BitOrder::LsbFirst => (u16::from(self.byte) >> (8 - self.left)) & mask,
+            };
+            value = match self.order {
+                BitOrder::MsbFirst => (value << take) | u64::from(bits),
+                BitOrder::LsbFirst => value | (u64::from(bits) << read),
+            };
+            self.left -= take;
+            read += take;
+        }
+        Ok(value)
+    }
+
+    /// Read an unsigned Exp-Golomb code, the `ue(v)` of H.264 and H.265
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the code has more than 31 leading zeros,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    pub fn read_ue(&mut self) -> io::Result<u32> {
+        let mut zeros = 0;
+        while !self.read_bit()? {
+            zeros += 1;
+            if zeros > 31 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Exp-Golomb code with more than 31 leading zeros",
+                ));
+            }
+        }
+        let suffix = self.read_bits(zeros)?;
+        Ok(((1u64 << zeros) - 1 + suffix) as u32)
+    }
+
+    /// Read a signed Exp-Golomb code, the `se(v)` of H.264 and H.265, which maps the codes
+    /// 0, 1, 2, 3, 4 to 0, 1, -1, 2, -2
+    /// # Errors
+    /// errors like [`BitReader::read_ue`]
+    pub fn read_se(&mut self) -> io::Result<i32> {
+        let code = i64::from(self.read_ue()?);
+        let value = if code % 2 == 1 {
+            (code + 1) / 2
+        } else {
+            -code / 2
+        };
+        Ok(value as i32)
+    }
+
+    /// Read a code of the prefix code `table`, returning the value of the code that matched
+    ///
+    /// Each entry is `(code, len, value)`: the code's `len` bits, the first one read the most
+    /// significant, for the small tables of headers like variable-length coded syntax elements.
+    /// Bits are read one at a time until they match a code, so no code may be a prefix of
+    /// another.
+    ///
+    /// ```
+    /// use read_primitives::{BitOrder, BitReader};
+    /// let table = [(0b0, 1, 'a'), (0b10, 2, 'b'), (0b11, 2, 'c')];
+    /// let mut bits = BitReader::new(&[0b10_0_11_000][..], BitOrder::MsbFirst);
+    /// assert_eq!('b', bits.read_prefix_code(&table).unwrap());
+    /// assert_eq!('a', bits.read_prefix_code(&table).unwrap());
+    /// assert_eq!('c', bits.read_prefix_code(&table).unwrap());
+    /// ```
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bits match no code,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    pub fn read_prefix_code<T: Copy>(&mut self, table: &[(u32, u32, T)]) -> io::Result<T> {
+        let max_len = table.iter().map(|&(_, len, _)| len).max().unwrap_or(0);
+        let mut code = 0u32;
+        for len in 1..=max_len.min(32) {
+            code = (code << 1) | u32::from(self.read_bit()?);
+            if let Some(&(_, _, value)) = table
+                .iter()
+                .find(|&&(entry, entry_len, _)| entry_len == len && entry == code)
+            {
+                return Ok(value);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("bits {code:#b} match no prefix code"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn bits_in_both_orders() {
+        let mut msb = BitReader::new(&[0b1010_0110, 0xff][..], BitOrder::MsbFirst);
+        assert_eq!(0b101, msb.read_bits(3).unwrap());
+        assert_eq!(0b0_0110_1111, msb.read_bits(9).unwrap());
+        assert!(!msb.is_aligned());
+        msb.align();
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            msb.read_bit().unwrap_err().kind()
+        );
+
+        let mut lsb = BitReader::new(&[0b1010_0110, 0b0000_0001][..], BitOrder::LsbFirst);
+        assert_eq!(0b110, lsb.read_bits(3).unwrap());
+        assert_eq!(0b11_0100, lsb.read_bits(6).unwrap());
+        assert!(lsb.read_bits(65).is_err());
+    }
+
+    #[test]
+    fn exp_golomb() {
+        // codes 0 to 8: 1, 010, 011, 00100, 00101, 00110, 00111, 0001000, 0001001
+        let mut bits = BitReader::new(
+            &[
+                0b1010_0110,
+                0b0100_0010,
+                0b1001_1000,
+                0b1110_0010,
+                0b0000_0100,
+                0b1000_0000,
+            ][..],
+            BitOrder::MsbFirst,
+        );
+        let codes: Vec<_> = (0..9).map(|_| bits.read_ue().unwrap()).collect();
+        assert_eq!((0..9).collect::<Vec<_>>(), codes);
+
+        let mut signed = BitReader::new(
+            &[0b1010_0110, 0b0100_0010, 0b1000_0000][..],
+            BitOrder::MsbFirst,
+        );
+        let values: Vec<_> = (0..5).map(|_| signed.read_se().unwrap()).collect();
+        assert_eq!([0, 1, -1, 2, -2], *values);
+
+        // the largest code, 31 zeros, a one and 31 ones
+        let mut max = BitReader::new(
+            &[0, 0, 0, 1, 0xff, 0xff, 0xff, 0xfe][..],
+            BitOrder::MsbFirst,
+        );
+        assert_eq!(u32::MAX - 1, max.read_ue().unwrap());
+        let mut zeros = BitReader::new(&[0u8; 5][..], BitOrder::MsbFirst);
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            zeros.read_ue().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn unmatched_prefix_code() {
+        let table = [(0b1, 1, 1u8), (0b01, 2, 2)];
+        let mut bits = BitReader::new(&[0b00_000000][..], BitOrder::MsbFirst);
+        let err = bits.read_prefix_code(&table).unwrap_err();
+        assert_eq!("bits 0b0 match no prefix code", err.to_string());
+    }
+}
@@ -0,0 +1,103 @@
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::io;
+
+/// Trait to read bytes and strings that borrow from the underlying data instead of copying it
+///
+/// Implemented for `&'a [u8]`, so parsers over fully-buffered or memory-mapped data can decode
+/// byte-blob and string fields without allocating.
+pub trait ReadBorrowed<'a> {
+    /// Read `len` bytes, borrowing them from the source
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if fewer than `len` bytes remain
+    fn read_borrowed_bytes(&mut self, len: usize) -> io::Result<&'a [u8]>;
+
+    /// Read a UTF-8 string of `len` bytes, borrowing it from the source
+    ///
+    /// The bytes are consumed even if they are not valid UTF-8.
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if fewer than `len` bytes remain,
+    /// and with [`io::ErrorKind::InvalidData`] if the bytes are not valid UTF-8
+    fn read_borrowed_str(&mut self, len: usize) -> io::Result<&'a str> {
+        let bytes = self.read_borrowed_bytes(len)?;
+        std::str::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Read a string of `len` bytes, borrowing it from the source unless invalid UTF-8 has to be
+    /// replaced with U+FFFD
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if fewer than `len` bytes remain
+    fn read_borrowed_str_lossy(&mut self, len: usize) -> io::Result<Cow<'a, str>> {
+        self.read_borrowed_bytes(len).map(String::from_utf8_lossy)
+    }
+
+    /// Read a fixed-size, NUL-terminated field of `len` bytes, borrowing the C string from the source
+    ///
+    /// The terminator may be followed by NUL padding up to `len`, but not by other bytes.
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if fewer than `len` bytes remain,
+    /// and with [`io::ErrorKind::InvalidData`] if the field has no terminator or an interior NUL
+    fn read_borrowed_cstr(&mut self, len: usize) -> io::Result<&'a CStr> {
+        crate::string::cstr_from_field(self.read_borrowed_bytes(len)?)
+    }
+}
+
+impl<'a> ReadBorrowed<'a> for &'a [u8] {
+    fn read_borrowed_bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len > self.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let (bytes, rest) = self.split_at(len);
+        *self = rest;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ReadBorrowed;
+    use std::borrow::Cow;
+    use std::io;
+
+    #[test]
+    fn read_borrowed_bytes() {
+        let data = [1u8, 2, 3, 4];
+        let mut slice = data.as_slice();
+        let bytes = slice.read_borrowed_bytes(3).unwrap();
+        assert_eq!([1, 2, 3], bytes);
+        assert_eq!([4], slice);
+        let err = slice.read_borrowed_bytes(2).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!([4], slice);
+    }
+
+    #[test]
+    fn read_borrowed_str() {
+        let data = b"hello\xff";
+        let mut slice = data.as_slice();
+        assert_eq!("hello", slice.read_borrowed_str(5).unwrap());
+        let err = slice.read_borrowed_str(1).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_borrowed_str_lossy() {
+        let data = b"ok\xff!";
+        let mut slice = data.as_slice();
+        assert!(matches!(
+            slice.read_borrowed_str_lossy(2).unwrap(),
+            Cow::Borrowed("ok")
+        ));
+        assert_eq!("\u{fffd}!", slice.read_borrowed_str_lossy(2).unwrap());
+    }
+
+    #[test]
+    fn read_borrowed_cstr() {
+        let data = b"name\0\0id\0nul\0x\0";
+        let mut slice = data.as_slice();
+        assert_eq!(c"name", slice.read_borrowed_cstr(6).unwrap());
+        assert_eq!(c"id", slice.read_borrowed_cstr(3).unwrap());
+        let err = slice.read_borrowed_cstr(6).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,310 @@
+//! Reads of BSON element encodings
+//!
+//! A BSON document is an i32 length, elements, then a NUL byte. Each element is a type byte, a
+//! NUL-terminated key, then a value encoded by the type. These reads decode the element header
+//! and the scalar values, leaving the caller to walk documents and arrays, as tools inspecting
+//! MongoDB dumps do. All numbers are little endian.
+//!
+//! ```
+//! use read_primitives::bson::{ElementType, ReadBson};
+//! // {"n": 5, "s": "hi"}
+//! let mut bytes: &[u8] = &[
+//!     0x16, 0, 0, 0,
+//!     0x10, b'n', 0, 5, 0, 0, 0,
+//!     0x02, b's', 0, 3, 0, 0, 0, b'h', b'i', 0,
+//!     0,
+//! ];
+//! assert_eq!(0x16, bytes.read_bson_document_len().unwrap());
+//! assert_eq!(Some((ElementType::Int32, "n".into())), bytes.read_bson_element_header().unwrap());
+//! assert_eq!(5, bytes.read_bson_int32().unwrap());
+//! assert_eq!(Some((ElementType::String, "s".into())), bytes.read_bson_element_header().unwrap());
+//! assert_eq!("hi", bytes.read_bson_string().unwrap());
+//! assert_eq!(None, bytes.read_bson_element_header().unwrap());
+//! ```
+
+use crate::limits::check_bytes;
+use crate::string::read_exact_vec;
+use std::io::{self, Read};
+
+/// Type of an element's value, its first byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementType {
+    /// f64
+    Double,
+    /// Length-prefixed UTF-8 string
+    String,
+    /// Embedded document
+    Document,
+    /// Document keyed by indices
+    Array,
+    /// Length-prefixed binary with a subtype
+    Binary,
+    /// Deprecated, no value
+    Undefined,
+    /// 12 byte ObjectId
+    ObjectId,
+    /// A byte of 0 or 1
+    Bool,
+    /// i64 milliseconds since the Unix epoch
+    DateTime,
+    /// No value
+    Null,
+    /// Two cstrings, the pattern and the options
+    Regex,
+    /// Deprecated, a string and a 12 byte ObjectId
+    DbPointer,
+    /// JavaScript code as a string
+    JavaScript,
+    /// Deprecated, a string
+    Symbol,
+    /// Deprecated, an i32 length, a string and a document
+    JavaScriptWithScope,
+    /// i32
+    Int32,
+    /// u64 of MongoDB's internal timestamps
+    Timestamp,
+    /// i64
+    Int64,
+    /// 16 byte IEEE 754 decimal128
+    Decimal128,
+    /// Sorts before all other values, no value
+    MinKey,
+    /// Sorts after all other values, no value
+    MaxKey,
+}
+
+impl ElementType {
+    /// Type of the type byte `byte`, `None` if it isn't one
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0x01 => ElementType::Double,
+            0x02 => ElementType::String,
+            0x03 => ElementType::Document,
+            0x04 => ElementType::Array,
+            0x05 => ElementType::Binary,
+            0x06 => ElementType::Undefined,
+            0x07 => ElementType::ObjectId,
+            0x08 => ElementType::Bool,
+            0x09 => ElementType::DateTime,
+            0x0a => ElementType::Null,
+            0x0b => ElementType::Regex,
+            0x0c => ElementType::DbPointer,
+            0x0d => ElementType::JavaScript,
+            0x0e => ElementType::Symbol,
+            0x0f => ElementType::JavaScriptWithScope,
+            0x10 => ElementType::Int32,
+            0x11 => ElementType::Timestamp,
+            0x12 => ElementType::Int64,
+            0x13 => ElementType::Decimal128,
+            0xff => ElementType::MinKey,
+            0x7f => ElementType::MaxKey,
+            _ => return None,
+        })
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn to_string(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Length prefix of a string or binary, which may not be negative
+fn read_len<R: Read + ?Sized>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    let len = i32::from_le_bytes(bytes);
+    usize::try_from(len).map_err(|_| invalid_data(format!("negative BSON length {len}")))
+}
+
+/// Trait to read BSON element headers and scalar values
+pub trait ReadBson: Read {
+    /// Read a document's length, which counts its own 4 bytes and the terminating NUL
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length is below 5,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bson_document_len(&mut self) -> io::Result<u32> {
+        let len = self.read_bson_int32()?;
+        if len < 5 {
+            return Err(invalid_data(format!(
+                "BSON document length {len} is below 5"
+            )));
+        }
+        Ok(len as u32)
+    }
+
+    /// Read the type and key of the next element, `None` at the NUL ending a document
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the type byte is unknown or the key is not
+    /// valid UTF-8, or when [`read_bson_cstring`](ReadBson::read_bson_cstring) errors
+    fn read_bson_element_header(&mut self) -> io::Result<Option<(ElementType, String)>> {
+        let mut byte = [0u8; 1];
+        self.read_exact(&mut byte)?;
+        if byte[0] == 0 {
+            return Ok(None);
+        }
+        let element_type = ElementType::from_u8(byte[0])
+            .ok_or_else(|| invalid_data(format!("unknown BSON element type {:#04x}", byte[0])))?;
+        Ok(Some((element_type, self.read_bson_cstring()?)))
+    }
+
+    /// Read a NUL-terminated UTF-8 string, the encoding of keys
+    ///
+    /// The bytes are read one at a time up to the NUL; wrap the reader in
+    /// [`Read::take`](https://doc.rust-lang.org/std/io/trait.Read.html#method.take) with the
+    /// document length to bound untrusted input.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the string is not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends before the NUL
+    fn read_bson_cstring(&mut self) -> io::Result<String> {
+        let mut bytes = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            self.read_exact(&mut byte)?;
+            if byte[0] == 0 {
+                return to_string(bytes);
+            }
+            bytes.push(byte[0]);
+        }
+    }
+
+    /// Read an i32
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bson_int32(&mut self) -> io::Result<i32> {
+        let mut bytes = [0u8; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(i32::from_le_bytes(bytes))
+    }
+
+    /// Read an i64, the encoding of int64 and of UTC datetimes
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bson_int64(&mut self) -> io::Result<i64> {
+        let mut bytes = [0u8; 8];
+        self.read_exact(&mut bytes)?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    /// Read a double
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bson_double(&mut self) -> io::Result<f64> {
+        let mut bytes = [0u8; 8];
+        self.read_exact(&mut bytes)?;
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    /// Read a bool
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the byte is not 0 or 1,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bson_bool(&mut self) -> io::Result<bool> {
+        let mut byte = [0u8; 1];
+        self.read_exact(&mut byte)?;
+        match byte[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(invalid_data(format!("invalid BSON bool {byte:#04x}"))),
+        }
+    }
+
+    /// Read a string: an i32 length counting the NUL, UTF-8, then the NUL
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length is negative, 0 or exceeds the
+    /// [`Limits`](crate::Limits), the string doesn't end in a NUL or is not valid UTF-8, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_bson_string(&mut self) -> io::Result<String> {
+        let len = read_len(self)?;
+        check_bytes(len)?;
+        let mut bytes = read_exact_vec(self, len)?;
+        if bytes.pop() != Some(0) {
+            return Err(invalid_data("BSON string has no terminator".into()));
+        }
+        to_string(bytes)
+    }
+
+    /// Read a binary: an i32 length, a subtype byte, then the bytes, returning the subtype and
+    /// the bytes
+    ///
+    /// The bytes of the old binary subtype `0x02` are returned as stored, with their own length
+    /// prefix.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length is negative or exceeds the
+    /// [`Limits`](crate::Limits), or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_bson_binary(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let len = read_len(self)?;
+        check_bytes(len)?;
+        let mut subtype = [0u8; 1];
+        self.read_exact(&mut subtype)?;
+        Ok((subtype[0], read_exact_vec(self, len)?))
+    }
+}
+impl<R> ReadBson for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::bson::*;
+    use std::io;
+
+    #[test]
+    fn scalars() {
+        let mut bytes: &[u8] = &[
+            0xff, 0xff, 0xff, 0xff, // -1
+            0, 0, 0, 0, 0, 0, 0xf0, 0x3f, // 1.0
+            1, 0, 0, 0, 0, 0, 0, 0x80, // i64::MIN + 1
+            1,
+        ];
+        assert_eq!(-1, bytes.read_bson_int32().unwrap());
+        assert_eq!(1.0, bytes.read_bson_double().unwrap());
+        assert_eq!(i64::MIN + 1, bytes.read_bson_int64().unwrap());
+        assert!(bytes.read_bson_bool().unwrap());
+        let err = (&[2u8][..]).read_bson_bool().unwrap_err();
+        assert_eq!("invalid BSON bool 0x02", err.to_string());
+    }
+
+    #[test]
+    fn strings() {
+        let mut bytes: &[u8] = &[
+            b'k', b'e', b'y', 0, 1, 0, 0, 0, 0, 2, 0, 0, 0, 0x80, 1, 0xaa, 0xbb,
+        ];
+        assert_eq!("key", bytes.read_bson_cstring().unwrap());
+        assert_eq!("", bytes.read_bson_string().unwrap());
+        assert_eq!((0x80, vec![1, 0xaa]), bytes.read_bson_binary().unwrap());
+        let err = (&[2u8, 0, 0, 0, b'a', b'b'][..])
+            .read_bson_string()
+            .unwrap_err();
+        assert_eq!("BSON string has no terminator", err.to_string());
+        let err = (&[0u8, 0, 0, 0][..]).read_bson_string().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0xffu8, 0xff, 0xff, 0xff, 0][..])
+            .read_bson_binary()
+            .unwrap_err();
+        assert_eq!("negative BSON length -1", err.to_string());
+        let err = (&[b'a'][..]).read_bson_cstring().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let limits = crate::Limits {
+            max_bytes_per_field: 1,
+            ..crate::Limits::DEFAULT
+        };
+        let err = limits
+            .apply(|| (&[2u8, 0, 0, 0, 0, 1, 2][..]).read_bson_binary())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn headers() {
+        let mut bytes: &[u8] = &[0x7f, b'z', 0, 0x14];
+        assert_eq!(
+            Some((ElementType::MaxKey, "z".into())),
+            bytes.read_bson_element_header().unwrap()
+        );
+        let err = bytes.read_bson_element_header().unwrap_err();
+        assert_eq!("unknown BSON element type 0x14", err.to_string());
+        let err = (&[4u8, 0, 0, 0][..]).read_bson_document_len().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
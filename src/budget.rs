@@ -0,0 +1,135 @@
+use crate::LimitExceeded;
+use std::io::{self, BufRead, Read};
+
+/// Reader failing once more than a total number of bytes has been read from it
+///
+/// Unlike [`Read::take`], which ends the reader quietly, reading past the budget errors with
+/// [`LimitExceeded::TotalBytes`], so a stream that is too long can't pass for a short one. This
+/// bounds the work a whole decode of untrusted input may do, however its fields are sized.
+///
+/// ```
+/// use read_primitives::{BudgetReader, LimitExceeded, ReadU16, ReadU8};
+/// use std::io::Read;
+/// let mut reader = BudgetReader::new(&[0, 1, 2, 3][..], 3);
+/// assert_eq!(1, reader.read_be_u16().unwrap());
+/// assert_eq!(2, reader.read_u8().unwrap());
+/// let err = reader.read(&mut [0]).unwrap_err();
+/// let limit = err.get_ref().unwrap().downcast_ref::<LimitExceeded>();
+/// assert_eq!(Some(&LimitExceeded::TotalBytes { max: 3 }), limit);
+/// ```
+#[derive(Debug)]
+pub struct BudgetReader<R> {
+    inner: R,
+    max: u64,
+    consumed: u64,
+}
+
+impl<R> BudgetReader<R> {
+    /// Reader allowing at most `max` bytes to be read from `inner`
+    pub fn new(inner: R, max: u64) -> Self {
+        BudgetReader {
+            inner,
+            max,
+            consumed: 0,
+        }
+    }
+
+    /// Number of bytes read so far
+    pub fn consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Number of bytes that may still be read
+    pub fn remaining(&self) -> u64 {
+        self.max - self.consumed
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped reader
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn exceeded(&self) -> io::Error {
+        LimitExceeded::TotalBytes { max: self.max }.into()
+    }
+}
+
+impl<R: Read> Read for BudgetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // one byte past the budget tells a reader that goes on from one that ends there
+        let allowed = usize::try_from(self.remaining().saturating_add(1)).unwrap_or(usize::MAX);
+        let len = buf.len().min(allowed);
+        let read = self.inner.read(&mut buf[..len])?;
+        if read as u64 > self.remaining() {
+            self.consumed = self.max;
+            return Err(self.exceeded());
+        }
+        self.consumed += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for BudgetReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let remaining = self.remaining();
+        let exceeded = self.exceeded();
+        let buf = self.inner.fill_buf()?;
+        if remaining == 0 && !buf.is_empty() {
+            return Err(exceeded);
+        }
+        Ok(&buf[..buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX))])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(usize::try_from(self.remaining()).unwrap_or(usize::MAX));
+        self.inner.consume(amt);
+        self.consumed += amt as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, BufRead, Read};
+
+    #[test]
+    fn exact_budget() {
+        let mut reader = BudgetReader::new(&[1, 2, 3, 4][..], 4);
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!([1, 2, 3, 4], *all);
+        assert_eq!((4, 0), (reader.consumed(), reader.remaining()));
+
+        let mut reader = BudgetReader::new(&[1, 2, 3, 4, 5][..], 4);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("more than 4 bytes read in total", err.to_string());
+    }
+
+    #[test]
+    fn buffered() {
+        let mut reader = BudgetReader::new(&b"ab\ncd\nef"[..], 6);
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line).unwrap();
+        reader.read_until(b'\n', &mut line).unwrap();
+        assert_eq!(b"ab\ncd\n", &*line);
+        let err = reader.fill_buf().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(BudgetReader::new(&b""[..], 0)
+            .fill_buf()
+            .unwrap()
+            .is_empty());
+    }
+}
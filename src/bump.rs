@@ -0,0 +1,185 @@
+use crate::{Endian, ReadFrom};
+use bumpalo::Bump;
+use std::io::{self, Read};
+
+/// A type that can be read from a byte stream, allocating its strings and collections in an
+/// arena
+///
+/// Implemented for every [`ReadFrom`] type. With the `derive` feature, `#[derive(ReadFrom)]` with
+/// `#[read(bump)]` on a struct with a lifetime parameter implements this trait instead, so that
+/// its `&'bump str`, `&'bump [u8]` and `bumpalo::collections::Vec<'bump, T>` fields are
+/// allocated in the [`Bump`] and freed all at once with it.
+///
+/// ```
+/// use bumpalo::Bump;
+/// use read_primitives::{Endian, ReadFrom, ReadFromIn};
+///
+/// struct Name<'bump>(&'bump str);
+///
+/// impl<'bump> ReadFromIn<'bump> for Name<'bump> {
+///     fn read_from_in<R: std::io::Read + ?Sized>(
+///         reader: &mut R,
+///         endian: Endian,
+///         bump: &'bump Bump,
+///     ) -> std::io::Result<Self> {
+///         let len = u8::read_from(reader, endian)?;
+///         let mut bytes = bumpalo::vec![in bump; 0; len.into()];
+///         reader.read_exact(&mut bytes)?;
+///         let name = std::str::from_utf8(bytes.into_bump_slice())
+///             .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+///         Ok(Name(name))
+///     }
+/// }
+///
+/// let bump = Bump::new();
+/// let name = Name::read_from_in(&mut &b"\x03abc"[..], Endian::Little, &bump).unwrap();
+/// assert_eq!("abc", name.0);
+/// ```
+pub trait ReadFromIn<'bump>: Sized {
+    /// Read a value, allocating in `bump` and using `endian` for the parts that do not specify
+    /// their own byte order
+    /// # Errors
+    /// errors when reading from `reader` errors, or if the data is invalid for the type
+    fn read_from_in<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        bump: &'bump Bump,
+    ) -> io::Result<Self>;
+}
+
+impl<'bump, T: ReadFrom> ReadFromIn<'bump> for T {
+    fn read_from_in<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        _bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        T::read_from(reader, endian)
+    }
+}
+
+/// Read `len` bytes into `bump`, growing the allocation as they arrive instead of trusting `len`
+#[cfg(feature = "derive")]
+fn read_bytes_in<'bump, R: Read + ?Sized>(
+    reader: &mut R,
+    len: usize,
+    bump: &'bump Bump,
+) -> io::Result<&'bump mut [u8]> {
+    crate::limits::check_bytes(len)?;
+    let mut bytes =
+        bumpalo::collections::Vec::with_capacity_in(len.min(crate::PREALLOC_LIMIT), bump);
+    let mut buf = [0u8; 4096];
+    while bytes.len() < len {
+        let chunk = &mut buf[..(len - bytes.len()).min(4096)];
+        reader.read_exact(chunk)?;
+        bytes.extend_from_slice(chunk);
+    }
+    Ok(bytes.into_bump_slice_mut())
+}
+
+/// Type of a `len` field of a struct read with `#[read(bump)]`
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub trait ReadLenIn<'bump>: Sized {
+    fn read_len_in<R: Read + ?Sized>(
+        reader: &mut R,
+        len: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self>;
+}
+
+#[cfg(feature = "derive")]
+impl<'bump> ReadLenIn<'bump> for &'bump [u8] {
+    fn read_len_in<R: Read + ?Sized>(
+        reader: &mut R,
+        len: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        read_bytes_in(reader, len, bump).map(|bytes| &*bytes)
+    }
+}
+
+#[cfg(feature = "derive")]
+impl<'bump> ReadLenIn<'bump> for &'bump str {
+    fn read_len_in<R: Read + ?Sized>(
+        reader: &mut R,
+        len: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        std::str::from_utf8(read_bytes_in(reader, len, bump)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(feature = "derive")]
+impl<'bump> ReadLenIn<'bump> for String {
+    fn read_len_in<R: Read + ?Sized>(
+        reader: &mut R,
+        len: usize,
+        _bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        crate::__private::read_string(reader, len)
+    }
+}
+
+/// Type of a `count` field of a struct read with `#[read(bump)]`
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub trait ReadCountIn<'bump>: Sized {
+    fn read_count_in<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self>;
+}
+
+/// Read `count` elements into `push`, attributing errors to the offset of their element
+#[cfg(feature = "derive")]
+fn read_elements<'bump, T: ReadFromIn<'bump>, R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    count: usize,
+    bump: &'bump Bump,
+    mut push: impl FnMut(T),
+) -> io::Result<()> {
+    use crate::__private::{shift_element_error, Counter};
+    let mut counter = Counter::new(reader);
+    for _ in 0..count {
+        let start = counter.position();
+        let element = T::read_from_in(&mut counter, endian, bump)
+            .map_err(|err| shift_element_error(err, start))?;
+        push(element);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "derive")]
+impl<'bump, T: ReadFromIn<'bump>> ReadCountIn<'bump> for bumpalo::collections::Vec<'bump, T> {
+    fn read_count_in<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        crate::limits::check_elements(count)?;
+        let capacity = count.min(crate::PREALLOC_LIMIT / size_of::<T>().max(1));
+        let mut vec = bumpalo::collections::Vec::with_capacity_in(capacity, bump);
+        read_elements(reader, endian, count, bump, |element| vec.push(element))?;
+        Ok(vec)
+    }
+}
+
+#[cfg(feature = "derive")]
+impl<'bump, T: ReadFromIn<'bump>> ReadCountIn<'bump> for Vec<T> {
+    fn read_count_in<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+        bump: &'bump Bump,
+    ) -> io::Result<Self> {
+        crate::limits::check_elements(count)?;
+        let mut vec = Vec::with_capacity(count.min(crate::PREALLOC_LIMIT / size_of::<T>().max(1)));
+        read_elements(reader, endian, count, bump, |element| vec.push(element))?;
+        Ok(vec)
+    }
+}
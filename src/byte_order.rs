@@ -0,0 +1,231 @@
+use crate::Primitive;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+/// Byte order fixed at compile time, open for orders beyond little and big endian
+///
+/// Implement it on a marker type to read values in an unusual order, like lanes scrambled by
+/// hardware or the layouts of legacy machines, with the generic reads of [`ReadByteOrder`].
+/// Signed integers and floats are decoded through the unsigned integer of their size.
+///
+/// ```
+/// use read_primitives::{ByteOrder, ReadByteOrder};
+///
+/// /// Little endian with the nibbles of each byte swapped
+/// struct Scrambled;
+///
+/// impl ByteOrder for Scrambled {
+///     fn read_u16(bytes: [u8; 2]) -> u16 {
+///         u16::from_le_bytes(bytes.map(|byte| byte.rotate_left(4)))
+///     }
+///     fn read_u32(bytes: [u8; 4]) -> u32 {
+///         u32::from_le_bytes(bytes.map(|byte| byte.rotate_left(4)))
+///     }
+///     fn read_u64(bytes: [u8; 8]) -> u64 {
+///         u64::from_le_bytes(bytes.map(|byte| byte.rotate_left(4)))
+///     }
+///     fn read_u128(bytes: [u8; 16]) -> u128 {
+///         u128::from_le_bytes(bytes.map(|byte| byte.rotate_left(4)))
+///     }
+/// }
+///
+/// let mut bytes: &[u8] = &[0x21, 0x43, 0x01, 0, 0x02, 0];
+/// assert_eq!(0x3412, bytes.read_ordered::<Scrambled, u16>().unwrap());
+/// assert_eq!(vec![0x10i16, 0x20], bytes.read_ordered_vec::<Scrambled, i16>(2).unwrap());
+/// ```
+pub trait ByteOrder {
+    /// Decode a u16
+    fn read_u16(bytes: [u8; 2]) -> u16;
+
+    /// Decode a u32
+    fn read_u32(bytes: [u8; 4]) -> u32;
+
+    /// Decode a u64
+    fn read_u64(bytes: [u8; 8]) -> u64;
+
+    /// Decode a u128
+    fn read_u128(bytes: [u8; 16]) -> u128;
+}
+
+/// Least significant byte first, as a [`ByteOrder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LittleEndian;
+
+/// Most significant byte first, as a [`ByteOrder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BigEndian;
+
+macro_rules! impl_byte_order {
+    ($order:ident, $from_bytes:ident) => {
+        impl ByteOrder for $order {
+            fn read_u16(bytes: [u8; 2]) -> u16 {
+                u16::$from_bytes(bytes)
+            }
+
+            fn read_u32(bytes: [u8; 4]) -> u32 {
+                u32::$from_bytes(bytes)
+            }
+
+            fn read_u64(bytes: [u8; 8]) -> u64 {
+                u64::$from_bytes(bytes)
+            }
+
+            fn read_u128(bytes: [u8; 16]) -> u128 {
+                u128::$from_bytes(bytes)
+            }
+        }
+    };
+}
+
+impl_byte_order!(LittleEndian, from_le_bytes);
+impl_byte_order!(BigEndian, from_be_bytes);
+
+/// A [`Primitive`] that can be decoded in any [`ByteOrder`]
+///
+/// Implemented for the fixed-width integers and floats.
+pub trait FromByteOrder: Primitive {
+    /// Decode from bytes in the byte order `O`
+    fn from_order_bytes<O: ByteOrder>(bytes: Self::Bytes) -> Self;
+}
+
+impl FromByteOrder for u8 {
+    fn from_order_bytes<O: ByteOrder>([byte]: [u8; 1]) -> Self {
+        byte
+    }
+}
+
+impl FromByteOrder for i8 {
+    fn from_order_bytes<O: ByteOrder>([byte]: [u8; 1]) -> Self {
+        byte as i8
+    }
+}
+
+macro_rules! impl_from_byte_order {
+    ($($type:ty => $read:ident, $convert:expr);+ $(;)?) => {
+        $(
+            impl FromByteOrder for $type {
+                fn from_order_bytes<O: ByteOrder>(bytes: Self::Bytes) -> Self {
+                    $convert(O::$read(bytes))
+                }
+            }
+        )+
+    };
+}
+
+impl_from_byte_order!(
+    u16 => read_u16, |bits| bits;
+    i16 => read_u16, |bits| bits as i16;
+    u32 => read_u32, |bits| bits;
+    i32 => read_u32, |bits| bits as i32;
+    u64 => read_u64, |bits| bits;
+    i64 => read_u64, |bits| bits as i64;
+    u128 => read_u128, |bits| bits;
+    i128 => read_u128, |bits| bits as i128;
+    f32 => read_u32, f32::from_bits;
+    f64 => read_u64, f64::from_bits;
+);
+
+/// Trait to read values in a [`ByteOrder`] given as a type parameter
+#[cfg(feature = "std")]
+pub trait ReadByteOrder: Read {
+    /// Read a `T` in the byte order `O`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_ordered<O: ByteOrder, T: FromByteOrder>(&mut self) -> io::Result<T> {
+        let mut bytes = T::Bytes::default();
+        self.read_exact(bytes.as_mut())?;
+        Ok(T::from_order_bytes::<O>(bytes))
+    }
+
+    /// Read `N` consecutive `T` in the byte order `O` into an array
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_ordered_array<O: ByteOrder, T: FromByteOrder, const N: usize>(
+        &mut self,
+    ) -> io::Result<[T; N]> {
+        match self.read_ordered_vec::<O, T>(N)?.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("read_ordered_vec returns exactly N elements"),
+        }
+    }
+
+    /// Read `count` consecutive `T` in the byte order `O` into a `Vec`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_ordered_vec<O: ByteOrder, T: FromByteOrder>(
+        &mut self,
+        count: usize,
+    ) -> io::Result<Vec<T>> {
+        let mut reader = self;
+        crate::outline::read_vec(&mut reader, count, T::from_order_bytes::<O>)
+    }
+}
+#[cfg(feature = "std")]
+impl<R> ReadByteOrder for R where R: Read {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn standard_orders() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(
+            0x0102_0304u32,
+            (&bytes[..]).read_ordered::<BigEndian, u32>().unwrap()
+        );
+        assert_eq!(
+            (&bytes[..]).read_le_f64().unwrap(),
+            (&bytes[..]).read_ordered::<LittleEndian, f64>().unwrap()
+        );
+        assert_eq!(
+            [0x0201i16, 0x0403],
+            (&bytes[..])
+                .read_ordered_array::<LittleEndian, i16, 2>()
+                .unwrap()
+        );
+        assert_eq!(
+            vec![1u8, 2, 3],
+            (&bytes[..]).read_ordered_vec::<BigEndian, u8>(3).unwrap()
+        );
+        assert!((&bytes[..]).read_ordered::<BigEndian, u128>().is_err());
+    }
+
+    #[test]
+    fn word_swapped() {
+        /// 16-bit big endian words, least significant first
+        struct Pdp;
+        impl ByteOrder for Pdp {
+            fn read_u16(bytes: [u8; 2]) -> u16 {
+                u16::from_be_bytes(bytes)
+            }
+            fn read_u32(bytes: [u8; 4]) -> u32 {
+                u32::from_be_bytes([bytes[2], bytes[3], bytes[0], bytes[1]])
+            }
+            fn read_u64(bytes: [u8; 8]) -> u64 {
+                let [a, b, c, d, e, f, g, h] = bytes;
+                u64::from_be_bytes([g, h, e, f, c, d, a, b])
+            }
+            fn read_u128(bytes: [u8; 16]) -> u128 {
+                let mut words = [0u8; 16];
+                for (word, bytes) in words.chunks_exact_mut(2).zip(bytes.chunks_exact(2).rev()) {
+                    word.copy_from_slice(bytes);
+                }
+                u128::from_be_bytes(words)
+            }
+        }
+        let mut bytes: &[u8] = &[0xcc, 0xdd, 0xaa, 0xbb, 0xff, 0xfe, 0xff, 0xff];
+        assert_eq!(0xaabb_ccdd, bytes.read_ordered::<Pdp, u32>().unwrap());
+        assert_eq!(-2, bytes.read_ordered::<Pdp, i32>().unwrap());
+        // the words 0x1000 to 0x1707, least significant first
+        let words: Vec<u8> = (0..8u8).flat_map(|word| [0x10 | word, word]).collect();
+        assert_eq!(
+            0x1303_1202_1101_1000,
+            (&words[..8]).read_ordered::<Pdp, u64>().unwrap()
+        );
+        assert_eq!(
+            0x1707_1606_1505_1404_1303_1202_1101_1000,
+            (&words[..]).read_ordered::<Pdp, u128>().unwrap()
+        );
+    }
+}
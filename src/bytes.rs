@@ -0,0 +1,132 @@
+//! Primitive reads for any [`bytes::Buf`](::bytes::Buf), without going through `std::io`
+//!
+//! Unlike the panicking `Buf::get_*` methods these return a [`ParseError`] if too few bytes remain.
+//! `Buf` doesn't track a position, so the offset reported in errors is always 0.
+//!
+//! ```
+//! use bytes::Bytes;
+//! use read_primitives::bytes::{ReadBytes, ReadU16};
+//! let mut buf = Bytes::from_static(&[0, 3, b'a', b'b', b'c']);
+//! let len = buf.read_be_u16().unwrap();
+//! let payload = buf.read_bytes(len.into()).unwrap();
+//! assert_eq!("abc", payload);
+//! ```
+
+use crate::ParseError;
+use ::bytes::{Buf, Bytes};
+
+fn ensure_remaining(buf: &impl Buf, needed: usize) -> Result<(), ParseError> {
+    if buf.remaining() < needed {
+        return Err(ParseError::UnexpectedEnd {
+            needed,
+            remaining: buf.remaining(),
+            offset: 0,
+        });
+    }
+    Ok(())
+}
+
+fn chunk<const N: usize>(buf: &mut impl Buf) -> Result<[u8; N], ParseError> {
+    ensure_remaining(buf, N)?;
+    let mut bytes = [0u8; N];
+    buf.copy_to_slice(&mut bytes);
+    Ok(bytes)
+}
+
+macro_rules! impl_buf_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors if fewer than " $type "'s size in bytes remain"]
+            fn [<read_ $order _ $type>](&mut self) -> Result<$type, ParseError> {
+                chunk(self).map($type::[<from_ $order _bytes>])
+            }
+        }
+    };
+}
+
+macro_rules! impl_buf_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to read " $type " from a [`Buf`]."]
+                pub trait [<Read $type:camel>]: Buf + Sized {
+                    impl_buf_methods!($type, ne, "native byte order");
+                    impl_buf_methods!($type, le, "little endian byte order");
+                    impl_buf_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Buf> [<Read $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+impl_buf_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// Trait to read u8 from a [`Buf`]
+pub trait ReadU8: Buf + Sized {
+    /// Read a u8
+    /// # Errors
+    /// errors if no bytes remain
+    fn read_u8(&mut self) -> Result<u8, ParseError> {
+        chunk(self).map(u8::from_ne_bytes)
+    }
+}
+impl<B: Buf> ReadU8 for B {}
+
+/// Trait to read bool from a [`Buf`]
+pub trait ReadBool: Buf + Sized {
+    /// Read a bool
+    /// # Errors
+    /// errors if no bytes remain
+    fn read_bool(&mut self) -> Result<bool, ParseError> {
+        chunk(self).map(|[byte]: [u8; 1]| byte != 0)
+    }
+}
+impl<B: Buf> ReadBool for B {}
+
+/// Trait to read counted byte fields from a [`Buf`]
+pub trait ReadBytes: Buf + Sized {
+    /// Read `len` bytes into [`Bytes`]
+    ///
+    /// This is a cheap reference-counted slice when the source is itself [`Bytes`].
+    /// # Errors
+    /// errors if fewer than `len` bytes remain
+    fn read_bytes(&mut self, len: usize) -> Result<Bytes, ParseError> {
+        ensure_remaining(self, len)?;
+        Ok(self.copy_to_bytes(len))
+    }
+}
+impl<B: Buf> ReadBytes for B {}
+
+#[cfg(test)]
+mod test {
+    use crate::bytes::*;
+
+    #[test]
+    fn read_primitives() {
+        let mut buf: &[u8] = &[1, 0, 0, 0, 0x40, 0x49, 0x0f, 0xdb, 1];
+        assert_eq!(1, buf.read_le_u32().unwrap());
+        assert_eq!(core::f32::consts::PI, buf.read_be_f32().unwrap());
+        assert!(buf.read_bool().unwrap());
+        assert_eq!(
+            ParseError::UnexpectedEnd {
+                needed: 1,
+                remaining: 0,
+                offset: 0
+            },
+            buf.read_u8().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn read_bytes_shares_storage() {
+        let data = Bytes::from_static(b"hello world");
+        let mut buf = data.clone();
+        let hello = buf.read_bytes(5).unwrap();
+        assert_eq!(data.as_ptr(), hello.as_ptr());
+        assert!(buf.read_bytes(7).is_err());
+        assert_eq!(6, buf.remaining());
+    }
+}
@@ -0,0 +1,172 @@
+//! Reads of CBOR data item heads
+//!
+//! Every CBOR data item starts with a head: a major type in the top 3 bits of the initial byte,
+//! and an argument in the low 5 bits or in the 1, 2, 4 or 8 big endian bytes after it.
+//! [`ReadCbor::read_cbor_head`] decodes just the head, leaving the caller to read the bytes of
+//! strings and walk arrays, maps and tags, as diagnostic tools and selective extractors do.
+//!
+//! ```
+//! use read_primitives::cbor::{MajorType, ReadCbor};
+//! // ["a", 1000]
+//! let mut bytes: &[u8] = &[0x82, 0x61, b'a', 0x19, 0x03, 0xe8];
+//! assert_eq!(MajorType::Array, bytes.read_cbor_head().unwrap().major);
+//! let text = bytes.read_cbor_head().unwrap();
+//! assert_eq!((MajorType::Text, 1), (text.major, text.argument));
+//! bytes = &bytes[1..];
+//! let number = bytes.read_cbor_head().unwrap();
+//! assert_eq!((MajorType::Unsigned, 1000, 2), (number.major, number.argument, number.argument_len));
+//! ```
+
+use std::io::{self, Read};
+
+/// Major type of a data item, the top 3 bits of its initial byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MajorType {
+    /// An unsigned integer, the argument
+    Unsigned,
+    /// A negative integer, -1 minus the argument
+    Negative,
+    /// A byte string of argument bytes
+    Bytes,
+    /// A UTF-8 text string of argument bytes
+    Text,
+    /// An array of argument data items
+    Array,
+    /// A map of argument pairs of data items
+    Map,
+    /// A tag numbered by the argument, tagging the data item after it
+    Tag,
+    /// A simple value or float, or the break ending an indefinite-length item
+    Simple,
+}
+
+impl MajorType {
+    /// Major type of the top 3 bits of `initial`
+    pub fn from_initial_byte(initial: u8) -> Self {
+        match initial >> 5 {
+            0 => MajorType::Unsigned,
+            1 => MajorType::Negative,
+            2 => MajorType::Bytes,
+            3 => MajorType::Text,
+            4 => MajorType::Array,
+            5 => MajorType::Map,
+            6 => MajorType::Tag,
+            _ => MajorType::Simple,
+        }
+    }
+}
+
+/// Head of a CBOR data item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Head {
+    /// Major type
+    pub major: MajorType,
+    /// The argument: the value, length, count or tag number; the simple value or the bits of a
+    /// float for [`MajorType::Simple`]; 0 if `indefinite`
+    pub argument: u64,
+    /// Bytes the argument took after the initial byte: 0, 1, 2, 4 or 8. For
+    /// [`MajorType::Simple`], 2, 4 and 8 mean a half, single and double float.
+    pub argument_len: u8,
+    /// Whether the additional info is 31: an indefinite-length string, array or map, or for
+    /// [`MajorType::Simple`] a break
+    pub indefinite: bool,
+}
+
+/// Trait to read CBOR data item heads
+pub trait ReadCbor: Read {
+    /// Read the head of a data item
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] for the reserved additional info 28 to 30, or
+    /// an indefinite length for integers and tags, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_cbor_head(&mut self) -> io::Result<Head> {
+        let mut initial = [0u8; 1];
+        self.read_exact(&mut initial)?;
+        let [initial] = initial;
+        let major = MajorType::from_initial_byte(initial);
+        let info = initial & 0x1f;
+        let (argument, argument_len, indefinite) = match info {
+            0..=23 => (info.into(), 0, false),
+            24..=27 => {
+                let len = 1u8 << (info - 24);
+                let mut bytes = [0u8; 8];
+                self.read_exact(&mut bytes[8 - usize::from(len)..])?;
+                (u64::from_be_bytes(bytes), len, false)
+            }
+            31 if !matches!(
+                major,
+                MajorType::Unsigned | MajorType::Negative | MajorType::Tag
+            ) =>
+            {
+                (0, 0, true)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid additional info {info} for CBOR {major:?}"),
+                ))
+            }
+        };
+        Ok(Head {
+            major,
+            argument,
+            argument_len,
+            indefinite,
+        })
+    }
+}
+impl<R> ReadCbor for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::cbor::*;
+    use std::io;
+
+    fn head(bytes: &[u8]) -> (MajorType, u64, u8, bool) {
+        let mut reader = bytes;
+        let head = reader.read_cbor_head().unwrap();
+        assert!(reader.is_empty(), "{bytes:x?} left bytes unread");
+        (
+            head.major,
+            head.argument,
+            head.argument_len,
+            head.indefinite,
+        )
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!((MajorType::Unsigned, 23, 0, false), head(&[0x17]));
+        assert_eq!((MajorType::Unsigned, 24, 1, false), head(&[0x18, 0x18]));
+        assert_eq!(
+            (MajorType::Negative, 499, 2, false),
+            head(&[0x39, 0x01, 0xf3])
+        );
+        assert_eq!((MajorType::Tag, 1, 4, false), head(&[0xda, 0, 0, 0, 1]));
+        assert_eq!(
+            (MajorType::Bytes, u64::MAX, 8, false),
+            head(&[0x5b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+        );
+        // 1.5 as a half float
+        assert_eq!(
+            (MajorType::Simple, 0x3e00, 2, false),
+            head(&[0xf9, 0x3e, 0x00])
+        );
+        assert_eq!((MajorType::Simple, 21, 0, false), head(&[0xf5]));
+    }
+
+    #[test]
+    fn indefinite() {
+        assert_eq!((MajorType::Text, 0, 0, true), head(&[0x7f]));
+        assert_eq!((MajorType::Map, 0, 0, true), head(&[0xbf]));
+        assert_eq!((MajorType::Simple, 0, 0, true), head(&[0xff]));
+        let err = (&[0x1f][..]).read_cbor_head().unwrap_err();
+        assert_eq!(
+            "invalid additional info 31 for CBOR Unsigned",
+            err.to_string()
+        );
+        let err = (&[0x5c][..]).read_cbor_head().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0x19, 1][..]).read_cbor_head().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{Endian, Primitive, ReadPrimitive};
+use std::fmt;
+use std::io::{self, Read};
+
+/// A checksum over the payload of a record, like a CRC-16 or CRC-32
+///
+/// Implemented for closures returning the checksum of the bytes, and with the `crc` feature for
+/// the 16 and 32 bit [`crc::Crc`] algorithms.
+pub trait Checksum {
+    /// Stored checksum type
+    type Value: Primitive + Copy + PartialEq + Into<u64>;
+
+    /// Checksum of `bytes`
+    fn checksum(&self, bytes: &[u8]) -> Self::Value;
+}
+
+impl<F, V> Checksum for F
+where
+    F: Fn(&[u8]) -> V,
+    V: Primitive + Copy + PartialEq + Into<u64>,
+{
+    type Value = V;
+
+    fn checksum(&self, bytes: &[u8]) -> V {
+        self(bytes)
+    }
+}
+
+#[cfg(feature = "crc")]
+impl Checksum for crc::Crc<u16> {
+    type Value = u16;
+
+    fn checksum(&self, bytes: &[u8]) -> u16 {
+        crc::Crc::<u16>::checksum(self, bytes)
+    }
+}
+
+#[cfg(feature = "crc")]
+impl Checksum for crc::Crc<u32> {
+    type Value = u32;
+
+    fn checksum(&self, bytes: &[u8]) -> u32 {
+        crc::Crc::<u32>::checksum(self, bytes)
+    }
+}
+
+/// Error of a record whose stored checksum doesn't match its payload, carried by an
+/// [`io::ErrorKind::InvalidData`] error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChecksumMismatch {
+    /// Checksum stored after the payload
+    pub expected: u64,
+    /// Checksum computed over the payload
+    pub actual: u64,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checksum mismatch: stored {:#x}, computed {:#x}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+impl From<ChecksumMismatch> for io::Error {
+    fn from(err: ChecksumMismatch) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Trait to read payloads followed by a checksum of them, the envelope of many embedded and
+/// storage protocols
+///
+/// ```
+/// use read_primitives::{ChecksumMismatch, Endian, ReadChecksummed};
+/// let sum = |bytes: &[u8]| bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+/// let mut record: &[u8] = &[1, 2, 3, 6, 1, 2, 3, 7];
+/// assert_eq!(vec![1, 2, 3], record.read_checksummed(3, &sum, Endian::Big).unwrap());
+/// let err = record.read_checksummed(3, &sum, Endian::Big).unwrap_err();
+/// let mismatch = err.get_ref().unwrap().downcast_ref::<ChecksumMismatch>().unwrap();
+/// assert_eq!((7, 6), (mismatch.expected, mismatch.actual));
+/// ```
+pub trait ReadChecksummed: Read {
+    /// Read `len` bytes of payload, then its checksum in `endian` byte order, and check that they
+    /// match
+    /// # Errors
+    /// errors with a [`ChecksumMismatch`] if the checksums don't match,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_checksummed<C: Checksum + ?Sized>(
+        &mut self,
+        len: usize,
+        checksum: &C,
+        endian: Endian,
+    ) -> io::Result<Vec<u8>> {
+        let mut reader = self;
+        let payload = crate::string::read_exact_vec(&mut reader, len)?;
+        let expected = reader.read_endian::<C::Value>(endian)?;
+        let actual = checksum.checksum(&payload);
+        if actual != expected {
+            return Err(ChecksumMismatch {
+                expected: expected.into(),
+                actual: actual.into(),
+            }
+            .into());
+        }
+        Ok(payload)
+    }
+}
+impl<R> ReadChecksummed for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    fn xor(bytes: &[u8]) -> u16 {
+        bytes.iter().fold(0, |sum, &byte| sum ^ u16::from(byte))
+    }
+
+    #[test]
+    fn checksummed() {
+        let mut bytes: &[u8] = &[0xf0, 0x0f, 0xff, 0, 9, 0];
+        assert_eq!(
+            vec![0xf0, 0x0f],
+            bytes.read_checksummed(2, &xor, Endian::Little).unwrap()
+        );
+        let err = bytes.read_checksummed(0, &xor, Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "checksum mismatch: stored 0x900, computed 0x0",
+            err.to_string()
+        );
+        let err = (&[1u8, 1][..])
+            .read_checksummed(1, &xor, Endian::Big)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn crc() {
+        let crc32 = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        let mut bytes = b"123456789".to_vec();
+        bytes.extend_from_slice(&0xcbf4_3926u32.to_le_bytes());
+        let payload = bytes
+            .as_slice()
+            .read_checksummed(9, &crc32, Endian::Little)
+            .unwrap();
+        assert_eq!(b"123456789", &*payload);
+        let crc16 = crc::Crc::<u16>::new(&crc::CRC_16_IBM_3740);
+        let mut bytes: &[u8] = b"123456789\x29\xb1";
+        assert!(bytes.read_checksummed(9, &crc16, Endian::Big).is_ok());
+    }
+}
@@ -0,0 +1,691 @@
+//! Generation of Rust structs with [`ReadFrom`](crate::ReadFrom) and [`WriteTo`](crate::WriteTo)
+//! impls from C struct definitions, for wire formats defined by existing C code
+//!
+//! [`generate`] lays the structs out like [`layout`](crate::layout) for a [`Target`], reading and
+//! skipping the padding a C compiler inserts and writing it as zeros. The byte order is fixed by
+//! the target, so the generated impls ignore the `endian` they are passed. It is meant to run in
+//! a build script, with the output written to `OUT_DIR` and `include!`d:
+//!
+//! ```no_run
+//! // build.rs, with read-primitives in [build-dependencies] with the codegen feature
+//! use read_primitives::codegen::generate;
+//! use read_primitives::layout::Target;
+//! let header = std::fs::read_to_string("wire.h").unwrap();
+//! let code = generate(&header, &Target::X86_64).unwrap();
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("wire.rs");
+//! std::fs::write(out, code).unwrap();
+//! println!("cargo:rerun-if-changed=wire.h");
+//! ```
+//!
+//! The supported subset of C is `struct` definitions and `typedef struct` of the fixed-width
+//! integers (`uint8_t` to `int64_t`), `char`, `short`, `int` and `long long` with `signed` and
+//! `unsigned`, `float`, `double`, pointers, which are read as their address, arrays and other
+//! structs. Plain `char` is read as `u8`. Comments and preprocessor lines are skipped. `long`,
+//! whose size differs between ABIs, bit-fields, unions and nested definitions are rejected.
+//! Structs are named after the tag of a `typedef struct` if it has one, converted to
+//! UpperCamelCase without a `_t` suffix, while field names are kept. Structs whose Rust names
+//! would be the same are rejected.
+
+use crate::layout::{CType, Target};
+use crate::Endian;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Number(usize),
+    Punct(char),
+}
+
+fn tokenize(source: &str) -> io::Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut line_start = true;
+    while let Some(char) = rest.chars().next() {
+        if char == '\n' {
+            line_start = true;
+            rest = &rest[1..];
+        } else if char.is_whitespace() {
+            rest = &rest[char.len_utf8()..];
+        } else if rest.starts_with("//") || (line_start && char == '#') {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .ok_or_else(|| invalid("unterminated comment".into()))?;
+            rest = &comment[end + 2..];
+        } else if char.is_ascii_alphabetic() || char == '_' {
+            line_start = false;
+            let len = rest
+                .find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(&rest[..len]));
+            rest = &rest[len..];
+        } else if char.is_ascii_digit() {
+            line_start = false;
+            let len = rest
+                .find(|char: char| !char.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let literal = rest[..len].trim_end_matches(['u', 'U', 'l', 'L']);
+            let number = match literal.strip_prefix("0x").or(literal.strip_prefix("0X")) {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => literal.parse(),
+            }
+            .map_err(|_| invalid(format!("invalid number {:?}", &rest[..len])))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[len..];
+        } else {
+            line_start = false;
+            tokens.push(Token::Punct(char));
+            rest = &rest[char.len_utf8()..];
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum FieldType {
+    Scalar(CType),
+    Pointer,
+    Array(Box<FieldType>, usize),
+    /// Index of a struct defined before
+    Struct(usize),
+}
+
+#[derive(Debug)]
+struct CStruct {
+    name: String,
+    fields: Vec<(String, FieldType)>,
+}
+
+/// Fixed-width integer typedefs of `<stdint.h>`
+fn stdint(name: &str) -> Option<CType> {
+    Some(match name {
+        "uint8_t" => CType::U8,
+        "int8_t" => CType::I8,
+        "uint16_t" => CType::U16,
+        "int16_t" => CType::I16,
+        "uint32_t" => CType::U32,
+        "int32_t" => CType::I32,
+        "uint64_t" => CType::U64,
+        "int64_t" => CType::I64,
+        _ => return None,
+    })
+}
+
+/// Type named by a combination of the basic type keywords
+fn basic(words: &[&str]) -> io::Result<CType> {
+    let count = |word| words.iter().filter(|&&other| other == word).count();
+    let (unsigned, signed) = (count("unsigned"), count("signed"));
+    let others = words.len() - unsigned - signed - count("int");
+    let ty = match (
+        count("char"),
+        count("short"),
+        count("long"),
+        count("float"),
+        count("double"),
+    ) {
+        _ if unsigned + signed > 1 || count("int") > 1 => None,
+        (1, 0, 0, 0, 0) if others == 1 && count("int") == 0 => Some(match unsigned {
+            1 => CType::U8,
+            _ if signed == 1 => CType::I8,
+            _ => CType::U8,
+        }),
+        (0, 1, 0, 0, 0) if others == 1 => Some(match unsigned {
+            1 => CType::U16,
+            _ => CType::I16,
+        }),
+        (0, 0, 2, 0, 0) if others == 2 => Some(match unsigned {
+            1 => CType::U64,
+            _ => CType::I64,
+        }),
+        (0, 0, 1, 0, _) => {
+            return Err(invalid(format!(
+                "{:?} has a size that differs between ABIs, use a fixed-width type",
+                words.join(" ")
+            )))
+        }
+        (0, 0, 0, 0, 0) if others == 0 => Some(match unsigned {
+            1 => CType::U32,
+            _ => CType::I32,
+        }),
+        (0, 0, 0, 1, 0) if words.len() == 1 => Some(CType::F32),
+        (0, 0, 0, 0, 1) if words.len() == 1 => Some(CType::F64),
+        _ => None,
+    };
+    ty.ok_or_else(|| invalid(format!("unsupported type {:?}", words.join(" "))))
+}
+
+const BASIC_WORDS: &[&str] = &[
+    "unsigned", "signed", "char", "short", "int", "long", "float", "double",
+];
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    structs: Vec<CStruct>,
+    names: HashMap<&'a str, usize>,
+    /// C name of the struct each Rust name was generated from
+    rust_names: HashMap<String, &'a str>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> io::Result<Token<'a>> {
+        let token = self
+            .peek()
+            .ok_or_else(|| invalid("unexpected end of input".into()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, punct: char) -> io::Result<()> {
+        match self.next()? {
+            Token::Punct(found) if found == punct => Ok(()),
+            found => Err(invalid(format!("expected '{punct}', found {found:?}"))),
+        }
+    }
+
+    fn ident(&mut self) -> io::Result<&'a str> {
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            found => Err(invalid(format!("expected a name, found {found:?}"))),
+        }
+    }
+
+    fn struct_index(&self, name: &str) -> io::Result<usize> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| invalid(format!("struct {name:?} is not defined before its use")))
+    }
+
+    fn parse(&mut self) -> io::Result<()> {
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Ident("typedef") => {
+                    self.pos += 1;
+                    if self.ident()? != "struct" {
+                        return Err(invalid("only structs may be typedef'd".into()));
+                    }
+                    let tag = match self.peek() {
+                        Some(Token::Ident(tag)) => {
+                            self.pos += 1;
+                            Some(tag)
+                        }
+                        _ => None,
+                    };
+                    let fields = self.body()?;
+                    let name = self.ident()?;
+                    self.expect(';')?;
+                    self.define(name, tag.unwrap_or(name), fields)?;
+                    if let Some(tag) = tag {
+                        self.names.insert(tag, self.structs.len() - 1);
+                    }
+                }
+                Token::Ident("struct") => {
+                    self.pos += 1;
+                    let name = self.ident()?;
+                    if self.peek() == Some(Token::Punct(';')) {
+                        self.pos += 1;
+                        continue;
+                    }
+                    let fields = self.body()?;
+                    self.expect(';')?;
+                    self.define(name, name, fields)?;
+                }
+                found => {
+                    return Err(invalid(format!(
+                        "unsupported declaration starting with {found:?}"
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Define the struct `name`, named after `rust_name` in Rust
+    fn define(
+        &mut self,
+        name: &'a str,
+        rust_name: &'a str,
+        fields: Vec<(String, FieldType)>,
+    ) -> io::Result<()> {
+        if self.names.contains_key(name) {
+            return Err(invalid(format!("struct {name:?} is defined twice")));
+        }
+        let generated = rust_struct_name(rust_name)?;
+        if let Some(other) = self.rust_names.insert(generated.clone(), rust_name) {
+            return Err(invalid(format!(
+                "structs {other:?} and {rust_name:?} are both named {generated} in Rust"
+            )));
+        }
+        self.names.insert(name, self.structs.len());
+        self.structs.push(CStruct {
+            name: generated,
+            fields,
+        });
+        Ok(())
+    }
+
+    fn body(&mut self) -> io::Result<Vec<(String, FieldType)>> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        while self.peek() != Some(Token::Punct('}')) {
+            let base = self.specifier()?;
+            loop {
+                let mut ty = base.clone();
+                while self.peek() == Some(Token::Punct('*')) {
+                    self.pos += 1;
+                    ty = FieldType::Pointer;
+                }
+                let name = self.ident()?;
+                let mut dims = Vec::new();
+                while self.peek() == Some(Token::Punct('[')) {
+                    self.pos += 1;
+                    match self.next()? {
+                        Token::Number(len) => dims.push(len),
+                        found => {
+                            return Err(invalid(format!(
+                                "unsupported array length {found:?} of field {name:?}"
+                            )))
+                        }
+                    }
+                    self.expect(']')?;
+                }
+                for len in dims.into_iter().rev() {
+                    ty = FieldType::Array(Box::new(ty), len);
+                }
+                fields.push((name.to_string(), ty));
+                match self.next()? {
+                    Token::Punct(',') => {}
+                    Token::Punct(';') => break,
+                    Token::Punct(':') => {
+                        return Err(invalid(format!("bit-field {name:?} is not supported")))
+                    }
+                    found => return Err(invalid(format!("expected ';', found {found:?}"))),
+                }
+            }
+        }
+        self.pos += 1;
+        Ok(fields)
+    }
+
+    fn specifier(&mut self) -> io::Result<FieldType> {
+        while let Some(Token::Ident("const" | "volatile")) = self.peek() {
+            self.pos += 1;
+        }
+        let mut words = Vec::new();
+        while let Some(Token::Ident(word)) = self.peek() {
+            if !BASIC_WORDS.contains(&word) {
+                break;
+            }
+            words.push(word);
+            self.pos += 1;
+        }
+        if !words.is_empty() {
+            return basic(&words).map(FieldType::Scalar);
+        }
+        match self.ident()? {
+            "struct" => {
+                let tag = self.ident()?;
+                if self.peek() == Some(Token::Punct('{')) {
+                    return Err(invalid(format!(
+                        "nested definition of struct {tag:?} is not supported"
+                    )));
+                }
+                self.struct_index(tag).map(FieldType::Struct)
+            }
+            "union" => Err(invalid("unions are not supported".into())),
+            name => match stdint(name) {
+                Some(ty) => Ok(FieldType::Scalar(ty)),
+                None => self.struct_index(name).map(FieldType::Struct),
+            },
+        }
+    }
+}
+
+fn rust_struct_name(c_name: &str) -> io::Result<String> {
+    let name: String = c_name
+        .strip_suffix("_t")
+        .unwrap_or(c_name)
+        .split('_')
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect();
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => Ok(name),
+        _ => Err(invalid(format!(
+            "struct name {c_name:?} has no Rust equivalent"
+        ))),
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+fn rust_field_name(c_name: &str) -> io::Result<String> {
+    match c_name {
+        "self" | "Self" | "super" | "crate" | "_" => Err(invalid(format!(
+            "field name {c_name:?} has no Rust equivalent"
+        ))),
+        name if RUST_KEYWORDS.contains(&name) => Ok(format!("r#{name}")),
+        name => Ok(name.to_string()),
+    }
+}
+
+/// A struct field as generated: its Rust name and type, and the padding before it
+struct RustField {
+    name: String,
+    ty: String,
+    padding: usize,
+}
+
+struct Generator<'a> {
+    structs: &'a [CStruct],
+    target: &'a Target,
+}
+
+impl Generator<'_> {
+    fn ctype(&self, ty: &FieldType) -> CType {
+        match ty {
+            FieldType::Scalar(ty) => ty.clone(),
+            FieldType::Pointer => CType::Pointer,
+            FieldType::Array(element, len) => CType::Array(Box::new(self.ctype(element)), *len),
+            FieldType::Struct(index) => CType::Struct(
+                self.structs[*index]
+                    .fields
+                    .iter()
+                    .map(|(_, ty)| self.ctype(ty))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn rust_type(&self, ty: &FieldType) -> String {
+        match ty {
+            FieldType::Scalar(ty) => match ty {
+                CType::U8 => "u8",
+                CType::I8 => "i8",
+                CType::U16 => "u16",
+                CType::I16 => "i16",
+                CType::U32 => "u32",
+                CType::I32 => "i32",
+                CType::U64 => "u64",
+                CType::I64 => "i64",
+                CType::F32 => "f32",
+                CType::F64 => "f64",
+                _ => unreachable!("scalars are parsed from basic types"),
+            }
+            .to_string(),
+            FieldType::Pointer => match self.target.pointer_size {
+                2 => "u16".to_string(),
+                4 => "u32".to_string(),
+                _ => "u64".to_string(),
+            },
+            FieldType::Array(element, len) => format!("[{}; {len}]", self.rust_type(element)),
+            FieldType::Struct(index) => self.structs[*index].name.clone(),
+        }
+    }
+
+    /// Fields of `cstruct`, and the padding at its end
+    fn fields(&self, cstruct: &CStruct) -> io::Result<(Vec<RustField>, usize)> {
+        let overflow = || invalid(format!("size of {} overflows usize", cstruct.name));
+        let mut fields = Vec::new();
+        let mut offset = 0usize;
+        for (name, ty) in &cstruct.fields {
+            let ctype = self.ctype(ty);
+            let start = offset
+                .checked_next_multiple_of(ctype.align(self.target))
+                .ok_or_else(overflow)?;
+            fields.push(RustField {
+                name: rust_field_name(name)?,
+                ty: self.rust_type(ty),
+                padding: start - offset,
+            });
+            offset = ctype
+                .checked_size(self.target)
+                .and_then(|size| start.checked_add(size))
+                .ok_or_else(overflow)?;
+        }
+        let ctype = CType::Struct(
+            cstruct
+                .fields
+                .iter()
+                .map(|(_, ty)| self.ctype(ty))
+                .collect(),
+        );
+        let size = ctype.checked_size(self.target).ok_or_else(overflow)?;
+        Ok((fields, size - offset))
+    }
+
+    fn write_struct(&self, code: &mut String, cstruct: &CStruct) -> io::Result<()> {
+        let (fields, trailing) = self.fields(cstruct)?;
+        let name = &cstruct.name;
+        let endian = match self.target.endian {
+            Endian::Little => "Little",
+            Endian::Big => "Big",
+        };
+        let skip = |code: &mut String, padding: usize| {
+            if padding > 0 {
+                writeln!(
+                    code,
+                    "        ::std::io::Read::read_exact(reader, &mut [0u8; {padding}])?;"
+                )
+                .unwrap();
+            }
+        };
+        let pad = |code: &mut String, padding: usize| {
+            if padding > 0 {
+                writeln!(
+                    code,
+                    "        ::std::io::Write::write_all(writer, &[0u8; {padding}])?;"
+                )
+                .unwrap();
+            }
+        };
+        writeln!(code, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        writeln!(code, "#[allow(non_snake_case)]").unwrap();
+        writeln!(code, "pub struct {name} {{").unwrap();
+        for RustField {
+            name: field, ty, ..
+        } in &fields
+        {
+            writeln!(code, "    pub {field}: {ty},").unwrap();
+        }
+        writeln!(code, "}}\n").unwrap();
+
+        writeln!(code, "impl ::read_primitives::ReadFrom for {name} {{").unwrap();
+        writeln!(code, "    fn read_from<R: ::std::io::Read + ?Sized>(").unwrap();
+        writeln!(code, "        reader: &mut R,").unwrap();
+        writeln!(code, "        _: ::read_primitives::Endian,").unwrap();
+        writeln!(code, "    ) -> ::std::io::Result<Self> {{").unwrap();
+        for RustField {
+            name: field,
+            ty,
+            padding,
+        } in &fields
+        {
+            skip(code, *padding);
+            writeln!(
+                code,
+                "        let {field} = <{ty} as ::read_primitives::ReadFrom>::read_from(\n            \
+                 reader,\n            ::read_primitives::Endian::{endian},\n        )?;"
+            )
+            .unwrap();
+        }
+        skip(code, trailing);
+        if fields.is_empty() && trailing == 0 {
+            writeln!(code, "        let _ = reader;").unwrap();
+        }
+        let names: Vec<_> = fields.iter().map(|field| field.name.as_str()).collect();
+        if names.is_empty() {
+            writeln!(code, "        Ok({name} {{}})").unwrap();
+        } else {
+            writeln!(code, "        Ok({name} {{").unwrap();
+            for field in names {
+                writeln!(code, "            {field},").unwrap();
+            }
+            writeln!(code, "        }})").unwrap();
+        }
+        writeln!(code, "    }}\n}}\n").unwrap();
+
+        writeln!(code, "impl ::read_primitives::WriteTo for {name} {{").unwrap();
+        writeln!(code, "    fn write_to<W: ::std::io::Write + ?Sized>(").unwrap();
+        writeln!(code, "        &self,").unwrap();
+        writeln!(code, "        writer: &mut W,").unwrap();
+        writeln!(code, "        _: ::read_primitives::Endian,").unwrap();
+        writeln!(code, "    ) -> ::std::io::Result<()> {{").unwrap();
+        for RustField {
+            name: field,
+            padding,
+            ..
+        } in &fields
+        {
+            pad(code, *padding);
+            writeln!(
+                code,
+                "        ::read_primitives::WriteTo::write_to(\n            &self.{field},\n            \
+                 writer,\n            ::read_primitives::Endian::{endian},\n        )?;"
+            )
+            .unwrap();
+        }
+        pad(code, trailing);
+        if fields.is_empty() && trailing == 0 {
+            writeln!(code, "        let _ = writer;").unwrap();
+        }
+        writeln!(code, "        Ok(())\n    }}\n}}").unwrap();
+        Ok(())
+    }
+}
+
+/// Generate a Rust struct with [`ReadFrom`](crate::ReadFrom) and [`WriteTo`](crate::WriteTo)
+/// impls laid out for `target` for each struct defined in the C `source`
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidInput`] if `source` uses C outside the supported subset,
+/// uses a struct before defining it, has two structs whose Rust names are the same or a struct
+/// larger than `usize::MAX` bytes, or `target` is not [valid](Target::validate)
+pub fn generate(source: &str, target: &Target) -> io::Result<String> {
+    target.validate()?;
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        structs: Vec::new(),
+        names: HashMap::new(),
+        rust_names: HashMap::new(),
+    };
+    parser.parse()?;
+    let generator = Generator {
+        structs: &parser.structs,
+        target,
+    };
+    let mut code = String::from("// Generated by read_primitives::codegen, do not edit\n");
+    for cstruct in &parser.structs {
+        code.push('\n');
+        generator.write_struct(&mut code, cstruct)?;
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codegen::*;
+
+    #[test]
+    fn types() {
+        for (words, ty) in [
+            (&["char"][..], CType::U8),
+            (&["signed", "char"], CType::I8),
+            (&["unsigned", "short", "int"], CType::U16),
+            (&["signed"], CType::I32),
+            (&["unsigned"], CType::U32),
+            (&["long", "long", "int"], CType::I64),
+            (&["unsigned", "long", "long"], CType::U64),
+            (&["double"], CType::F64),
+        ] {
+            assert_eq!(ty, basic(words).unwrap(), "{words:?}");
+        }
+        for words in [
+            &["long"][..],
+            &["unsigned", "long"],
+            &["long", "double"],
+            &["short", "char"],
+            &["unsigned", "signed", "int"],
+            &["unsigned", "float"],
+        ] {
+            assert!(basic(words).is_err(), "{words:?}");
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        for (source, message) in [
+            ("struct a { long b; };", "\"long\" has a size"),
+            ("struct a { int b : 3; };", "bit-field \"b\""),
+            ("struct a { struct b c; };", "struct \"b\" is not defined"),
+            (
+                "struct a { union { int b; } c; };",
+                "unions are not supported",
+            ),
+            (
+                "struct a { int b[N]; };",
+                "unsupported array length Ident(\"N\")",
+            ),
+            (
+                "int a;",
+                "unsupported declaration starting with Ident(\"int\")",
+            ),
+            (
+                "struct a { int b; }; struct a { int c; };",
+                "struct \"a\" is defined twice",
+            ),
+            (
+                "struct a_b { int c; }; struct aB { int c; };",
+                "structs \"a_b\" and \"aB\" are both named AB in Rust",
+            ),
+            (
+                "typedef struct a_b_t { int c; } a; struct a_b { int c; };",
+                "structs \"a_b_t\" and \"a_b\" are both named AB in Rust",
+            ),
+            ("struct a { int self; };", "field name \"self\""),
+            ("/* open", "unterminated comment"),
+            (
+                "struct a { double b[2305843009213693952]; };",
+                "size of A overflows usize",
+            ),
+        ] {
+            let err = generate(source, &Target::X86_64).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+            assert!(
+                err.to_string().starts_with(message),
+                "{err} does not start with {message}"
+            );
+        }
+        let target = Target {
+            align_8: 0,
+            ..Target::X86_64
+        };
+        let err = generate("struct a { double b; };", &target).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+}
@@ -0,0 +1,532 @@
+//! Combinators for parsers written as closures over a reader
+//!
+//! ```
+//! use read_primitives::combinator::alt;
+//! use read_primitives::{ReadU16, ReadU8, RewindReader};
+//! use std::io;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Record {
+//!     Short(u8),
+//!     Long(u16),
+//! }
+//!
+//! let mut reader = RewindReader::new(&[0xff, 0, 7][..], 16);
+//! let record = alt(&mut reader, (
+//!     |reader: &mut RewindReader<_>| match reader.read_u8()? {
+//!         0xff => Err(io::Error::new(io::ErrorKind::InvalidData, "escape")),
+//!         byte => Ok(Record::Short(byte)),
+//!     },
+//!     |reader: &mut RewindReader<_>| {
+//!         reader.read_u8()?;
+//!         reader.read_be_u16().map(Record::Long)
+//!     },
+//! ));
+//! assert_eq!(Record::Long(7), record.unwrap());
+//! ```
+
+use crate::{Endian, FixedSize, PeekReader, Primitive, ReadFrom, RewindReader, PREALLOC_LIMIT};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+/// Error of [`alt`] when none of the alternatives matched, carried inside an [`io::Error`]
+#[derive(Debug)]
+pub struct AltError {
+    attempts: Vec<io::Error>,
+}
+
+impl AltError {
+    /// Error of each alternative, in the order they were tried
+    pub fn attempts(&self) -> &[io::Error] {
+        &self.attempts
+    }
+}
+
+impl fmt::Display for AltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no alternative matched")?;
+        for (index, err) in self.attempts.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { "; " };
+            write!(f, "{separator}{} ({err})", index + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for AltError {}
+
+impl From<AltError> for io::Error {
+    /// Keeps the kind of the attempts if they all agree, such as
+    /// [`io::ErrorKind::UnexpectedEof`] when the input is too short for any of them
+    fn from(err: AltError) -> Self {
+        let kind = match err.attempts.split_first() {
+            Some((first, rest)) if rest.iter().all(|other| other.kind() == first.kind()) => {
+                first.kind()
+            }
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+/// Error of [`read_variant`] when no parser has the tag, carried inside an [`io::Error`] of kind
+/// [`io::ErrorKind::InvalidData`]
+#[derive(Debug)]
+pub struct UnknownTag<K> {
+    tag: K,
+}
+
+impl<K> UnknownTag<K> {
+    /// The tag no parser has
+    pub fn tag(&self) -> &K {
+        &self.tag
+    }
+
+    /// Unwrap the tag
+    pub fn into_tag(self) -> K {
+        self.tag
+    }
+}
+
+impl<K: fmt::Debug> fmt::Display for UnknownTag<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown tag {:?}", self.tag)
+    }
+}
+
+impl<K: fmt::Debug> Error for UnknownTag<K> {}
+
+impl<K: fmt::Debug + Send + Sync + 'static> From<UnknownTag<K>> for io::Error {
+    fn from(err: UnknownTag<K>) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Tuples of parsers that [`alt`] can choose from
+pub trait Alt<R, T> {
+    /// Try each parser in a transaction and return the first success
+    /// # Errors
+    /// errors with an [`AltError`] if every parser errors
+    fn choose(&mut self, reader: &mut RewindReader<R>) -> io::Result<T>;
+}
+
+macro_rules! impl_alt {
+    ($($parser:ident $index:tt),+) => {
+        impl<R: Read, T, $($parser),+> Alt<R, T> for ($($parser,)+)
+        where
+            $($parser: FnMut(&mut RewindReader<R>) -> io::Result<T>),+
+        {
+            fn choose(&mut self, reader: &mut RewindReader<R>) -> io::Result<T> {
+                let mut attempts = Vec::new();
+                $(
+                    match reader.transaction(&mut self.$index) {
+                        Ok(value) => return Ok(value),
+                        Err(err) => attempts.push(err),
+                    }
+                )+
+                Err(AltError { attempts }.into())
+            }
+        }
+    };
+}
+
+impl_alt!(A 0);
+impl_alt!(A 0, B 1);
+impl_alt!(A 0, B 1, C 2);
+impl_alt!(A 0, B 1, C 2, D 3);
+impl_alt!(A 0, B 1, C 2, D 3, E 4);
+impl_alt!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_alt!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_alt!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Try the parsers in turn, each from the same position, and return the first success
+///
+/// Each attempt runs in a [`RewindReader::transaction`], so a failed attempt consumes nothing.
+/// # Errors
+/// errors with an [`AltError`] listing every attempt's error if none succeeds
+pub fn alt<R: Read, T>(reader: &mut RewindReader<R>, mut parsers: impl Alt<R, T>) -> io::Result<T> {
+    parsers.choose(reader)
+}
+
+/// Parser of one variant for [`read_variant`]
+pub type VariantParser<R, T> = fn(&mut R) -> io::Result<T>;
+
+/// Run the parser paired with `tag`, for tags known before the payload, like those of TLV records
+/// or of an outer header
+///
+/// ```
+/// use read_primitives::combinator::{read_variant, UnknownTag, VariantParser};
+/// use read_primitives::{ReadU16, ReadU8};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Field {
+///     Port(u16),
+///     Flag(bool),
+/// }
+///
+/// let parsers: &[(u8, VariantParser<&[u8], Field>)] = &[
+///     (1, |r| r.read_be_u16().map(Field::Port)),
+///     (2, |r| r.read_u8().map(|byte| Field::Flag(byte != 0))),
+/// ];
+/// let mut payload: &[u8] = &[0x1f, 0x90];
+/// assert_eq!(Field::Port(8080), read_variant(&mut payload, 1, parsers).unwrap());
+/// let err = read_variant(&mut payload, 7, parsers).unwrap_err();
+/// let unknown = err.get_ref().unwrap().downcast_ref::<UnknownTag<u8>>().unwrap();
+/// assert_eq!(7, *unknown.tag());
+/// ```
+/// # Errors
+/// errors with an [`UnknownTag`] if no parser has `tag`, or when the parser errors
+pub fn read_variant<K, R, T>(
+    reader: &mut R,
+    tag: K,
+    parsers: &[(K, VariantParser<R, T>)],
+) -> io::Result<T>
+where
+    K: PartialEq + fmt::Debug + Send + Sync + 'static,
+    R: ?Sized,
+{
+    match parsers.iter().find(|(known, _)| *known == tag) {
+        Some((_, parse)) => parse(reader),
+        None => Err(UnknownTag { tag }.into()),
+    }
+}
+
+/// Capacity to reserve for `count` values of `T`, so a corrupt count can't allocate much up front
+fn capacity<T>(count: usize) -> usize {
+    count.min(PREALLOC_LIMIT / size_of::<T>().max(1))
+}
+
+/// Run `parse` `count` times and collect the values
+/// # Errors
+/// errors when `parse` errors
+pub fn repeat_n<R: Read + ?Sized, T>(
+    reader: &mut R,
+    count: usize,
+    mut parse: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let mut values = Vec::with_capacity(capacity::<T>(count));
+    for _ in 0..count {
+        values.push(parse(reader)?);
+    }
+    Ok(values)
+}
+
+/// Run `parse` until the next bytes are `sentinel`, consume the sentinel and collect the values
+///
+/// ```
+/// use read_primitives::combinator::many_until;
+/// use read_primitives::{PeekReader, ReadU16};
+/// let mut reader = PeekReader::new(&[0, 1, 0, 2, 0xff, 0xff, 9][..]);
+/// let values = many_until(&mut reader, &[0xff, 0xff], |reader| reader.read_be_u16()).unwrap();
+/// assert_eq!(vec![1, 2], values);
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends before the sentinel,
+/// or when `parse` errors
+pub fn many_until<R: Read, T>(
+    reader: &mut PeekReader<R>,
+    sentinel: &[u8],
+    mut parse: impl FnMut(&mut PeekReader<R>) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let mut values = Vec::new();
+    while reader.peek_bytes(sentinel.len())? != sentinel {
+        values.push(parse(reader)?);
+    }
+    io::copy(&mut reader.take(sentinel.len() as u64), &mut io::sink())?;
+    Ok(values)
+}
+
+/// Read a count as a `C` in `endian` byte order, then run `parse` that many times
+///
+/// ```
+/// use read_primitives::combinator::count_prefixed;
+/// use read_primitives::{Endian, ReadU8};
+/// let mut bytes: &[u8] = &[2, 0, 7, 8];
+/// let values = count_prefixed::<u16, _, _>(&mut bytes, Endian::Little, |r| r.read_u8()).unwrap();
+/// assert_eq!(vec![7, 8], values);
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidData`] if the count doesn't fit in a `usize`,
+/// or when reading the count or `parse` errors
+pub fn count_prefixed<C, R, T>(
+    reader: &mut R,
+    endian: Endian,
+    parse: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>>
+where
+    C: Primitive + TryInto<usize>,
+    R: Read + ?Sized,
+{
+    count_prefixed_max::<C, R, T>(reader, endian, usize::MAX, parse)
+}
+
+/// [`count_prefixed`], rejecting counts over `max_count` before parsing any value
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidData`] if the count doesn't fit in a `usize` or exceeds
+/// `max_count`, or when reading the count or `parse` errors
+pub fn count_prefixed_max<C, R, T>(
+    reader: &mut R,
+    endian: Endian,
+    max_count: usize,
+    parse: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>>
+where
+    C: Primitive + TryInto<usize>,
+    R: Read + ?Sized,
+{
+    let mut bytes = C::Bytes::default();
+    reader.read_exact(bytes.as_mut())?;
+    let count = C::from_endian_bytes(bytes, endian)
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "count does not fit in usize"))?;
+    crate::check_max_len(count, max_count)?;
+    repeat_n(reader, count, parse)
+}
+
+/// Read a discriminant as a `D` in `endian` byte order and convert it into the enum `E`, mapping
+/// values without a variant to `other` instead of erroring
+///
+/// For forward compatible formats, where a newer producer may write discriminants this parser
+/// doesn't know: `other` is typically a variant keeping the raw value, like `Kind::Other`.
+///
+/// ```
+/// use read_primitives::combinator::read_enum_or;
+/// use read_primitives::Endian;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Kind {
+///     File,
+///     Dir,
+///     Other(u8),
+/// }
+///
+/// impl TryFrom<u8> for Kind {
+///     type Error = ();
+///     fn try_from(raw: u8) -> Result<Self, ()> {
+///         match raw {
+///             0 => Ok(Kind::File),
+///             1 => Ok(Kind::Dir),
+///             _ => Err(()),
+///         }
+///     }
+/// }
+///
+/// let mut bytes: &[u8] = &[1, 7];
+/// assert_eq!(Kind::Dir, read_enum_or(&mut bytes, Endian::Little, Kind::Other).unwrap());
+/// assert_eq!(Kind::Other(7), read_enum_or(&mut bytes, Endian::Little, Kind::Other).unwrap());
+/// ```
+/// # Errors
+/// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+pub fn read_enum_or<D, E, R>(
+    reader: &mut R,
+    endian: Endian,
+    other: impl FnOnce(D) -> E,
+) -> io::Result<E>
+where
+    D: Primitive + Copy,
+    E: TryFrom<D>,
+    R: Read + ?Sized,
+{
+    let mut bytes = D::Bytes::default();
+    reader.read_exact(bytes.as_mut())?;
+    let raw = D::from_endian_bytes(bytes, endian);
+    Ok(E::try_from(raw).unwrap_or_else(|_| other(raw)))
+}
+
+/// Decode a `T` from all of `bytes`
+fn decode_exact<T: ReadFrom>(bytes: &[u8], endian: Endian) -> io::Result<T> {
+    let mut input = bytes;
+    let value = T::read_from(&mut input, endian).map_err(|err| match err.kind() {
+        io::ErrorKind::UnexpectedEof => io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("value reads past its size of {} bytes", bytes.len()),
+        ),
+        _ => err,
+    })?;
+    match input.len() {
+        0 => Ok(value),
+        unread => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{unread} unread bytes in value of {} bytes", bytes.len()),
+        )),
+    }
+}
+
+/// Read the [`FixedSize::SIZE`] bytes of a `T` and decode it, checking that decoding consumes
+/// exactly those bytes
+///
+/// Catches a `ReadFrom` that disagrees with the size, as when a struct definition drifts from the
+/// format.
+///
+/// ```
+/// use read_primitives::combinator::read_exact_size;
+/// use read_primitives::Endian;
+/// let mut bytes: &[u8] = &[1, 0, 2, 0];
+/// assert_eq!((1, 2), read_exact_size::<(u16, u16), _>(&mut bytes, Endian::Little).unwrap());
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early, with
+/// [`io::ErrorKind::InvalidData`] if decoding leaves bytes unread or reads past them,
+/// or when decoding errors otherwise
+pub fn read_exact_size<T: FixedSize, R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+) -> io::Result<T> {
+    read_sized(reader, endian, T::SIZE)
+}
+
+/// Read the next `len` bytes and decode a `T` from them, checking that decoding consumes exactly
+/// those bytes, for values whose size is declared by a header or length field
+///
+/// ```
+/// use read_primitives::combinator::read_sized;
+/// use read_primitives::Endian;
+/// let mut bytes: &[u8] = &[0, 0, 0, 7, 9];
+/// assert_eq!(7, read_sized::<u32, _>(&mut bytes, Endian::Big, 4).unwrap());
+/// assert!(read_sized::<u32, _>(&mut &[0; 6][..], Endian::Big, 6).is_err());
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early, with
+/// [`io::ErrorKind::InvalidData`] if decoding leaves bytes unread or reads past them,
+/// or when decoding errors otherwise
+pub fn read_sized<T: ReadFrom, R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    len: usize,
+) -> io::Result<T> {
+    decode_exact(&crate::string::read_exact_vec(reader, len)?, endian)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::combinator::*;
+    use crate::*;
+
+    fn tag(expected: u8) -> impl FnMut(&mut RewindReader<&[u8]>) -> io::Result<u8> {
+        move |reader| match reader.read_u8()? {
+            byte if byte == expected => reader.read_u8(),
+            byte => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("tag {byte}"),
+            )),
+        }
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let mut reader = RewindReader::new(&[2, 9, 5][..], 8);
+        assert_eq!(9, alt(&mut reader, (tag(1), tag(2), tag(2))).unwrap());
+        assert_eq!(5, reader.read_u8().unwrap());
+    }
+
+    #[test]
+    fn repetition() {
+        let mut bytes: &[u8] = &[1, 2, 3];
+        assert_eq!(
+            vec![1, 2],
+            repeat_n(&mut bytes, 2, |r| r.read_u8()).unwrap()
+        );
+        let err = repeat_n(&mut bytes, 2, |r| r.read_u8()).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        let mut reader = PeekReader::new(&[5, 6, 0, 7][..]);
+        assert_eq!(
+            vec![5, 6],
+            many_until(&mut reader, &[0], |r| r.read_u8()).unwrap()
+        );
+        assert_eq!(7, reader.read_u8().unwrap());
+        let mut unterminated = PeekReader::new(&[5, 6][..]);
+        assert!(many_until(&mut unterminated, &[0], |r| r.read_u8()).is_err());
+
+        let mut huge: &[u8] = &[0xff, 0xff, 0xff, 0xff, 1];
+        let err = count_prefixed::<u32, _, _>(&mut huge, Endian::Big, |r| r.read_u8()).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut huge: &[u8] = &[0xff, 0xff, 0xff, 0xff, 1];
+        let err = count_prefixed_max::<u32, _, _>(&mut huge, Endian::Big, 16, |r| r.read_u8())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let mut negative: &[u8] = &[0xff];
+        let err =
+            count_prefixed::<i8, _, _>(&mut negative, Endian::Big, |r| r.read_u8()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn enum_fallback() {
+        #[derive(Debug, PartialEq)]
+        enum Op {
+            Nop,
+            Other(u16),
+        }
+        impl TryFrom<u16> for Op {
+            type Error = u16;
+            fn try_from(raw: u16) -> Result<Self, u16> {
+                match raw {
+                    0 => Ok(Op::Nop),
+                    raw => Err(raw),
+                }
+            }
+        }
+        let mut bytes: &[u8] = &[0, 0, 0x12, 0x34, 1];
+        assert_eq!(
+            Op::Nop,
+            read_enum_or(&mut bytes, Endian::Big, Op::Other).unwrap()
+        );
+        assert_eq!(
+            Op::Other(0x1234),
+            read_enum_or(&mut bytes, Endian::Big, Op::Other).unwrap()
+        );
+        let err = read_enum_or(&mut bytes, Endian::Big, Op::Other).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn variant_dispatch() {
+        let parsers: &[(&str, VariantParser<&[u8], u32>)] = &[
+            ("byte", |r| r.read_u8().map(u32::from)),
+            ("word", |r| r.read_le_u32()),
+        ];
+        let mut bytes: &[u8] = &[7, 1, 0, 0, 0];
+        assert_eq!(7, read_variant(&mut bytes, "byte", parsers).unwrap());
+        assert_eq!(1, read_variant(&mut bytes, "word", parsers).unwrap());
+        let err = read_variant(&mut bytes, "long", parsers).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("unknown tag \"long\"", err.to_string());
+        let err = read_variant(&mut bytes, "word", parsers).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn all_attempts_reported() {
+        let mut reader = RewindReader::new(&[3, 0][..], 8);
+        let err = alt(&mut reader, (tag(1), tag(2))).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "no alternative matched: 1 (tag 3); 2 (tag 3)",
+            err.to_string()
+        );
+        let attempts = err.get_ref().unwrap().downcast_ref::<AltError>().unwrap();
+        assert_eq!(2, attempts.attempts().len());
+        assert_eq!(3, reader.read_u8().unwrap());
+        let mut short = RewindReader::new(&[1][..], 8);
+        let err = alt(&mut short, (tag(1), tag(1))).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn exact_size() {
+        let mut bytes: &[u8] = &[1, 2, 3, 4, 5];
+        assert_eq!(
+            [1, 2],
+            read_exact_size::<[u8; 2], _>(&mut bytes, Endian::Big).unwrap()
+        );
+        let err = read_sized::<u16, _>(&mut bytes, Endian::Big, 3).unwrap_err();
+        assert_eq!("1 unread bytes in value of 3 bytes", err.to_string());
+        let err = read_sized::<u32, _>(&mut &[0; 8][..], Endian::Big, 2).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("value reads past its size of 2 bytes", err.to_string());
+        let err = read_exact_size::<u64, _>(&mut &[0; 7][..], Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,82 @@
+/// Conversion from `S` that clamps values out of range to the nearest bound of `Self`, for
+/// [`ReadPrimitive::read_le_saturating`](crate::ReadPrimitive::read_le_saturating) and friends
+///
+/// Implemented between all integer types, and from `f32` and `f64` to integers, where NaN
+/// converts to 0 like an `as` cast.
+pub trait SaturatingFrom<S> {
+    /// `value` clamped to the range of `Self`
+    fn saturating_from(value: S) -> Self;
+}
+
+/// Conversion from `S` that keeps the low bits of values out of range, like an `as` cast, for
+/// [`ReadPrimitive::read_le_wrapping`](crate::ReadPrimitive::read_le_wrapping) and friends
+///
+/// Implemented between all integer types.
+pub trait WrappingFrom<S> {
+    /// `value` modulo the range of `Self`
+    fn wrapping_from(value: S) -> Self;
+}
+
+macro_rules! impl_int_conversions {
+    ($($target:ty),+) => {
+        $(
+            impl_int_conversions!(@from $target; u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+            impl SaturatingFrom<f32> for $target {
+                fn saturating_from(value: f32) -> Self {
+                    value as $target
+                }
+            }
+
+            impl SaturatingFrom<f64> for $target {
+                fn saturating_from(value: f64) -> Self {
+                    value as $target
+                }
+            }
+        )+
+    };
+    (@from $target:ty; $($source:ty),+) => {
+        $(
+            impl SaturatingFrom<$source> for $target {
+                fn saturating_from(value: $source) -> Self {
+                    match <$target>::try_from(value) {
+                        Ok(value) => value,
+                        Err(_) if value > <$source>::default() => <$target>::MAX,
+                        Err(_) => <$target>::MIN,
+                    }
+                }
+            }
+
+            impl WrappingFrom<$source> for $target {
+                fn wrapping_from(value: $source) -> Self {
+                    value as $target
+                }
+            }
+        )+
+    };
+}
+
+impl_int_conversions!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn saturating() {
+        assert_eq!(u16::MAX, u16::saturating_from(70_000u32));
+        assert_eq!(0u8, u8::saturating_from(-5i64));
+        assert_eq!(i8::MIN, i8::saturating_from(i128::MIN));
+        assert_eq!(i8::MAX, i8::saturating_from(u8::MAX));
+        assert_eq!(300i16, i16::saturating_from(300u64));
+        assert_eq!(i16::MAX, i16::saturating_from(1e9f32));
+        assert_eq!(0u32, u32::saturating_from(f64::NAN));
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(4464u16, u16::wrapping_from(70_000u32));
+        assert_eq!(-1i8, i8::wrapping_from(u8::MAX));
+        assert_eq!(u64::MAX, u64::wrapping_from(-1i32));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{Endian, ReadFrom};
+use std::io::{self, Read};
+
+/// A type read with a caller-provided context, for decoders that depend on what was read before,
+/// such as a version, a byte order or a lookup table
+///
+/// The context is passed by value; for a table, pass a reference to it. Every [`ReadFrom`] type
+/// implements `ReadFromCtx<Endian>`, so contexts can hand their byte order down to plain fields.
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom, ReadFromCtx};
+/// use std::io::{self, Read};
+///
+/// #[derive(Clone, Copy)]
+/// struct Ctx {
+///     version: u8,
+///     endian: Endian,
+/// }
+///
+/// impl Default for Ctx {
+///     fn default() -> Self {
+///         Ctx { version: 2, endian: Endian::Little }
+///     }
+/// }
+///
+/// /// A size that version 2 widened
+/// struct Size(u32);
+///
+/// impl ReadFromCtx<Ctx> for Size {
+///     fn read_from_ctx<R: Read + ?Sized>(reader: &mut R, ctx: Ctx) -> io::Result<Self> {
+///         match ctx.version {
+///             1 => u16::read_from_ctx(reader, ctx.endian).map(|size| Size(size.into())),
+///             _ => u32::read_from(reader, ctx.endian).map(Size),
+///         }
+///     }
+/// }
+///
+/// let ctx = Ctx { version: 1, endian: Endian::Big };
+/// assert_eq!(0x102, Size::read_from_ctx(&mut &[1u8, 2][..], ctx).unwrap().0);
+/// let size = Size::read_from_default_ctx(&mut &[1u8, 0, 0, 0][..]).unwrap();
+/// assert_eq!(1, size.0);
+/// ```
+pub trait ReadFromCtx<Ctx>: Sized {
+    /// Read a value with `ctx`
+    /// # Errors
+    /// errors when reading from `reader` errors, or if the data is invalid for the type in `ctx`
+    fn read_from_ctx<R: Read + ?Sized>(reader: &mut R, ctx: Ctx) -> io::Result<Self>;
+
+    /// [`ReadFromCtx::read_from_ctx`] with the default context
+    /// # Errors
+    /// errors like [`ReadFromCtx::read_from_ctx`]
+    fn read_from_default_ctx<R: Read + ?Sized>(reader: &mut R) -> io::Result<Self>
+    where
+        Ctx: Default,
+    {
+        Self::read_from_ctx(reader, Ctx::default())
+    }
+}
+
+impl<T: ReadFrom> ReadFromCtx<Endian> for T {
+    fn read_from_ctx<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+        T::read_from(reader, endian)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Read};
+
+    /// Index into a table of names passed as the context
+    #[derive(Debug, PartialEq)]
+    struct Name<'t>(&'t str);
+
+    impl<'t> ReadFromCtx<&'t [&'t str]> for Name<'t> {
+        fn read_from_ctx<R: Read + ?Sized>(
+            reader: &mut R,
+            names: &'t [&'t str],
+        ) -> io::Result<Self> {
+            let index = u8::read_from_ctx(reader, Endian::Little)?;
+            names
+                .get(usize::from(index))
+                .map(|name| Name(name))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no such name"))
+        }
+    }
+
+    #[test]
+    fn table_context() {
+        let names = ["init", "main"];
+        let mut bytes: &[u8] = &[1, 2];
+        assert_eq!(
+            Name("main"),
+            Name::read_from_ctx(&mut bytes, &names[..]).unwrap()
+        );
+        assert!(Name::read_from_ctx(&mut bytes, &names[..]).is_err());
+        let value = u16::read_from_ctx(&mut &[1u8, 0][..], Endian::Big).unwrap();
+        assert_eq!(0x100, value);
+    }
+}
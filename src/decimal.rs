@@ -0,0 +1,107 @@
+use crate::{ReadI16, ReadI32, ReadI64};
+use std::io::{self, Read};
+
+/// Integer with an implied decimal point, `mantissa * 10^exponent`
+///
+/// ```
+/// use read_primitives::{Decimal, ReadDecimal};
+/// let mut bytes: &[u8] = &[0x39, 0x30, 0, 0];
+/// let price = bytes.read_le_i32_decimal(-2).unwrap();
+/// assert_eq!(Decimal { mantissa: 12345, exponent: -2 }, price);
+/// assert_eq!(123.45, price.to_f64());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    /// Integer as stored
+    pub mantissa: i64,
+    /// Power of ten the mantissa is scaled by
+    pub exponent: i32,
+}
+
+impl Decimal {
+    /// Nearest f64
+    ///
+    /// Exact integers and powers of ten up to `10^22` give the correctly rounded result, and
+    /// exponents out of the range of f64 saturate to 0 or infinity.
+    pub fn to_f64(self) -> f64 {
+        if self.mantissa == 0 {
+            return 0.0;
+        }
+        let mantissa = self.mantissa as f64;
+        match self.exponent.checked_neg() {
+            Some(exponent) if exponent > 0 => mantissa / 10f64.powi(exponent),
+            // `10^-i32::MIN` is infinite, keeping the sign of the mantissa on the 0
+            None => mantissa * 0.0,
+            _ => mantissa * 10f64.powi(self.exponent),
+        }
+    }
+}
+
+impl From<Decimal> for f64 {
+    fn from(decimal: Decimal) -> Self {
+        decimal.to_f64()
+    }
+}
+
+macro_rules! impl_decimal_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc " as the mantissa of a [`Decimal`] with `exponent`"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _decimal>](&mut self, exponent: i32) -> io::Result<Decimal> {
+                let mut reader = self;
+                let mantissa = reader.[<read_ $order _ $type>]()?.into();
+                Ok(Decimal { mantissa, exponent })
+            }
+            #[doc = "Read " $type " in " $order_doc " scaled by `10^exponent`, see [`Decimal::to_f64`]"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _scaled>](&mut self, exponent: i32) -> io::Result<f64> {
+                self.[<read_ $order _ $type _decimal>](exponent).map(Decimal::to_f64)
+            }
+        }
+    };
+}
+
+macro_rules! impl_decimal_reads {
+    ($($type:ty),+) => {
+        $(
+            impl_decimal_methods!($type, ne, "native byte order");
+            impl_decimal_methods!($type, le, "little endian byte order");
+            impl_decimal_methods!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+/// Trait to read integers with an implied decimal point, as stored for currency and measurements
+pub trait ReadDecimal: Read {
+    impl_decimal_reads!(i16, i32, i64);
+}
+impl<R> ReadDecimal for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn scaled() {
+        let bytes = (-250i16).to_be_bytes();
+        assert_eq!(-2.5, bytes.as_slice().read_be_i16_scaled(-2).unwrap());
+        let bytes = 7i64.to_le_bytes();
+        assert_eq!(7000.0, bytes.as_slice().read_le_i64_scaled(3).unwrap());
+        let decimal = bytes.as_slice().read_le_i64_decimal(-1).unwrap();
+        assert_eq!(0.7, f64::from(decimal));
+        assert!(bytes[..4].as_ref().read_le_i64_scaled(0).is_err());
+        for (mantissa, exponent, f64) in [
+            (-3, i32::MIN, -0.0),
+            (3, i32::MIN + 1, 0.0),
+            (3, i32::MAX, f64::INFINITY),
+            (-3, 400, f64::NEG_INFINITY),
+            (0, 400, 0.0),
+        ] {
+            let value = Decimal { mantissa, exponent }.to_f64();
+            assert_eq!(f64.to_bits(), value.to_bits(), "{mantissa}e{exponent}");
+        }
+    }
+}
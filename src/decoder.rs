@@ -0,0 +1,151 @@
+use crate::{Endian, ReadFrom, RingBuffer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+/// Push-based decoder of a stream of `T`s, for code that receives bytes instead of reading them
+///
+/// Bytes are [pushed](Decoder::push) as they arrive, in chunks of any size, and each complete
+/// value is taken with [`Decoder::poll_value`]; the bytes of a partial value stay buffered in the
+/// decoder until the rest of them arrived.
+///
+/// ```
+/// use read_primitives::{Decoder, Endian};
+/// let mut decoder = Decoder::<(u8, u16)>::new(Endian::Big);
+/// decoder.push(&[1, 0]);
+/// assert_eq!(None, decoder.poll_value().unwrap());
+/// decoder.push(&[2, 3, 0]);
+/// assert_eq!(Some((1, 2)), decoder.poll_value().unwrap());
+/// assert_eq!(None, decoder.poll_value().unwrap());
+/// assert_eq!(2, decoder.buffered());
+/// ```
+///
+/// With the `serde` feature the decoder is serializable, buffered bytes included, so a decode
+/// can be suspended and resumed by another process.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct Decoder<T> {
+    buffer: VecDeque<u8>,
+    endian: Endian,
+    values: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for Decoder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decoder")
+            .field("buffered", &self.buffer.len())
+            .field("endian", &self.endian)
+            .finish()
+    }
+}
+
+impl<T: ReadFrom> Decoder<T> {
+    /// Decoder of values in `endian` byte order
+    pub fn new(endian: Endian) -> Self {
+        Decoder {
+            buffer: VecDeque::new(),
+            endian,
+            values: PhantomData,
+        }
+    }
+
+    /// Add bytes that arrived
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend(bytes);
+    }
+
+    /// Take the next value if all of its bytes arrived
+    /// # Errors
+    /// errors when decoding the value errors other than by running out of bytes; the value's bytes
+    /// stay buffered, so the next call errors again unless they are [cleared](Decoder::clear)
+    pub fn poll_value(&mut self) -> io::Result<Option<T>> {
+        Ok(self.buffer.try_decode(self.endian)?.ok())
+    }
+
+    /// Number of bytes pushed but not yet decoded
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drop the buffered bytes, to resynchronize after an error
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Check that no partial value is left, at the end of the stream
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if bytes of a value are still buffered
+    pub fn finish(self) -> io::Result<()> {
+        match self.buffer.len() {
+            0 => Ok(()),
+            len => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("stream ended in a value after {len} of its bytes"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn byte_at_a_time() {
+        let mut decoder = Decoder::<[u16; 2]>::new(Endian::Little);
+        let mut values = Vec::new();
+        for byte in [1, 0, 2, 0, 3, 0, 4, 0, 5] {
+            decoder.push(&[byte]);
+            while let Some(value) = decoder.poll_value().unwrap() {
+                values.push(value);
+            }
+        }
+        assert_eq!(vec![[1, 2], [3, 4]], values);
+        let err = decoder.finish().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            "stream ended in a value after 1 of its bytes",
+            err.to_string()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint() {
+        let mut decoder = Decoder::<u32>::new(Endian::Big);
+        decoder.push(&[0, 0]);
+        let checkpoint = bincode::serialize(&decoder).unwrap();
+        let mut resumed: Decoder<u32> = bincode::deserialize(&checkpoint).unwrap();
+        resumed.push(&[1, 2]);
+        assert_eq!(Some(0x0102), resumed.poll_value().unwrap());
+    }
+
+    #[test]
+    fn error_and_clear() {
+        #[derive(Debug)]
+        struct Even(u8);
+
+        impl ReadFrom for Even {
+            fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+                match u8::read_from(reader, endian)? {
+                    byte if byte % 2 == 0 => Ok(Even(byte)),
+                    _ => Err(io::Error::new(io::ErrorKind::InvalidData, "odd")),
+                }
+            }
+        }
+
+        let mut decoder = Decoder::<Even>::new(Endian::Big);
+        decoder.push(&[3]);
+        assert!(decoder.poll_value().is_err());
+        assert!(decoder.poll_value().is_err());
+        decoder.clear();
+        decoder.push(&[4]);
+        assert_eq!(4, decoder.poll_value().unwrap().unwrap().0);
+        decoder.finish().unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Endian, Primitive, ReadPrimitive, ReadVarint, PREALLOC_LIMIT};
+use std::io::{self, Read};
+
+/// An integer type that delta and frame-of-reference encoded sequences can be built from
+///
+/// Values are summed with wrapping arithmetic, so encoders may rely on deltas that overflow.
+pub trait DeltaInt: Primitive + Copy {
+    /// `self + delta`, wrapping around at the bounds of the type
+    fn wrapping_add(self, delta: Self) -> Self;
+}
+
+macro_rules! impl_delta_int {
+    ($($type:ty),+) => {
+        $(
+            impl DeltaInt for $type {
+                fn wrapping_add(self, delta: Self) -> Self {
+                    <$type>::wrapping_add(self, delta)
+                }
+            }
+        )+
+    };
+}
+
+impl_delta_int!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128);
+
+fn with_capacity<T>(count: usize) -> Vec<T> {
+    Vec::with_capacity(count.min(PREALLOC_LIMIT / std::mem::size_of::<T>().max(1)))
+}
+
+/// Trait to read delta and frame-of-reference encoded integer sequences, as used by log and
+/// telemetry formats for timestamps and counters
+///
+/// A delta encoded sequence stores its first value and then the difference of each value from
+/// the one before it. A frame-of-reference encoded sequence stores a base and then the
+/// difference of each value from the base.
+///
+/// ```
+/// use read_primitives::{Endian, ReadDelta};
+/// let mut timestamps: &[u8] = &[0x03, 0xe8, 0, 10, 0, 5];
+/// let timestamps = timestamps.read_delta_vec::<u16>(Endian::Big, 3).unwrap();
+/// assert_eq!(vec![1000, 1010, 1015], timestamps);
+/// let mut levels: &[u8] = &[0xe8, 0x07, 0, 0x80, 0x01];
+/// assert_eq!(vec![1000, 1128], levels.read_frame_of_reference_varint_vec(2).unwrap());
+/// ```
+pub trait ReadDelta: Read {
+    /// Read `count` delta encoded `T` in `endian` byte order: the first value, then `count - 1`
+    /// deltas each added to the value before
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_delta_vec<T: DeltaInt>(&mut self, endian: Endian, count: usize) -> io::Result<Vec<T>> {
+        let mut reader = self;
+        let mut values = with_capacity(count);
+        let mut value = None;
+        for _ in 0..count {
+            let read = reader.read_endian::<T>(endian)?;
+            let next = value.map_or(read, |value: T| value.wrapping_add(read));
+            values.push(next);
+            value = Some(next);
+        }
+        Ok(values)
+    }
+
+    /// Read `count` frame-of-reference encoded `T` in `endian` byte order: the base, then `count`
+    /// offsets each added to the base
+    ///
+    /// The base is read even if `count` is 0.
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_frame_of_reference_vec<T: DeltaInt>(
+        &mut self,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Vec<T>> {
+        let mut reader = self;
+        let base = reader.read_endian::<T>(endian)?;
+        let mut values = with_capacity(count);
+        for _ in 0..count {
+            values.push(base.wrapping_add(reader.read_endian(endian)?));
+        }
+        Ok(values)
+    }
+
+    /// Read `count` delta encoded i64 packed as zigzag varints: the first value, then `count - 1`
+    /// deltas each added to the value before
+    /// # Errors
+    /// errors exactly when [`read_varint_i64`](ReadVarint::read_varint_i64) errors
+    fn read_delta_varint_vec(&mut self, count: usize) -> io::Result<Vec<i64>> {
+        let mut reader = self;
+        let mut values = with_capacity(count);
+        let mut value = 0i64;
+        for _ in 0..count {
+            value = value.wrapping_add(reader.read_varint_i64()?);
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Read `count` frame-of-reference encoded u64 packed as unsigned LEB128 varints: the base,
+    /// then `count` offsets each added to the base
+    ///
+    /// The base is read even if `count` is 0.
+    /// # Errors
+    /// errors exactly when [`read_varint_u64`](ReadVarint::read_varint_u64) errors
+    fn read_frame_of_reference_varint_vec(&mut self, count: usize) -> io::Result<Vec<u64>> {
+        let mut reader = self;
+        let base = reader.read_varint_u64()?;
+        let mut values = with_capacity(count);
+        for _ in 0..count {
+            values.push(base.wrapping_add(reader.read_varint_u64()?));
+        }
+        Ok(values)
+    }
+}
+impl<R> ReadDelta for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn delta() {
+        let mut bytes: &[u8] = &[0xfe, 0xff, 3, 0, 0xff, 0xff];
+        assert_eq!(
+            vec![-2i16, 1, 0],
+            bytes.read_delta_vec(Endian::Little, 3).unwrap()
+        );
+        let mut wrapping: &[u8] = &[0xff, 2];
+        assert_eq!(
+            vec![0xff, 1],
+            wrapping.read_delta_vec::<u8>(Endian::Big, 2).unwrap()
+        );
+        let err = (&[1u8, 0][..])
+            .read_delta_vec::<u16>(Endian::Big, 2)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert!((&[][..])
+            .read_delta_vec::<u32>(Endian::Big, 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn frame_of_reference() {
+        let mut bytes: &[u8] = &[0, 100, 0, 0, 0, 7, 0, 3];
+        assert_eq!(
+            vec![100u16, 107, 103],
+            bytes.read_frame_of_reference_vec(Endian::Big, 3).unwrap()
+        );
+        assert!((&[][..])
+            .read_frame_of_reference_vec::<u8>(Endian::Big, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn varint() {
+        // 300, then deltas -1 and +2 zigzag encoded
+        let mut bytes: &[u8] = &[0xd8, 0x04, 0x01, 0x04];
+        assert_eq!(vec![300, 299, 301], bytes.read_delta_varint_vec(3).unwrap());
+        let mut bytes: &[u8] = &[0xe8, 0x07, 0, 0x80, 0x01];
+        assert_eq!(
+            vec![1000, 1128],
+            bytes.read_frame_of_reference_varint_vec(2).unwrap()
+        );
+        assert!((&[0x80][..]).read_delta_varint_vec(1).is_err());
+    }
+}
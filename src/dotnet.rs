@@ -0,0 +1,245 @@
+//! Reads of data written by .NET's `System.IO.BinaryWriter`
+//!
+//! `BinaryWriter` writes numbers in little endian, so its primitive fields are read with the
+//! `read_le_*` methods. This module covers the remaining conventions: 7-bit encoded lengths,
+//! length-prefixed UTF-8 strings, UTF-8 chars and the 16-byte `decimal` layout.
+//!
+//! ```
+//! use read_primitives::dotnet::ReadDotNet;
+//! let mut bytes: &[u8] = &[5, b'h', b'e', b'l', b'l', b'o'];
+//! assert_eq!("hello", bytes.read_dotnet_string().unwrap());
+//! ```
+
+use crate::string::read_exact_vec;
+use core::fmt;
+use std::io::{self, Read};
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// A .NET `decimal`: `mantissa / 10^scale`, negated if `negative`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    /// 96-bit unsigned integer value
+    pub mantissa: u128,
+    /// Power of ten to divide by, at most 28
+    pub scale: u8,
+    /// Sign bit
+    pub negative: bool,
+}
+
+impl Decimal {
+    /// Nearest `f64` to the value
+    pub fn to_f64(&self) -> f64 {
+        let value = self.mantissa as f64 / 10f64.powi(i32::from(self.scale));
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa,
+            width = usize::from(self.scale) + 1
+        );
+        let (int, frac) = digits.split_at(digits.len() - usize::from(self.scale));
+        if self.negative && self.mantissa != 0 {
+            f.write_str("-")?;
+        }
+        f.write_str(int)?;
+        if !frac.is_empty() {
+            write!(f, ".{frac}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Trait to read the encodings of .NET's `BinaryWriter`
+pub trait ReadDotNet: Read {
+    /// Read a `Write7BitEncodedInt` value: 7 bits per byte, least significant first
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the encoding is longer than 5 bytes or
+    /// overflows 32 bits, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_7bit_encoded_int(&mut self) -> io::Result<i32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let mut byte = [0u8; 1];
+            self.read_exact(&mut byte)?;
+            let [byte] = byte;
+            if shift == 28 && byte > 0x0f {
+                return Err(invalid_data("7-bit encoded int overflows 32 bits"));
+            }
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value as i32);
+            }
+        }
+        unreachable!("the fifth byte has no continuation bit")
+    }
+
+    /// Read a `Write7BitEncodedInt64` value: 7 bits per byte, least significant first
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the encoding is longer than 10 bytes or
+    /// overflows 64 bits, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_7bit_encoded_int64(&mut self) -> io::Result<i64> {
+        let mut value = 0u64;
+        for shift in (0..70).step_by(7) {
+            let mut byte = [0u8; 1];
+            self.read_exact(&mut byte)?;
+            let [byte] = byte;
+            if shift == 63 && byte > 0x01 {
+                return Err(invalid_data("7-bit encoded int overflows 64 bits"));
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value as i64);
+            }
+        }
+        unreachable!("the tenth byte has no continuation bit")
+    }
+
+    /// Read a string prefixed with its UTF-8 byte length as a 7-bit encoded int
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length is negative or the bytes are not
+    /// valid UTF-8, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_dotnet_string(&mut self) -> io::Result<String> {
+        self.read_dotnet_string_max(usize::MAX)
+    }
+
+    /// Read a string prefixed with its UTF-8 byte length as a 7-bit encoded int, rejecting
+    /// lengths over `max_len` before reading the string
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length is negative or exceeds `max_len`,
+    /// or the bytes are not valid UTF-8, or with [`io::ErrorKind::UnexpectedEof`] if the reader
+    /// ends early
+    fn read_dotnet_string_max(&mut self, max_len: usize) -> io::Result<String> {
+        let len = usize::try_from(self.read_7bit_encoded_int()?)
+            .map_err(|_| invalid_data("negative string length"))?;
+        crate::check_max_len(len, max_len)?;
+        String::from_utf8(read_exact_vec(self, len)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Read a char written as its UTF-8 encoding
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not a valid UTF-8 char,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_dotnet_char(&mut self) -> io::Result<char> {
+        let mut bytes = [0u8; 4];
+        self.read_exact(&mut bytes[..1])?;
+        let len = match bytes[0].leading_ones() {
+            0 => 1,
+            len @ 2..=4 => len as usize,
+            _ => return Err(invalid_data("invalid UTF-8 char")),
+        };
+        self.read_exact(&mut bytes[1..len])?;
+        std::str::from_utf8(&bytes[..len])
+            .ok()
+            .and_then(|str| str.chars().next())
+            .ok_or_else(|| invalid_data("invalid UTF-8 char"))
+    }
+
+    /// Read a `decimal`: the low, middle and high 32 bits of the mantissa, then the flags
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the scale exceeds 28 or reserved flag bits are
+    /// set, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_dotnet_decimal(&mut self) -> io::Result<Decimal> {
+        let mut bytes = [0u8; 16];
+        self.read_exact(&mut bytes)?;
+        let mut mantissa = [0u8; 16];
+        mantissa[..12].copy_from_slice(&bytes[..12]);
+        let flags = u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+        let scale = (flags >> 16) as u8;
+        if flags & 0x7f00_ffff != 0 || scale > 28 {
+            return Err(invalid_data("invalid decimal flags"));
+        }
+        Ok(Decimal {
+            mantissa: u128::from_le_bytes(mantissa),
+            scale,
+            negative: flags >> 31 != 0,
+        })
+    }
+}
+impl<R> ReadDotNet for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::dotnet::*;
+
+    #[test]
+    fn read_7bit_encoded_int() {
+        let mut bytes: &[u8] = &[0x7f, 0x80, 0x01, 0xff, 0xff, 0xff, 0xff, 0x0f];
+        assert_eq!(127, bytes.read_7bit_encoded_int().unwrap());
+        assert_eq!(128, bytes.read_7bit_encoded_int().unwrap());
+        assert_eq!(-1, bytes.read_7bit_encoded_int().unwrap());
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0x10];
+        let err = bytes.read_7bit_encoded_int().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_7bit_encoded_int64() {
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(-1, bytes.read_7bit_encoded_int64().unwrap());
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        let err = bytes.read_7bit_encoded_int64().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_dotnet_string() {
+        let mut string = vec![0x80, 0x01];
+        string.extend([b'x'; 128]);
+        assert_eq!(
+            "x".repeat(128),
+            string.as_slice().read_dotnet_string().unwrap()
+        );
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0x0f];
+        let err = bytes.read_dotnet_string().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = string.as_slice().read_dotnet_string_max(127).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_dotnet_char() {
+        let mut bytes: &[u8] = "aé€😀".as_bytes();
+        for char in "aé€😀".chars() {
+            assert_eq!(char, bytes.read_dotnet_char().unwrap());
+        }
+        let err = (&[0x80u8][..]).read_dotnet_char().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_dotnet_decimal() {
+        // -1.25m
+        let mut bytes: &[u8] = &[125, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0x80];
+        let decimal = bytes.read_dotnet_decimal().unwrap();
+        assert_eq!(
+            Decimal {
+                mantissa: 125,
+                scale: 2,
+                negative: true
+            },
+            decimal
+        );
+        assert_eq!("-1.25", decimal.to_string());
+        assert_eq!(-1.25, decimal.to_f64());
+        let small = Decimal {
+            mantissa: 5,
+            scale: 3,
+            negative: false,
+        };
+        assert_eq!("0.005", small.to_string());
+        let mut bytes: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 29, 0];
+        let err = bytes.read_dotnet_decimal().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
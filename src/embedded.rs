@@ -0,0 +1,581 @@
+//! Primitive reads and writes for [`embedded_io`] readers and writers
+//!
+//! The traits mirror the ones at the crate root, but build on [`embedded_io::Read`] and
+//! [`embedded_io::Write`] instead of `std::io`, so they work on targets without `std` and keep the
+//! error type of the underlying reader or writer. Composite values implement [`ReadFrom`], which
+//! `#[derive(ReadFrom)]` with `#[read(embedded)]` implements for structs.
+//!
+//! ```
+//! use read_primitives::embedded::{ReadU16, WriteU16};
+//! let mut buf = [0u8; 2];
+//! buf.as_mut_slice().write_be_u16(0x1234).unwrap();
+//! assert_eq!(0x1234, buf.as_slice().read_be_u16().unwrap());
+//! ```
+
+use crate::{Endian, Needed, Primitive};
+use core::convert::Infallible;
+use embedded_io::{ErrorType, Read, ReadExactError, Write};
+
+macro_rules! impl_embedded_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`] errors"]
+            fn [<read_ $order _ $type>](&mut self) -> Result<$type, ReadExactError<Self::Error>> {
+                let mut bytes = [0u8; core::mem::size_of::<$type>()];
+                self.read_exact(&mut bytes)?;
+                Ok($type::[<from_ $order _bytes>](bytes))
+            }
+            #[cfg(feature = "heapless")]
+            #[doc = "Read `count` " $type " in " $order_doc " into a [`heapless::Vec`] with capacity `N`"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`DecodeErrorKind::Capacity`] if `count` exceeds `N`, or when [`Read::read_exact`] errors"]
+            fn [<read_ $order _ $type _heapless>]<const N: usize>(&mut self, count: usize) -> Result<heapless::Vec<$type, N>, DecodeError<Self::Error>> {
+                if count > N {
+                    return Err(DecodeErrorKind::Capacity.into());
+                }
+                let mut vec = heapless::Vec::new();
+                for _ in 0..count {
+                    // count was checked against the capacity, so the pushes can't fail
+                    let _ = vec.push(self.[<read_ $order _ $type>]()?);
+                }
+                Ok(vec)
+            }
+            #[cfg(feature = "arrayvec")]
+            #[doc = "Read `count` " $type " in " $order_doc " into an [`arrayvec::ArrayVec`] with capacity `CAP`"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`DecodeErrorKind::Capacity`] if `count` exceeds `CAP`, or when [`Read::read_exact`] errors"]
+            fn [<read_ $order _ $type _arrayvec>]<const CAP: usize>(&mut self, count: usize) -> Result<arrayvec::ArrayVec<$type, CAP>, DecodeError<Self::Error>> {
+                if count > CAP {
+                    return Err(DecodeErrorKind::Capacity.into());
+                }
+                let mut vec = arrayvec::ArrayVec::new();
+                for _ in 0..count {
+                    vec.push(self.[<read_ $order _ $type>]()?);
+                }
+                Ok(vec)
+            }
+        }
+    };
+}
+
+macro_rules! impl_embedded_write_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Write " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Write::write_all`] errors"]
+            fn [<write_ $order _ $type>](&mut self, value: $type) -> Result<(), Self::Error> {
+                self.write_all(&value.[<to_ $order _bytes>]())
+            }
+        }
+    };
+}
+
+macro_rules! impl_embedded_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to read " $type " from an [`embedded_io::Read`]."]
+                pub trait [<Read $type:camel>]: Read {
+                    impl_embedded_methods!($type, ne, "native byte order");
+                    impl_embedded_methods!($type, le, "little endian byte order");
+                    impl_embedded_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Read + ?Sized> [<Read $type:camel>] for T {}
+
+                #[doc = "Trait to write " $type " to an [`embedded_io::Write`]."]
+                pub trait [<Write $type:camel>]: Write {
+                    impl_embedded_write_methods!($type, ne, "native byte order");
+                    impl_embedded_write_methods!($type, le, "little endian byte order");
+                    impl_embedded_write_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Write + ?Sized> [<Write $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+impl_embedded_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// Trait to read u8 from an [`embedded_io::Read`]
+pub trait ReadU8: Read {
+    /// Read a u8
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_u8(&mut self) -> Result<u8, ReadExactError<Self::Error>> {
+        let mut bytes = [0u8; 1];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes[0])
+    }
+}
+impl<R: Read + ?Sized> ReadU8 for R {}
+
+/// Trait to read char from an [`embedded_io::Read`]
+pub trait ReadChar: Read {
+    /// Read a char
+    ///  It is a assumed that the char is represented in native byte order
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_char(&mut self) -> Result<Option<char>, ReadExactError<Self::Error>> {
+        let mut bytes = [0u8; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(char::from_u32(u32::from_ne_bytes(bytes)))
+    }
+}
+impl<R: Read + ?Sized> ReadChar for R {}
+
+/// Trait to read bool from an [`embedded_io::Read`]
+pub trait ReadBool: Read {
+    /// Read a bool
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_bool(&mut self) -> Result<bool, ReadExactError<Self::Error>> {
+        self.read_u8().map(|byte| byte != 0)
+    }
+}
+impl<R: Read + ?Sized> ReadBool for R {}
+
+/// Trait to write u8 to an [`embedded_io::Write`]
+pub trait WriteU8: Write {
+    /// Write a u8
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_u8(&mut self, value: u8) -> Result<(), Self::Error> {
+        self.write_all(&[value])
+    }
+}
+impl<W: Write + ?Sized> WriteU8 for W {}
+
+/// Trait to write char to an [`embedded_io::Write`]
+pub trait WriteChar: Write {
+    /// Write a char as a u32 in native byte order
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_char(&mut self, value: char) -> Result<(), Self::Error> {
+        self.write_all(&u32::from(value).to_ne_bytes())
+    }
+}
+impl<W: Write + ?Sized> WriteChar for W {}
+
+/// Trait to write bool to an [`embedded_io::Write`]
+pub trait WriteBool: Write {
+    /// Write a bool as a single byte
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.write_all(&[u8::from(value)])
+    }
+}
+impl<W: Write + ?Sized> WriteBool for W {}
+
+/// Error of an [`embedded::ReadFrom`](ReadFrom) read, keeping the reader's error type
+///
+/// For derived reads, the path and offset name the innermost struct and field that failed, like
+/// the [`FieldError`](crate::FieldError) of `std` reads, without allocating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError<E> {
+    kind: DecodeErrorKind<E>,
+    path: Option<&'static str>,
+    offset: u64,
+}
+
+impl<E> DecodeError<E> {
+    /// What went wrong
+    pub fn kind(&self) -> &DecodeErrorKind<E> {
+        &self.kind
+    }
+
+    /// Unwrap what went wrong
+    pub fn into_kind(self) -> DecodeErrorKind<E> {
+        self.kind
+    }
+
+    /// Struct and field that failed to read, for derived reads
+    pub fn path(&self) -> Option<&'static str> {
+        self.path
+    }
+
+    /// Offset of the start of the field from the start of the outermost value, for derived reads
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Convert the error of the reader with `f`, keeping the path and offset
+    pub fn map_io<F>(self, f: impl FnOnce(E) -> F) -> DecodeError<F> {
+        let kind = match self.kind {
+            DecodeErrorKind::UnexpectedEof => DecodeErrorKind::UnexpectedEof,
+            DecodeErrorKind::Io(err) => DecodeErrorKind::Io(f(err)),
+            DecodeErrorKind::Magic => DecodeErrorKind::Magic,
+            DecodeErrorKind::Assertion(condition) => DecodeErrorKind::Assertion(condition),
+            DecodeErrorKind::OutOfRange(check) => DecodeErrorKind::OutOfRange(check),
+            DecodeErrorKind::Invalid => DecodeErrorKind::Invalid,
+            DecodeErrorKind::UnsupportedVersion => DecodeErrorKind::UnsupportedVersion,
+            DecodeErrorKind::Size => DecodeErrorKind::Size,
+            DecodeErrorKind::Capacity => DecodeErrorKind::Capacity,
+        };
+        DecodeError {
+            kind,
+            path: self.path,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<E> From<DecodeErrorKind<E>> for DecodeError<E> {
+    fn from(kind: DecodeErrorKind<E>) -> Self {
+        DecodeError {
+            kind,
+            path: None,
+            offset: 0,
+        }
+    }
+}
+
+impl<E> From<ReadExactError<E>> for DecodeError<E> {
+    fn from(err: ReadExactError<E>) -> Self {
+        match err {
+            ReadExactError::UnexpectedEof => DecodeErrorKind::UnexpectedEof,
+            ReadExactError::Other(err) => DecodeErrorKind::Io(err),
+        }
+        .into()
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for DecodeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(path) = self.path {
+            write!(f, "{path} at offset {:#x}: ", self.offset)?;
+        }
+        match &self.kind {
+            DecodeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeErrorKind::Io(err) => write!(f, "{err:?}"),
+            DecodeErrorKind::Magic => write!(f, "magic bytes do not match"),
+            DecodeErrorKind::Assertion(condition) => write!(f, "assertion `{condition}` failed"),
+            DecodeErrorKind::OutOfRange(range) => write!(f, "value is out of range {range}"),
+            DecodeErrorKind::Invalid => write!(f, "invalid value"),
+            DecodeErrorKind::UnsupportedVersion => write!(f, "unsupported version"),
+            DecodeErrorKind::Size => write!(f, "size, alignment or padding out of range"),
+            DecodeErrorKind::Capacity => write!(f, "count exceeds capacity"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug> core::error::Error for DecodeError<E> {}
+
+/// What went wrong in an [`embedded::ReadFrom`](ReadFrom) read
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeErrorKind<E> {
+    /// The reader ended before the value
+    UnexpectedEof,
+    /// The reader errored
+    Io(E),
+    /// `magic` bytes did not match
+    Magic,
+    /// An `assert` condition failed, as written in the attribute
+    Assertion(&'static str),
+    /// A value was outside its `check` range, as written in the attribute
+    OutOfRange(&'static str),
+    /// A `check_fn` rejected a value
+    Invalid,
+    /// No layout of a `version_from` field has the version
+    UnsupportedVersion,
+    /// A padding size or alignment was out of range
+    Size,
+    /// A count exceeded the capacity of the fixed-capacity collection read into
+    Capacity,
+}
+
+/// A type that can be read from an [`embedded_io::Read`], the counterpart of
+/// [`ReadFrom`](crate::ReadFrom) without `std`
+///
+/// Implemented for the integer and float types, bool and arrays. With the `derive` feature,
+/// `#[derive(ReadFrom)]` implements it as well when the struct is marked with
+/// `#[read(embedded)]`.
+///
+/// ```
+/// use read_primitives::embedded::ReadFrom;
+/// use read_primitives::Endian;
+/// let mut bytes: &[u8] = &[1, 0, 2, 0];
+/// assert_eq!([1, 2], <[u16; 2]>::read_from(&mut bytes, Endian::Little).unwrap());
+/// ```
+pub trait ReadFrom: Sized {
+    /// Read a value, using `endian` for the parts that do not specify their own byte order
+    /// # Errors
+    /// errors when reading from `reader` errors, or if the data is invalid for the type
+    fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> Result<Self, DecodeError<R::Error>>;
+}
+
+macro_rules! impl_embedded_read_from {
+    ($($type:ty),+) => {
+        $(
+            impl ReadFrom for $type {
+                fn read_from<R: Read + ?Sized>(
+                    reader: &mut R,
+                    endian: Endian,
+                ) -> Result<Self, DecodeError<R::Error>> {
+                    let mut bytes = <$type as Primitive>::Bytes::default();
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$type as Primitive>::from_endian_bytes(bytes, endian))
+                }
+            }
+        )+
+    };
+}
+
+impl_embedded_read_from!(
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool
+);
+
+impl<T: ReadFrom, const N: usize> ReadFrom for [T; N] {
+    fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> Result<Self, DecodeError<R::Error>> {
+        let mut slots: [Option<T>; N] = core::array::from_fn(|_| None);
+        for slot in &mut slots {
+            *slot = Some(T::read_from(reader, endian)?);
+        }
+        Ok(slots.map(|slot| slot.expect("every element was read")))
+    }
+}
+
+/// Reader over the two parts of a buffer that wraps around, counting the bytes read
+struct Halves<'b> {
+    front: &'b [u8],
+    back: &'b [u8],
+    position: usize,
+}
+
+impl ErrorType for Halves<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Halves<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        if self.front.is_empty() {
+            self.front = core::mem::take(&mut self.back);
+        }
+        let len = buf.len().min(self.front.len());
+        buf[..len].copy_from_slice(&self.front[..len]);
+        self.front = &self.front[len..];
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Decode a `T` from the bytes of `front` followed by those of `back`, the two parts of a ring
+/// buffer's contents, returning it with the number of bytes it took, or [`Needed`] if the bytes
+/// ended before the value
+///
+/// The buffer is only read, so it can be released by that many bytes afterwards, or left as it is
+/// to retry once more bytes arrived.
+///
+/// ```
+/// use read_primitives::embedded::decode_split;
+/// use read_primitives::{Endian, Needed};
+/// assert_eq!(Ok((0x0102, 2)), decode_split::<u16>(&[1], &[2, 3], Endian::Big).unwrap());
+/// assert_eq!(Err(Needed::Unknown), decode_split::<u32>(&[1], &[2, 3], Endian::Big).unwrap());
+/// ```
+/// # Errors
+/// errors if the data is invalid for the type
+pub fn decode_split<T: ReadFrom>(
+    front: &[u8],
+    back: &[u8],
+    endian: Endian,
+) -> Result<Result<(T, usize), Needed>, DecodeError<Infallible>> {
+    let mut reader = Halves {
+        front,
+        back,
+        position: 0,
+    };
+    match T::read_from(&mut reader, endian) {
+        Ok(value) => Ok(Ok((value, reader.position))),
+        Err(err) if matches!(err.kind(), DecodeErrorKind::UnexpectedEof) => {
+            Ok(Err(Needed::Unknown))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Helpers called by the code generated by the derive, not public API
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive_support {
+    use super::{DecodeError, DecodeErrorKind};
+    use embedded_io::ErrorType;
+    pub use embedded_io::Read;
+
+    /// Reader counting the bytes read through it, for the offsets of [`DecodeError`] and for
+    /// alignment
+    pub struct Counter<'r, R: ?Sized> {
+        inner: &'r mut R,
+        position: u64,
+    }
+
+    impl<'r, R: ?Sized> Counter<'r, R> {
+        pub fn new(inner: &'r mut R) -> Self {
+            Counter { inner, position: 0 }
+        }
+
+        pub fn position(&self) -> u64 {
+            self.position
+        }
+    }
+
+    impl<R: ErrorType + ?Sized> ErrorType for Counter<'_, R> {
+        type Error = R::Error;
+    }
+
+    impl<R: Read + ?Sized> Read for Counter<'_, R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let read = self.inner.read(buf)?;
+            self.position += read as u64;
+            Ok(read)
+        }
+    }
+
+    /// Attribute an error to the field starting at `offset`
+    ///
+    /// Errors of nested fields keep their path and have their offset moved by `offset`.
+    pub fn field_error<E>(
+        mut err: DecodeError<E>,
+        path: &'static str,
+        offset: u64,
+    ) -> DecodeError<E> {
+        err.offset += offset;
+        err.path.get_or_insert(path);
+        err
+    }
+
+    pub fn with_field<T, E>(
+        result: Result<T, DecodeError<E>>,
+        path: &'static str,
+        offset: u64,
+    ) -> Result<T, DecodeError<E>> {
+        result.map_err(|err| field_error(err, path, offset))
+    }
+
+    pub fn size<E>(value: impl TryInto<usize>) -> Result<usize, DecodeError<E>> {
+        value.try_into().map_err(|_| DecodeErrorKind::Size.into())
+    }
+
+    pub fn check_magic<R: Read + ?Sized>(
+        reader: &mut R,
+        expected: &[u8],
+    ) -> Result<(), DecodeError<R::Error>> {
+        let mut buf = [0u8; 16];
+        for chunk in expected.chunks(buf.len()) {
+            let found = &mut buf[..chunk.len()];
+            reader.read_exact(found)?;
+            if found != chunk {
+                return Err(DecodeErrorKind::Magic.into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn assertion_failed<E>(condition: &'static str) -> DecodeError<E> {
+        DecodeErrorKind::Assertion(condition).into()
+    }
+
+    pub fn check_range<T: PartialOrd, E>(
+        value: &T,
+        range: impl core::ops::RangeBounds<T>,
+        text: &'static str,
+    ) -> Result<(), DecodeError<E>> {
+        if range.contains(value) {
+            return Ok(());
+        }
+        Err(DecodeErrorKind::OutOfRange(text).into())
+    }
+
+    pub fn check_fn<X, E>(result: Result<(), X>) -> Result<(), DecodeError<E>> {
+        result.map_err(|_| DecodeErrorKind::Invalid.into())
+    }
+
+    pub fn unsupported_version<E>(_: &dyn core::fmt::Debug) -> DecodeError<E> {
+        DecodeErrorKind::UnsupportedVersion.into()
+    }
+
+    /// Skip `len` bytes of padding
+    pub fn skip<R: Read + ?Sized>(
+        reader: &mut R,
+        mut len: usize,
+    ) -> Result<(), DecodeError<R::Error>> {
+        let mut buf = [0u8; 32];
+        while len > 0 {
+            let chunk = len.min(buf.len());
+            reader.read_exact(&mut buf[..chunk])?;
+            len -= chunk;
+        }
+        Ok(())
+    }
+
+    /// Number of padding bytes from `position` to the next multiple of `align`
+    pub fn align<E>(position: u64, align: usize) -> Result<usize, DecodeError<E>> {
+        if align == 0 {
+            return Err(DecodeErrorKind::Size.into());
+        }
+        Ok(((align as u64 - position % align as u64) % align as u64) as usize)
+    }
+
+    /// Number of padding bytes after a field of `used` bytes padded to `size`
+    pub fn pad_to<E>(used: u64, size: usize) -> Result<usize, DecodeError<E>> {
+        (size as u64)
+            .checked_sub(used)
+            .map(|len| len as usize)
+            .ok_or(DecodeErrorKind::Size.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::embedded::*;
+
+    #[test]
+    fn round_trip() {
+        let mut buf = [0u8; 18];
+        let mut writer = buf.as_mut_slice();
+        writer.write_le_u32(37).unwrap();
+        writer.write_be_f64(1.5).unwrap();
+        writer.write_u8(7).unwrap();
+        writer.write_bool(true).unwrap();
+        writer.write_char('x').unwrap();
+        assert!(writer.write_u8(0).is_err());
+
+        let mut reader = buf.as_slice();
+        assert_eq!(37, reader.read_le_u32().unwrap());
+        assert_eq!(1.5, reader.read_be_f64().unwrap());
+        assert_eq!(7, reader.read_u8().unwrap());
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(Some('x'), reader.read_char().unwrap());
+        assert_eq!(ReadExactError::UnexpectedEof, reader.read_u8().unwrap_err());
+    }
+
+    #[cfg(all(feature = "heapless", feature = "arrayvec"))]
+    #[test]
+    fn fixed_capacity() {
+        let mut reader: &[u8] = &[1, 0, 2, 0, 3, 0];
+        assert_eq!([1, 2], *reader.read_le_u16_heapless::<2>(2).unwrap());
+        let err = reader.read_le_u16_arrayvec::<1>(2).unwrap_err();
+        assert_eq!(&DecodeErrorKind::Capacity, err.kind());
+        let err = reader.read_le_u16_arrayvec::<4>(2).unwrap_err();
+        assert_eq!(&DecodeErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn split() {
+        let value = decode_split::<[u16; 2]>(&[0, 1, 0], &[2, 9], Endian::Big).unwrap();
+        assert_eq!(Ok(([1, 2], 4)), value);
+        let value = decode_split::<u8>(&[], &[7], Endian::Big).unwrap();
+        assert_eq!(Ok((7, 1)), value);
+        let needed = decode_split::<u64>(&[0; 3], &[0; 4], Endian::Big).unwrap();
+        assert_eq!(Err(Needed::Unknown), needed);
+    }
+}
@@ -0,0 +1,168 @@
+//! Async primitive reads and writes for [`embedded_io_async`] readers and writers
+//!
+//! The async counterpart of [`embedded`](crate::embedded): the traits build on
+//! [`embedded_io_async::Read`] and [`embedded_io_async::Write`] and keep their error types.
+
+use core::future::Future;
+use embedded_io_async::{Read, ReadExactError, Write};
+
+macro_rules! impl_embedded_async_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`] errors"]
+            fn [<read_ $order _ $type>](&mut self) -> impl Future<Output = Result<$type, ReadExactError<Self::Error>>> {
+                async move {
+                    let mut bytes = [0u8; core::mem::size_of::<$type>()];
+                    self.read_exact(&mut bytes).await?;
+                    Ok($type::[<from_ $order _bytes>](bytes))
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_embedded_async_write_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Write " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Write::write_all`] errors"]
+            fn [<write_ $order _ $type>](&mut self, value: $type) -> impl Future<Output = Result<(), Self::Error>> {
+                async move { self.write_all(&value.[<to_ $order _bytes>]()).await }
+            }
+        }
+    };
+}
+
+macro_rules! impl_embedded_async_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to asynchronously read " $type " from an [`embedded_io_async::Read`]."]
+                pub trait [<Read $type:camel>]: Read {
+                    impl_embedded_async_methods!($type, ne, "native byte order");
+                    impl_embedded_async_methods!($type, le, "little endian byte order");
+                    impl_embedded_async_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Read + ?Sized> [<Read $type:camel>] for T {}
+
+                #[doc = "Trait to asynchronously write " $type " to an [`embedded_io_async::Write`]."]
+                pub trait [<Write $type:camel>]: Write {
+                    impl_embedded_async_write_methods!($type, ne, "native byte order");
+                    impl_embedded_async_write_methods!($type, le, "little endian byte order");
+                    impl_embedded_async_write_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Write + ?Sized> [<Write $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+impl_embedded_async_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// Trait to asynchronously read u8 from an [`embedded_io_async::Read`]
+pub trait ReadU8: Read {
+    /// Read a u8
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_u8(&mut self) -> impl Future<Output = Result<u8, ReadExactError<Self::Error>>> {
+        async move {
+            let mut bytes = [0u8; 1];
+            self.read_exact(&mut bytes).await?;
+            Ok(bytes[0])
+        }
+    }
+}
+impl<R: Read + ?Sized> ReadU8 for R {}
+
+/// Trait to asynchronously read char from an [`embedded_io_async::Read`]
+pub trait ReadChar: Read {
+    /// Read a char stored as a u32 in native byte order, or `None` if it is not a valid char
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_char(
+        &mut self,
+    ) -> impl Future<Output = Result<Option<char>, ReadExactError<Self::Error>>> {
+        async move {
+            let mut bytes = [0u8; 4];
+            self.read_exact(&mut bytes).await?;
+            Ok(char::from_u32(u32::from_ne_bytes(bytes)))
+        }
+    }
+}
+impl<R: Read + ?Sized> ReadChar for R {}
+
+/// Trait to asynchronously read bool from an [`embedded_io_async::Read`]
+pub trait ReadBool: Read {
+    /// Read a bool
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`] errors
+    fn read_bool(&mut self) -> impl Future<Output = Result<bool, ReadExactError<Self::Error>>> {
+        async move { self.read_u8().await.map(|byte| byte != 0) }
+    }
+}
+impl<R: Read + ?Sized> ReadBool for R {}
+
+/// Trait to asynchronously write u8 to an [`embedded_io_async::Write`]
+pub trait WriteU8: Write {
+    /// Write a u8
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_u8(&mut self, value: u8) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.write_all(&[value]).await }
+    }
+}
+impl<W: Write + ?Sized> WriteU8 for W {}
+
+/// Trait to asynchronously write char to an [`embedded_io_async::Write`]
+pub trait WriteChar: Write {
+    /// Write a char as a u32 in native byte order
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_char(&mut self, value: char) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.write_all(&u32::from(value).to_ne_bytes()).await }
+    }
+}
+impl<W: Write + ?Sized> WriteChar for W {}
+
+/// Trait to asynchronously write bool to an [`embedded_io_async::Write`]
+pub trait WriteBool: Write {
+    /// Write a bool as a single byte
+    /// # Errors
+    /// errors exactly when [`Write::write_all`] errors
+    fn write_bool(&mut self, value: bool) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.write_all(&[u8::from(value)]).await }
+    }
+}
+impl<W: Write + ?Sized> WriteBool for W {}
+
+#[cfg(test)]
+mod test {
+    use crate::embedded_async::*;
+
+    #[test]
+    fn round_trip() {
+        futures::executor::block_on(async {
+            let mut buf = [0u8; 12];
+            let mut writer = buf.as_mut_slice();
+            writer.write_be_u16(0x1234).await.unwrap();
+            writer.write_le_i32(-5).await.unwrap();
+            writer.write_u8(9).await.unwrap();
+            writer.write_bool(true).await.unwrap();
+            writer.write_char('é').await.unwrap();
+
+            let mut reader = buf.as_slice();
+            assert_eq!(0x1234, reader.read_be_u16().await.unwrap());
+            assert_eq!(-5, reader.read_le_i32().await.unwrap());
+            assert_eq!(9, reader.read_u8().await.unwrap());
+            assert!(reader.read_bool().await.unwrap());
+            assert_eq!(Some('é'), reader.read_char().await.unwrap());
+            assert_eq!(
+                ReadExactError::UnexpectedEof,
+                reader.read_u8().await.unwrap_err()
+            );
+        });
+    }
+}
@@ -0,0 +1,199 @@
+use crate::string::read_exact_vec;
+use encoding_rs::{DecoderResult, Encoding};
+use std::io::{self, Read};
+
+fn decode(bytes: &[u8], encoding: &'static Encoding) -> io::Result<String> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(String::from)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {} text", encoding.name()),
+            )
+        })
+}
+
+/// Check that `encoding` encodes ASCII as itself, and so never has a NUL byte within a
+/// multi-byte character
+fn check_ascii_compatible(encoding: &'static Encoding) -> io::Result<()> {
+    if encoding.is_ascii_compatible() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not ASCII-compatible", encoding.name()),
+        ))
+    }
+}
+
+/// Trait to read strings in legacy text encodings, decoded with [`encoding_rs`]
+///
+/// Decoding never sniffs a byte order mark, so the field decodes with the given encoding only.
+///
+/// ```
+/// use read_primitives::ReadEncoded;
+/// use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+/// let mut file: &[u8] = b"caf\xe9\0\0\0\x93\xfa\x96\x7b;";
+/// assert_eq!("café", file.read_encoded_name_field(7, WINDOWS_1252).unwrap());
+/// assert_eq!("日本", file.read_encoded_until(b';', 16, SHIFT_JIS).unwrap());
+/// ```
+pub trait ReadEncoded: Read {
+    /// Read a string of `len` bytes in `encoding`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are malformed in `encoding`,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_encoded_string(
+        &mut self,
+        len: usize,
+        encoding: &'static Encoding,
+    ) -> io::Result<String> {
+        decode(&read_exact_vec(self, len)?, encoding)
+    }
+
+    /// Read a string of `len` bytes in `encoding`, replacing malformed sequences with
+    /// [`char::REPLACEMENT_CHARACTER`]
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_encoded_string_lossy(
+        &mut self,
+        len: usize,
+        encoding: &'static Encoding,
+    ) -> io::Result<String> {
+        let bytes = read_exact_vec(self, len)?;
+        Ok(encoding.decode_without_bom_handling(&bytes).0.into_owned())
+    }
+
+    /// Read a fixed-size name field of `len` bytes in `encoding`, ending at the first NUL or
+    /// filling the field
+    ///
+    /// Unlike [`ReadString::read_name_field`](crate::ReadString::read_name_field), the bytes after
+    /// the terminator are ignored, as legacy writers often left garbage there.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `encoding` is not ASCII-compatible, like
+    /// UTF-16, with [`io::ErrorKind::InvalidData`] if the name is malformed in `encoding`,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_encoded_name_field(
+        &mut self,
+        len: usize,
+        encoding: &'static Encoding,
+    ) -> io::Result<String> {
+        check_ascii_compatible(encoding)?;
+        let bytes = read_exact_vec(self, len)?;
+        let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(len);
+        decode(&bytes[..end], encoding)
+    }
+
+    /// Read a string in `encoding` terminated by `delim`, consuming the delimiter
+    ///
+    /// The delimiter ends the string only where it decodes as itself, not as a trail byte of a
+    /// multi-byte character, as in Shift_JIS, Big5 and GBK. The bytes are read and decoded one at
+    /// a time; wrap the reader in a
+    /// [`BufReader`](https://doc.rust-lang.org/std/io/struct.BufReader.html) for unbuffered sources.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `encoding` is not ASCII-compatible or
+    /// `delim` is not ASCII, with [`io::ErrorKind::InvalidData`] if no delimiter follows within
+    /// `max_len` bytes or the string is malformed in `encoding`, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends before the delimiter
+    fn read_encoded_until(
+        &mut self,
+        delim: u8,
+        max_len: usize,
+        encoding: &'static Encoding,
+    ) -> io::Result<String> {
+        check_ascii_compatible(encoding)?;
+        if !delim.is_ascii() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("delimiter {delim:#04x} is not ASCII"),
+            ));
+        }
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let mut string = String::new();
+        let mut len = 0;
+        let mut byte = [0u8; 1];
+        loop {
+            self.read_exact(&mut byte)?;
+            let start = string.len();
+            // a byte completes at most one character, of at most 4 bytes of UTF-8
+            string.reserve(8);
+            let (result, _) =
+                decoder.decode_to_string_without_replacement(&byte, &mut string, false);
+            if !matches!(result, DecoderResult::InputEmpty) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid {} text", encoding.name()),
+                ));
+            }
+            if string.as_bytes()[start..] == [delim] {
+                string.truncate(start);
+                return Ok(string);
+            }
+            if len == max_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no delimiter within {max_len} bytes"),
+                ));
+            }
+            len += 1;
+        }
+    }
+}
+impl<R> ReadEncoded for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use encoding_rs::{ISO_8859_2, SHIFT_JIS, UTF_16LE, WINDOWS_1252};
+    use std::io;
+
+    #[test]
+    fn fixed_length() {
+        let mut reader: &[u8] = b"\xa3\xf3d\xbc\x82\xff\xfe";
+        assert_eq!("Łódź", reader.read_encoded_string(4, ISO_8859_2).unwrap());
+        assert_eq!(3, reader.len());
+        let mut copy = reader;
+        let err = copy.read_encoded_string(3, SHIFT_JIS).unwrap_err();
+        assert_eq!("invalid Shift_JIS text", err.to_string());
+        assert_eq!(
+            "\u{fffd}",
+            reader.read_encoded_string_lossy(1, SHIFT_JIS).unwrap()
+        );
+        assert_eq!("\u{feff}", reader.read_encoded_string(2, UTF_16LE).unwrap());
+        let err = reader.read_encoded_string(1, WINDOWS_1252).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn terminated() {
+        let mut reader: &[u8] = b"\x80 \0\xff\xffabc;";
+        let name = reader.read_encoded_name_field(5, WINDOWS_1252).unwrap();
+        assert_eq!("€ ", name);
+        let mut copy = reader;
+        let err = copy.read_encoded_until(b';', 2, WINDOWS_1252).unwrap_err();
+        assert_eq!("no delimiter within 2 bytes", err.to_string());
+        assert_eq!(
+            "abc",
+            reader.read_encoded_until(b';', 3, WINDOWS_1252).unwrap()
+        );
+        let err = reader.read_encoded_until(0, 3, UTF_16LE).unwrap_err();
+        assert_eq!("UTF-16LE is not ASCII-compatible", err.to_string());
+        let err = reader.read_encoded_until(0xff, 3, SHIFT_JIS).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn delimiter_in_trail_byte() {
+        // the trail byte of katakana so is a backslash
+        let mut reader: &[u8] = b"\x83\\\\x";
+        assert_eq!(
+            "ソ",
+            reader.read_encoded_until(b'\\', 2, SHIFT_JIS).unwrap()
+        );
+        assert_eq!(b"x", reader);
+        let err = (&b"\x83;"[..])
+            .read_encoded_until(b';', 2, SHIFT_JIS)
+            .unwrap_err();
+        assert_eq!("invalid Shift_JIS text", err.to_string());
+    }
+}
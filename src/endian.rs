@@ -0,0 +1,143 @@
+/// Byte order chosen at runtime
+///
+/// For layouts described by data, such as format strings or file headers, rather than fixed at
+/// compile time by the `le`/`be` method names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endian {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+impl Endian {
+    /// Byte order of the target platform
+    pub const NATIVE: Endian = if cfg!(target_endian = "little") {
+        Endian::Little
+    } else {
+        Endian::Big
+    };
+}
+
+/// Byte order in which the 4 byte `magic` is written at the start of `probe`, for formats that
+/// take their byte order from the writer without a flag for it, like pcap captures
+///
+/// Big endian is tried first, so it is returned for a magic whose bytes read the same in both
+/// orders, which can't tell them apart. `None` if `probe` is shorter or starts with neither form.
+///
+/// ```
+/// use read_primitives::{detect_endianness, Endian};
+/// let pcap = [0xd4, 0xc3, 0xb2, 0xa1, 2, 0];
+/// assert_eq!(Some(Endian::Little), detect_endianness(&pcap, 0xa1b2_c3d4));
+/// assert_eq!(None, detect_endianness(&pcap[1..], 0xa1b2_c3d4));
+/// ```
+pub fn detect_endianness(probe: &[u8], magic: u32) -> Option<Endian> {
+    let bytes = probe.first_chunk::<4>()?;
+    if *bytes == magic.to_be_bytes() {
+        Some(Endian::Big)
+    } else if *bytes == magic.to_le_bytes() {
+        Some(Endian::Little)
+    } else {
+        None
+    }
+}
+
+/// Order of the words of a value split into machine words, independent of the byte order within
+/// each word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordOrder {
+    /// Most significant word first
+    HiLo,
+    /// Least significant word first
+    LoHi,
+}
+
+/// Byte layout of a value split into words: the size of the words, the byte order within each
+/// word and the order of the words, for legacy formats storing values word-swapped or
+/// byte-swapped within words
+///
+/// The constants name the layouts of 16-bit words the way PLC documentation does, with the
+/// bytes of a 32-bit value `ABCD` from most significant.
+///
+/// ```
+/// use read_primitives::{Endian, MixedEndian, ReadMixedEndian, WordOrder};
+/// let mut bytes: &[u8] = &[0xbb, 0xaa, 0xdd, 0xcc];
+/// assert_eq!(0xaabb_ccdd, bytes.read_mixed_u32(MixedEndian::BADC).unwrap());
+/// // a u64 as two little endian u32 words, most significant first
+/// let layout = MixedEndian::new(4, Endian::Little, WordOrder::HiLo);
+/// let mut bytes: &[u8] = &[4, 3, 2, 1, 8, 7, 6, 5];
+/// assert_eq!(0x0102_0304_0506_0708, bytes.read_mixed_u64(layout).unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MixedEndian {
+    word_size: usize,
+    endian: Endian,
+    order: WordOrder,
+}
+
+impl MixedEndian {
+    /// Plain big endian
+    pub const ABCD: MixedEndian = MixedEndian::new(2, Endian::Big, WordOrder::HiLo);
+    /// Big endian 16-bit words, least significant first
+    pub const CDAB: MixedEndian = MixedEndian::new(2, Endian::Big, WordOrder::LoHi);
+    /// Little endian 16-bit words, most significant first
+    pub const BADC: MixedEndian = MixedEndian::new(2, Endian::Little, WordOrder::HiLo);
+    /// Plain little endian
+    pub const DCBA: MixedEndian = MixedEndian::new(2, Endian::Little, WordOrder::LoHi);
+
+    /// Layout of words of `word_size` bytes in `endian` byte order, the words in `order`
+    ///
+    /// A value no larger than a word is read as a single word.
+    /// # Panics
+    /// panics if `word_size` is not 1, 2, 4 or 8
+    pub const fn new(word_size: usize, endian: Endian, order: WordOrder) -> Self {
+        assert!(
+            matches!(word_size, 1 | 2 | 4 | 8),
+            "word size is not 1, 2, 4 or 8"
+        );
+        MixedEndian {
+            word_size,
+            endian,
+            order,
+        }
+    }
+
+    /// Size of the words in bytes
+    pub const fn word_size(&self) -> usize {
+        self.word_size
+    }
+
+    /// Byte order within each word
+    pub const fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Order of the words
+    pub const fn order(&self) -> WordOrder {
+        self.order
+    }
+
+    /// Rearrange the bytes of a value stored in this layout into big endian
+    pub fn to_be_bytes<const N: usize>(&self, mut bytes: [u8; N]) -> [u8; N] {
+        let word_size = self.word_size.min(N);
+        if self.order == WordOrder::LoHi {
+            bytes.reverse();
+            if self.endian == Endian::Big {
+                bytes.chunks_mut(word_size).for_each(<[u8]>::reverse);
+            }
+        } else if self.endian == Endian::Little {
+            bytes.chunks_mut(word_size).for_each(<[u8]>::reverse);
+        }
+        bytes
+    }
+}
+
+/// Order of the bits of packed flags within each byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first flag in the most significant bit
+    MsbFirst,
+    /// The first flag in the least significant bit
+    LsbFirst,
+}
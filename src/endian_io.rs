@@ -0,0 +1,216 @@
+use crate::{Endian, ReadFrom, ReadPrimitive, WriteTo};
+use std::io::{self, Read, Write};
+
+macro_rules! impl_endian_methods {
+    ($($type:ty),+) => {
+        impl<R: Read> EndianReader<R> {
+            $(
+                ::paste::paste! {
+                    #[doc = "Read " $type " in the reader's byte order"]
+                    #[doc = "# Errors"]
+                    #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                    pub fn [<read_ $type>](&mut self) -> io::Result<$type> {
+                        self.inner.read_endian(self.endian)
+                    }
+                }
+            )+
+        }
+
+        impl<W: Write> EndianWriter<W> {
+            $(
+                ::paste::paste! {
+                    #[doc = "Write " $type " in the writer's byte order"]
+                    #[doc = "# Errors"]
+                    #[doc = "errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors"]
+                    pub fn [<write_ $type>](&mut self, value: $type) -> io::Result<()> {
+                        match self.endian {
+                            Endian::Little => self.inner.write_all(&value.to_le_bytes()),
+                            Endian::Big => self.inner.write_all(&value.to_be_bytes()),
+                        }
+                    }
+                }
+            )+
+        }
+    };
+}
+
+/// Reader with a byte order fixed at construction
+///
+/// For files whose byte order is only known at runtime, for example from a header. The reader
+/// still implements [`Read`], so the other extension traits can be used on it.
+///
+/// ```
+/// use read_primitives::{Endian, EndianReader};
+/// let mut reader = EndianReader::new(&[0u8, 1, 0, 0, 0, 2][..], Endian::Big);
+/// assert_eq!(1, reader.read_u16().unwrap());
+/// assert_eq!(2, reader.read_u32().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct EndianReader<R> {
+    inner: R,
+    endian: Endian,
+}
+
+impl<R> EndianReader<R> {
+    /// Wrap `inner`, reading numbers in `endian` byte order
+    pub fn new(inner: R, endian: Endian) -> Self {
+        EndianReader { inner, endian }
+    }
+
+    /// Byte order of the reads
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped reader
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> EndianReader<R> {
+    /// Read a composite value, such as a struct deriving [`ReadFrom`], handing the reader's byte
+    /// order down to every part that does not specify its own
+    /// # Errors
+    /// errors like [`ReadFrom::read_from`]
+    pub fn read_value<T: ReadFrom>(&mut self) -> io::Result<T> {
+        T::read_from(&mut self.inner, self.endian)
+    }
+
+    /// Read `count` values with [`EndianReader::read_value`]
+    /// # Errors
+    /// errors like [`ReadFrom::read_from`]
+    pub fn read_values<T: ReadFrom>(&mut self, count: usize) -> io::Result<Vec<T>> {
+        crate::combinator::repeat_n(self, count, |reader| reader.read_value())
+    }
+}
+
+impl<R: Read> Read for EndianReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+/// Writer with a byte order fixed at construction, mirroring [`EndianReader`]
+///
+/// ```
+/// use read_primitives::{Endian, EndianWriter};
+/// let mut writer = EndianWriter::new(Vec::new(), Endian::Little);
+/// writer.write_u16(1).unwrap();
+/// writer.write_f32(1.0).unwrap();
+/// assert_eq!([1, 0, 0, 0, 0x80, 0x3f], writer.into_inner().as_slice());
+/// ```
+#[derive(Debug)]
+pub struct EndianWriter<W> {
+    inner: W,
+    endian: Endian,
+}
+
+impl<W> EndianWriter<W> {
+    /// Wrap `inner`, writing numbers in `endian` byte order
+    pub fn new(inner: W, endian: Endian) -> Self {
+        EndianWriter { inner, endian }
+    }
+
+    /// Byte order of the writes
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Borrow the wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> EndianWriter<W> {
+    /// Write a composite value, such as a struct deriving [`WriteTo`], in the writer's byte order
+    /// # Errors
+    /// errors like [`WriteTo::write_to`]
+    pub fn write_value<T: WriteTo + ?Sized>(&mut self, value: &T) -> io::Result<()> {
+        value.write_to(&mut self.inner, self.endian)
+    }
+}
+
+impl<W: Write> Write for EndianWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl_endian_methods!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    macro_rules! impl_tests {
+        ($($type:ty),+) => {
+            $(
+                ::paste::paste! {
+                    #[test]
+                    fn [<endian_ $type>]() {
+                        for endian in [Endian::Little, Endian::Big] {
+                            let mut writer = EndianWriter::new(Vec::new(), endian);
+                            writer.[<write_ $type>]([<37 $type>]).unwrap();
+                            let bytes = writer.into_inner();
+                            let mut reader = EndianReader::new(bytes.as_slice(), endian);
+                            assert_eq!([<37 $type>], reader.[<read_ $type>]().unwrap());
+                            assert!(reader.into_inner().is_empty());
+                        }
+                    }
+                }
+            )+
+        };
+    }
+    impl_tests!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
+    #[test]
+    fn byte_order() {
+        let mut writer = EndianWriter::new(Vec::new(), Endian::Big);
+        writer.write_u32(0x0102_0304).unwrap();
+        writer.write_le_u16(5).unwrap();
+        assert_eq!([1, 2, 3, 4, 5, 0], writer.get_ref().as_slice());
+        let mut reader = EndianReader::new(&[1u8, 2, 3, 4][..], Endian::Little);
+        assert_eq!(Endian::Little, reader.endian());
+        assert_eq!(0x0201, reader.read_u16().unwrap());
+        assert_eq!(0x0304, reader.read_be_u16().unwrap());
+    }
+
+    #[test]
+    fn composite_values() {
+        let mut writer = EndianWriter::new(Vec::new(), Endian::Big);
+        writer.write_value(&(1u16, [2u32])).unwrap();
+        writer.write_value(&[3u16, 4][..]).unwrap();
+        assert_eq!([0, 1, 0, 0, 0, 2, 0, 3, 0, 4], writer.get_ref().as_slice());
+        let bytes = writer.into_inner();
+        let mut reader = EndianReader::new(bytes.as_slice(), Endian::Big);
+        assert_eq!((1, [2]), reader.read_value::<(u16, [u32; 1])>().unwrap());
+        assert_eq!(vec![3, 4], reader.read_values::<u16>(2).unwrap());
+        assert!(reader.read_values::<u16>(1).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{self, Read};
+
+/// Trait to check that a reader has been fully consumed
+pub trait EnsureEof: Read {
+    /// Check that no bytes remain
+    ///
+    /// Any remaining bytes are consumed in order to count them.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] reporting the number of trailing bytes if the reader
+    /// is not at its end, or when [`Read::read`](https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read) errors
+    fn ensure_eof(&mut self) -> io::Result<()> {
+        match io::copy(self, &mut io::sink())? {
+            0 => Ok(()),
+            trailing => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{trailing} trailing bytes"),
+            )),
+        }
+    }
+}
+impl<R> EnsureEof for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn ensure_eof() {
+        let mut empty: &[u8] = &[];
+        empty.ensure_eof().unwrap();
+
+        let mut bytes = [1u8, 2, 3].as_slice();
+        bytes.read_u8().unwrap();
+        let err = bytes.ensure_eof().unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("2 trailing bytes", err.to_string());
+    }
+}
@@ -0,0 +1,315 @@
+#[cfg(feature = "error-context")]
+use crate::Endian;
+use core::fmt;
+use core::num::NonZeroUsize;
+#[cfg(feature = "std")]
+use std::io;
+
+/// How much more input a streaming parse needs, as reported by [`ParseError::Incomplete`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Needed {
+    /// More input is needed, but not how much
+    Unknown,
+    /// At least this many more bytes are needed
+    Size(NonZeroUsize),
+}
+
+/// Error returned by the slice-based readers and parse functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input ended before a value could be read
+    UnexpectedEnd {
+        /// Number of bytes the read needed
+        needed: usize,
+        /// Number of bytes that were left
+        remaining: usize,
+        /// Offset at which the read started
+        offset: usize,
+    },
+    /// Bytes were left over after the input should have been fully consumed
+    TrailingBytes {
+        /// Number of unread bytes
+        count: usize,
+        /// Offset of the first unread byte
+        offset: usize,
+    },
+    /// The input ended before a value could be read, but more of it may still arrive
+    ///
+    /// Returned instead of [`ParseError::UnexpectedEnd`] by readers created with
+    /// [`SliceReader::streaming`](crate::SliceReader::streaming), and by
+    /// [`ParseError::into_streaming`].
+    Incomplete(Needed),
+    /// A typed view started at an offset that is not aligned for its element type
+    Misaligned {
+        /// Alignment the element type needs
+        align: usize,
+        /// Offset of the view
+        offset: usize,
+    },
+    /// A count exceeded the capacity of the fixed-capacity collection read into
+    CapacityExceeded {
+        /// Number of values to read
+        count: usize,
+        /// Capacity of the collection
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd {
+                needed,
+                remaining,
+                offset,
+            } => write!(
+                f,
+                "needed {needed} bytes, {remaining} remaining at offset {offset:#x}"
+            ),
+            ParseError::TrailingBytes { count, offset } => {
+                write!(f, "{count} trailing bytes at offset {offset:#x}")
+            }
+            ParseError::Incomplete(Needed::Unknown) => write!(f, "incomplete input"),
+            ParseError::Incomplete(Needed::Size(size)) => {
+                write!(f, "incomplete input, needed {size} more bytes")
+            }
+            ParseError::Misaligned { align, offset } => {
+                write!(f, "offset {offset:#x} is not aligned to {align} bytes")
+            }
+            ParseError::CapacityExceeded { count, capacity } => {
+                write!(f, "count {count} exceeds capacity {capacity}")
+            }
+        }
+    }
+}
+
+impl ParseError {
+    /// How many more bytes the failed read needed, if it failed because the input ended
+    pub fn needed(&self) -> Option<Needed> {
+        match *self {
+            ParseError::UnexpectedEnd {
+                needed, remaining, ..
+            } => Some(match NonZeroUsize::new(needed.saturating_sub(remaining)) {
+                Some(size) => Needed::Size(size),
+                None => Needed::Unknown,
+            }),
+            ParseError::Incomplete(needed) => Some(needed),
+            _ => None,
+        }
+    }
+
+    /// Turn the end of the input into [`ParseError::Incomplete`], for parsing data that is
+    /// still arriving with the [`parse`](crate::parse) functions
+    ///
+    /// ```
+    /// use read_primitives::{parse::parse_be_u32, Needed, ParseError};
+    /// let err = parse_be_u32(&[0, 0, 1]).map_err(ParseError::into_streaming).unwrap_err();
+    /// assert_eq!(ParseError::Incomplete(Needed::Size(1.try_into().unwrap())), err);
+    /// ```
+    pub fn into_streaming(self) -> Self {
+        match self.needed() {
+            Some(needed) => ParseError::Incomplete(needed),
+            None => self,
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+#[cfg(feature = "std")]
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> Self {
+        let kind = match err {
+            ParseError::UnexpectedEnd { .. } | ParseError::Incomplete(_) => {
+                io::ErrorKind::UnexpectedEof
+            }
+            ParseError::TrailingBytes { .. }
+            | ParseError::Misaligned { .. }
+            | ParseError::CapacityExceeded { .. } => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+/// Error payload naming the primitive whose read failed, added with the `error-context` feature
+///
+/// The [`io::Error`] returned by the primitive reads keeps the kind of the underlying error and
+/// carries this as its inner error, so that logs say what was being decoded.
+///
+/// ```
+/// use read_primitives::{Endian, PrimitiveError, ReadU64};
+/// let err = [0u8; 3].as_slice().read_be_u64().unwrap_err();
+/// assert_eq!("failed reading be u64: failed to fill whole buffer", err.to_string());
+/// let context = err.get_ref().unwrap().downcast_ref::<PrimitiveError>().unwrap();
+/// assert_eq!(("u64", Some(Endian::Big)), (context.type_name(), context.endian()));
+/// ```
+#[cfg(feature = "error-context")]
+#[derive(Debug)]
+pub struct PrimitiveError {
+    type_name: &'static str,
+    endian: Option<Endian>,
+    source: io::Error,
+}
+
+#[cfg(feature = "error-context")]
+impl PrimitiveError {
+    /// Name of the type that was being read
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Byte order of the read, `None` for single bytes
+    pub fn endian(&self) -> Option<Endian> {
+        self.endian
+    }
+}
+
+#[cfg(feature = "error-context")]
+impl fmt::Display for PrimitiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.endian {
+            Some(Endian::Little) => write!(f, "failed reading le {}", self.type_name)?,
+            Some(Endian::Big) => write!(f, "failed reading be {}", self.type_name)?,
+            None => write!(f, "failed reading {}", self.type_name)?,
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+#[cfg(feature = "error-context")]
+impl std::error::Error for PrimitiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Error of [`ReadU8::read_to_vec_limited`](crate::ReadU8::read_to_vec_limited) when the reader
+/// holds more than the limit, carried inside an [`io::ErrorKind::InvalidData`] error
+///
+/// Keeps the bytes read before giving up, for callers that want to salvage them.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TooLongError {
+    max_len: usize,
+    partial: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl TooLongError {
+    pub(crate) fn new(max_len: usize, partial: Vec<u8>) -> Self {
+        TooLongError { max_len, partial }
+    }
+
+    /// The limit that was exceeded
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// The first `max_len` bytes of the reader
+    pub fn partial(&self) -> &[u8] {
+        &self.partial
+    }
+
+    /// Take the first `max_len` bytes of the reader
+    pub fn into_partial(self) -> Vec<u8> {
+        self.partial
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reader holds more than {} bytes", self.max_len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooLongError {}
+
+/// Error of the `read_*_as` reads of [`ReadPrimitive`](crate::ReadPrimitive) when the stored
+/// value doesn't convert to the target type, carried inside an [`io::ErrorKind::InvalidData`]
+/// error
+///
+/// ```
+/// use read_primitives::{ConversionError, ReadPrimitive};
+/// let err = [0, 0, 1, 0].as_slice().read_le_as::<u32, u16>().unwrap_err();
+/// assert_eq!("stored value 65536 is out of range for u16", err.to_string());
+/// let inner = err.get_ref().unwrap().downcast_ref::<ConversionError<u32>>().unwrap();
+/// assert_eq!(65536, *inner.value());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConversionError<S> {
+    value: S,
+    target: &'static str,
+}
+
+#[cfg(feature = "std")]
+impl<S> ConversionError<S> {
+    pub(crate) fn new<T>(value: S) -> Self {
+        ConversionError {
+            value,
+            target: core::any::type_name::<T>(),
+        }
+    }
+
+    /// The value as it was stored
+    pub fn value(&self) -> &S {
+        &self.value
+    }
+
+    /// Name of the type the value didn't convert to
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug> fmt::Display for ConversionError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stored value {:?} is out of range for {}",
+            self.value, self.target
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug> std::error::Error for ConversionError<S> {}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug + Send + Sync + 'static> From<ConversionError<S>> for io::Error {
+    fn from(err: ConversionError<S>) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Attach the type and byte order (`"ne"`, `"le"`, `"be"` or `""`) of a failed read to `err`
+/// if the `error-context` feature is enabled
+#[cfg(feature = "std")]
+pub(crate) fn context(err: io::Error, type_name: &'static str, order: &'static str) -> io::Error {
+    #[cfg(feature = "error-context")]
+    {
+        let endian = match order {
+            "le" => Some(Endian::Little),
+            "be" => Some(Endian::Big),
+            "ne" => Some(Endian::NATIVE),
+            _ => None,
+        };
+        io::Error::new(
+            err.kind(),
+            PrimitiveError {
+                type_name,
+                endian,
+                source: err,
+            },
+        )
+    }
+    #[cfg(not(feature = "error-context"))]
+    {
+        let _ = (type_name, order);
+        err
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{self, Read};
+
+/// Size of the stack buffer expected bytes are compared through
+const CHUNK_LEN: usize = 64;
+
+/// Read `expected.len()` bytes, comparing them with `expected` on the stack, and return the
+/// position and byte of the first difference
+fn first_mismatch<R: Read + ?Sized>(
+    reader: &mut R,
+    expected: &[u8],
+) -> io::Result<Option<(usize, u8)>> {
+    let mut buf = [0u8; CHUNK_LEN];
+    let mut mismatch = None;
+    for (index, chunk) in expected.chunks(CHUNK_LEN).enumerate() {
+        let found = &mut buf[..chunk.len()];
+        reader.read_exact(found)?;
+        if mismatch.is_none() {
+            mismatch = chunk
+                .iter()
+                .zip(&*found)
+                .position(|(expected, found)| expected != found)
+                .map(|offset| (index * CHUNK_LEN + offset, found[offset]));
+        }
+    }
+    Ok(mismatch)
+}
+
+/// Trait to compare incoming bytes with an expected constant, like a header or keyword, without
+/// allocating
+///
+/// Both reads consume exactly as many bytes as expected, even after a difference, so the reader
+/// stays at the same place whether or not the bytes match.
+///
+/// ```
+/// use read_primitives::ReadExpect;
+/// let mut file: &[u8] = b"HEADERv1";
+/// let err = file.expect_str("HEADERv2").unwrap_err();
+/// assert_eq!("expected b\"HEADERv2\", found b'1' instead of b'2' at byte 7", err.to_string());
+/// ```
+pub trait ReadExpect: Read {
+    /// Read bytes matching `expected`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] naming the position and bytes of the first
+    /// difference if the bytes don't match, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn expect_bytes(&mut self, expected: &[u8]) -> io::Result<()> {
+        match first_mismatch(self, expected)? {
+            None => Ok(()),
+            Some((position, found)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected b\"{}\", found b'{}' instead of b'{}' at byte {position}",
+                    expected.escape_ascii(),
+                    found.escape_ascii(),
+                    expected[position].escape_ascii()
+                ),
+            )),
+        }
+    }
+
+    /// Read the bytes of `expected`
+    /// # Errors
+    /// errors like [`ReadExpect::expect_bytes`]
+    fn expect_str(&mut self, expected: &str) -> io::Result<()> {
+        self.expect_bytes(expected.as_bytes())
+    }
+
+    /// Read `expected.len()` bytes, returning whether they match `expected`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_matches(&mut self, expected: &[u8]) -> io::Result<bool> {
+        first_mismatch(self, expected).map(|mismatch| mismatch.is_none())
+    }
+}
+impl<R> ReadExpect for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn expect() {
+        let mut bytes: &[u8] = b"%PDF-1.7\n";
+        bytes.expect_str("%PDF-").unwrap();
+        assert!(!bytes.read_matches(b"2.0").unwrap());
+        assert!(bytes.read_matches(b"\n").unwrap());
+        assert!(bytes.read_matches(b"").unwrap());
+        let err = bytes.expect_bytes(b"x").unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn mismatch_past_first_chunk() {
+        let expected = [b'a'; 100];
+        let mut found = expected;
+        found[70] = 0;
+        found[90] = b'b';
+        let mut reader = &found[..];
+        let err = reader.expect_bytes(&expected).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err
+            .to_string()
+            .ends_with(", found b'\\x00' instead of b'a' at byte 70"));
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{Endian, ReadBorrowed, ReadFrom, PREALLOC_LIMIT};
+use std::io::{self, Read};
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A [`ReadFrom`] type whose encoding always takes the same number of bytes
+///
+/// Implemented for the integer and float types, bool, arrays and tuples. With the `derive`
+/// feature, `#[read(fixed_size)]` on a struct deriving `ReadFrom` implements it from the sizes of
+/// the fields, magic and padding.
+///
+/// ```
+/// use read_primitives::{Endian, FixedSize};
+/// assert_eq!(10, <(u16, [u32; 2])>::SIZE);
+/// let mut bytes: &[u8] = &[0, 1, 0, 2, 0, 3];
+/// let values = u16::read_batch(&mut bytes, Endian::Big, 3).unwrap();
+/// assert_eq!(vec![1, 2, 3], values);
+/// ```
+pub trait FixedSize: ReadFrom {
+    /// Size of an encoded value in bytes
+    const SIZE: usize;
+
+    /// Read `count` values with a single read of `count * SIZE` bytes, then decode them
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `count * SIZE` overflows `usize`,
+    /// with [`io::ErrorKind::UnexpectedEof`] if the reader ends early, or if decoding a value
+    /// errors
+    fn read_batch<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Vec<Self>> {
+        let bytes = crate::string::read_exact_vec(reader, batch_len::<Self>(count)?)?;
+        decode_batch(&bytes, endian, count)
+    }
+
+    /// Read `count` values like [`FixedSize::read_batch`] into a boxed slice, allocated once
+    /// the bytes of every value have arrived
+    /// # Errors
+    /// errors like [`FixedSize::read_batch`]
+    fn read_boxed_slice<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Box<[Self]>> {
+        Self::read_batch(reader, endian, count).map(Vec::into_boxed_slice)
+    }
+
+    /// Read `count` values into a boxed slice, decoding them straight from bytes borrowed from
+    /// the source, without copying them first
+    ///
+    /// ```
+    /// use read_primitives::{Endian, FixedSize};
+    /// let mut table: &[u8] = &[0, 1, 0, 10, 0, 2, 0, 20, 0xff];
+    /// let rows = <(u16, u16)>::read_boxed_slice_borrowed(&mut table, Endian::Big, 2).unwrap();
+    /// assert_eq!([(1, 10), (2, 20)], *rows);
+    /// assert_eq!([0xff], table);
+    /// ```
+    /// # Errors
+    /// errors like [`FixedSize::read_batch`]
+    fn read_boxed_slice_borrowed<'a, R: ReadBorrowed<'a> + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Box<[Self]>> {
+        let bytes = reader.read_borrowed_bytes(batch_len::<Self>(count)?)?;
+        decode_batch(bytes, endian, count).map(Vec::into_boxed_slice)
+    }
+
+    /// Read `count` values like [`FixedSize::read_batch`] into an `Arc<[Self]>` to share
+    /// between threads, decoding them straight into the shared allocation
+    ///
+    /// ```
+    /// use read_primitives::{Endian, FixedSize};
+    /// let mut bytes: &[u8] = &[1, 0, 2, 0];
+    /// let shared = u16::read_arc_slice(&mut bytes, Endian::Little, 2).unwrap();
+    /// let worker = std::thread::spawn({
+    ///     let shared = shared.clone();
+    ///     move || shared.iter().sum::<u16>()
+    /// });
+    /// assert_eq!(3, worker.join().unwrap());
+    /// ```
+    /// # Errors
+    /// errors like [`FixedSize::read_batch`]
+    fn read_arc_slice<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Arc<[Self]>> {
+        let bytes = crate::string::read_exact_vec(reader, batch_len::<Self>(count)?)?;
+        let mut values = Arc::new_uninit_slice(count);
+        let slots = Arc::get_mut(&mut values).expect("a new Arc is unique");
+        decode_uninit(&bytes, endian, slots)?;
+        // SAFETY: decode_uninit initialized every slot
+        Ok(unsafe { values.assume_init() })
+    }
+
+    /// Read `count` values like [`FixedSize::read_arc_slice`] into an `Rc<[Self]>`
+    /// # Errors
+    /// errors like [`FixedSize::read_batch`]
+    fn read_rc_slice<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Rc<[Self]>> {
+        let bytes = crate::string::read_exact_vec(reader, batch_len::<Self>(count)?)?;
+        let mut values = Rc::new_uninit_slice(count);
+        let slots = Rc::get_mut(&mut values).expect("a new Rc is unique");
+        decode_uninit(&bytes, endian, slots)?;
+        // SAFETY: decode_uninit initialized every slot
+        Ok(unsafe { values.assume_init() })
+    }
+}
+
+/// Bytes of `count` values of `T`
+pub(crate) fn batch_len<T: FixedSize>(count: usize) -> io::Result<usize> {
+    count
+        .checked_mul(T::SIZE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "batch size overflows usize"))
+}
+
+/// Decode a value of `T` from `bytes` into each of `slots`, all of them unless it errors
+///
+/// The values decoded before an error are leaked, not dropped.
+fn decode_uninit<T: FixedSize>(
+    mut bytes: &[u8],
+    endian: Endian,
+    slots: &mut [MaybeUninit<T>],
+) -> io::Result<()> {
+    for slot in slots {
+        slot.write(T::read_from(&mut bytes, endian)?);
+    }
+    Ok(())
+}
+
+/// Decode `count` values of `T` from `bytes`, into a `Vec` of exactly that capacity
+pub(crate) fn decode_batch<T: FixedSize>(
+    mut bytes: &[u8],
+    endian: Endian,
+    count: usize,
+) -> io::Result<Vec<T>> {
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        values.push(T::read_from(&mut bytes, endian)?);
+    }
+    Ok(values)
+}
+
+/// Number of bytes a value takes when encoded, for types whose size depends on the value
+///
+/// Implemented for every [`FixedSize`] type, slices, `Vec`s, `str` and `String`, whose bytes are
+/// counted without a length prefix or terminator.
+///
+/// ```
+/// use read_primitives::ByteSize;
+/// assert_eq!(6, vec![1u16, 2, 3].byte_size());
+/// assert_eq!(5, "héllo"[1..].byte_size());
+/// ```
+pub trait ByteSize {
+    /// Size of the encoded value in bytes
+    fn byte_size(&self) -> usize;
+}
+
+impl<T: FixedSize> ByteSize for T {
+    fn byte_size(&self) -> usize {
+        T::SIZE
+    }
+}
+
+impl<T: ByteSize> ByteSize for [T] {
+    fn byte_size(&self) -> usize {
+        self.iter().map(ByteSize::byte_size).sum()
+    }
+}
+
+impl<T: ByteSize> ByteSize for Vec<T> {
+    fn byte_size(&self) -> usize {
+        self.as_slice().byte_size()
+    }
+}
+
+impl ByteSize for str {
+    fn byte_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl ByteSize for String {
+    fn byte_size(&self) -> usize {
+        self.len()
+    }
+}
+
+macro_rules! impl_fixed_size {
+    ($($type:ty),+) => {
+        $(
+            impl FixedSize for $type {
+                const SIZE: usize = size_of::<$type>();
+            }
+        )+
+    };
+}
+
+impl_fixed_size!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool);
+
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+}
+
+/// A tuple of [`FixedSize`] fields making up a record, whose records can be decoded into a `Vec`
+/// per field with [`read_columns`]
+pub trait Columns: FixedSize {
+    /// A `Vec` per field, in the order of the fields
+    type Vecs: Default;
+
+    /// Reserve room for `additional` more records in each `Vec`
+    fn reserve(vecs: &mut Self::Vecs, additional: usize);
+
+    /// Append each field to its `Vec`
+    fn push(self, vecs: &mut Self::Vecs);
+}
+
+macro_rules! impl_fixed_size_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl<$($type: FixedSize),+> FixedSize for ($($type,)+) {
+            const SIZE: usize = 0 $(+ $type::SIZE)+;
+        }
+
+        impl<$($type: FixedSize),+> Columns for ($($type,)+) {
+            type Vecs = ($(Vec<$type>,)+);
+
+            fn reserve(vecs: &mut Self::Vecs, additional: usize) {
+                $(vecs.$index.reserve(additional);)+
+            }
+
+            fn push(self, vecs: &mut Self::Vecs) {
+                $(vecs.$index.push(self.$index);)+
+            }
+        }
+    };
+}
+
+impl_fixed_size_tuple!(A 0);
+impl_fixed_size_tuple!(A 0, B 1);
+impl_fixed_size_tuple!(A 0, B 1, C 2);
+impl_fixed_size_tuple!(A 0, B 1, C 2, D 3);
+impl_fixed_size_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_fixed_size_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_fixed_size_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_fixed_size_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Read `count` interleaved records of the fields in the tuple `T` into a `Vec` per field, for
+/// time series and other record tables used a column at a time
+///
+/// The records are read in blocks of up to [`PREALLOC_LIMIT`] bytes, and the `Vec`s grow as they
+/// arrive, so a corrupt count can't allocate much up front. Padding between fields can be read as
+/// a `[u8; N]` column.
+///
+/// ```
+/// use read_primitives::{read_columns, Endian};
+/// let mut samples: &[u8] = &[0, 1, 0x3f, 0x80, 0, 0, 0, 2, 0x40, 0, 0, 0];
+/// let (times, values) = read_columns::<(u16, f32), _>(&mut samples, Endian::Big, 2).unwrap();
+/// assert_eq!(vec![1, 2], times);
+/// assert_eq!(vec![1.0, 2.0], values);
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early, or if decoding a field
+/// errors
+pub fn read_columns<T: Columns, R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    count: usize,
+) -> io::Result<T::Vecs> {
+    let mut vecs = T::Vecs::default();
+    read_columns_into::<T, R>(reader, endian, count, &mut vecs)?;
+    Ok(vecs)
+}
+
+/// Read `count` interleaved records like [`read_columns`], appending the fields to existing
+/// `Vec`s to reuse their allocations
+/// # Errors
+/// errors like [`read_columns`]; the records decoded before the error stay appended
+pub fn read_columns_into<T: Columns, R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    count: usize,
+    vecs: &mut T::Vecs,
+) -> io::Result<()> {
+    let block_records = (PREALLOC_LIMIT / T::SIZE.max(1)).max(1);
+    let mut block = vec![0; block_records.min(count) * T::SIZE];
+    let mut left = count;
+    while left > 0 {
+        let records = left.min(block_records);
+        let bytes = &mut block[..records * T::SIZE];
+        reader.read_exact(bytes)?;
+        T::reserve(vecs, records);
+        let mut input = &bytes[..];
+        for _ in 0..records {
+            T::read_from(&mut input, endian)?.push(vecs);
+        }
+        left -= records;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(1, <bool as FixedSize>::SIZE);
+        assert_eq!(24, <[[f64; 1]; 3]>::SIZE);
+        assert_eq!(3, <(u8, i16)>::SIZE);
+    }
+
+    #[test]
+    fn columns() {
+        let mut bytes = Vec::new();
+        for i in 0..20_000u32 {
+            bytes.extend_from_slice(&u64::from(i).to_le_bytes());
+            bytes.extend_from_slice(&[i as u8, 0xee]);
+        }
+        let (times, flags, _) =
+            read_columns::<(u64, u8, [u8; 1]), _>(&mut bytes.as_slice(), Endian::Little, 20_000)
+                .unwrap();
+        assert_eq!(20_000, times.len());
+        assert_eq!((19_999, 0x1f), (times[19_999], flags[19_999]));
+        let mut vecs = (vec![7u16], Vec::new());
+        let err = read_columns_into::<(u16, bool), _>(
+            &mut &[1, 0, 1, 2][..],
+            Endian::Little,
+            2,
+            &mut vecs,
+        )
+        .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(vec![7], vecs.0);
+        read_columns_into::<(u16, bool), _>(&mut &[1, 0, 1][..], Endian::Little, 1, &mut vecs)
+            .unwrap();
+        assert_eq!((vec![7, 1], vec![true]), vecs);
+    }
+
+    #[test]
+    fn byte_sizes() {
+        assert_eq!(8, 0u64.byte_size());
+        assert_eq!(0, Vec::<u32>::new().byte_size());
+        let nested = vec![vec![[0u8; 3]], vec![[1; 3], [2; 3]]];
+        assert_eq!(9, nested.byte_size());
+        assert_eq!(2, String::from("ab").byte_size());
+    }
+
+    #[test]
+    fn batch() {
+        let mut bytes: &[u8] = &[1, 0, 2, 2, 0, 3, 9];
+        let pairs = <(u8, u16)>::read_batch(&mut bytes, Endian::Little, 2).unwrap();
+        assert_eq!(vec![(1, 0x200), (2, 0x300)], pairs);
+        assert_eq!([9], bytes);
+        let err = u32::read_batch(&mut &[0u8; 7][..], Endian::Little, 2).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let err = u64::read_batch(&mut &[][..], Endian::Little, usize::MAX).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn boxed_slice() {
+        let bytes = [0u8, 1, 0, 2, 0, 3];
+        let boxed = u16::read_boxed_slice(&mut &bytes[..], Endian::Big, 3).unwrap();
+        assert_eq!([1, 2, 3], *boxed);
+        let mut reader = SliceReader::new(&bytes);
+        let boxed = <[u8; 2]>::read_boxed_slice_borrowed(&mut reader, Endian::Big, 2).unwrap();
+        assert_eq!([[0, 1], [0, 2]], *boxed);
+        assert_eq!(2, reader.remaining());
+        let mut short = &bytes[..5];
+        let err = u16::read_boxed_slice_borrowed(&mut short, Endian::Big, 3).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(5, short.len());
+    }
+
+    #[test]
+    fn shared_slices() {
+        let bytes = [1u8, 0, 2, 0, 3];
+        let arc = <[u8; 2]>::read_arc_slice(&mut &bytes[..], Endian::Little, 2).unwrap();
+        assert_eq!([[1, 0], [2, 0]], *arc);
+        let rc = u16::read_rc_slice(&mut &bytes[..], Endian::Little, 2).unwrap();
+        assert_eq!([1, 2], *rc);
+        assert!(u16::read_rc_slice(&mut &[][..], Endian::Little, 0)
+            .unwrap()
+            .is_empty());
+        let err = u16::read_arc_slice(&mut &bytes[..], Endian::Little, 3).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{Endian, Primitive, ReadF32, ReadF64};
+use std::io::{self, Read};
+
+macro_rules! impl_float_methods {
+    ($type:ty, $order:ident, $order_doc:literal, $nan:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc ", replacing any NaN with the quiet NaN `" $nan "`"]
+            #[doc = ""]
+            #[doc = "NaN payloads and signs differ between producers; canonical ones hash and compare the same bitwise."]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _canonical>](&mut self) -> io::Result<$type> {
+                let mut reader = self;
+                let value = reader.[<read_ $order _ $type>]()?;
+                Ok(if value.is_nan() { $type::from_bits($nan) } else { value })
+            }
+            #[doc = "Read " $type " in " $order_doc ", rejecting NaN and infinities"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidData`] if the value is not finite,"]
+            #[doc = "or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _finite>](&mut self) -> io::Result<$type> {
+                let mut reader = self;
+                let value = reader.[<read_ $order _ $type>]()?;
+                if !value.is_finite() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("expected a finite {}, found {value}", stringify!($type)),
+                    ));
+                }
+                Ok(value)
+            }
+        }
+    };
+}
+
+macro_rules! impl_float_reads {
+    ($($type:ty, $nan:literal);+) => {
+        $(
+            impl_float_methods!($type, ne, "native byte order", $nan);
+            impl_float_methods!($type, le, "little endian byte order", $nan);
+            impl_float_methods!($type, be, "big endian byte order", $nan);
+        )+
+    };
+}
+
+/// Trait to read floats with their special values normalized or rejected
+pub trait ReadFloat: Read {
+    impl_float_reads!(f32, 0x7fc0_0000; f64, 0x7ff8_0000_0000_0000);
+
+    /// Read an f64 whose two 32-bit words are swapped relative to `endian` byte order
+    ///
+    /// With [`Endian::Little`] this is the layout of the ARM FPA and some legacy middle-endian
+    /// sources: each word little endian, the most significant word first.
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_f64_word_swapped(&mut self, endian: Endian) -> io::Result<f64> {
+        let mut bytes = [0u8; 8];
+        self.read_exact(&mut bytes)?;
+        bytes.rotate_left(4);
+        Ok(f64::from_endian_bytes(bytes, endian))
+    }
+}
+impl<R> ReadFloat for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn canonical() {
+        let nans = [0xffc0_0001u32, 0x7f80_0001, 0x7fc0_0000];
+        for nan in nans {
+            let bytes = nan.to_be_bytes();
+            let value = bytes.as_slice().read_be_f32_canonical().unwrap();
+            assert_eq!(0x7fc0_0000, value.to_bits());
+        }
+        let bytes = (-1.5f64).to_le_bytes();
+        assert_eq!(-1.5, bytes.as_slice().read_le_f64_canonical().unwrap());
+        let bytes = f64::from_bits(0xfff0_0000_0000_0002).to_ne_bytes();
+        let value = bytes.as_slice().read_ne_f64_canonical().unwrap();
+        assert_eq!(0x7ff8_0000_0000_0000, value.to_bits());
+    }
+
+    #[test]
+    fn finite() {
+        let bytes = 2.5f32.to_le_bytes();
+        assert_eq!(2.5, bytes.as_slice().read_le_f32_finite().unwrap());
+        let bytes = f64::NEG_INFINITY.to_be_bytes();
+        let err = bytes.as_slice().read_be_f64_finite().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("expected a finite f64, found -inf", err.to_string());
+        let bytes = f32::NAN.to_ne_bytes();
+        assert!(bytes.as_slice().read_ne_f32_finite().is_err());
+    }
+
+    #[test]
+    fn word_swapped() {
+        // 1.0 is 0x3ff0_0000_0000_0000
+        let mut fpa: &[u8] = &[0, 0, 0xf0, 0x3f, 0, 0, 0, 0];
+        assert_eq!(1.0, fpa.read_f64_word_swapped(Endian::Little).unwrap());
+        let mut big: &[u8] = &[0, 0, 0, 1, 0x3f, 0xf0, 0, 0];
+        let value = big.read_f64_word_swapped(Endian::Big).unwrap();
+        assert_eq!(0x3ff0_0000_0000_0001, value.to_bits());
+    }
+}
@@ -0,0 +1,238 @@
+//! Reads of layouts described at runtime by Python `struct` format strings
+//!
+//! A format starts with a byte order character — `<` for little endian, `>` or `!` for big
+//! endian, `=` for native — followed by items, each an optional repeat count and a code:
+//!
+//! | code | value |
+//! |------|-------|
+//! | `x` | pad byte, no value |
+//! | `c` | [`Value::Bytes`] of 1 byte |
+//! | `b`, `h`, `i`, `l`, `q` | [`Value::Int`] of 1, 2, 4, 4, 8 bytes |
+//! | `B`, `H`, `I`, `L`, `Q` | [`Value::UInt`] of 1, 2, 4, 4, 8 bytes |
+//! | `?` | [`Value::Bool`] |
+//! | `f`, `d` | [`Value::Float`] of 4, 8 bytes |
+//! | `s` | [`Value::Bytes`]; the count is the length rather than a repeat count |
+//!
+//! Sizes are the standard sizes of Python's `struct`. Native alignment (`@` or no prefix) is not
+//! supported.
+//!
+//! ```
+//! use read_primitives::format::{ReadFormat, Value};
+//! let mut bytes: &[u8] = &[1, 0, 0, 0, 2, 0, 0, 0, 0x80, 0x3f];
+//! assert_eq!(
+//!     vec![Value::UInt(1), Value::UInt(2), Value::Float(1.0)],
+//!     bytes.read_fmt("<IHf").unwrap()
+//! );
+//! ```
+
+use crate::string::read_exact_vec;
+use crate::{Endian, Primitive};
+use std::io::{self, Read};
+
+/// A value decoded by [`ReadFormat`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// `?`
+    Bool(bool),
+    /// `b`, `h`, `i`, `l`, `q`
+    Int(i64),
+    /// `B`, `H`, `I`, `L`, `Q`
+    UInt(u64),
+    /// `f`, `d`
+    Float(f64),
+    /// `c`, `s`
+    Bytes(Vec<u8>),
+}
+
+/// A parsed format string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Format {
+    endian: Endian,
+    /// (count, code) pairs
+    items: Vec<(usize, u8)>,
+    size: usize,
+}
+
+fn code_size(code: u8) -> Option<usize> {
+    Some(match code {
+        b'x' | b'c' | b'b' | b'B' | b'?' | b's' => 1,
+        b'h' | b'H' => 2,
+        b'i' | b'I' | b'l' | b'L' | b'f' => 4,
+        b'q' | b'Q' | b'd' => 8,
+        _ => return None,
+    })
+}
+
+fn invalid_format(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+impl Format {
+    /// Parse a format string
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if the byte order character is missing or
+    /// unsupported, on unknown codes, or if the size overflows `usize`
+    pub fn parse(format: &str) -> io::Result<Self> {
+        let bytes = format.as_bytes();
+        let endian = match bytes.first() {
+            Some(b'<') => Endian::Little,
+            Some(b'>' | b'!') => Endian::Big,
+            Some(b'=') => Endian::NATIVE,
+            _ => {
+                return Err(invalid_format(format!(
+                    "format {format:?} must start with '<', '>', '!' or '='"
+                )))
+            }
+        };
+        let mut items = Vec::new();
+        let mut size = 0usize;
+        let mut rest = &bytes[1..];
+        while let Some(&first) = rest.first() {
+            if first.is_ascii_whitespace() {
+                rest = &rest[1..];
+                continue;
+            }
+            let digits = rest.iter().take_while(|byte| byte.is_ascii_digit()).count();
+            let count = match digits {
+                0 => 1,
+                _ => format[format.len() - rest.len()..][..digits]
+                    .parse()
+                    .map_err(|_| invalid_format(format!("repeat count overflows in {format:?}")))?,
+            };
+            let Some(&code) = rest.get(digits) else {
+                return Err(invalid_format(format!(
+                    "repeat count without code in {format:?}"
+                )));
+            };
+            let item_size = code_size(code).ok_or_else(|| {
+                invalid_format(format!("unsupported code {:?}", char::from(code)))
+            })?;
+            size = item_size
+                .checked_mul(count)
+                .and_then(|len| size.checked_add(len))
+                .ok_or_else(|| invalid_format(format!("size of {format:?} overflows")))?;
+            items.push((count, code));
+            rest = &rest[digits + 1..];
+        }
+        Ok(Format {
+            endian,
+            items,
+            size,
+        })
+    }
+
+    /// Number of bytes the format reads
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Decode the next `T` from the front of `bytes`
+fn next<T: Primitive>(bytes: &mut &[u8], endian: Endian) -> T {
+    let mut array = T::Bytes::default();
+    let (head, tail) = bytes.split_at(T::SIZE);
+    array.as_mut().copy_from_slice(head);
+    *bytes = tail;
+    T::from_endian_bytes(array, endian)
+}
+
+/// Trait to read values laid out by a [`Format`]
+pub trait ReadFormat: Read {
+    /// Read the values described by `format`, skipping pad bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_format(&mut self, format: &Format) -> io::Result<Vec<Value>> {
+        let data = read_exact_vec(self, format.size)?;
+        let mut bytes = data.as_slice();
+        let endian = format.endian;
+        let mut values = Vec::new();
+        for &(count, code) in &format.items {
+            if code == b's' {
+                let (head, tail) = bytes.split_at(count);
+                values.push(Value::Bytes(head.to_vec()));
+                bytes = tail;
+                continue;
+            }
+            for _ in 0..count {
+                let value = match code {
+                    b'x' => {
+                        bytes = &bytes[1..];
+                        continue;
+                    }
+                    b'c' => Value::Bytes(vec![next::<u8>(&mut bytes, endian)]),
+                    b'?' => Value::Bool(next(&mut bytes, endian)),
+                    b'b' => Value::Int(next::<i8>(&mut bytes, endian).into()),
+                    b'h' => Value::Int(next::<i16>(&mut bytes, endian).into()),
+                    b'i' | b'l' => Value::Int(next::<i32>(&mut bytes, endian).into()),
+                    b'q' => Value::Int(next(&mut bytes, endian)),
+                    b'B' => Value::UInt(next::<u8>(&mut bytes, endian).into()),
+                    b'H' => Value::UInt(next::<u16>(&mut bytes, endian).into()),
+                    b'I' | b'L' => Value::UInt(next::<u32>(&mut bytes, endian).into()),
+                    b'Q' => Value::UInt(next(&mut bytes, endian)),
+                    b'f' => Value::Float(next::<f32>(&mut bytes, endian).into()),
+                    b'd' => Value::Float(next(&mut bytes, endian)),
+                    _ => unreachable!("codes are checked by Format::parse"),
+                };
+                values.push(value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Parse `format` and read the values it describes
+    /// # Errors
+    /// errors like [`Format::parse`] and [`ReadFormat::read_format`]
+    fn read_fmt(&mut self, format: &str) -> io::Result<Vec<Value>> {
+        self.read_format(&Format::parse(format)?)
+    }
+}
+impl<R> ReadFormat for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::format::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(18, Format::parse("<2h x ? 4s Q").unwrap().size());
+        assert_eq!(0, Format::parse(">").unwrap().size());
+        for format in [
+            "IH",
+            "@I",
+            "<2",
+            "<z",
+            "<99999999999999999999999b",
+            "<18446744073709551615Q",
+        ] {
+            let err = Format::parse(format).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        }
+    }
+
+    #[test]
+    fn read_fmt() {
+        let mut bytes: &[u8] = &[
+            0xff, 0xff, 0, 0, 0, 2, 1, b'a', b'b', b'c', b'z', 0x3f, 0xf0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(
+            vec![
+                Value::Int(-1),
+                Value::Int(-256),
+                Value::UInt(2),
+                Value::Bool(true),
+                Value::Bytes(b"abc".to_vec()),
+                Value::Bytes(b"z".to_vec()),
+                Value::Float(1.0),
+            ],
+            bytes.read_fmt("!bhxH?3scd").unwrap()
+        );
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn read_short() {
+        let mut bytes: &[u8] = &[1, 2, 3];
+        let err = bytes.read_fmt("<I").unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{Endian, FixedSize, ReadFrom};
+use std::fmt;
+use std::io::{self, Read};
+
+/// Four-character code tagging chunks and boxes in RIFF, MP4, QuickTime and AIFF, or an `OSType`
+///
+/// Compares equal to byte string constants, and displays as its characters with bytes outside
+/// printable ASCII escaped.
+///
+/// ```
+/// use read_primitives::{FourCC, ReadFourCC};
+/// let mut mp4: &[u8] = b"ftypisom";
+/// let tag = mp4.read_fourcc().unwrap();
+/// assert_eq!(tag, b"ftyp");
+/// assert_eq!("isom", mp4.read_fourcc().unwrap().to_string());
+/// assert_eq!(FourCC::from_u32(0x6d6f_6f76), *b"moov");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FourCC(pub [u8; 4]);
+
+/// Which bytes a [`FourCC`] may hold, for [`ReadFourCC::read_fourcc_checked`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FourCCCharset {
+    /// Any bytes
+    Any,
+    /// Printable ASCII, space to `~`
+    Printable,
+    /// ASCII letters and digits, padded at the end with spaces like `"mp4 "`
+    Alphanumeric,
+}
+
+impl FourCC {
+    /// Code of the bytes `bytes`
+    pub const fn new(bytes: [u8; 4]) -> Self {
+        FourCC(bytes)
+    }
+
+    /// Code of the big endian bytes of `value`, as an `OSType` stores it
+    pub const fn from_u32(value: u32) -> Self {
+        FourCC(value.to_be_bytes())
+    }
+
+    /// The code as a big endian u32
+    pub const fn to_u32(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+
+    /// The bytes of the code
+    pub const fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// The code as a string, `None` if it isn't valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    /// Whether every byte of the code is in `charset`
+    pub fn is_in(&self, charset: FourCCCharset) -> bool {
+        match charset {
+            FourCCCharset::Any => true,
+            FourCCCharset::Printable => self.0.iter().all(|&byte| matches!(byte, b' '..=b'~')),
+            FourCCCharset::Alphanumeric => {
+                let text = self.0.len() - self.0.iter().rev().take_while(|&&b| b == b' ').count();
+                text > 0 && self.0[..text].iter().all(u8::is_ascii_alphanumeric)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &byte in &self.0 {
+            match byte {
+                b' '..=b'~' if byte != b'\\' => write!(f, "{}", byte as char)?,
+                _ => write!(f, "{}", byte.escape_ascii())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FourCC(\"{self}\")")
+    }
+}
+
+impl From<[u8; 4]> for FourCC {
+    fn from(bytes: [u8; 4]) -> Self {
+        FourCC(bytes)
+    }
+}
+
+impl From<FourCC> for [u8; 4] {
+    fn from(code: FourCC) -> Self {
+        code.0
+    }
+}
+
+impl PartialEq<[u8; 4]> for FourCC {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<&[u8; 4]> for FourCC {
+    fn eq(&self, other: &&[u8; 4]) -> bool {
+        self.0 == **other
+    }
+}
+
+impl PartialEq<FourCC> for [u8; 4] {
+    fn eq(&self, other: &FourCC) -> bool {
+        *self == other.0
+    }
+}
+
+impl ReadFrom for FourCC {
+    fn read_from<R: Read + ?Sized>(reader: &mut R, _: Endian) -> io::Result<Self> {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(FourCC(bytes))
+    }
+}
+
+impl FixedSize for FourCC {
+    const SIZE: usize = 4;
+}
+
+/// Trait to read [`FourCC`] codes
+pub trait ReadFourCC: Read {
+    /// Read a four-character code
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_fourcc(&mut self) -> io::Result<FourCC> {
+        FourCC::read_from(self, Endian::Big)
+    }
+
+    /// Read a four-character code whose bytes must be in `charset`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if a byte is outside `charset`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_fourcc_checked(&mut self, charset: FourCCCharset) -> io::Result<FourCC> {
+        let code = self.read_fourcc()?;
+        if !code.is_in(charset) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("FourCC \"{code}\" is not {charset:?}"),
+            ));
+        }
+        Ok(code)
+    }
+}
+impl<R> ReadFourCC for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn compare_and_display() {
+        let code = FourCC::new(*b"RIFF");
+        assert!(code == b"RIFF" && *b"RIFF" == code);
+        assert_ne!(code, b"RIFX");
+        assert_eq!(0x5249_4646, code.to_u32());
+        assert_eq!(Some("RIFF"), code.as_str());
+        let odd = FourCC(*b"a\\\x00\xff");
+        assert_eq!("a\\\\\\x00\\xff", odd.to_string());
+        assert_eq!("FourCC(\"a\\\\\\x00\\xff\")", format!("{odd:?}"));
+        assert_eq!(None, odd.as_str());
+    }
+
+    #[test]
+    fn charsets() {
+        assert!(FourCC(*b"mp4 ").is_in(FourCCCharset::Alphanumeric));
+        assert!(!FourCC(*b" mp4").is_in(FourCCCharset::Alphanumeric));
+        assert!(!FourCC(*b"    ").is_in(FourCCCharset::Alphanumeric));
+        assert!(FourCC(*b"(c) ").is_in(FourCCCharset::Printable));
+        assert!(!FourCC(*b"\xa9nam").is_in(FourCCCharset::Printable));
+        assert!(FourCC(*b"\xa9nam").is_in(FourCCCharset::Any));
+    }
+
+    #[test]
+    fn read() {
+        let mut bytes: &[u8] = b"data\xa9day";
+        let code = bytes
+            .read_fourcc_checked(FourCCCharset::Alphanumeric)
+            .unwrap();
+        assert_eq!(code, b"data");
+        let err = bytes
+            .read_fourcc_checked(FourCCCharset::Printable)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("FourCC \"\\xa9day\" is not Printable", err.to_string());
+        let err = bytes.read_fourcc().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let (code, len) =
+            <(FourCC, u32)>::read_from(&mut &b"abcd\0\0\0\x08"[..], Endian::Big).unwrap();
+        assert_eq!((FourCC(*b"abcd"), 8), (code, len));
+    }
+}
@@ -0,0 +1,180 @@
+//! Async primitive writes for [`futures_io::AsyncWrite`], cancellation safe reads for
+//! [`futures_io::AsyncRead`] and peeks for [`futures_io::AsyncBufRead`]
+//!
+//! ```
+//! # futures::executor::block_on(async {
+//! use read_primitives::futures::AsyncWriteI16;
+//! let mut bytes = Vec::new();
+//! bytes.write_le_i16(-2).await?;
+//! assert_eq!([0xFE, 0xFF], *bytes);
+//! # std::io::Result::Ok(())
+//! # }).unwrap();
+//! ```
+
+impl_async_write!(::futures_io::AsyncWrite);
+impl_async_read!(
+    ::futures_io::AsyncBufRead,
+    ::futures_io::AsyncRead,
+    poll_read_slice
+);
+
+fn poll_read_slice<R: AsyncRead + Unpin + ?Sized>(
+    reader: &mut R,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+) -> Poll<io::Result<usize>> {
+    Pin::new(reader).poll_read(cx, buf)
+}
+
+impl_async_read_from!(futures, ::futures_io::AsyncRead, poll_read_slice);
+
+#[cfg(feature = "derive")]
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for derive_support::Counter<'_, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_read_slice(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::futures::*;
+    use crate::{ReadChar, ReadI128, ReadU32};
+
+    #[test]
+    fn write_primitives() {
+        let mut bytes = Vec::new();
+        ::futures::executor::block_on(async {
+            bytes.write_be_u32(37).await.unwrap();
+            bytes.write_le_i128(-1).await.unwrap();
+            bytes.write_char('x').await.unwrap();
+        });
+        let mut reader = bytes.as_slice();
+        assert_eq!(37, reader.read_be_u32().unwrap());
+        assert_eq!(-1, reader.read_le_i128().unwrap());
+        assert_eq!(Some('x'), reader.read_char().unwrap());
+    }
+
+    /// Reader returning one byte per read, and pending before each byte
+    struct Trickle {
+        data: &'static [u8],
+        ready: bool,
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let ready = self.ready;
+            self.ready = !ready;
+            if !ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let Some((&byte, rest)) = self.data.split_first() else {
+                return Poll::Ready(Ok(0));
+            };
+            buf[0] = byte;
+            self.data = rest;
+            Poll::Ready(Ok(1))
+        }
+    }
+
+    #[test]
+    fn read_primitives() {
+        let mut reader = AsyncPrimitiveReader::new(&[1u8, 0, 0, 2, 3][..]);
+        ::futures::executor::block_on(async {
+            assert_eq!(1u16, reader.read_le().await.unwrap());
+            assert_eq!(2u16, reader.read_endian(crate::Endian::Big).await.unwrap());
+            let err = reader.read_be::<u16>().await.unwrap_err();
+            assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        });
+        assert_eq!([3], reader.pending());
+    }
+
+    #[test]
+    fn cancelled_reads_keep_bytes() {
+        let mut reader = AsyncPrimitiveReader::new(Trickle {
+            data: &[1, 2, 3, 4, 5, 6],
+            ready: false,
+        });
+        let waker = ::futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        // poll until the read is halfway through, then drop it
+        {
+            let mut read = reader.read_be::<u32>();
+            for _ in 0..3 {
+                assert!(Pin::new(&mut read).poll(&mut cx).is_pending());
+            }
+        }
+        assert_eq!([1, 2], reader.pending());
+        ::futures::executor::block_on(async {
+            assert_eq!(0x01u8, reader.read_ne().await.unwrap());
+            assert_eq!(0x0203_0405u32, reader.read_be().await.unwrap());
+        });
+        let (inner, pending) = reader.into_parts();
+        assert_eq!(([6u8].as_slice(), Vec::new()), (inner.data, pending));
+    }
+
+    #[test]
+    fn peek_across_reads() {
+        // the underlying reader returns one byte per read, so no buffer holds the whole value
+        let mut reader = AsyncPrimitiveReader::new(Trickle {
+            data: &[1, 2, 3, 4, 5],
+            ready: false,
+        });
+        ::futures::executor::block_on(async {
+            assert_eq!(0x0102_0304u32, reader.peek_be().await.unwrap());
+            assert_eq!(0x0201u16, reader.peek_le().await.unwrap());
+            assert_eq!(0x0102u16, reader.read_be().await.unwrap());
+            let err = reader
+                .peek_endian::<u32>(crate::Endian::Big)
+                .await
+                .unwrap_err();
+            assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        });
+        assert_eq!([3, 4, 5], reader.pending());
+    }
+
+    #[test]
+    fn read_vec_yields() {
+        let bytes = [1u8, 0, 2, 0, 3, 0, 4, 0, 5, 0];
+        let mut reader = &bytes[..];
+        let waker = ::futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut read = std::pin::pin!(u16::read_vec_async(
+            &mut reader,
+            crate::Endian::Little,
+            5,
+            2
+        ));
+        let mut pending = 0;
+        let values = loop {
+            match read.as_mut().poll(&mut cx) {
+                Poll::Ready(values) => break values.unwrap(),
+                Poll::Pending => pending += 1,
+            }
+        };
+        assert_eq!((vec![1, 2, 3, 4, 5], 2), (values, pending));
+        let mut reader = &bytes[..];
+        let never = u8::read_vec_async(&mut reader, crate::Endian::Little, 10, 0);
+        assert!(std::pin::pin!(never).poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn peek_primitives() {
+        let mut reader: &[u8] = &[0xff, 0xfe];
+        ::futures::executor::block_on(async {
+            assert_eq!(-2i16, reader.peek_be().await.unwrap());
+            assert_eq!(-257i16, reader.peek_le().await.unwrap());
+            let err = reader.peek_le::<u32>().await.unwrap_err();
+            assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        });
+        assert_eq!(2, reader.len());
+    }
+}
@@ -0,0 +1,113 @@
+//! Glue for driving decoders from fuzzer input with the `arbitrary` crate
+//!
+//! [`UnstructuredReader`] reads the raw bytes of an [`Unstructured`], and [`Decoded`] implements
+//! [`Arbitrary`] for any [`ReadFrom`] type by decoding it in an arbitrary byte order, so a fuzz
+//! target can take `Decoded<MyHeader>` directly.
+//!
+//! ```
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use read_primitives::fuzz::Decoded;
+//! let mut input = Unstructured::new(&[0, 1, 0, 2, 0, 9]);
+//! let Decoded(value) = Decoded::<[u16; 2]>::arbitrary(&mut input).unwrap();
+//! assert_eq!([1, 2], value);
+//! assert_eq!(1, input.len());
+//! ```
+
+use crate::{Endian, ReadFrom};
+use arbitrary::{Arbitrary, Unstructured};
+use std::io::{self, Read};
+
+impl<'a> Arbitrary<'a> for Endian {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if bool::arbitrary(u)? {
+            Endian::Big
+        } else {
+            Endian::Little
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        bool::size_hint(depth)
+    }
+}
+
+/// Reader over the remaining bytes of an [`Unstructured`]
+///
+/// Consumes the bytes it reads, so other values can still be taken from the same input afterwards.
+#[derive(Debug)]
+pub struct UnstructuredReader<'u, 'a> {
+    inner: &'u mut Unstructured<'a>,
+}
+
+impl<'u, 'a> UnstructuredReader<'u, 'a> {
+    /// Read from the bytes of `inner`
+    pub fn new(inner: &'u mut Unstructured<'a>) -> Self {
+        UnstructuredReader { inner }
+    }
+
+    /// Unwrap the input
+    pub fn into_inner(self) -> &'u mut Unstructured<'a> {
+        self.inner
+    }
+}
+
+impl Read for UnstructuredReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.inner.len());
+        let bytes = self.inner.bytes(len).map_err(io::Error::other)?;
+        buf[..len].copy_from_slice(bytes);
+        Ok(len)
+    }
+}
+
+/// Decode a `T` from `u` in `endian` byte order
+/// # Errors
+/// errors with [`arbitrary::Error::NotEnoughData`] if the input ends early,
+/// or [`arbitrary::Error::IncorrectFormat`] if the decoder rejects it
+pub fn read_from_unstructured<T: ReadFrom>(
+    u: &mut Unstructured<'_>,
+    endian: Endian,
+) -> arbitrary::Result<T> {
+    T::read_from(&mut UnstructuredReader::new(u), endian).map_err(|err| match err.kind() {
+        io::ErrorKind::UnexpectedEof => arbitrary::Error::NotEnoughData,
+        _ => arbitrary::Error::IncorrectFormat,
+    })
+}
+
+/// A `T` decoded from fuzzer input, in a byte order also taken from the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded<T>(pub T);
+
+impl<'a, T: ReadFrom> Arbitrary<'a> for Decoded<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let endian = Endian::arbitrary(u)?;
+        read_from_unstructured(u, endian).map(Decoded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fuzz::*;
+    use crate::*;
+
+    #[test]
+    fn unstructured_reader() {
+        let mut input = Unstructured::new(&[1, 0, 0, 0, 2, 3]);
+        let mut reader = UnstructuredReader::new(&mut input);
+        assert_eq!(1, reader.read_le_u32().unwrap());
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!([2, 3], *rest);
+        assert!(reader.into_inner().is_empty());
+    }
+
+    #[test]
+    fn decoded() {
+        let mut input = Unstructured::new(&[1, 0, 1, 0, 2, 7]);
+        let Decoded(value) = Decoded::<[u16; 2]>::arbitrary(&mut input).unwrap();
+        assert_eq!([1, 2], value);
+        assert_eq!(1, input.len());
+        let err = read_from_unstructured::<u32>(&mut input, Endian::Little).unwrap_err();
+        assert!(matches!(err, arbitrary::Error::NotEnoughData));
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::{self, Read};
+
+/// Size of the chunks of hex text read from the inner reader
+const CHUNK_LEN: usize = 4096;
+
+/// Reader decoding ASCII hex text from the inner reader into bytes
+///
+/// Digits may be upper or lower case. The separators, ASCII whitespace unless set with
+/// [`HexReader::with_separators`], are skipped anywhere, even between the two digits of a byte.
+///
+/// ```
+/// use read_primitives::{HexReader, ReadU16, ReadU8};
+/// let mut reader = HexReader::new(&b"01 02\nFF"[..]);
+/// assert_eq!(0x0102, reader.read_be_u16().unwrap());
+/// assert_eq!(0xff, reader.read_u8().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct HexReader<R> {
+    inner: R,
+    separators: Vec<u8>,
+    text: Vec<u8>,
+    pos: usize,
+    high: Option<u8>,
+}
+
+impl<R> HexReader<R> {
+    /// Decode `inner`, skipping ASCII whitespace
+    pub fn new(inner: R) -> Self {
+        HexReader::with_separators(inner, b" \t\r\n")
+    }
+
+    /// Decode `inner`, skipping the bytes in `separators`
+    pub fn with_separators(inner: R, separators: &[u8]) -> Self {
+        HexReader {
+            inner,
+            separators: separators.to_vec(),
+            text: Vec::new(),
+            pos: 0,
+            high: None,
+        }
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the reader, dropping text that was read from it but not yet decoded
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decode the buffered text into `out`, returning how many bytes were written
+    fn decode(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < out.len() && self.pos < self.text.len() {
+            let char = self.text[self.pos];
+            self.pos += 1;
+            if self.separators.contains(&char) {
+                continue;
+            }
+            let nibble = match char {
+                b'0'..=b'9' => char - b'0',
+                b'a'..=b'f' => char - b'a' + 10,
+                b'A'..=b'F' => char - b'A' + 10,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid hex digit '{}'", char.escape_ascii()),
+                    ))
+                }
+            };
+            match self.high.take() {
+                Some(high) => {
+                    out[written] = (high << 4) | nibble;
+                    written += 1;
+                }
+                None => self.high = Some(nibble),
+            }
+        }
+        Ok(written)
+    }
+}
+
+impl<R: Read> Read for HexReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let written = self.decode(out)?;
+            if written > 0 {
+                return Ok(written);
+            }
+            self.text.resize(CHUNK_LEN, 0);
+            self.pos = 0;
+            let read = match self.inner.read(&mut self.text) {
+                Ok(read) => read,
+                Err(err) => {
+                    self.text.clear();
+                    return Err(err);
+                }
+            };
+            self.text.truncate(read);
+            if read == 0 {
+                if self.high.is_some() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "odd number of hex digits",
+                    ));
+                }
+                return Ok(0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Read};
+
+    #[test]
+    fn decode() {
+        let mut reader = HexReader::with_separators(&b"de:AD:b\ne:ef"[..], b":\n");
+        assert_eq!(0xdead_beef, reader.read_be_u32().unwrap());
+        assert_eq!(0, reader.read(&mut [0; 4]).unwrap());
+
+        let mut odd = HexReader::new(&b"abc"[..]);
+        let err = odd.read_bytes(2).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut invalid = HexReader::new(&b"0g"[..]);
+        let err = invalid.read(&mut [0; 1]).unwrap_err();
+        assert_eq!("invalid hex digit 'g'", err.to_string());
+    }
+
+    #[test]
+    fn across_chunks() {
+        let text: Vec<u8> = (0..5000u32)
+            .flat_map(|i| format!("{:02x} ", i as u8).into_bytes())
+            .collect();
+        let mut decoded = Vec::new();
+        HexReader::new(text.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!((0..5000u32).map(|i| i as u8).collect::<Vec<_>>(), decoded);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{Endian, ReadFrom};
+use std::fmt;
+use std::io;
+
+/// A type that can be decoded field by field from input that arrives in pieces
+///
+/// `#[derive(ReadFrom)]` implements it for structs marked with `#[read(incremental)]`; read them
+/// with an [`IncrementalDecoder`].
+pub trait ReadIncremental: ReadFrom {
+    /// The fields decoded so far and where the next one starts
+    type State: Default;
+
+    /// Decode as many of the remaining fields as `input` holds completely, advancing it past
+    /// them, and return the value once its last field was decoded
+    ///
+    /// A field that `input` holds only part of is left unread, to be decoded from the start again
+    /// by the next call, which gets the rest of its bytes after those.
+    /// # Errors
+    /// errors when decoding a field errors other than by running out of bytes
+    fn resume(
+        state: &mut Self::State,
+        input: &mut &[u8],
+        endian: Endian,
+    ) -> io::Result<Option<Self>>;
+}
+
+/// Push-based decoder of a stream of `T`s that keeps only the bytes of the field being decoded,
+/// for large messages that should not be buffered as a whole
+///
+/// Works like [`Decoder`](crate::Decoder), but the fields decoded so far are kept as values, so
+/// only a field's own bytes wait for the rest of them to arrive.
+///
+/// ```
+/// use read_primitives::{Endian, IncrementalDecoder, ReadFrom};
+///
+/// #[derive(ReadFrom, Debug, PartialEq)]
+/// #[read(big, incremental)]
+/// struct Frame {
+///     len: u16,
+///     #[read(count = "len")]
+///     payload: Vec<u8>,
+/// }
+///
+/// let mut decoder = IncrementalDecoder::<Frame>::new(Endian::Big);
+/// decoder.push(&[0, 3, 1]);
+/// assert_eq!(None, decoder.poll_value().unwrap());
+/// // the length was decoded, only the start of the payload is buffered
+/// assert_eq!(1, decoder.buffered());
+/// decoder.push(&[2, 3]);
+/// let frame = decoder.poll_value().unwrap().unwrap();
+/// assert_eq!(vec![1, 2, 3], frame.payload);
+/// ```
+///
+/// With the `serde` feature the decoder is serializable if its state is, which
+/// `#[read(incremental, serde)]` derives, so a decode of a huge input can be checkpointed and
+/// resumed after a restart.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "T::State: serde::Serialize",
+        deserialize = "T::State: serde::Deserialize<'de>"
+    ))
+)]
+pub struct IncrementalDecoder<T: ReadIncremental> {
+    state: T::State,
+    pending: Vec<u8>,
+    endian: Endian,
+}
+
+impl<T: ReadIncremental> fmt::Debug for IncrementalDecoder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncrementalDecoder")
+            .field("buffered", &self.pending.len())
+            .field("endian", &self.endian)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: ReadIncremental> IncrementalDecoder<T> {
+    /// Decoder of values in `endian` byte order
+    pub fn new(endian: Endian) -> Self {
+        IncrementalDecoder {
+            state: T::State::default(),
+            pending: Vec::new(),
+            endian,
+        }
+    }
+
+    /// Add bytes that arrived
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Decode the fields the buffered bytes complete, and take the value once all of its fields
+    /// were decoded
+    /// # Errors
+    /// errors when decoding a field errors other than by running out of bytes; the field's bytes
+    /// stay buffered, so the next call errors again unless the decoder is
+    /// [cleared](IncrementalDecoder::clear)
+    pub fn poll_value(&mut self) -> io::Result<Option<T>> {
+        let mut input = self.pending.as_slice();
+        let value = T::resume(&mut self.state, &mut input, self.endian);
+        let used = self.pending.len() - input.len();
+        self.pending.drain(..used);
+        value
+    }
+
+    /// Number of bytes pushed but not yet decoded
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Drop the buffered bytes and the fields decoded so far, to resynchronize after an error
+    pub fn clear(&mut self) {
+        self.state = T::State::default();
+        self.pending.clear();
+    }
+}
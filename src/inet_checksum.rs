@@ -0,0 +1,210 @@
+use std::io::{self, Read};
+
+/// Running RFC 1071 Internet checksum, the ones' complement sum of 16 bit big endian words used
+/// by IPv4, ICMP, UDP and TCP
+///
+/// Bytes may be added in pieces of any length: a piece ending in the middle of a word is
+/// continued by the next, and a final odd byte is padded with zero.
+///
+/// ```
+/// use read_primitives::InternetChecksum;
+/// let mut header = [0x45, 0, 0, 0x73, 0, 0, 0x40, 0, 0x40, 0x11, 0, 0, 0xc0, 0xa8, 0, 1, 0xc0, 0xa8, 0, 0xc7];
+/// let mut sum = InternetChecksum::new();
+/// sum.update(&header);
+/// assert_eq!(0xb861, sum.checksum());
+/// header[10..12].copy_from_slice(&0xb861u16.to_be_bytes());
+/// let mut check = InternetChecksum::new();
+/// check.update(&header);
+/// assert!(check.is_valid());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternetChecksum {
+    sum: u32,
+    odd: Option<u8>,
+}
+
+impl InternetChecksum {
+    /// Sum of no bytes
+    pub fn new() -> Self {
+        InternetChecksum::default()
+    }
+
+    fn add_word(&mut self, word: u16) {
+        let sum = self.sum + u32::from(word);
+        self.sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    /// Add `bytes` to the sum
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        if let Some(high) = self.odd {
+            let Some((&low, rest)) = bytes.split_first() else {
+                return;
+            };
+            self.add_word(u16::from_be_bytes([high, low]));
+            self.odd = None;
+            bytes = rest;
+        }
+        let mut words = bytes.chunks_exact(2);
+        for word in &mut words {
+            self.add_word(u16::from_be_bytes([word[0], word[1]]));
+        }
+        self.odd = words.remainder().first().copied();
+    }
+
+    /// Add the IPv4 pseudo-header that UDP and TCP checksums cover: the addresses, the protocol
+    /// and the length of the UDP or TCP header and data
+    pub fn add_ipv4_pseudo_header(
+        &mut self,
+        source: [u8; 4],
+        destination: [u8; 4],
+        protocol: u8,
+        len: u16,
+    ) {
+        self.update(&source);
+        self.update(&destination);
+        self.update(&[0, protocol]);
+        self.update(&len.to_be_bytes());
+    }
+
+    /// Add the IPv6 pseudo-header that UDP, TCP and ICMPv6 checksums cover: the addresses, the
+    /// upper-layer length and the next header
+    pub fn add_ipv6_pseudo_header(
+        &mut self,
+        source: [u8; 16],
+        destination: [u8; 16],
+        next_header: u8,
+        len: u32,
+    ) {
+        self.update(&source);
+        self.update(&destination);
+        self.update(&len.to_be_bytes());
+        self.update(&[0, 0, 0, next_header]);
+    }
+
+    /// The sum so far, folded to 16 bits, with a trailing odd byte padded with zero
+    pub fn sum(&self) -> u16 {
+        let mut total = *self;
+        if let Some(high) = total.odd.take() {
+            total.add_word(u16::from_be_bytes([high, 0]));
+        }
+        total.sum as u16
+    }
+
+    /// The checksum to store for the bytes so far, the ones' complement of [`sum`](Self::sum)
+    pub fn checksum(&self) -> u16 {
+        !self.sum()
+    }
+
+    /// Whether bytes including their stored checksum sum correctly
+    pub fn is_valid(&self) -> bool {
+        self.sum() == 0xffff
+    }
+}
+
+/// Reader adding each byte read from the inner reader to an [`InternetChecksum`], to check a
+/// packet while parsing its fields instead of in a second pass
+///
+/// ```
+/// use read_primitives::{InternetChecksumReader, ReadU16};
+/// // a UDP header from 10.0.0.1:1234 to 10.0.0.2:53 with 2 bytes of data
+/// let datagram = [0x04, 0xd2, 0, 0x35, 0, 10, 0x3b, 0x03, 0xab, 0xcd];
+/// let mut reader = InternetChecksumReader::new(&datagram[..]);
+/// reader.checksum_mut().add_ipv4_pseudo_header([10, 0, 0, 1], [10, 0, 0, 2], 17, 10);
+/// assert_eq!(1234, reader.read_be_u16().unwrap());
+/// std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+/// assert!(reader.checksum().is_valid());
+/// ```
+#[derive(Debug)]
+pub struct InternetChecksumReader<R> {
+    inner: R,
+    checksum: InternetChecksum,
+}
+
+impl<R> InternetChecksumReader<R> {
+    /// Wrap `inner`, starting from an empty sum
+    pub fn new(inner: R) -> Self {
+        InternetChecksumReader {
+            inner,
+            checksum: InternetChecksum::new(),
+        }
+    }
+
+    /// The sum of the bytes read so far
+    pub fn checksum(&self) -> &InternetChecksum {
+        &self.checksum
+    }
+
+    /// Mutably borrow the sum, to add a pseudo-header or skip a field
+    pub fn checksum_mut(&mut self) -> &mut InternetChecksum {
+        &mut self.checksum
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for InternetChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.checksum.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::Read;
+
+    #[test]
+    fn rfc1071() {
+        let bytes = [0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7];
+        let mut whole = InternetChecksum::new();
+        whole.update(&bytes);
+        assert_eq!(0xddf2, whole.sum());
+        assert_eq!(0x220d, whole.checksum());
+        let mut pieces = InternetChecksum::new();
+        for piece in [&bytes[..1], &[], &bytes[1..4], &bytes[4..]] {
+            pieces.update(piece);
+        }
+        assert_eq!(whole, pieces);
+    }
+
+    #[test]
+    fn odd_length() {
+        let mut sum = InternetChecksum::new();
+        sum.update(&[0x12, 0x34, 0x56]);
+        assert_eq!(0x6834, sum.sum());
+        sum.update(&[0x78]);
+        assert_eq!(0x68ac, sum.sum());
+        assert!(!InternetChecksum::new().is_valid());
+    }
+
+    #[test]
+    fn ipv6_pseudo_header() {
+        let mut source = [0u8; 16];
+        source[15] = 1;
+        let mut sum = InternetChecksum::new();
+        sum.add_ipv6_pseudo_header(source, source, 58, 8);
+        assert_eq!(0x0001 + 0x0001 + 8 + 58, sum.sum());
+    }
+
+    #[test]
+    fn reader() {
+        let mut reader = InternetChecksumReader::new(&[0xff, 0xff, 0x00, 0x01, 0x02][..]);
+        let mut first = [0u8; 3];
+        reader.read_exact(&mut first).unwrap();
+        assert_eq!(0xffff, reader.checksum().sum());
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        // 0xffff + 0x0001 + 0x0200 folds to 0x0201
+        assert_eq!(0x0201, reader.checksum().sum());
+        assert!(reader.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::io::{self, Read};
+
+/// Adapter that reads from an `Iterator<Item = u8>`
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{IterReader, ReadU16};
+/// let mut reader = IterReader::new([1u8, 2].into_iter().map(|byte| byte * 2));
+/// assert_eq!(0x0402, reader.read_le_u16().unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct IterReader<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = u8>> IterReader<I> {
+    /// Create a reader over the bytes yielded by `iter`
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        IterReader {
+            iter: iter.into_iter(),
+        }
+    }
+
+    /// Get back the underlying iterator
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: Iterator<Item = u8>> Read for IterReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut len = 0;
+        for (slot, byte) in buf.iter_mut().zip(&mut self.iter) {
+            *slot = byte;
+            len += 1;
+        }
+        Ok(len)
+    }
+}
+
+/// Adapter that reads from an `Iterator<Item = io::Result<u8>>`, such as [`Read::bytes`]
+///
+/// Errors yielded by the iterator are passed through. Bytes read before an error are kept
+/// and returned from the next call to `read`.
+#[derive(Debug)]
+pub struct TryIterReader<I> {
+    iter: I,
+    error: Option<io::Error>,
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> TryIterReader<I> {
+    /// Create a reader over the bytes yielded by `iter`
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        TryIterReader {
+            iter: iter.into_iter(),
+            error: None,
+        }
+    }
+
+    /// Get back the underlying iterator
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> Read for TryIterReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let mut len = 0;
+        for slot in buf.iter_mut() {
+            match self.iter.next() {
+                Some(Ok(byte)) => {
+                    *slot = byte;
+                    len += 1;
+                }
+                Some(Err(err)) if len == 0 => return Err(err),
+                Some(Err(err)) => {
+                    self.error = Some(err);
+                    break;
+                }
+                None => break,
+            }
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn iter_reader() {
+        let mut reader = IterReader::new(0x1234_5678u32.to_be_bytes());
+        assert_eq!(0x1234_5678, reader.read_be_u32().unwrap());
+        let err = reader.read_u8().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn try_iter_reader() {
+        let items = vec![Ok(1), Ok(0), Err(io::Error::other("broken")), Ok(2)];
+        let mut reader = TryIterReader::new(items);
+        assert_eq!(1, reader.read_le_u16().unwrap());
+        let err = reader.read_exact(&mut [0u8; 1]).unwrap_err();
+        assert_eq!("broken", err.to_string());
+        assert_eq!(2, reader.read_u8().unwrap());
+    }
+
+    #[test]
+    fn try_iter_reader_keeps_bytes_before_error() {
+        let items = vec![Ok(7), Err(io::Error::other("broken"))];
+        let mut reader = TryIterReader::new(items);
+        let mut buf = [0u8; 4];
+        assert_eq!(1, std::io::Read::read(&mut reader, &mut buf).unwrap());
+        assert_eq!(7, buf[0]);
+        assert!(std::io::Read::read(&mut reader, &mut buf).is_err());
+    }
+}
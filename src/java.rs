@@ -0,0 +1,121 @@
+//! Reads of data written by Java's `java.io.DataOutputStream`
+//!
+//! `DataOutputStream` writes numbers in big endian, so its primitive fields are read with the
+//! `read_be_*` methods, and `readBoolean` matches [`ReadBool::read_bool`](crate::ReadBool::read_bool):
+//! any non-zero byte is `true`. This module covers the string and char conventions.
+//!
+//! ```
+//! use read_primitives::java::ReadJava;
+//! // "a\0" in modified UTF-8
+//! let mut bytes: &[u8] = &[0, 3, b'a', 0xc0, 0x80];
+//! assert_eq!("a\0", bytes.read_java_utf().unwrap());
+//! ```
+
+use crate::string::read_exact_vec;
+use std::io::{self, Read};
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn from_utf16(units: &[u16]) -> io::Result<String> {
+    String::from_utf16(units).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Decode modified UTF-8: one to three bytes per UTF-16 code unit, so supplementary characters
+/// are encoded as two surrogates and NUL as `C0 80`
+fn decode_modified_utf8(bytes: &[u8]) -> io::Result<String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some((&lead, tail)) = rest.split_first() {
+        let (len, bits) = match lead {
+            0x00..=0x7f => (0, lead),
+            0xc0..=0xdf => (1, lead & 0x1f),
+            0xe0..=0xef => (2, lead & 0x0f),
+            _ => return Err(invalid_data("malformed modified UTF-8")),
+        };
+        let continuation = tail
+            .get(..len)
+            .filter(|bytes| bytes.iter().all(|byte| byte & 0xc0 == 0x80))
+            .ok_or_else(|| invalid_data("malformed modified UTF-8"))?;
+        let unit = continuation.iter().fold(u16::from(bits), |unit, byte| {
+            unit << 6 | u16::from(byte & 0x3f)
+        });
+        units.push(unit);
+        rest = &tail[len..];
+    }
+    from_utf16(&units)
+}
+
+/// Trait to read the encodings of Java's `DataOutputStream`
+pub trait ReadJava: Read {
+    /// Read a `writeUTF` string: a big endian u16 byte length, then modified UTF-8
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not valid modified UTF-8
+    /// or contain unpaired surrogates, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_java_utf(&mut self) -> io::Result<String> {
+        let mut len = [0u8; 2];
+        self.read_exact(&mut len)?;
+        decode_modified_utf8(&read_exact_vec(self, u16::from_be_bytes(len).into())?)
+    }
+
+    /// Read a `writeChar` value: one big endian UTF-16 code unit, which may be half of a surrogate pair
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_java_char(&mut self) -> io::Result<u16> {
+        let mut bytes = [0u8; 2];
+        self.read_exact(&mut bytes)?;
+        Ok(u16::from_be_bytes(bytes))
+    }
+
+    /// Read `count` chars written by `writeChars`: big endian UTF-16 without a length prefix
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] on unpaired surrogates,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_java_chars(&mut self, count: usize) -> io::Result<String> {
+        let bytes = read_exact_vec(self, count.saturating_mul(2))?;
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .collect();
+        from_utf16(&units)
+    }
+}
+impl<R> ReadJava for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::java::*;
+
+    #[test]
+    fn read_java_utf() {
+        // "é😀" with the emoji as two 3-byte surrogates
+        let mut bytes: &[u8] = &[0, 8, 0xc3, 0xa9, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80, 0, 0];
+        assert_eq!("é😀", bytes.read_java_utf().unwrap());
+        assert_eq!("", bytes.read_java_utf().unwrap());
+    }
+
+    #[test]
+    fn read_java_utf_errors() {
+        for bytes in [
+            &[0, 4, 0xf0, 0x9f, 0x98, 0x80][..],
+            &[0, 2, 0xc3, b'a'],
+            &[0, 1, 0xc3],
+            &[0, 3, 0xed, 0xa0, 0xbd],
+        ] {
+            let err = (&bytes[..]).read_java_utf().unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        }
+        let err = (&[0u8, 5, b'a'][..]).read_java_utf().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_java_chars() {
+        let mut bytes: &[u8] = &[0xd8, 0x3d, 0, b'h', 0, b'i', 0xd8, 0x3d];
+        assert_eq!(0xd83d, bytes.read_java_char().unwrap());
+        assert_eq!("hi", bytes.read_java_chars(2).unwrap());
+        let err = bytes.read_java_chars(1).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
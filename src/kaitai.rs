@@ -0,0 +1,451 @@
+//! Import of [Kaitai Struct](https://kaitai.io) `.ksy` definitions as runtime [`Layout`]s
+//!
+//! The supported subset covers sequential formats:
+//!
+//! - `meta/endian` of `le` or `be`, in the root or any type
+//! - `seq` attributes of the integer and float types `u1` to `u8`, `s1` to `s8`, `f4` and `f8`,
+//!   with an optional `le` or `be` suffix, of user types from `types`, and of byte strings given
+//!   by `size`, `type: str` with `size`, or `contents`
+//! - `repeat: expr` with `repeat-expr`
+//! - `size` and `repeat-expr` of an integer or the id of an earlier attribute
+//!
+//! `enum` and `encoding` are ignored, leaving integers and bytes undecoded, and so are
+//! `instances` and `enums`. `contents` becomes a [`Type::Contents`] that the bytes must match.
+//! Other keys, like `if`, `process` or `switch-on`, are rejected rather than misread.
+//!
+//! ```
+//! use read_primitives::kaitai::parse_ksy;
+//! use read_primitives::schema::Value;
+//! let layout = parse_ksy(
+//!     "
+//! meta:
+//!   id: chunks
+//!   endian: be
+//! seq:
+//!   - id: magic
+//!     contents: CHNK
+//!   - id: num_chunks
+//!     type: u2
+//!   - id: chunks
+//!     type: chunk
+//!     repeat: expr
+//!     repeat-expr: num_chunks
+//! types:
+//!   chunk:
+//!     seq:
+//!       - id: len
+//!         type: u1
+//!       - id: body
+//!         size: len
+//! ",
+//! )
+//! .unwrap();
+//! let mut bytes: &[u8] = b"CHNK\0\x02\x01a\x02bc";
+//! let record = layout.read(&mut bytes).unwrap();
+//! let Some(Value::Array(chunks)) = record.get("chunks") else { panic!() };
+//! let Value::Record(last) = &chunks[1] else { panic!() };
+//! assert_eq!(Some(&Value::Bytes(b"bc".to_vec())), last.get("body"));
+//! ```
+
+use crate::schema::{Count, Layout, Type};
+use crate::Endian;
+use std::io;
+use yaml_rust2::{Yaml, YamlLoader};
+
+fn unsupported(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Keys of a type that are read or may be ignored
+const TYPE_KEYS: &[&str] = &[
+    "meta",
+    "seq",
+    "types",
+    "instances",
+    "enums",
+    "doc",
+    "doc-ref",
+];
+
+/// Keys of an attribute that are read or may be ignored
+const ATTRIBUTE_KEYS: &[&str] = &[
+    "id",
+    "type",
+    "size",
+    "contents",
+    "repeat",
+    "repeat-expr",
+    "encoding",
+    "enum",
+    "doc",
+    "doc-ref",
+    "-orig-id",
+];
+
+fn check_keys(node: &Yaml, allowed: &[&str], what: &str) -> io::Result<()> {
+    let Some(hash) = node.as_hash() else {
+        return Err(unsupported(format!("{what} is not a map")));
+    };
+    for key in hash.keys() {
+        match key.as_str() {
+            Some(key) if allowed.contains(&key) => {}
+            _ => return Err(unsupported(format!("unsupported key {key:?} in {what}"))),
+        }
+    }
+    Ok(())
+}
+
+fn endian_of(def: &Yaml) -> io::Result<Option<Endian>> {
+    match &def["meta"]["endian"] {
+        Yaml::BadValue => Ok(None),
+        endian => match endian.as_str() {
+            Some("le") => Ok(Some(Endian::Little)),
+            Some("be") => Ok(Some(Endian::Big)),
+            _ => Err(unsupported(format!("unsupported endian {endian:?}"))),
+        },
+    }
+}
+
+/// Type, size and byte order suffix of a built-in numeric type name
+fn primitive(name: &str) -> Option<(Type, usize, Option<Endian>)> {
+    let (base, endian) = match name.len() {
+        4 if name.ends_with("le") => (&name[..2], Some(Endian::Little)),
+        4 if name.ends_with("be") => (&name[..2], Some(Endian::Big)),
+        _ => (name, None),
+    };
+    let (ty, size) = match base {
+        "u1" if endian.is_none() => (Type::U8, 1),
+        "s1" if endian.is_none() => (Type::I8, 1),
+        "u2" => (Type::U16, 2),
+        "s2" => (Type::I16, 2),
+        "u4" => (Type::U32, 4),
+        "s4" => (Type::I32, 4),
+        "u8" => (Type::U64, 8),
+        "s8" => (Type::I64, 8),
+        "f4" => (Type::F32, 4),
+        "f8" => (Type::F64, 8),
+        _ => return None,
+    };
+    Some((ty, size, endian))
+}
+
+fn count(node: &Yaml, id: &str) -> io::Result<Count> {
+    match node {
+        Yaml::Integer(count) => usize::try_from(*count)
+            .map(Count::Fixed)
+            .map_err(|_| unsupported(format!("negative count {count} in attribute {id:?}"))),
+        Yaml::String(name)
+            if name.starts_with(|char: char| char.is_ascii_lowercase())
+                && name
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '_') =>
+        {
+            Ok(Count::Field(name.clone()))
+        }
+        _ => Err(unsupported(format!(
+            "unsupported expression {node:?} in attribute {id:?}"
+        ))),
+    }
+}
+
+fn contents(contents: &Yaml, id: &str) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let items = match contents {
+        Yaml::Array(items) => items.as_slice(),
+        item => std::slice::from_ref(item),
+    };
+    for item in items {
+        match item {
+            Yaml::String(string) => bytes.extend_from_slice(string.as_bytes()),
+            &Yaml::Integer(byte @ 0..=255) => bytes.push(byte as u8),
+            _ => {
+                return Err(unsupported(format!(
+                    "unsupported contents {item:?} in attribute {id:?}"
+                )))
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Converter of a type and the types it uses, tracking the enclosing types for name lookup
+struct Converter<'a> {
+    /// Definitions of the types enclosing the one being converted, innermost last, with their
+    /// byte orders
+    scopes: Vec<(&'a Yaml, Option<Endian>)>,
+    /// Definitions of the types being converted, outermost first
+    active: Vec<&'a Yaml>,
+}
+
+impl<'a> Converter<'a> {
+    /// Definition of the type `name` and the number of scopes enclosing it
+    fn lookup(&self, name: &str) -> Option<(&'a Yaml, usize)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, (def, _))| (&def["types"][name], index + 1))
+            .find(|(def, _)| !def.is_badvalue())
+    }
+
+    /// Convert the type `def` enclosed by the first `depth` scopes, from which it inherits its
+    /// byte order
+    fn convert(&mut self, def: &'a Yaml, depth: usize, what: &str) -> io::Result<Layout> {
+        if self.active.iter().any(|&active| std::ptr::eq(active, def)) {
+            return Err(unsupported(format!("{what} is recursive")));
+        }
+        check_keys(def, TYPE_KEYS, what)?;
+        let endian = match endian_of(def)? {
+            Some(endian) => Some(endian),
+            None => self.scopes[..depth].last().and_then(|&(_, endian)| endian),
+        };
+        let inner = self.scopes.split_off(depth);
+        self.scopes.push((def, endian));
+        self.active.push(def);
+        let layout = self.convert_seq(def, endian);
+        self.active.pop();
+        self.scopes.pop();
+        self.scopes.extend(inner);
+        layout
+    }
+
+    fn convert_seq(&mut self, def: &'a Yaml, endian: Option<Endian>) -> io::Result<Layout> {
+        let mut layout = Layout::new(endian.unwrap_or(Endian::Little));
+        let attributes = match &def["seq"] {
+            Yaml::BadValue => &[][..],
+            Yaml::Array(attributes) => attributes,
+            seq => return Err(unsupported(format!("seq {seq:?} is not a list"))),
+        };
+        for attribute in attributes {
+            let id = attribute["id"]
+                .as_str()
+                .ok_or_else(|| unsupported("attribute without an id".into()))?;
+            check_keys(attribute, ATTRIBUTE_KEYS, &format!("attribute {id:?}"))?;
+            let size = &attribute["size"];
+            let (mut ty, field_endian) = match (&attribute["contents"], attribute["type"].as_str())
+            {
+                (Yaml::BadValue, None | Some("str")) if !size.is_badvalue() => {
+                    (Type::Bytes(count(size, id)?), None)
+                }
+                (Yaml::BadValue, None | Some("str")) => {
+                    return Err(unsupported(format!("attribute {id:?} has no size")));
+                }
+                (Yaml::BadValue, Some(name)) => {
+                    if !size.is_badvalue() {
+                        return Err(unsupported(format!(
+                            "size of attribute {id:?} with type {name:?}"
+                        )));
+                    }
+                    match primitive(name) {
+                        Some((ty, 1, _)) => (ty, None),
+                        Some((ty, _, Some(endian))) => (ty, Some(endian)),
+                        Some((ty, _, None)) => match endian {
+                            Some(_) => (ty, None),
+                            None => {
+                                return Err(unsupported(format!(
+                                    "type {name:?} of attribute {id:?} needs meta/endian"
+                                )))
+                            }
+                        },
+                        None => {
+                            let (def, depth) = self
+                                .lookup(name)
+                                .ok_or_else(|| unsupported(format!("unknown type {name:?}")))?;
+                            let what = format!("type {name:?}");
+                            (Type::Record(self.convert(def, depth, &what)?), None)
+                        }
+                    }
+                }
+                (bytes, _) => (Type::Contents(contents(bytes, id)?), None),
+            };
+            match (&attribute["repeat"], &attribute["repeat-expr"]) {
+                (Yaml::BadValue, Yaml::BadValue) => {}
+                (repeat, expr) if repeat.as_str() == Some("expr") && !expr.is_badvalue() => {
+                    ty = Type::Array(Box::new(ty), count(expr, id)?);
+                }
+                (repeat, _) => {
+                    return Err(unsupported(format!(
+                        "unsupported repeat {repeat:?} in attribute {id:?}"
+                    )));
+                }
+            }
+            layout = match field_endian {
+                Some(endian) => layout.field_with_endian(id, ty, endian),
+                None => layout.field(id, ty),
+            };
+        }
+        Ok(layout)
+    }
+}
+
+/// Convert the `.ksy` definition in `source` into the [`Layout`] of its root type
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidInput`] if `source` is not YAML, or the definition uses
+/// anything outside the supported subset
+pub fn parse_ksy(source: &str) -> io::Result<Layout> {
+    let docs = YamlLoader::load_from_str(source)
+        .map_err(|err| unsupported(format!("invalid YAML: {err}")))?;
+    let [root] = docs.as_slice() else {
+        return Err(unsupported(format!(
+            "expected 1 YAML document, found {}",
+            docs.len()
+        )));
+    };
+    Converter {
+        scopes: Vec::new(),
+        active: Vec::new(),
+    }
+    .convert(root, 0, "root type")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kaitai::*;
+    use crate::schema::Value;
+
+    #[test]
+    fn primitives() {
+        let layout = parse_ksy(
+            "
+meta:
+  id: header
+  endian: le
+seq:
+  - id: magic
+    contents: [0x7f, 'EL']
+  - id: version
+    type: u1
+    enum: versions
+  - id: entry
+    type: u4be
+  - id: offsets
+    type: s2
+    repeat: expr
+    repeat-expr: 2
+  - id: scale
+    type: f4
+  - id: name
+    type: str
+    size: 3
+    encoding: ASCII
+enums:
+  versions:
+    1: current
+",
+        )
+        .unwrap();
+        let mut bytes: &[u8] = b"\x7fEL\x01\0\0\x01\0\xff\xff\x02\0\0\0\x80\x3fabc";
+        let record = layout.read(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        let values: Vec<_> = record.iter().map(|(_, value)| value.clone()).collect();
+        assert_eq!(
+            vec![
+                Value::Bytes(b"\x7fEL".to_vec()),
+                Value::UInt(1),
+                Value::UInt(256),
+                Value::Array(vec![Value::Int(-1), Value::Int(2)]),
+                Value::Float(1.0),
+                Value::Bytes(b"abc".to_vec()),
+            ],
+            values
+        );
+    }
+
+    #[test]
+    fn nested_endian() {
+        let layout = parse_ksy(
+            "
+seq:
+  - id: inner
+    type: inner
+types:
+  inner:
+    meta:
+      endian: be
+    seq:
+      - id: value
+        type: leaf
+    types:
+      leaf:
+        seq:
+          - id: value
+            type: u2
+",
+        )
+        .unwrap();
+        let record = layout.read(&mut &[1, 2][..]).unwrap();
+        let Some(Value::Record(inner)) = record.get("inner") else {
+            panic!("inner is not a record");
+        };
+        let Some(Value::Record(leaf)) = inner.get("value") else {
+            panic!("leaf is not a record");
+        };
+        assert_eq!(Some(&Value::UInt(0x0102)), leaf.get("value"));
+    }
+
+    #[test]
+    fn endian_of_defining_type() {
+        // `leaf` is defined in the little endian root, so it is little endian wherever it is used
+        let layout = parse_ksy(
+            "
+meta:
+  endian: le
+seq:
+  - id: inner
+    type: inner
+types:
+  inner:
+    meta:
+      endian: be
+    seq:
+      - id: value
+        type: leaf
+  leaf:
+    seq:
+      - id: value
+        type: u2
+",
+        )
+        .unwrap();
+        let record = layout.read(&mut &[1, 2][..]).unwrap();
+        let Some(Value::Record(inner)) = record.get("inner") else {
+            panic!("inner is not a record");
+        };
+        let Some(Value::Record(leaf)) = inner.get("value") else {
+            panic!("leaf is not a record");
+        };
+        assert_eq!(Some(&Value::UInt(0x0201)), leaf.get("value"));
+    }
+
+    #[test]
+    fn contents() {
+        let layout = parse_ksy("seq:\n  - id: magic\n    contents: [0x7f, 'EL']\n").unwrap();
+        let err = layout.read(&mut &b"\x7fEX"[..]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "expected b\"\\x7fEL\", found b'X' instead of b'L' at byte 2",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        for (source, message) in [
+            ("seq:\n  - id: a\n    type: u2\n", "type \"u2\" of attribute \"a\" needs meta/endian"),
+            ("seq:\n  - id: a\n    type: u1\n    if: b\n", "unsupported key String(\"if\") in attribute \"a\""),
+            ("seq:\n  - id: a\n    size: b + 1\n", "unsupported expression String(\"b + 1\") in attribute \"a\""),
+            ("seq:\n  - id: a\n    type: u1\n    repeat: eos\n", "unsupported repeat String(\"eos\") in attribute \"a\""),
+            ("seq:\n  - id: a\n    type: b\n", "unknown type \"b\""),
+            ("seq:\n  - id: a\n    type: a\ntypes:\n  a:\n    seq:\n      - id: a\n        type: a\n", "type \"a\" is recursive"),
+            ("seq:\n  - id: a\n", "attribute \"a\" has no size"),
+            ("seq: [", "invalid YAML"),
+        ] {
+            let err = parse_ksy(source).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+            assert!(
+                err.to_string().starts_with(message),
+                "{err} does not start with {message}"
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::{BudgetReader, InternetChecksum};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+/// A cross-cutting wrapper around a reader, stacked onto one with [`ReadLayer::layer`]
+///
+/// Layers box the reader they wrap, so a stack of them is one [`Layered`] type however many
+/// there are. Layers that observe the bytes, like [`Counting`] and [`Hashing`], are handles
+/// whose clones report what the layer saw.
+pub trait Layer {
+    /// Wrap `inner`
+    fn wrap<'a>(self, inner: Box<dyn Read + 'a>) -> Box<dyn Read + 'a>
+    where
+        Self: 'a;
+}
+
+/// Reader made of a stack of [`Layer`]s
+///
+/// ```
+/// use read_primitives::{Counting, Hashing, InternetChecksum, Limit, ReadLayer, ReadU16, ReadU8};
+/// let count = Counting::new();
+/// let sum = Hashing::new(InternetChecksum::new());
+/// // the first layer is innermost and sees the bytes first
+/// let mut reader = (&[0x45, 0, 0, 0x73, 0xff][..])
+///     .layer(Limit(4))
+///     .layer(count.clone())
+///     .layer(sum.clone());
+/// assert_eq!(0x4500, reader.read_be_u16().unwrap());
+/// assert_eq!(0x0073, reader.read_be_u16().unwrap());
+/// assert!(reader.read_u8().is_err());
+/// assert_eq!(!(0x4500u16 + 0x0073), sum.value());
+/// assert_eq!(4, count.count());
+/// ```
+pub struct Layered<'a>(Box<dyn Read + 'a>);
+
+impl<'a> Layered<'a> {
+    /// Stack `layer` on top
+    pub fn layer<L: Layer + 'a>(self, layer: L) -> Layered<'a> {
+        Layered(layer.wrap(self.0))
+    }
+
+    /// Unwrap the stack
+    pub fn into_inner(self) -> Box<dyn Read + 'a> {
+        self.0
+    }
+}
+
+impl fmt::Debug for Layered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Layered").finish_non_exhaustive()
+    }
+}
+
+impl Read for Layered<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+/// Trait to stack [`Layer`]s onto a reader
+pub trait ReadLayer: Read {
+    /// Wrap the reader in `layer`
+    fn layer<'a, L: Layer + 'a>(self, layer: L) -> Layered<'a>
+    where
+        Self: Sized + 'a,
+    {
+        Layered(layer.wrap(Box::new(self)))
+    }
+}
+impl<R> ReadLayer for R where R: Read {}
+
+/// Layer counting the bytes read through it
+#[derive(Debug, Clone, Default)]
+pub struct Counting(Rc<Cell<u64>>);
+
+impl Counting {
+    /// Counter at 0
+    pub fn new() -> Self {
+        Counting::default()
+    }
+
+    /// Number of bytes read through the layer so far
+    pub fn count(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl Layer for Counting {
+    fn wrap<'a>(self, inner: Box<dyn Read + 'a>) -> Box<dyn Read + 'a>
+    where
+        Self: 'a,
+    {
+        struct Reader<'a>(Box<dyn Read + 'a>, Counting);
+        impl Read for Reader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let read = self.0.read(buf)?;
+                (self.1).0.set(self.1.count() + read as u64);
+                Ok(read)
+            }
+        }
+        Box::new(Reader(inner, self))
+    }
+}
+
+/// Layer failing once more than this many bytes were read through it, with a
+/// [`BudgetReader`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit(pub u64);
+
+impl Layer for Limit {
+    fn wrap<'a>(self, inner: Box<dyn Read + 'a>) -> Box<dyn Read + 'a>
+    where
+        Self: 'a,
+    {
+        Box::new(BudgetReader::new(inner, self.0))
+    }
+}
+
+/// Hash or checksum computed incrementally over bytes added in pieces, for [`Hashing`]
+///
+/// Implemented for [`InternetChecksum`], and with the `crc` feature for the 16, 32 and 64 bit
+/// [`crc::Digest`]s.
+pub trait RunningHash {
+    /// Value of the hash
+    type Output;
+
+    /// Add `bytes`
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Hash of the bytes added so far
+    fn value(&self) -> Self::Output;
+}
+
+impl RunningHash for InternetChecksum {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        InternetChecksum::update(self, bytes);
+    }
+
+    fn value(&self) -> u16 {
+        self.checksum()
+    }
+}
+
+macro_rules! impl_running_crc {
+    ($($width:ty),+) => {
+        $(
+            #[cfg(feature = "crc")]
+            impl RunningHash for crc::Digest<'static, $width> {
+                type Output = $width;
+
+                fn update(&mut self, bytes: &[u8]) {
+                    crc::Digest::<'static, $width>::update(self, bytes);
+                }
+
+                fn value(&self) -> $width {
+                    self.clone().finalize()
+                }
+            }
+        )+
+    };
+}
+
+impl_running_crc!(u16, u32, u64);
+
+/// Layer hashing the bytes read through it
+#[derive(Debug, Default)]
+pub struct Hashing<H>(Rc<RefCell<H>>);
+
+impl<H> Clone for Hashing<H> {
+    fn clone(&self) -> Self {
+        Hashing(Rc::clone(&self.0))
+    }
+}
+
+impl<H: RunningHash> Hashing<H> {
+    /// Layer adding the bytes to `hash`
+    pub fn new(hash: H) -> Self {
+        Hashing(Rc::new(RefCell::new(hash)))
+    }
+
+    /// Hash of the bytes read through the layer so far
+    pub fn value(&self) -> H::Output {
+        self.0.borrow().value()
+    }
+}
+
+impl<H: RunningHash> Layer for Hashing<H> {
+    fn wrap<'a>(self, inner: Box<dyn Read + 'a>) -> Box<dyn Read + 'a>
+    where
+        H: 'a,
+    {
+        struct Reader<'a, H>(Box<dyn Read + 'a>, Hashing<H>);
+        impl<H: RunningHash> Read for Reader<'_, H> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let read = self.0.read(buf)?;
+                (self.1).0.borrow_mut().update(&buf[..read]);
+                Ok(read)
+            }
+        }
+        Box::new(Reader(inner, self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Read};
+
+    #[test]
+    fn order_of_layers() {
+        // the limit below the counter stops the read before the counter sees it
+        let count = Counting::new();
+        let mut reader = (&[1u8; 10][..]).layer(Limit(4)).layer(count.clone());
+        reader.read_bytes(4).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(4, count.count());
+
+        let count = Counting::new();
+        let mut all = Vec::new();
+        (&[1u8; 10][..])
+            .layer(count.clone())
+            .layer(Limit(10))
+            .read_to_end(&mut all)
+            .unwrap();
+        assert_eq!((10, 10), (all.len(), count.count()));
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn crc_layer() {
+        const CRC: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        let hash = Hashing::new(CRC.digest());
+        let mut reader = (&b"123456789"[..]).layer(hash.clone());
+        assert_eq!(b"1234", &*reader.read_bytes(4).unwrap());
+        reader.read_bytes(5).unwrap();
+        assert_eq!(0xcbf4_3926, hash.value());
+    }
+}
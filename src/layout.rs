@@ -0,0 +1,316 @@
+//! Reads of structs laid out as a C compiler lays them out in memory
+//!
+//! A [`CType`] describes a struct; [`ReadCLayout::read_c_layout`] inserts and skips padding as
+//! the C ABI of a chosen [`Target`] does, including the trailing padding counted by `sizeof`, so
+//! records written with `fwrite(&s, sizeof s, 1, f)` or found in memory dumps can be read back.
+//!
+//! ```
+//! use read_primitives::format::Value;
+//! use read_primitives::layout::{CType, ReadCLayout, Target};
+//! // struct { uint8_t tag; uint32_t value; uint16_t flags; }
+//! let record = CType::Struct(vec![CType::U8, CType::U32, CType::U16]);
+//! assert_eq!(12, record.size(&Target::X86_64));
+//! let mut bytes: &[u8] = &[7, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0];
+//! assert_eq!(
+//!     vec![Value::UInt(7), Value::UInt(1), Value::UInt(2)],
+//!     bytes.read_c_layout(&record, &Target::X86_64).unwrap()
+//! );
+//! ```
+
+use crate::format::Value;
+use crate::string::read_exact_vec;
+use crate::{Endian, Primitive};
+use std::io::{self, Read};
+
+/// ABI properties of a target that decide the layout of a struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Target {
+    /// Byte order
+    pub endian: Endian,
+    /// Size and alignment of pointers in bytes
+    pub pointer_size: usize,
+    /// Alignment of 8-byte integers and doubles in bytes
+    pub align_8: usize,
+}
+
+impl Target {
+    /// x86-64 System V and Windows
+    pub const X86_64: Target = Target {
+        endian: Endian::Little,
+        pointer_size: 8,
+        align_8: 8,
+    };
+    /// 32-bit x86 System V, which aligns 8-byte scalars to 4 bytes
+    pub const I386: Target = Target {
+        endian: Endian::Little,
+        pointer_size: 4,
+        align_8: 4,
+    };
+    /// 64-bit ARM
+    pub const AARCH64: Target = Target {
+        endian: Endian::Little,
+        pointer_size: 8,
+        align_8: 8,
+    };
+    /// 32-bit ARM EABI
+    pub const ARM: Target = Target {
+        endian: Endian::Little,
+        pointer_size: 4,
+        align_8: 8,
+    };
+
+    /// Check that pointers are 2, 4 or 8 bytes and that `align_8` is 1, 2, 4 or 8
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if either is not
+    pub fn validate(&self) -> io::Result<()> {
+        if !matches!(self.pointer_size, 2 | 4 | 8) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("pointer size of {} bytes", self.pointer_size),
+            ));
+        }
+        if !matches!(self.align_8, 1 | 2 | 4 | 8) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("alignment of {} bytes for 8-byte scalars", self.align_8),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A C type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CType {
+    /// `uint8_t`, `unsigned char`
+    U8,
+    /// `int8_t`, `signed char`
+    I8,
+    /// `uint16_t`
+    U16,
+    /// `int16_t`
+    I16,
+    /// `uint32_t`
+    U32,
+    /// `int32_t`
+    I32,
+    /// `uint64_t`
+    U64,
+    /// `int64_t`
+    I64,
+    /// `float`
+    F32,
+    /// `double`
+    F64,
+    /// Any pointer, read as its address
+    Pointer,
+    /// Fixed-size array `T[N]`
+    Array(Box<CType>, usize),
+    /// Struct with fields in declaration order
+    Struct(Vec<CType>),
+}
+
+fn align_up(offset: usize, align: usize) -> Option<usize> {
+    offset.checked_next_multiple_of(align)
+}
+
+impl CType {
+    /// `_Alignof` on `target`
+    pub fn align(&self, target: &Target) -> usize {
+        match self {
+            CType::U8 | CType::I8 => 1,
+            CType::U16 | CType::I16 => 2,
+            CType::U32 | CType::I32 | CType::F32 => 4,
+            CType::U64 | CType::I64 | CType::F64 => target.align_8,
+            CType::Pointer => target.pointer_size,
+            CType::Array(element, _) => element.align(target),
+            CType::Struct(fields) => fields
+                .iter()
+                .map(|field| field.align(target))
+                .max()
+                .unwrap_or(1),
+        }
+    }
+
+    /// `sizeof` on `target`, including trailing padding
+    /// # Panics
+    /// panics if an alignment of `target` is 0 or the size overflows `usize`
+    pub fn size(&self, target: &Target) -> usize {
+        self.checked_size(target)
+            .expect("size of the C type overflows usize")
+    }
+
+    /// `sizeof` on `target`, including trailing padding, or `None` if an alignment of `target` is
+    /// 0 or the size overflows `usize`
+    pub fn checked_size(&self, target: &Target) -> Option<usize> {
+        match self {
+            CType::U8 | CType::I8 => Some(1),
+            CType::U16 | CType::I16 => Some(2),
+            CType::U32 | CType::I32 | CType::F32 => Some(4),
+            CType::U64 | CType::I64 | CType::F64 => Some(8),
+            CType::Pointer => Some(target.pointer_size),
+            CType::Array(element, len) => element.checked_size(target)?.checked_mul(*len),
+            CType::Struct(fields) => {
+                let mut end = 0usize;
+                for field in fields {
+                    end = align_up(end, field.align(target))?
+                        .checked_add(field.checked_size(target)?)?;
+                }
+                align_up(end, self.align(target))
+            }
+        }
+    }
+
+    /// Decode the value at the start of `bytes`, which holds at least `size` bytes, on a
+    /// [validated](Target::validate) `target`
+    fn decode(&self, bytes: &[u8], target: &Target, values: &mut Vec<Value>) {
+        fn get<T: Primitive>(bytes: &[u8], endian: Endian) -> T {
+            let mut array = T::Bytes::default();
+            array.as_mut().copy_from_slice(&bytes[..T::SIZE]);
+            T::from_endian_bytes(array, endian)
+        }
+        let endian = target.endian;
+        let value = match self {
+            CType::U8 => Value::UInt(get::<u8>(bytes, endian).into()),
+            CType::I8 => Value::Int(get::<i8>(bytes, endian).into()),
+            CType::U16 => Value::UInt(get::<u16>(bytes, endian).into()),
+            CType::I16 => Value::Int(get::<i16>(bytes, endian).into()),
+            CType::U32 => Value::UInt(get::<u32>(bytes, endian).into()),
+            CType::I32 => Value::Int(get::<i32>(bytes, endian).into()),
+            CType::U64 => Value::UInt(get(bytes, endian)),
+            CType::I64 => Value::Int(get(bytes, endian)),
+            CType::F32 => Value::Float(get::<f32>(bytes, endian).into()),
+            CType::F64 => Value::Float(get(bytes, endian)),
+            CType::Pointer => Value::UInt(match target.pointer_size {
+                2 => get::<u16>(bytes, endian).into(),
+                4 => get::<u32>(bytes, endian).into(),
+                _ => get(bytes, endian),
+            }),
+            CType::Array(element, len) => {
+                let size = element.size(target);
+                // an element without bytes holds no scalars, however many there are
+                if size == 0 {
+                    return;
+                }
+                for index in 0..*len {
+                    element.decode(&bytes[index * size..], target, values);
+                }
+                return;
+            }
+            CType::Struct(fields) => {
+                let mut offset = 0usize;
+                for field in fields {
+                    let size = field.size(target);
+                    offset = offset.next_multiple_of(field.align(target));
+                    field.decode(&bytes[offset..], target, values);
+                    offset += size;
+                }
+                return;
+            }
+        };
+        values.push(value);
+    }
+}
+
+/// Trait to read values laid out by a [`CType`]
+pub trait ReadCLayout: Read {
+    /// Read `sizeof` bytes of `ty` on `target` and decode its scalars, skipping padding
+    ///
+    /// Fields of nested structs and elements of arrays are flattened into the returned values in
+    /// declaration order.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `target` is not
+    /// [valid](Target::validate) or the size of `ty` overflows `usize`,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_c_layout(&mut self, ty: &CType, target: &Target) -> io::Result<Vec<Value>> {
+        target.validate()?;
+        let size = ty.checked_size(target).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "size of the C type overflows usize",
+            )
+        })?;
+        let bytes = read_exact_vec(self, size)?;
+        let mut values = Vec::new();
+        ty.decode(&bytes, target, &mut values);
+        Ok(values)
+    }
+}
+impl<R> ReadCLayout for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::layout::*;
+
+    #[test]
+    fn layout() {
+        // struct { char c; double d; void *p; short s[3]; }
+        let record = CType::Struct(vec![
+            CType::I8,
+            CType::F64,
+            CType::Pointer,
+            CType::Array(Box::new(CType::I16), 3),
+        ]);
+        assert_eq!(
+            (32, 8),
+            (record.size(&Target::X86_64), record.align(&Target::X86_64))
+        );
+        assert_eq!(
+            (24, 4),
+            (record.size(&Target::I386), record.align(&Target::I386))
+        );
+        assert_eq!(
+            (32, 8),
+            (record.size(&Target::ARM), record.align(&Target::ARM))
+        );
+        assert_eq!(1, CType::Struct(Vec::new()).align(&Target::X86_64));
+    }
+
+    #[test]
+    fn read_nested() {
+        // struct { uint16_t a; struct { uint8_t b; uint32_t c; } inner; } on a big endian target
+        let inner = CType::Struct(vec![CType::U8, CType::U32]);
+        let outer = CType::Struct(vec![CType::U16, inner]);
+        let target = Target {
+            endian: Endian::Big,
+            ..Target::I386
+        };
+        let mut bytes: &[u8] = &[0, 1, 0xee, 0xee, 2, 0xee, 0xee, 0xee, 0, 0, 0, 3, 9];
+        assert_eq!(
+            vec![Value::UInt(1), Value::UInt(2), Value::UInt(3)],
+            bytes.read_c_layout(&outer, &target).unwrap()
+        );
+        assert_eq!([9], bytes);
+        let err = bytes.read_c_layout(&outer, &target).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn invalid() {
+        let huge = CType::Array(Box::new(CType::U64), usize::MAX / 4);
+        assert_eq!(None, huge.checked_size(&Target::X86_64));
+        let err = (&[0u8; 8][..])
+            .read_c_layout(&huge, &Target::X86_64)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let empty = CType::Array(Box::new(CType::Struct(Vec::new())), usize::MAX);
+        let value = (&[0u8; 8][..]).read_c_layout(&empty, &Target::X86_64);
+        assert!(value.unwrap().is_empty());
+        for target in [
+            Target {
+                pointer_size: 3,
+                ..Target::X86_64
+            },
+            Target {
+                align_8: 0,
+                ..Target::X86_64
+            },
+        ] {
+            assert!(target.validate().is_err());
+            let err = (&[0u8; 8][..])
+                .read_c_layout(&CType::Struct(vec![CType::Pointer, CType::U64]), &target)
+                .unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        }
+    }
+}
@@ -9,40 +9,441 @@ read-primitives provides traits to read primitive types from any type that imple
     let float = bytes.as_slice().read_le_f64().unwrap();
     assert_eq!(std::f64::consts::PI, float)
 ```
+
+ # `no_std`
+
+Without the default `std` feature the crate is `no_std`; [`SliceReader`], the [`parse`] functions
+and the `embedded-io`/`embedded-io-async` integrations remain available, as does decoding from
+`ringbuf` and `bbqueue` consumers with the features of the same name. The `alloc` feature adds
+the helpers that return collections.
+
+ # Allocation-free builds
+
+Without `alloc`, which `std` and `bytes` enable, the crate doesn't link `alloc` at all, so every
+API it exposes, derived `no_std` reads included, is structurally unable to allocate. The
+`checks/no-alloc` crate is a `no_std` library without a global allocator over the
+allocation-free features, whose build fails if `alloc` is linked in:
+`cargo build --manifest-path checks/no-alloc/Cargo.toml --target thumbv7em-none-eabihf`.
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+#[cfg(feature = "std")]
 use std::io::{self, Read};
 
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[macro_use]
+mod async_read;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[macro_use]
+mod async_write;
+#[cfg(feature = "std")]
+mod ascii;
+#[cfg(feature = "std")]
+pub mod asn1;
+#[cfg(feature = "std")]
+pub mod audio;
+#[cfg(feature = "bbqueue")]
+pub mod bbqueue;
+mod bitfield;
+#[cfg(feature = "std")]
+mod bits;
+#[cfg(feature = "std")]
+mod borrowed;
+#[cfg(feature = "std")]
+pub mod bson;
+#[cfg(feature = "std")]
+mod budget;
+#[cfg(feature = "bumpalo")]
+mod bump;
+mod byte_order;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+#[cfg(feature = "std")]
+pub mod cbor;
+#[cfg(feature = "std")]
+mod checksum;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod combinator;
+mod convert;
+#[cfg(feature = "std")]
+mod ctx;
+#[cfg(feature = "std")]
+mod decimal;
+#[cfg(feature = "std")]
+mod decoder;
+#[cfg(feature = "std")]
+mod delta;
+#[cfg(feature = "std")]
+pub mod dotnet;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "embedded-io-async")]
+pub mod embedded_async;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod endian;
+#[cfg(feature = "std")]
+mod endian_io;
+#[cfg(feature = "std")]
+mod eof;
+mod error;
+#[cfg(feature = "std")]
+mod expect;
+#[cfg(feature = "std")]
+mod fixed;
+#[cfg(feature = "std")]
+mod float;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+mod fourcc;
+#[cfg(feature = "futures-io")]
+pub mod futures;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+mod hex;
+#[cfg(feature = "std")]
+mod incremental;
+#[cfg(feature = "std")]
+mod inet_checksum;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(feature = "std")]
+pub mod java;
+#[cfg(feature = "kaitai")]
+pub mod kaitai;
+#[cfg(feature = "std")]
+mod layer;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "std")]
+mod limits;
+#[cfg(any(feature = "glam", feature = "nalgebra"))]
+pub mod math;
+#[cfg(feature = "std")]
+mod migrate;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "std")]
+pub mod msgpack;
+#[cfg(all(feature = "std", any(unix, windows)))]
+mod os;
+#[cfg(feature = "std")]
+mod outline;
+#[cfg(feature = "std")]
+pub mod owned;
+pub mod parse;
+#[cfg(feature = "std")]
+mod peek;
+#[cfg(feature = "pixel")]
+pub mod pixel;
+mod primitive;
+#[cfg(feature = "std")]
+pub mod protobuf;
+#[cfg(feature = "std")]
+mod ptr;
+#[cfg(feature = "num-rational")]
+mod ratio;
+#[cfg(all(feature = "std", any(unix, windows)))]
+mod read_at;
+#[cfg(feature = "std")]
+mod read_from;
+#[cfg(feature = "std")]
+mod rewind;
+#[cfg(feature = "std")]
+mod ring;
+#[cfg(feature = "ringbuf")]
+pub mod ringbuf;
+#[cfg(feature = "std")]
+mod scatter;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+mod scope;
+#[cfg(feature = "std")]
+mod sentinel;
+#[cfg(feature = "std")]
+mod session;
+mod slice;
+#[cfg(feature = "std")]
+mod strided;
+#[cfg(feature = "std")]
+mod string;
+#[cfg(feature = "std")]
+mod string_table;
+#[cfg(feature = "std")]
+mod swap;
+#[cfg(feature = "std")]
+mod table;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "std")]
+mod trace;
+#[cfg(feature = "std")]
+mod varint;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod wide;
+#[cfg(feature = "std")]
+mod words;
+#[cfg(feature = "std")]
+mod write;
+#[cfg(feature = "std")]
+mod write_to;
+#[cfg(feature = "std")]
+pub use ascii::ReadAscii;
+#[doc(hidden)]
+pub use bitfield::__bitfield_width;
+pub use bitfield::Bitfield;
+#[cfg(feature = "std")]
+pub use bits::BitReader;
+#[cfg(feature = "std")]
+pub use borrowed::ReadBorrowed;
+#[cfg(feature = "std")]
+pub use budget::BudgetReader;
+#[cfg(feature = "bumpalo")]
+pub use bump::ReadFromIn;
+#[cfg(feature = "std")]
+pub use byte_order::ReadByteOrder;
+pub use byte_order::{BigEndian, ByteOrder, FromByteOrder, LittleEndian};
+#[cfg(feature = "std")]
+pub use checksum::{Checksum, ChecksumMismatch, ReadChecksummed};
+pub use convert::{SaturatingFrom, WrappingFrom};
+#[cfg(feature = "std")]
+pub use ctx::ReadFromCtx;
+#[cfg(feature = "std")]
+pub use decimal::{Decimal, ReadDecimal};
+#[cfg(feature = "std")]
+pub use decoder::Decoder;
+#[cfg(feature = "std")]
+pub use delta::{DeltaInt, ReadDelta};
+#[cfg(feature = "encoding")]
+pub use encoding::ReadEncoded;
+pub use endian::{detect_endianness, BitOrder, Endian, MixedEndian, WordOrder};
+#[cfg(feature = "std")]
+pub use endian_io::{EndianReader, EndianWriter};
+#[cfg(feature = "std")]
+pub use eof::EnsureEof;
+#[cfg(feature = "std")]
+pub use error::ConversionError;
+#[cfg(feature = "error-context")]
+pub use error::PrimitiveError;
+#[cfg(feature = "std")]
+pub use error::TooLongError;
+pub use error::{Needed, ParseError};
+#[cfg(feature = "std")]
+pub use expect::ReadExpect;
+#[cfg(feature = "std")]
+pub use fixed::{read_columns, read_columns_into, ByteSize, Columns, FixedSize};
+#[cfg(feature = "std")]
+pub use float::ReadFloat;
+#[cfg(feature = "std")]
+pub use fourcc::{FourCC, FourCCCharset, ReadFourCC};
+#[cfg(feature = "std")]
+pub use hex::HexReader;
+#[cfg(feature = "std")]
+pub use incremental::{IncrementalDecoder, ReadIncremental};
+#[cfg(feature = "std")]
+pub use inet_checksum::{InternetChecksum, InternetChecksumReader};
+#[cfg(feature = "std")]
+pub use iter::{IterReader, TryIterReader};
+#[cfg(feature = "std")]
+pub use layer::{Counting, Hashing, Layer, Layered, Limit, ReadLayer, RunningHash};
+#[cfg(feature = "std")]
+pub use limits::{DepthGuard, LimitExceeded, Limits};
+#[cfg(feature = "std")]
+pub use migrate::{Migrate, Migrations};
+#[cfg(feature = "mmap")]
+pub use mmap::MmapReader;
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub use os::ReadOsString;
+#[cfg(feature = "std")]
+pub use peek::PeekReader;
+pub use primitive::Primitive;
+#[cfg(feature = "std")]
+pub use primitive::{PrimitiveIter, ReadPrimitive};
+#[cfg(feature = "std")]
+pub use ptr::{PtrWidth, ReadPtr};
+#[cfg(feature = "num-rational")]
+pub use ratio::ReadRatio;
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub use read_at::ReadAt;
+#[cfg(all(feature = "rayon", any(unix, windows)))]
+pub use read_at::{par_read_ranges, par_read_records};
+#[cfg(all(feature = "derive", feature = "std"))]
+#[doc(hidden)]
+pub use read_from::derive_support as __private;
+#[cfg(feature = "std")]
+pub use read_from::{FieldError, ReadFrom, ReadFromSlice, ReadTagged};
+#[cfg(feature = "derive")]
+pub use read_primitives_derive::{ReadFrom, ReadTagged, WriteTo};
+#[cfg(feature = "std")]
+pub use rewind::RewindReader;
+#[cfg(feature = "std")]
+pub use ring::RingBuffer;
+#[cfg(feature = "std")]
+pub use scatter::ScatterReader;
+#[cfg(feature = "std")]
+pub use scope::{ReadWithin, Scope};
+#[cfg(feature = "std")]
+pub use sentinel::ReadUntilSentinel;
+#[cfg(feature = "std")]
+pub use session::{SequenceError, Sequenced, Session};
+pub use slice::{SliceReader, Strided};
+#[cfg(feature = "std")]
+pub use strided::ReadStrided;
+#[cfg(feature = "std")]
+pub use string::{NamePadding, ReadString, WriteString};
+#[cfg(feature = "std")]
+pub use string_table::{LazyStringTable, StringTable};
+#[cfg(feature = "std")]
+pub use swap::ByteSwapReader;
+#[cfg(feature = "std")]
+pub use table::{RecordTable, Records};
+#[cfg(feature = "std")]
+pub use trace::{Trace, TraceEntry, TraceMark};
+#[cfg(feature = "std")]
+pub use varint::{ReadVarint, WriteVarint};
+#[cfg(feature = "std")]
+pub use words::{ReadMixedEndian, ReadModbus, ReadWords};
+#[cfg(feature = "std")]
+pub use write::*;
+#[cfg(feature = "std")]
+pub use write_to::WriteTo;
+
+/// Upper bound in bytes for the capacity that counted reads reserve up front
+///
+/// The `Vec` and `String` returning reads grow past this only as data actually arrives, so a
+/// corrupt count allocates at most about as much as the input holds. To reject large lengths
+/// before reading anything, use the `_max` variants of the length-prefixed reads, such as
+/// [`ReadString::read_le_string_u32_max`], or check counts read by hand.
+#[cfg(feature = "std")]
+pub const PREALLOC_LIMIT: usize = 64 * 1024;
+
+/// Check a length read from the input against the caller's maximum
+#[cfg(feature = "std")]
+fn check_max_len(len: usize, max_len: usize) -> io::Result<()> {
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("length {len} exceeds the maximum of {max_len}"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "std", any(feature = "heapless", feature = "arrayvec")))]
+fn capacity_exceeded(count: usize, capacity: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("count {count} exceeds capacity {capacity}"),
+    )
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type>](&mut self) -> io::Result<$type> {
+                let mut bytes = [0u8; std::mem::size_of::<$type>()];
+                self.read_exact(&mut bytes)
+                    .map_err(|err| error::context(err, stringify!($type), stringify!($order)))?;
+                Ok($type::[<from_ $order _bytes>](bytes))
+            }
+            #[doc = "Read `count` " $type " in " $order_doc " into a `Vec`"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _vec>](&mut self, count: usize) -> io::Result<Vec<$type>> {
+                let mut reader = self;
+                outline::read_vec(&mut reader, count, $type::[<from_ $order _bytes>])
+            }
+            #[doc = "Read `count` " $type " in " $order_doc " into a `Vec`, reporting progress"]
+            #[doc = ""]
+            #[doc = "Elements are decoded in chunks of at most `granularity`, and `progress` is called with the number of"]
+            #[doc = "elements read so far every time another `granularity` elements (or the last element) are done."]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _vec_with_progress>](&mut self, count: usize, granularity: usize, mut progress: impl FnMut(usize)) -> io::Result<Vec<$type>> {
+                let mut reader = self;
+                let mut vec = Vec::with_capacity(count.min(PREALLOC_LIMIT / std::mem::size_of::<$type>()));
+                outline::read_values(&mut reader, count, granularity, $type::[<from_ $order _bytes>], &mut |value| vec.push(value), &mut progress)?;
+                Ok(vec)
+            }
+            #[doc = "Read the rest of the reader as " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidData`] if the number of remaining bytes is not a multiple of " $type "'s size,"]
+            #[doc = "or when [`Read::read_to_end`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end) errors"]
+            fn [<read_to_end_ $order _ $type>](&mut self) -> io::Result<Vec<$type>> {
+                let mut reader = self;
+                outline::read_to_end(&mut reader, $type::[<from_ $order _bytes>])
+            }
+            #[cfg(feature = "allocator-api2")]
+            #[doc = "Read `count` " $type " in " $order_doc " into an [`allocator_api2::vec::Vec`] allocated in `alloc`"]
+            #[doc = ""]
+            #[doc = "With the `nightly` feature of `allocator-api2` this is the standard `Vec` and allocator API."]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _vec_in>]<A: allocator_api2::alloc::Allocator>(&mut self, count: usize, alloc: A) -> io::Result<allocator_api2::vec::Vec<$type, A>> {
+                let mut reader = self;
+                let mut vec = allocator_api2::vec::Vec::with_capacity_in(count.min(PREALLOC_LIMIT / std::mem::size_of::<$type>()), alloc);
+                outline::read_values(&mut reader, count, usize::MAX, $type::[<from_ $order _bytes>], &mut |value| vec.push(value), &mut |_| {})?;
+                Ok(vec)
+            }
+            #[cfg(feature = "heapless")]
+            #[doc = "Read `count` " $type " in " $order_doc " into a [`heapless::Vec`] with capacity `N`"]
+            #[doc = "# Errors"]
+            #[doc = "errors if `count` exceeds `N`, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _heapless>]<const N: usize>(&mut self, count: usize) -> io::Result<heapless::Vec<$type, N>> {
+                if count > N {
+                    return Err(capacity_exceeded(count, N));
+                }
+                let mut reader = self;
+                let mut vec = heapless::Vec::new();
+                // count was checked against the capacity, so the pushes can't fail
+                outline::read_values(&mut reader, count, usize::MAX, $type::[<from_ $order _bytes>], &mut |value| { let _ = vec.push(value); }, &mut |_| {})?;
+                Ok(vec)
+            }
+            #[cfg(feature = "arrayvec")]
+            #[doc = "Read `count` " $type " in " $order_doc " into an [`arrayvec::ArrayVec`] with capacity `CAP`"]
+            #[doc = "# Errors"]
+            #[doc = "errors if `count` exceeds `CAP`, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ $type _arrayvec>]<const CAP: usize>(&mut self, count: usize) -> io::Result<arrayvec::ArrayVec<$type, CAP>> {
+                if count > CAP {
+                    return Err(capacity_exceeded(count, CAP));
+                }
+                let mut reader = self;
+                let mut vec = arrayvec::ArrayVec::new();
+                outline::read_values(&mut reader, count, usize::MAX, $type::[<from_ $order _bytes>], &mut |value| vec.push(value), &mut |_| {})?;
+                Ok(vec)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std")]
 macro_rules! impl_traits {
     ($($type:ty),+) => {
         $(
             ::paste::paste!{
-                #[doc = "Trait to read "$type "."]
+                #[doc = "Trait to read " $type "."]
                 #[doc = "# Errors"]
                 #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
                 pub trait [<Read $type:camel>]: Read {
-                    #[doc = "Read " $type "in native byte order"]
-                    fn [<read_ne_  $type>](&mut self) -> io::Result<$type> {
-                        let mut bytes = [0u8; std::mem::size_of::<$type>()];
-                        self.read_exact(&mut bytes)?;
-                        Ok($type::from_ne_bytes(bytes))
-                    }
-                    #[doc = "Read " $type "in little endian byte order"]
-                    #[doc = "# Errors"]
-                    #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
-                    fn [<read_le_  $type>](&mut self) -> io::Result<$type> {
-                        let mut bytes = [0u8; std::mem::size_of::<$type>()];
-                        self.read_exact(&mut bytes)?;
-                        Ok($type::from_le_bytes(bytes))
-                    }
-                    #[doc = "Read " $type "in big endian byte order"]
-                    #[doc = "# Errors"]
-                    #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
-                    fn [<read_be_  $type>](&mut self) -> io::Result<$type> {
-                        let mut bytes = [0u8; std::mem::size_of::<$type>()];
-                        self.read_exact(&mut bytes)?;
-                        Ok($type::from_be_bytes(bytes))
-                    }
+                    impl_methods!($type, ne, "native byte order");
+                    impl_methods!($type, le, "little endian byte order");
+                    impl_methods!($type, be, "big endian byte order");
                 }
                 impl<T:Read> [<Read $type:camel>] for T{}
             }
@@ -50,22 +451,122 @@ macro_rules! impl_traits {
     };
 }
 
+#[cfg(feature = "std")]
 impl_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
 
 /// Trait to read u8
+#[cfg(feature = "std")]
 pub trait ReadU8: Read {
     /// Read a u8
     /// # Errors
     /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
     fn read_u8(&mut self) -> io::Result<u8> {
         let mut bytes = [0u8; 1];
-        self.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)
+            .map_err(|err| error::context(err, "u8", ""))?;
         Ok(u8::from_ne_bytes(bytes))
     }
+    /// Read `N` bytes into an array
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_byte_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Read `len` bytes into a `Vec`
+    ///
+    /// The capacity grows as the bytes arrive, see [`PREALLOC_LIMIT`].
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        string::read_exact_vec(self, len)
+    }
+    /// Read `len` bytes into an [`allocator_api2::vec::Vec`] allocated in `alloc`
+    ///
+    /// The capacity grows as the bytes arrive, see [`PREALLOC_LIMIT`].
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    #[cfg(feature = "allocator-api2")]
+    fn read_bytes_in<A: allocator_api2::alloc::Allocator>(
+        &mut self,
+        len: usize,
+        alloc: A,
+    ) -> io::Result<allocator_api2::vec::Vec<u8, A>> {
+        let mut bytes = allocator_api2::vec::Vec::new_in(alloc);
+        let mut buf = [0u8; 4096];
+        while bytes.len() < len {
+            let chunk = &mut buf[..(len - bytes.len()).min(4096)];
+            self.read_exact(chunk)?;
+            bytes.extend_from_slice(chunk);
+        }
+        Ok(bytes)
+    }
+    /// [`ReadU8::read_bytes`], rejecting a `len` over `max_len` before reading
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `len` exceeds `max_len`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bytes_max(&mut self, len: usize, max_len: usize) -> io::Result<Vec<u8>> {
+        check_max_len(len, max_len)?;
+        self.read_bytes(len)
+    }
+    /// Read `len` bytes into `bytes`, replacing its contents but reusing its capacity
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bytes_into_vec(&mut self, len: usize, bytes: &mut Vec<u8>) -> io::Result<()> {
+        string::read_exact_into(self, len, bytes)
+    }
+    /// Fill `buf` with the next bytes
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.read_exact(buf)
+    }
+    /// Fill as much of `buf` as the reader holds, returning how many bytes were read
+    ///
+    /// Unlike [`Read::read`](https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read), a
+    /// count short of `buf.len()` means the reader ended, and unlike [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact)
+    /// the bytes before the end are kept, for tail records of unknown length and truncated headers.
+    /// # Errors
+    /// errors when [`Read::read`](https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read)
+    /// errors with a kind other than [`io::ErrorKind::Interrupted`]
+    fn read_exact_or_eof(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(filled)
+    }
+    /// Read the rest of the reader into a `Vec`, failing if it holds more than `max_len` bytes
+    ///
+    /// Reads at most one byte past `max_len` to find out.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] carrying a [`TooLongError`] with the first
+    /// `max_len` bytes if there are more, or when [`Read::read_to_end`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end) errors
+    fn read_to_vec_limited(&mut self, max_len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(max_len.min(PREALLOC_LIMIT));
+        self.take(max_len.saturating_add(1) as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() > max_len {
+            bytes.truncate(max_len);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                TooLongError::new(max_len, bytes),
+            ));
+        }
+        Ok(bytes)
+    }
 }
+#[cfg(feature = "std")]
 impl<R> ReadU8 for R where R: Read {}
 
 /// Trait to read char
+#[cfg(feature = "std")]
 pub trait ReadChar: Read {
     /// Read a char
     ///  It is a assumed that the char is represented in native byte order
@@ -73,56 +574,130 @@ pub trait ReadChar: Read {
     /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
     fn read_char(&mut self) -> io::Result<Option<char>> {
         let mut bytes = [0u8; 4];
-        self.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)
+            .map_err(|err| error::context(err, "char", "ne"))?;
         Ok(char::from_u32(u32::from_ne_bytes(bytes)))
     }
 }
+#[cfg(feature = "std")]
 impl<R> ReadChar for R where R: Read {}
 
 /// Trait to read bool
+#[cfg(feature = "std")]
 pub trait ReadBool: Read {
     /// Read a bool
     /// # Errors
     /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
     fn read_bool(&mut self) -> io::Result<bool> {
         let mut bytes = [0u8; 1];
-        self.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)
+            .map_err(|err| error::context(err, "bool", ""))?;
         Ok(u8::from_ne_bytes(bytes) != 0)
     }
+    /// Read `count` flags packed into `count.div_ceil(8)` bytes in `order`
+    ///
+    /// The unused bits of the last byte are ignored.
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bool_bitmap(&mut self, count: usize, order: BitOrder) -> io::Result<Vec<bool>> {
+        let bytes = string::read_exact_vec(self, count.div_ceil(8))?;
+        Ok((0..count)
+            .map(|index| bitmap_bit(&bytes, index, order))
+            .collect())
+    }
+    /// Fill `flags` with flags packed into `flags.len().div_ceil(8)` bytes in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_bool_bitmap_into(&mut self, flags: &mut [bool], order: BitOrder) -> io::Result<()> {
+        let bytes = string::read_exact_vec(self, flags.len().div_ceil(8))?;
+        for (index, flag) in flags.iter_mut().enumerate() {
+            *flag = bitmap_bit(&bytes, index, order);
+        }
+        Ok(())
+    }
 }
+#[cfg(feature = "std")]
 impl<R> ReadBool for R where R: Read {}
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+fn bitmap_bit(bytes: &[u8], index: usize, order: BitOrder) -> bool {
+    let shift = match order {
+        BitOrder::MsbFirst => 7 - index % 8,
+        BitOrder::LsbFirst => index % 8,
+    };
+    (bytes[index / 8] >> shift) & 1 != 0
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use crate::*;
+    use std::io;
 
-    macro_rules! impl_tests {
-        ($($type:ty),+) => {
-            $(
-                ::paste::paste! {
-                    #[test]
-                    fn [<read_ne_ $type>]() {
-                        let bytes = [<37 $type>].to_ne_bytes();
-                        let number = bytes.as_slice().[<read_ne_ $type>]().unwrap();
-                        assert_eq!([<37 $type>], number)
-                    }
+    macro_rules! impl_order_tests {
+        ($type:ty, $order:ident) => {
+            ::paste::paste! {
+                #[test]
+                fn [<read_ $order _ $type>]() {
+                    let bytes = [<37 $type>].[<to_ $order _bytes>]();
+                    let number = bytes.as_slice().[<read_ $order _ $type>]().unwrap();
+                    assert_eq!([<37 $type>], number)
+                }
+                #[test]
+                fn [<read_ $order _ $type _vec>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>]()].concat();
+                    let numbers = bytes.as_slice().[<read_ $order _ $type _vec>](2).unwrap();
+                    assert_eq!(vec![[<37 $type>], [<42 $type>]], numbers)
+                }
+                #[test]
+                fn [<read_ $order _ $type _vec_with_progress>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>](), [<7 $type>].[<to_ $order _bytes>]()].concat();
+                    let mut reports = Vec::new();
+                    let numbers = bytes.as_slice().[<read_ $order _ $type _vec_with_progress>](3, 2, |done| reports.push(done)).unwrap();
+                    assert_eq!(vec![[<37 $type>], [<42 $type>], [<7 $type>]], numbers);
+                    assert_eq!(vec![2, 3], reports);
+                }
+                #[test]
+                fn [<read_to_end_ $order _ $type>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>]()].concat();
+                    let numbers = bytes.as_slice().[<read_to_end_ $order _ $type>]().unwrap();
+                    assert_eq!(vec![[<37 $type>], [<42 $type>]], numbers);
+                    let err = bytes[1..].as_ref().[<read_to_end_ $order _ $type>]().unwrap_err();
+                    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+                }
+                #[cfg(feature = "allocator-api2")]
+                #[test]
+                fn [<read_ $order _ $type _vec_in>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>]()].concat();
+                    let numbers = bytes.as_slice().[<read_ $order _ $type _vec_in>](2, allocator_api2::alloc::Global).unwrap();
+                    assert_eq!([[<37 $type>], [<42 $type>]], numbers.as_slice());
+                    assert!(bytes.as_slice().[<read_ $order _ $type _vec_in>](3, allocator_api2::alloc::Global).is_err());
                 }
-                ::paste::paste! {
-                    #[test]
-                    fn [<read_le_ $type>]() {
-                        let bytes = [<37 $type>].to_le_bytes();
-                        let number = bytes.as_slice().[<read_le_ $type>]().unwrap();
-                        assert_eq!([<37 $type>], number)
-                    }
+                #[cfg(feature = "heapless")]
+                #[test]
+                fn [<read_ $order _ $type _heapless>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>]()].concat();
+                    let numbers = bytes.as_slice().[<read_ $order _ $type _heapless>]::<2>(2).unwrap();
+                    assert_eq!([[<37 $type>], [<42 $type>]], numbers.as_slice());
+                    assert!(bytes.as_slice().[<read_ $order _ $type _heapless>]::<1>(2).is_err());
                 }
-                ::paste::paste! {
-                    #[test]
-                    fn [<read_be_ $type>]() {
-                        let bytes = [<37 $type>].to_be_bytes();
-                        let number = bytes.as_slice().[<read_be_ $type>]().unwrap();
-                        assert_eq!([<37 $type>], number)
-                    }
+                #[cfg(feature = "arrayvec")]
+                #[test]
+                fn [<read_ $order _ $type _arrayvec>]() {
+                    let bytes = [[<37 $type>].[<to_ $order _bytes>](), [<42 $type>].[<to_ $order _bytes>]()].concat();
+                    let numbers = bytes.as_slice().[<read_ $order _ $type _arrayvec>]::<2>(2).unwrap();
+                    assert_eq!([[<37 $type>], [<42 $type>]], numbers.as_slice());
+                    assert!(bytes.as_slice().[<read_ $order _ $type _arrayvec>]::<1>(2).is_err());
                 }
+            }
+        };
+    }
+
+    macro_rules! impl_tests {
+        ($($type:ty),+) => {
+            $(
+                impl_order_tests!($type, ne);
+                impl_order_tests!($type, le);
+                impl_order_tests!($type, be);
             )+
         };
     }
@@ -134,4 +709,89 @@ mod test {
         let byte = bytes.as_slice().read_u8().unwrap();
         assert_eq!(37, byte)
     }
+
+    #[test]
+    fn read_byte_array() {
+        let bytes = [1u8, 2, 3, 4, 5];
+        let mut reader = bytes.as_slice();
+        assert_eq!([1, 2, 3], reader.read_byte_array().unwrap());
+        assert!(reader.read_byte_array::<3>().is_err());
+    }
+
+    #[test]
+    fn read_bytes() {
+        let mut reader: &[u8] = &[1, 2, 3, 4, 5, 6];
+        assert_eq!([1, 2], *reader.read_bytes(2).unwrap());
+        let mut buf = [0u8; 2];
+        reader.read_bytes_into(&mut buf).unwrap();
+        assert_eq!([3, 4], buf);
+        let mut vec = vec![9; 8];
+        reader.read_bytes_into_vec(1, &mut vec).unwrap();
+        assert_eq!([5], *vec);
+        let err = reader.read_bytes_max(2, 1).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            reader.read_bytes(2).unwrap_err().kind()
+        );
+        #[cfg(feature = "allocator-api2")]
+        {
+            let long = vec![7u8; 5000];
+            let bytes = long
+                .as_slice()
+                .read_bytes_in(5000, allocator_api2::alloc::Global)
+                .unwrap();
+            assert_eq!(long, bytes.as_slice());
+            assert!((&long[..4])
+                .read_bytes_in(5, allocator_api2::alloc::Global)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn read_exact_or_eof() {
+        let mut reader = io::Read::chain(&[1u8, 2][..], &[3u8][..]);
+        let mut buf = [0u8; 4];
+        assert_eq!(3, reader.read_exact_or_eof(&mut buf).unwrap());
+        assert_eq!([1, 2, 3, 0], buf);
+        assert_eq!(0, reader.read_exact_or_eof(&mut buf).unwrap());
+        assert_eq!(0, reader.read_exact_or_eof(&mut []).unwrap());
+    }
+
+    #[test]
+    fn read_bool_bitmap() {
+        let bytes = [0b1000_0001u8, 0b0000_0010];
+        let flags = bytes
+            .as_slice()
+            .read_bool_bitmap(10, BitOrder::MsbFirst)
+            .unwrap();
+        let set: Vec<_> = (0..10).filter(|&index| flags[index]).collect();
+        assert_eq!([0, 7], *set);
+        let mut flags = [false; 10];
+        bytes
+            .as_slice()
+            .read_bool_bitmap_into(&mut flags, BitOrder::LsbFirst)
+            .unwrap();
+        let set: Vec<_> = (0..10).filter(|&index| flags[index]).collect();
+        assert_eq!([0, 7, 9], *set);
+        assert!(bytes
+            .as_slice()
+            .read_bool_bitmap(17, BitOrder::LsbFirst)
+            .is_err());
+    }
+
+    #[test]
+    fn read_to_vec_limited() {
+        let bytes = [1u8, 2, 3];
+        assert_eq!([1, 2, 3], *bytes.as_slice().read_to_vec_limited(3).unwrap());
+        let err = bytes.as_slice().read_to_vec_limited(2).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = err
+            .into_inner()
+            .unwrap()
+            .downcast::<TooLongError>()
+            .unwrap();
+        assert_eq!(2, err.max_len());
+        assert_eq!([1, 2], *err.into_partial());
+    }
 }
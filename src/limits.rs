@@ -0,0 +1,274 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+
+thread_local! {
+    static CURRENT: Cell<Limits> = const { Cell::new(Limits::DEFAULT) };
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Security policy for a whole decode: how much each field may allocate and how deep it may nest
+///
+/// Installed for the current thread by [`Limits::apply`] or
+/// [`ReadFrom::read_from_with_limits`](crate::ReadFrom::read_from_with_limits), and honored by
+/// every derived read in between, so the policy doesn't have to be passed through each call.
+/// Exceeding a limit is an [`io::ErrorKind::InvalidData`] error carrying a [`LimitExceeded`].
+/// Outside of any [`Limits::apply`], [`Limits::DEFAULT`] is in effect, so runaway nesting in
+/// untrusted input errors instead of overflowing the stack. Async reads are not covered, since
+/// they can move between threads.
+///
+/// ```
+/// use read_primitives::{Endian, Limits, ReadFrom};
+/// # #[cfg(feature = "derive")] {
+/// #[derive(ReadFrom)]
+/// struct Names {
+///     count: u32,
+///     #[read(count = "count")]
+///     names: Vec<u8>,
+/// }
+///
+/// let limits = Limits { max_elements: 16, ..Limits::UNLIMITED };
+/// let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+/// let err = Names::read_from_with_limits(&mut bytes, Endian::Big, limits).err().unwrap();
+/// assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// Most bytes a single string or byte field may hold
+    pub max_bytes_per_field: usize,
+    /// Most elements a single collection field may hold
+    pub max_elements: usize,
+    /// Most derived values that may be nested inside each other, counting the outermost
+    pub max_depth: usize,
+}
+
+impl Limits {
+    /// Nesting allowed by [`Limits::DEFAULT`]
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    /// No limits except a nesting depth of [`Limits::DEFAULT_MAX_DEPTH`], the default
+    pub const DEFAULT: Limits = Limits {
+        max_depth: Limits::DEFAULT_MAX_DEPTH,
+        ..Limits::UNLIMITED
+    };
+
+    /// No limits at all
+    pub const UNLIMITED: Limits = Limits {
+        max_bytes_per_field: usize::MAX,
+        max_elements: usize::MAX,
+        max_depth: usize::MAX,
+    };
+
+    /// Limits in effect on this thread
+    pub fn current() -> Limits {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Run `f` with these limits in effect on this thread, restoring the previous ones after
+    pub fn apply<T>(self, f: impl FnOnce() -> T) -> T {
+        struct Restore(Limits);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.with(|current| current.set(self.0));
+            }
+        }
+        let _restore = Restore(CURRENT.with(|current| current.replace(self)));
+        f()
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::DEFAULT
+    }
+}
+
+/// Which of the [`Limits`] a read exceeded, inside an [`io::ErrorKind::InvalidData`] error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// A field would hold more bytes than [`Limits::max_bytes_per_field`]
+    FieldBytes {
+        /// Length of the field
+        len: usize,
+        /// The limit
+        max: usize,
+    },
+    /// A collection would hold more elements than [`Limits::max_elements`]
+    Elements {
+        /// Number of elements of the collection, or reached so far if it isn't counted
+        count: usize,
+        /// The limit
+        max: usize,
+    },
+    /// Values were nested deeper than [`Limits::max_depth`]
+    Depth {
+        /// The limit
+        max: usize,
+    },
+    /// More bytes were read than a [`BudgetReader`](crate::BudgetReader) allows
+    TotalBytes {
+        /// The limit
+        max: u64,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::FieldBytes { len, max } => {
+                write!(f, "field of {len} bytes exceeds the limit of {max}")
+            }
+            LimitExceeded::Elements { count, max } => {
+                write!(f, "{count} elements exceed the limit of {max}")
+            }
+            LimitExceeded::Depth { max } => write!(f, "values nested deeper than {max}"),
+            LimitExceeded::TotalBytes { max } => write!(f, "more than {max} bytes read in total"),
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl From<LimitExceeded> for io::Error {
+    fn from(err: LimitExceeded) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Check a field length against [`Limits::max_bytes_per_field`]
+pub(crate) fn check_bytes(len: usize) -> io::Result<()> {
+    let max = Limits::current().max_bytes_per_field;
+    if len > max {
+        return Err(LimitExceeded::FieldBytes { len, max }.into());
+    }
+    Ok(())
+}
+
+/// Check a collection length against [`Limits::max_elements`]
+pub(crate) fn check_elements(count: usize) -> io::Result<()> {
+    let max = Limits::current().max_elements;
+    if count > max {
+        return Err(LimitExceeded::Elements { count, max }.into());
+    }
+    Ok(())
+}
+
+/// One level of nesting counted against [`Limits::max_depth`], left when dropped
+///
+/// Derived reads enter one for each value. Recursive [`ReadFrom`](crate::ReadFrom)
+/// implementations written by hand hold one while reading, to be limited the same way.
+///
+/// ```
+/// use read_primitives::{DepthGuard, Endian, ReadFrom};
+/// use std::io::{self, Read};
+///
+/// enum List {
+///     Nil,
+///     Cons(u8, Box<List>),
+/// }
+///
+/// impl ReadFrom for List {
+///     fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+///         let _depth = DepthGuard::enter()?;
+///         let mut value = [0u8];
+///         reader.read_exact(&mut value)?;
+///         Ok(match value[0] {
+///             0 => List::Nil,
+///             value => List::Cons(value, Box::new(List::read_from(reader, endian)?)),
+///         })
+///     }
+/// }
+///
+/// let endless = vec![1; 1000];
+/// assert!(List::read_from(&mut &endless[..], Endian::Big).is_err());
+/// ```
+#[derive(Debug)]
+pub struct DepthGuard(());
+
+impl DepthGuard {
+    /// Enter one more level of nesting
+    /// # Errors
+    /// errors with [`LimitExceeded::Depth`] if that is deeper than [`Limits::max_depth`]
+    pub fn enter() -> io::Result<DepthGuard> {
+        let max = Limits::current().max_depth;
+        DEPTH.with(|depth| {
+            if depth.get() >= max {
+                return Err(LimitExceeded::Depth { max }.into());
+            }
+            depth.set(depth.get() + 1);
+            Ok(DepthGuard(()))
+        })
+    }
+
+    /// Levels of nesting entered on this thread
+    pub fn depth() -> usize {
+        DEPTH.with(Cell::get)
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::limits::*;
+
+    #[test]
+    fn apply_restores() {
+        let outer = Limits {
+            max_elements: 4,
+            ..Limits::UNLIMITED
+        };
+        outer.apply(|| {
+            assert!(check_elements(4).is_ok());
+            let err = check_elements(5).unwrap_err();
+            assert_eq!("5 elements exceed the limit of 4", err.to_string());
+            Limits::UNLIMITED.apply(|| assert!(check_elements(5).is_ok()));
+            assert_eq!(outer, Limits::current());
+        });
+        assert_eq!(Limits::DEFAULT, Limits::current());
+    }
+
+    #[test]
+    fn default_depth() {
+        let guards: Vec<_> = (0..Limits::DEFAULT_MAX_DEPTH)
+            .map(|_| DepthGuard::enter().unwrap())
+            .collect();
+        let err = DepthGuard::enter().unwrap_err();
+        assert_eq!("values nested deeper than 128", err.to_string());
+        drop(guards);
+        Limits::UNLIMITED.apply(|| {
+            let _guards: Vec<_> = (0..1000).map(|_| DepthGuard::enter().unwrap()).collect();
+        });
+    }
+
+    #[test]
+    fn depth() {
+        let limits = Limits {
+            max_depth: 2,
+            ..Limits::UNLIMITED
+        };
+        limits.apply(|| {
+            let first = DepthGuard::enter().unwrap();
+            let second = DepthGuard::enter().unwrap();
+            let err = DepthGuard::enter().unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+            drop(second);
+            assert_eq!(1, DepthGuard::depth());
+            drop(DepthGuard::enter().unwrap());
+            drop(first);
+        });
+        assert_eq!(0, DepthGuard::depth());
+        let err = (Limits {
+            max_bytes_per_field: 1,
+            ..Limits::UNLIMITED
+        })
+        .apply(|| check_bytes(2).unwrap_err());
+        assert_eq!("field of 2 bytes exceeds the limit of 1", err.to_string());
+    }
+}
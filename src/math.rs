@@ -0,0 +1,207 @@
+//! Reads of vector, quaternion and matrix types from math crates
+//!
+//! Vectors are stored as consecutive components, quaternions as x, y, z, w and matrices as
+//! column-major unless the method name says otherwise.
+
+use std::io::{self, Read};
+
+fn read_f32s<R: Read + ?Sized, const N: usize>(
+    reader: &mut R,
+    from_bytes: fn([u8; 4]) -> f32,
+) -> io::Result<[f32; N]> {
+    let mut bytes = [[0u8; 4]; N];
+    reader.read_exact(bytes.as_flattened_mut())?;
+    Ok(bytes.map(from_bytes))
+}
+
+/// Reads of [`glam`](::glam) types
+#[cfg(feature = "glam")]
+pub mod glam {
+    use super::read_f32s;
+    use ::glam::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+    use std::io::{self, Read};
+
+    macro_rules! impl_glam_methods {
+        ($order:ident, $order_doc:literal) => {
+            ::paste::paste! {
+                #[doc = "Read a [`Vec2`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec2_f32>](&mut self) -> io::Result<Vec2> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vec2::from_array)
+                }
+                #[doc = "Read a [`Vec3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec3_f32>](&mut self) -> io::Result<Vec3> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vec3::from_array)
+                }
+                #[doc = "Read a [`Vec4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec4_f32>](&mut self) -> io::Result<Vec4> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vec4::from_array)
+                }
+                #[doc = "Read a [`Quat`] stored as x, y, z, w in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _quat_f32>](&mut self) -> io::Result<Quat> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Quat::from_array)
+                }
+                #[doc = "Read a column-major [`Mat3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat3_f32>](&mut self) -> io::Result<Mat3> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(|array| Mat3::from_cols_array(&array))
+                }
+                #[doc = "Read a row-major [`Mat3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat3_f32_row_major>](&mut self) -> io::Result<Mat3> {
+                    self.[<read_ $order _mat3_f32>]().map(|matrix| matrix.transpose())
+                }
+                #[doc = "Read a column-major [`Mat4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat4_f32>](&mut self) -> io::Result<Mat4> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(|array| Mat4::from_cols_array(&array))
+                }
+                #[doc = "Read a row-major [`Mat4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat4_f32_row_major>](&mut self) -> io::Result<Mat4> {
+                    self.[<read_ $order _mat4_f32>]().map(|matrix| matrix.transpose())
+                }
+            }
+        };
+    }
+
+    /// Trait to read glam vectors, quaternions and matrices
+    pub trait ReadGlam: Read {
+        impl_glam_methods!(le, "little endian byte order");
+        impl_glam_methods!(be, "big endian byte order");
+    }
+    impl<R> ReadGlam for R where R: Read {}
+
+    #[cfg(test)]
+    mod test {
+        use crate::math::glam::*;
+
+        #[test]
+        fn read_glam() {
+            let floats: Vec<u8> = (1..=16).flat_map(|i| (i as f32).to_le_bytes()).collect();
+            assert_eq!(
+                Vec3::new(1.0, 2.0, 3.0),
+                floats.as_slice().read_le_vec3_f32().unwrap()
+            );
+            assert_eq!(
+                Quat::from_xyzw(1.0, 2.0, 3.0, 4.0),
+                floats.as_slice().read_le_quat_f32().unwrap()
+            );
+            let matrix = floats.as_slice().read_le_mat4_f32().unwrap();
+            assert_eq!(Vec4::new(5.0, 6.0, 7.0, 8.0), matrix.col(1));
+            let matrix = floats.as_slice().read_le_mat4_f32_row_major().unwrap();
+            assert_eq!(Vec4::new(5.0, 6.0, 7.0, 8.0), matrix.row(1));
+
+            let floats: Vec<u8> = (1..=2).flat_map(|i| (i as f32).to_be_bytes()).collect();
+            assert_eq!(
+                Vec2::new(1.0, 2.0),
+                floats.as_slice().read_be_vec2_f32().unwrap()
+            );
+            assert!(floats.as_slice().read_be_vec3_f32().is_err());
+        }
+    }
+}
+
+/// Reads of [`nalgebra`](::nalgebra) types
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra {
+    use super::read_f32s;
+    use ::nalgebra::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+    use std::io::{self, Read};
+
+    macro_rules! impl_nalgebra_methods {
+        ($order:ident, $order_doc:literal) => {
+            ::paste::paste! {
+                #[doc = "Read a [`Vector2`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec2_f32>](&mut self) -> io::Result<Vector2<f32>> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vector2::from)
+                }
+                #[doc = "Read a [`Vector3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec3_f32>](&mut self) -> io::Result<Vector3<f32>> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vector3::from)
+                }
+                #[doc = "Read a [`Vector4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _vec4_f32>](&mut self) -> io::Result<Vector4<f32>> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(Vector4::from)
+                }
+                #[doc = "Read a [`Quaternion`] stored as x, y, z, w in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _quat_f32>](&mut self) -> io::Result<Quaternion<f32>> {
+                    read_f32s(self, f32::[<from_ $order _bytes>]).map(|[x, y, z, w]| Quaternion::new(w, x, y, z))
+                }
+                #[doc = "Read a column-major [`Matrix3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat3_f32>](&mut self) -> io::Result<Matrix3<f32>> {
+                    read_f32s::<_, 9>(self, f32::[<from_ $order _bytes>]).map(|array| Matrix3::from_column_slice(&array))
+                }
+                #[doc = "Read a row-major [`Matrix3`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat3_f32_row_major>](&mut self) -> io::Result<Matrix3<f32>> {
+                    read_f32s::<_, 9>(self, f32::[<from_ $order _bytes>]).map(|array| Matrix3::from_row_slice(&array))
+                }
+                #[doc = "Read a column-major [`Matrix4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat4_f32>](&mut self) -> io::Result<Matrix4<f32>> {
+                    read_f32s::<_, 16>(self, f32::[<from_ $order _bytes>]).map(|array| Matrix4::from_column_slice(&array))
+                }
+                #[doc = "Read a row-major [`Matrix4`] in " $order_doc]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_ $order _mat4_f32_row_major>](&mut self) -> io::Result<Matrix4<f32>> {
+                    read_f32s::<_, 16>(self, f32::[<from_ $order _bytes>]).map(|array| Matrix4::from_row_slice(&array))
+                }
+            }
+        };
+    }
+
+    /// Trait to read nalgebra vectors, quaternions and matrices
+    pub trait ReadNalgebra: Read {
+        impl_nalgebra_methods!(le, "little endian byte order");
+        impl_nalgebra_methods!(be, "big endian byte order");
+    }
+    impl<R> ReadNalgebra for R where R: Read {}
+
+    #[cfg(test)]
+    mod test {
+        use crate::math::nalgebra::*;
+
+        #[test]
+        fn read_nalgebra() {
+            let floats: Vec<u8> = (1..=16).flat_map(|i| (i as f32).to_be_bytes()).collect();
+            assert_eq!(
+                Vector3::new(1.0, 2.0, 3.0),
+                floats.as_slice().read_be_vec3_f32().unwrap()
+            );
+            let quat = floats.as_slice().read_be_quat_f32().unwrap();
+            assert_eq!(4.0, quat.w);
+            assert_eq!(1.0, quat.i);
+            let matrix = floats.as_slice().read_be_mat4_f32().unwrap();
+            assert_eq!(5.0, matrix[(0, 1)]);
+            let matrix = floats.as_slice().read_be_mat4_f32_row_major().unwrap();
+            assert_eq!(2.0, matrix[(0, 1)]);
+            let matrix = floats.as_slice().read_be_mat3_f32().unwrap();
+            assert_eq!(4.0, matrix[(0, 1)]);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{Endian, ReadFrom};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+/// Layout of a versioned format that upgrades to the next newer layout
+///
+/// Each old layout only knows its successor; [`Migrations`] chains the upgrades, so that the
+/// oldest files still reach the current type.
+pub trait Migrate: Sized {
+    /// The next newer layout
+    type Next;
+
+    /// Convert to the next newer layout
+    /// # Errors
+    /// errors, usually with [`io::ErrorKind::InvalidData`], if the value has no equivalent in
+    /// the newer layout
+    fn migrate(self) -> io::Result<Self::Next>;
+}
+
+type DecodeFn<T> = Arc<dyn Fn(&mut dyn Read, Endian) -> io::Result<T> + Send + Sync>;
+type UpgradeFn<L, T> = Arc<dyn Fn(L) -> io::Result<T> + Send + Sync>;
+
+/// Reader of every layout of a versioned format, migrating each to the current type `T`
+///
+/// Built from the current layout towards older ones, each of which implements [`Migrate`] into
+/// the layout added before it. `L` is the oldest layout added so far.
+///
+/// ```
+/// use read_primitives::{Endian, Migrate, Migrations, ReadFrom};
+/// use std::io;
+///
+/// struct HeaderV1(u16);
+/// struct HeaderV2(u32);
+/// #[derive(Debug)]
+/// struct Header {
+///     len: u64,
+///     flags: u8,
+/// }
+/// # impl ReadFrom for HeaderV1 {
+/// #     fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+/// #         u16::read_from(reader, endian).map(HeaderV1)
+/// #     }
+/// # }
+/// # impl ReadFrom for HeaderV2 {
+/// #     fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+/// #         u32::read_from(reader, endian).map(HeaderV2)
+/// #     }
+/// # }
+/// # impl ReadFrom for Header {
+/// #     fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+/// #         let (len, flags) = ReadFrom::read_from(reader, endian)?;
+/// #         Ok(Header { len, flags })
+/// #     }
+/// # }
+///
+/// impl Migrate for HeaderV1 {
+///     type Next = HeaderV2;
+///     fn migrate(self) -> io::Result<HeaderV2> {
+///         Ok(HeaderV2(self.0.into()))
+///     }
+/// }
+///
+/// impl Migrate for HeaderV2 {
+///     type Next = Header;
+///     fn migrate(self) -> io::Result<Header> {
+///         Ok(Header { len: self.0.into(), flags: 0 })
+///     }
+/// }
+///
+/// let migrations = Migrations::<Header>::new(3).older::<HeaderV2>(2).older::<HeaderV1>(1);
+/// // a u8 version, then the header in the layout of that version
+/// let header = migrations.read_versioned::<u8, _>(&mut &[1, 0, 7][..], Endian::Big).unwrap();
+/// assert_eq!((7, 0), (header.len, header.flags));
+/// let err = migrations.read(&mut &[0u8; 8][..], Endian::Big, 4).unwrap_err();
+/// assert_eq!("unsupported version 4", err.to_string());
+/// ```
+pub struct Migrations<T, L = T> {
+    layouts: Vec<(u64, DecodeFn<T>)>,
+    upgrade: UpgradeFn<L, T>,
+}
+
+impl<T, L> fmt::Debug for Migrations<T, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("versions", &self.versions().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T, L> Clone for Migrations<T, L> {
+    fn clone(&self) -> Self {
+        Migrations {
+            layouts: self.layouts.clone(),
+            upgrade: Arc::clone(&self.upgrade),
+        }
+    }
+}
+
+impl<T: ReadFrom + 'static> Migrations<T> {
+    /// Reader of `T` as the layout of `version`
+    pub fn new(version: u64) -> Self {
+        Migrations {
+            layouts: vec![(
+                version,
+                Arc::new(|reader, endian| T::read_from(reader, endian)),
+            )],
+            upgrade: Arc::new(Ok),
+        }
+    }
+}
+
+impl<T, L> Migrations<T, L> {
+    /// Versions with a layout, from the current one to the oldest
+    pub fn versions(&self) -> impl Iterator<Item = u64> + '_ {
+        self.layouts.iter().map(|&(version, _)| version)
+    }
+}
+
+impl<T: 'static, L: 'static> Migrations<T, L> {
+    /// Add the layout `O` of `version`, read and migrated through the newer layouts to `T`
+    /// # Panics
+    /// panics if a layout of `version` was added before
+    pub fn older<O>(mut self, version: u64) -> Migrations<T, O>
+    where
+        O: ReadFrom + Migrate<Next = L> + 'static,
+    {
+        assert!(
+            self.versions().all(|known| known != version),
+            "version {version} added twice"
+        );
+        let newer = self.upgrade;
+        let upgrade: UpgradeFn<O, T> = Arc::new(move |old: O| newer(old.migrate()?));
+        let decode = Arc::clone(&upgrade);
+        self.layouts.push((
+            version,
+            Arc::new(move |reader, endian| decode(O::read_from(reader, endian)?)),
+        ));
+        Migrations {
+            layouts: self.layouts,
+            upgrade,
+        }
+    }
+
+    /// Read a value in the layout of `version` and migrate it to `T`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if no layout has `version`, or when reading
+    /// the layout or migrating it errors
+    pub fn read<R: Read + ?Sized>(
+        &self,
+        reader: &mut R,
+        endian: Endian,
+        version: u64,
+    ) -> io::Result<T> {
+        let mut reader = reader;
+        match self.layouts.iter().find(|&&(known, _)| known == version) {
+            Some((_, decode)) => decode(&mut reader, endian),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported version {version}"),
+            )),
+        }
+    }
+
+    /// Read a version as a `V`, then the value in the layout of that version, migrated to `T`
+    /// # Errors
+    /// errors like [`Migrations::read`], or when reading the version errors
+    pub fn read_versioned<V, R>(&self, reader: &mut R, endian: Endian) -> io::Result<T>
+    where
+        V: ReadFrom + Into<u64>,
+        R: Read + ?Sized,
+    {
+        let version = V::read_from(reader, endian)?.into();
+        self.read(reader, endian, version)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[derive(Debug, PartialEq)]
+    struct Small(u8);
+
+    impl ReadFrom for Small {
+        fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+            u8::read_from(reader, endian).map(Small)
+        }
+    }
+
+    impl Migrate for Small {
+        type Next = u16;
+        fn migrate(self) -> io::Result<u16> {
+            Ok(self.0.into())
+        }
+    }
+
+    impl Migrate for u16 {
+        type Next = u32;
+        fn migrate(self) -> io::Result<u32> {
+            if self == u16::MAX {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "no value"));
+            }
+            Ok(self.into())
+        }
+    }
+
+    #[test]
+    fn chain() {
+        let migrations = Migrations::<u32>::new(30)
+            .older::<u16>(20)
+            .older::<Small>(10);
+        assert_eq!([30, 20, 10], *migrations.versions().collect::<Vec<_>>());
+        let read =
+            |bytes: &[u8], version| migrations.read(&mut &bytes[..], Endian::Little, version);
+        assert_eq!(7, read(&[7], 10).unwrap());
+        assert_eq!(0x0201, read(&[1, 2], 20).unwrap());
+        assert_eq!(0x04030201, read(&[1, 2, 3, 4], 30).unwrap());
+        let err = read(&[0xff, 0xff], 20).unwrap_err();
+        assert_eq!("no value", err.to_string());
+        let err = read(&[1], 20).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let versioned = migrations.read_versioned::<u16, _>(&mut &[0, 10, 9][..], Endian::Big);
+        assert_eq!(9, versioned.unwrap());
+        assert_eq!(
+            "Migrations { versions: [30, 20, 10] }",
+            format!("{migrations:?}")
+        );
+    }
+
+    #[test]
+    #[should_panic = "version 1 added twice"]
+    fn duplicate_version() {
+        let _ = Migrations::<u32>::new(1).older::<u16>(1);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{Endian, ParseError, Primitive, SliceReader};
+use bytemuck::{Pod, PodCastError};
+use memmap2::Mmap;
+use std::borrow::Cow;
+
+/// Zero-copy reads over a memory-mapped file
+///
+/// Creating the [`Mmap`] is left to the caller, since mapping a file is only sound if nothing
+/// else truncates or modifies it while it is mapped. Reads borrow from the mapping, through
+/// [`SliceReader`] cursors placed at any offset or through typed views of whole arrays.
+///
+/// Offsets in the returned errors are relative to the start of the file.
+///
+/// ```
+/// use read_primitives::{Endian, MmapReader};
+/// let mut map = memmap2::MmapMut::map_anon(16).unwrap();
+/// map[8..12].copy_from_slice(&7u32.to_be_bytes());
+/// let file = MmapReader::new(map.make_read_only().unwrap());
+/// assert_eq!(7, file.reader_at(8).unwrap().read_be_u32().unwrap());
+/// assert_eq!([7], *file.view_endian::<u32>(8, 1, Endian::Big).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct MmapReader {
+    map: Mmap,
+}
+
+impl MmapReader {
+    /// Read from `map`
+    pub fn new(map: Mmap) -> Self {
+        MmapReader { map }
+    }
+
+    /// The whole mapped file
+    pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Length of the mapped file in bytes
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether the mapped file is empty
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Cursor at the start of the file
+    pub fn reader(&self) -> SliceReader<'_> {
+        SliceReader::new(&self.map)
+    }
+
+    /// Cursor at `offset`, whose positions count from the start of the file
+    /// # Errors
+    /// errors if `offset` is past the end of the file
+    pub fn reader_at(&self, offset: usize) -> Result<SliceReader<'_>, ParseError> {
+        let mut reader = self.reader();
+        reader.skip(offset)?;
+        Ok(reader)
+    }
+
+    /// Borrow `len` bytes starting at `offset`
+    /// # Errors
+    /// errors if the file ends before `offset + len`
+    pub fn bytes_at(&self, offset: usize, len: usize) -> Result<&[u8], ParseError> {
+        self.reader_at(offset)?.read_slice(len)
+    }
+
+    /// View `count` values in native byte order starting at `offset`, without copying
+    /// # Errors
+    /// errors if the file ends before the last value, or with [`ParseError::Misaligned`] if
+    /// `offset` is not a multiple of `T`'s alignment
+    pub fn view<T: Pod>(&self, offset: usize, count: usize) -> Result<&[T], ParseError> {
+        let bytes = self.bytes_at(offset, count.saturating_mul(core::mem::size_of::<T>()))?;
+        bytemuck::try_cast_slice(bytes).map_err(|err| match err {
+            PodCastError::TargetAlignmentGreaterAndInputNotAligned => ParseError::Misaligned {
+                align: core::mem::align_of::<T>(),
+                offset,
+            },
+            _ => unreachable!("the length is a multiple of the size"),
+        })
+    }
+
+    /// `count` values in `endian` byte order starting at `offset`
+    ///
+    /// Borrows through [`MmapReader::view`] when `endian` is native and `offset` is aligned,
+    /// otherwise decodes a copy.
+    /// # Errors
+    /// errors if the file ends before the last value
+    pub fn view_endian<T: Pod + Primitive>(
+        &self,
+        offset: usize,
+        count: usize,
+        endian: Endian,
+    ) -> Result<Cow<'_, [T]>, ParseError> {
+        if endian == Endian::NATIVE {
+            match self.view(offset, count) {
+                Ok(view) => return Ok(Cow::Borrowed(view)),
+                Err(ParseError::Misaligned { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let bytes = self.bytes_at(offset, count.saturating_mul(T::SIZE))?;
+        Ok(Cow::Owned(
+            bytes
+                .chunks_exact(T::SIZE)
+                .map(|chunk| {
+                    let mut value = T::Bytes::default();
+                    value.as_mut().copy_from_slice(chunk);
+                    T::from_endian_bytes(value, endian)
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl From<Mmap> for MmapReader {
+    fn from(map: Mmap) -> Self {
+        MmapReader::new(map)
+    }
+}
+
+impl AsRef<[u8]> for MmapReader {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::borrow::Cow;
+
+    fn mapped(bytes: &[u8]) -> MmapReader {
+        let mut map = memmap2::MmapMut::map_anon(bytes.len()).unwrap();
+        map.copy_from_slice(bytes);
+        MmapReader::new(map.make_read_only().unwrap())
+    }
+
+    #[test]
+    fn reader_at() {
+        let file = mapped(&[0, 0, 1, 2, 3]);
+        let mut reader = file.reader_at(2).unwrap();
+        assert_eq!(0x0102, reader.read_be_u16().unwrap());
+        assert_eq!(
+            ParseError::UnexpectedEnd {
+                needed: 2,
+                remaining: 1,
+                offset: 4
+            },
+            reader.read_be_u16().unwrap_err()
+        );
+        assert_eq!([2, 3], file.bytes_at(3, 2).unwrap());
+        assert!(file.reader_at(6).is_err());
+    }
+
+    #[test]
+    fn view() {
+        let bytes: Vec<u8> = [1u32, 2, 3]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        let file = mapped(&bytes);
+        assert_eq!([2, 3], file.view::<u32>(4, 2).unwrap());
+        assert_eq!(
+            ParseError::Misaligned {
+                align: 4,
+                offset: 2
+            },
+            file.view::<u32>(2, 1).unwrap_err()
+        );
+        assert!(file.view::<u32>(4, 3).is_err());
+    }
+
+    #[test]
+    fn view_endian() {
+        let file = mapped(&[0, 0, 1, 0, 2, 0, 0, 0]);
+        let native = file.view_endian::<u16>(2, 2, Endian::NATIVE).unwrap();
+        assert!(matches!(native, Cow::Borrowed(_)));
+        let little = file.view_endian::<u16>(2, 2, Endian::Little).unwrap();
+        assert_eq!([1, 2], *little);
+        let big = file.view_endian::<u16>(1, 2, Endian::Big).unwrap();
+        assert!(matches!(big, Cow::Owned(_)));
+        assert_eq!([1, 2], *big);
+    }
+}
@@ -0,0 +1,307 @@
+//! Reads of MessagePack scalars
+//!
+//! [`ReadMsgpack::read_msgpack_head`] decodes the head of the next value: a scalar, or the
+//! length of a string, binary, extension or container whose contents follow it. The caller
+//! walks containers itself, reading as many values as an array or map head counts, so a stream
+//! processor can skim frames and decode only the fields it needs.
+//!
+//! ```
+//! use read_primitives::msgpack::{Head, ReadMsgpack};
+//! // {"id": 7, "ok": true}
+//! let mut bytes: &[u8] = &[0x82, 0xa2, b'i', b'd', 0x07, 0xa2, b'o', b'k', 0xc3];
+//! assert_eq!(Head::Map(2), bytes.read_msgpack_head().unwrap());
+//! assert_eq!("id", bytes.read_msgpack_str().unwrap());
+//! assert_eq!(7, bytes.read_msgpack_u64().unwrap());
+//! assert_eq!("ok", bytes.read_msgpack_str().unwrap());
+//! assert!(bytes.read_msgpack_bool().unwrap());
+//! ```
+
+use crate::limits::check_bytes;
+use crate::string::read_exact_vec;
+use crate::{Endian, ReadFrom};
+use std::io::{self, Read};
+
+/// Head of a MessagePack value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Head {
+    /// `nil`
+    Nil,
+    /// `true` or `false`
+    Bool(bool),
+    /// A positive fixint or uint of 8 to 64 bits
+    Uint(u64),
+    /// A negative fixint or int of 8 to 64 bits
+    Int(i64),
+    /// A float 32
+    F32(f32),
+    /// A float 64
+    F64(f64),
+    /// A string of this many bytes of UTF-8 follows
+    Str(u32),
+    /// This many bytes of binary follow
+    Bin(u32),
+    /// An array of this many values follows
+    Array(u32),
+    /// A map of this many key and value pairs follows
+    Map(u32),
+    /// An extension value of type `tag` and `len` bytes follows
+    Ext {
+        /// Application or predefined type, like -1 for timestamps
+        tag: i8,
+        /// Length of the data
+        len: u32,
+    },
+}
+
+fn read<T: ReadFrom, R: Read + ?Sized>(reader: &mut R) -> io::Result<T> {
+    T::read_from(reader, Endian::Big)
+}
+
+fn unexpected(expected: &str, head: Head) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("expected msgpack {expected}, found {head:?}"),
+    )
+}
+
+/// Trait to read MessagePack scalars and the heads of other values
+pub trait ReadMsgpack: Read {
+    /// Read the head of the next value
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] for the never used byte `0xc1`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_msgpack_head(&mut self) -> io::Result<Head> {
+        let reader = self;
+        let marker: u8 = read(reader)?;
+        Ok(match marker {
+            0x00..=0x7f => Head::Uint(marker.into()),
+            0x80..=0x8f => Head::Map((marker & 0x0f).into()),
+            0x90..=0x9f => Head::Array((marker & 0x0f).into()),
+            0xa0..=0xbf => Head::Str((marker & 0x1f).into()),
+            0xc0 => Head::Nil,
+            0xc1 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid msgpack marker 0xc1",
+                ))
+            }
+            0xc2 => Head::Bool(false),
+            0xc3 => Head::Bool(true),
+            0xc4 => Head::Bin(read::<u8, _>(reader)?.into()),
+            0xc5 => Head::Bin(read::<u16, _>(reader)?.into()),
+            0xc6 => Head::Bin(read(reader)?),
+            0xc7 => {
+                let len = read::<u8, _>(reader)?.into();
+                Head::Ext {
+                    tag: read(reader)?,
+                    len,
+                }
+            }
+            0xc8 => {
+                let len = read::<u16, _>(reader)?.into();
+                Head::Ext {
+                    tag: read(reader)?,
+                    len,
+                }
+            }
+            0xc9 => {
+                let len = read(reader)?;
+                Head::Ext {
+                    tag: read(reader)?,
+                    len,
+                }
+            }
+            0xca => Head::F32(read(reader)?),
+            0xcb => Head::F64(read(reader)?),
+            0xcc => Head::Uint(read::<u8, _>(reader)?.into()),
+            0xcd => Head::Uint(read::<u16, _>(reader)?.into()),
+            0xce => Head::Uint(read::<u32, _>(reader)?.into()),
+            0xcf => Head::Uint(read(reader)?),
+            0xd0 => Head::Int(read::<i8, _>(reader)?.into()),
+            0xd1 => Head::Int(read::<i16, _>(reader)?.into()),
+            0xd2 => Head::Int(read::<i32, _>(reader)?.into()),
+            0xd3 => Head::Int(read(reader)?),
+            0xd4..=0xd8 => Head::Ext {
+                tag: read(reader)?,
+                len: 1 << (marker - 0xd4),
+            },
+            0xd9 => Head::Str(read::<u8, _>(reader)?.into()),
+            0xda => Head::Str(read::<u16, _>(reader)?.into()),
+            0xdb => Head::Str(read(reader)?),
+            0xdc => Head::Array(read::<u16, _>(reader)?.into()),
+            0xdd => Head::Array(read(reader)?),
+            0xde => Head::Map(read::<u16, _>(reader)?.into()),
+            0xdf => Head::Map(read(reader)?),
+            0xe0..=0xff => Head::Int((marker as i8).into()),
+        })
+    }
+
+    /// Read `nil`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not `nil`, or when reading the
+    /// head errors
+    fn read_msgpack_nil(&mut self) -> io::Result<()> {
+        match self.read_msgpack_head()? {
+            Head::Nil => Ok(()),
+            head => Err(unexpected("nil", head)),
+        }
+    }
+
+    /// Read a bool
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a bool, or when reading the
+    /// head errors
+    fn read_msgpack_bool(&mut self) -> io::Result<bool> {
+        match self.read_msgpack_head()? {
+            Head::Bool(value) => Ok(value),
+            head => Err(unexpected("bool", head)),
+        }
+    }
+
+    /// Read an integer of any encoding that fits a u64
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not an integer or is negative,
+    /// or when reading the head errors
+    fn read_msgpack_u64(&mut self) -> io::Result<u64> {
+        match self.read_msgpack_head()? {
+            Head::Uint(value) => Ok(value),
+            Head::Int(value) if value >= 0 => Ok(value as u64),
+            head => Err(unexpected("u64", head)),
+        }
+    }
+
+    /// Read an integer of any encoding that fits an i64
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not an integer or is above
+    /// [`i64::MAX`], or when reading the head errors
+    fn read_msgpack_i64(&mut self) -> io::Result<i64> {
+        match self.read_msgpack_head()? {
+            Head::Int(value) => Ok(value),
+            Head::Uint(value) if value <= i64::MAX as u64 => Ok(value as i64),
+            head => Err(unexpected("i64", head)),
+        }
+    }
+
+    /// Read a float 32 or float 64
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a float, or when reading
+    /// the head errors
+    fn read_msgpack_f64(&mut self) -> io::Result<f64> {
+        match self.read_msgpack_head()? {
+            Head::F32(value) => Ok(value.into()),
+            Head::F64(value) => Ok(value),
+            head => Err(unexpected("float", head)),
+        }
+    }
+
+    /// Read the head of a string, returning its length in bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a string, or when reading
+    /// the head errors
+    fn read_msgpack_str_len(&mut self) -> io::Result<u32> {
+        match self.read_msgpack_head()? {
+            Head::Str(len) => Ok(len),
+            head => Err(unexpected("str", head)),
+        }
+    }
+
+    /// Read a string
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a string, is longer than the
+    /// [`Limits`](crate::Limits) allow or is not valid UTF-8, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_msgpack_str(&mut self) -> io::Result<String> {
+        let len = self.read_msgpack_str_len()?;
+        check_bytes(len as usize)?;
+        String::from_utf8(read_exact_vec(self, len as usize)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Read the head of a binary, returning its length in bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a binary, or when reading
+    /// the head errors
+    fn read_msgpack_bin_len(&mut self) -> io::Result<u32> {
+        match self.read_msgpack_head()? {
+            Head::Bin(len) => Ok(len),
+            head => Err(unexpected("bin", head)),
+        }
+    }
+
+    /// Read a binary
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value is not a binary or is longer than
+    /// the [`Limits`](crate::Limits) allow, or with [`io::ErrorKind::UnexpectedEof`] if the
+    /// reader ends early
+    fn read_msgpack_bin(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.read_msgpack_bin_len()?;
+        check_bytes(len as usize)?;
+        read_exact_vec(self, len as usize)
+    }
+}
+impl<R> ReadMsgpack for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::msgpack::*;
+    use std::io;
+
+    fn head(bytes: &[u8]) -> Head {
+        let mut reader = bytes;
+        let head = reader.read_msgpack_head().unwrap();
+        assert!(reader.is_empty(), "{bytes:x?} left bytes unread");
+        head
+    }
+
+    #[test]
+    fn heads() {
+        assert_eq!(Head::Uint(0x7f), head(&[0x7f]));
+        assert_eq!(Head::Int(-32), head(&[0xe0]));
+        assert_eq!(Head::Uint(0x1234), head(&[0xcd, 0x12, 0x34]));
+        assert_eq!(
+            Head::Uint(u64::MAX),
+            head(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+        );
+        assert_eq!(Head::Int(-2), head(&[0xd1, 0xff, 0xfe]));
+        assert_eq!(Head::F32(1.5), head(&[0xca, 0x3f, 0xc0, 0, 0]));
+        assert_eq!(Head::Str(31), head(&[0xbf]));
+        assert_eq!(Head::Str(0x100), head(&[0xda, 1, 0]));
+        assert_eq!(Head::Bin(3), head(&[0xc4, 3]));
+        assert_eq!(Head::Array(15), head(&[0x9f]));
+        assert_eq!(Head::Map(0x1_0000), head(&[0xdf, 0, 1, 0, 0]));
+        assert_eq!(Head::Ext { tag: -1, len: 8 }, head(&[0xd7, 0xff]));
+        assert_eq!(Head::Ext { tag: 5, len: 0x200 }, head(&[0xc8, 2, 0, 5]));
+        assert_eq!(Head::Nil, head(&[0xc0]));
+        let err = (&[0xc1u8][..]).read_msgpack_head().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0xce, 0, 0][..]).read_msgpack_head().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn scalars() {
+        let mut bytes: &[u8] = &[0xd0, 0x05, 0xcc, 0xff, 0xcb, 0x40, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(5, bytes.read_msgpack_u64().unwrap());
+        assert_eq!(255, bytes.read_msgpack_i64().unwrap());
+        assert_eq!(2.0, bytes.read_msgpack_f64().unwrap());
+        let mut bytes: &[u8] = &[0xc4, 2, 1, 2, 0xc0, 0xff];
+        assert_eq!(vec![1, 2], bytes.read_msgpack_bin().unwrap());
+        bytes.read_msgpack_nil().unwrap();
+        let err = bytes.read_msgpack_u64().unwrap_err();
+        assert_eq!("expected msgpack u64, found Int(-1)", err.to_string());
+        let err = (&[0xa1, 0xff][..]).read_msgpack_str().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0xcf, 0x80, 0, 0, 0, 0, 0, 0, 0][..])
+            .read_msgpack_i64()
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let limits = crate::Limits {
+            max_bytes_per_field: 1,
+            ..crate::Limits::DEFAULT
+        };
+        let err = limits
+            .apply(|| (&[0xc4, 2, 1, 2][..]).read_msgpack_bin())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
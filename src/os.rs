@@ -0,0 +1,73 @@
+use crate::string::read_exact_vec;
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Trait to read native OS strings and paths without a lossy round-trip through `String`
+///
+/// On Unix the field is `len` raw bytes. On Windows it is `len` little endian UTF-16 code units,
+/// which may contain unpaired surrogates.
+pub trait ReadOsString: Read {
+    /// Read an [`OsString`] from `len` bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    #[cfg(unix)]
+    fn read_os_string(&mut self, len: usize) -> io::Result<OsString> {
+        use std::os::unix::ffi::OsStringExt;
+        read_exact_vec(self, len).map(OsString::from_vec)
+    }
+
+    /// Read an [`OsString`] from `len` little endian UTF-16 code units
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    #[cfg(windows)]
+    fn read_os_string(&mut self, len: usize) -> io::Result<OsString> {
+        use std::os::windows::ffi::OsStringExt;
+        let bytes = read_exact_vec(self, len.saturating_mul(2))?;
+        let wide: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        Ok(OsString::from_wide(&wide))
+    }
+
+    /// Read a [`PathBuf`], encoded as for [`ReadOsString::read_os_string`]
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_path_buf(&mut self, len: usize) -> io::Result<PathBuf> {
+        self.read_os_string(len).map(PathBuf::from)
+    }
+}
+impl<R> ReadOsString for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::path::Path;
+
+    #[cfg(unix)]
+    #[test]
+    fn read_non_utf8_os_string() {
+        use std::os::unix::ffi::OsStrExt;
+        let mut bytes: &[u8] = b"caf\xe9/x";
+        let string = bytes.read_os_string(4).unwrap();
+        assert_eq!(b"caf\xe9", string.as_bytes());
+        assert!(string.to_str().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_path_buf() {
+        let mut bytes: &[u8] = b"/tmp/file";
+        assert_eq!(Path::new("/tmp/file"), bytes.read_path_buf(9).unwrap());
+        assert!(bytes.read_path_buf(1).is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn read_path_buf() {
+        let mut bytes: &[u8] = &[b'C', 0, b':', 0, b'\\', 0, b'x', 0];
+        assert_eq!(Path::new("C:\\x"), bytes.read_path_buf(4).unwrap());
+        assert!(bytes.read_path_buf(1).is_err());
+    }
+}
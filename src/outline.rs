@@ -0,0 +1,182 @@
+//! Non-generic cores of the bulk reads and writes
+//!
+//! The extension traits are implemented for every reader and writer type, so anything longer
+//! than a `read_exact` would be compiled once per element type, byte order and reader type.
+//! The trait methods instead hand a `&mut dyn Read` or `&mut dyn Write` and the conversion
+//! function to these cores, which are compiled once per element type.
+
+use crate::{Primitive, PREALLOC_LIMIT};
+use std::io::{self, Read, Write};
+
+/// Size of the buffer that slice writes encode into
+const CHUNK_LEN: usize = 4096;
+
+/// Read `count` values with `decode` and hand each to `sink`
+///
+/// The bytes are read in chunks of at most `granularity` values, and `progress` is called with
+/// the number of values read so far every time another `granularity` values (or the last value)
+/// are done.
+pub(crate) fn read_values<T: Primitive>(
+    reader: &mut dyn Read,
+    count: usize,
+    granularity: usize,
+    decode: fn(T::Bytes) -> T,
+    sink: &mut dyn FnMut(T),
+    progress: &mut dyn FnMut(usize),
+) -> io::Result<()> {
+    let granularity = granularity.max(1);
+    let chunk_len = granularity
+        .min(PREALLOC_LIMIT / T::SIZE.max(1))
+        .min(count)
+        .max(1);
+    let mut bytes = vec![0u8; chunk_len * T::SIZE];
+    let mut done = 0;
+    let mut next_report = granularity;
+    while done < count {
+        let len = (count - done).min(chunk_len);
+        let chunk = &mut bytes[..len * T::SIZE];
+        reader.read_exact(chunk)?;
+        for index in 0..len {
+            let mut value = T::Bytes::default();
+            value
+                .as_mut()
+                .copy_from_slice(&chunk[index * T::SIZE..][..T::SIZE]);
+            sink(decode(value));
+        }
+        done += len;
+        if done >= next_report || done == count {
+            progress(done);
+            next_report = done.saturating_add(granularity);
+        }
+    }
+    Ok(())
+}
+
+/// Read `count` values with `decode` into a `Vec`
+pub(crate) fn read_vec<T: Primitive>(
+    reader: &mut dyn Read,
+    count: usize,
+    decode: fn(T::Bytes) -> T,
+) -> io::Result<Vec<T>> {
+    let mut vec = Vec::with_capacity(count.min(PREALLOC_LIMIT / T::SIZE.max(1)));
+    read_values(
+        reader,
+        count,
+        usize::MAX,
+        decode,
+        &mut |value| vec.push(value),
+        &mut |_| {},
+    )?;
+    Ok(vec)
+}
+
+/// Read `count` values with `decode` from the starts of records `stride` bytes apart into a `Vec`
+///
+/// The records are read in chunks of whole records, and the bytes after the last value are left
+/// unread.
+pub(crate) fn read_strided_vec<T: Primitive>(
+    reader: &mut dyn Read,
+    count: usize,
+    stride: usize,
+    decode: fn(T::Bytes) -> T,
+) -> io::Result<Vec<T>> {
+    let mut vec = Vec::with_capacity(count.min(PREALLOC_LIMIT / T::SIZE.max(1)));
+    if stride > PREALLOC_LIMIT {
+        // records longer than the buffer are read one value at a time, discarding the gaps
+        let gap = (stride - T::SIZE) as u64;
+        for index in 0..count {
+            if index > 0 {
+                let skipped = io::copy(&mut reader.take(gap), &mut io::sink())?;
+                if skipped < gap {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
+            let mut value = T::Bytes::default();
+            reader.read_exact(value.as_mut())?;
+            vec.push(decode(value));
+        }
+        return Ok(vec);
+    }
+    let overflow = || io::Error::new(io::ErrorKind::InvalidInput, "strided read overflows usize");
+    let chunk_records = (PREALLOC_LIMIT / stride.max(1)).clamp(1, count.max(1));
+    let mut bytes = vec![0u8; chunk_records.checked_mul(stride).ok_or_else(overflow)?];
+    while vec.len() < count {
+        let records = (count - vec.len()).min(chunk_records);
+        let last = vec.len() + records == count;
+        // the gap after the last value is not read
+        let len = if last {
+            (records - 1).checked_mul(stride).ok_or_else(overflow)? + T::SIZE
+        } else {
+            records.checked_mul(stride).ok_or_else(overflow)?
+        };
+        let chunk = &mut bytes[..len];
+        reader.read_exact(chunk)?;
+        for index in 0..records {
+            let mut value = T::Bytes::default();
+            value
+                .as_mut()
+                .copy_from_slice(&chunk[index * stride..][..T::SIZE]);
+            vec.push(decode(value));
+        }
+    }
+    Ok(vec)
+}
+
+/// Read the rest of `reader` as values decoded with `decode`
+pub(crate) fn read_to_end<T: Primitive>(
+    reader: &mut dyn Read,
+    decode: fn(T::Bytes) -> T,
+) -> io::Result<Vec<T>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let chunks = bytes.chunks_exact(T::SIZE);
+    if !chunks.remainder().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} bytes is not a multiple of {}", bytes.len(), T::SIZE),
+        ));
+    }
+    Ok(chunks
+        .map(|chunk| {
+            let mut value = T::Bytes::default();
+            value.as_mut().copy_from_slice(chunk);
+            decode(value)
+        })
+        .collect())
+}
+
+/// Write `values` encoded with `encode`, through a buffer of `CHUNK_LEN` bytes
+pub(crate) fn write_values<T: Copy, const SIZE: usize>(
+    writer: &mut dyn Write,
+    values: &[T],
+    encode: fn(T) -> [u8; SIZE],
+) -> io::Result<()> {
+    let mut buf = [0u8; CHUNK_LEN];
+    for chunk in values.chunks(CHUNK_LEN / SIZE) {
+        for (bytes, value) in buf.chunks_exact_mut(SIZE).zip(chunk) {
+            bytes.copy_from_slice(&encode(*value));
+        }
+        writer.write_all(&buf[..chunk.len() * SIZE])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn read_across_chunks() {
+        let values: Vec<u32> = (0..40_000).collect();
+        let mut bytes = Vec::new();
+        bytes.write_be_u32_slice(&values).unwrap();
+        let mut reports = Vec::new();
+        let mut reader = bytes.as_slice();
+        let read = reader
+            .read_be_u32_vec_with_progress(values.len(), 30_000, |done| reports.push(done))
+            .unwrap();
+        assert_eq!(values, read);
+        assert_eq!(vec![32_768, 40_000], reports);
+        assert_eq!(values, bytes.as_slice().read_be_vec::<u32>(40_000).unwrap());
+    }
+}
@@ -0,0 +1,137 @@
+//! Primitive reads for completion-based I/O, where buffers are passed by value
+//!
+//! Runtimes built on io_uring (monoio, tokio-uring, glommio) take ownership of the buffer for the duration
+//! of a read and hand it back together with the result. [`OwnedRead`] captures that shape; the
+//! per-type traits decode primitives on top of it and return the buffer so it can be reused.
+//!
+//! ```
+//! # futures::executor::block_on(async {
+//! use read_primitives::owned::ReadU32;
+//! let mut source: &[u8] = &[0xEF, 0xBE, 0xAD, 0xDE];
+//! let (value, buf) = source.read_le_u32_owned(Vec::new()).await;
+//! assert_eq!(0xDEAD_BEEF, value?);
+//! // the returned buffer can be passed to the next read
+//! assert!(buf.capacity() >= 4);
+//! # std::io::Result::Ok(())
+//! # }).unwrap();
+//! ```
+
+use std::future::Future;
+use std::io;
+
+/// A source that reads into owned buffers
+pub trait OwnedRead {
+    /// Append at most `max` bytes to `buf`
+    ///
+    /// Resolves to the number of bytes read, where 0 signals the end of the stream, and the buffer.
+    fn read_owned(
+        &mut self,
+        buf: Vec<u8>,
+        max: usize,
+    ) -> impl Future<Output = (io::Result<usize>, Vec<u8>)>;
+
+    /// Replace the contents of `buf` with exactly the next `len` bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the source ends early, or when [`OwnedRead::read_owned`] errors
+    fn read_exact_owned(
+        &mut self,
+        mut buf: Vec<u8>,
+        len: usize,
+    ) -> impl Future<Output = (io::Result<()>, Vec<u8>)> {
+        async move {
+            buf.clear();
+            while buf.len() < len {
+                let remaining = len - buf.len();
+                let (result, returned) = self.read_owned(buf, remaining).await;
+                buf = returned;
+                match result {
+                    Ok(0) => return (Err(io::ErrorKind::UnexpectedEof.into()), buf),
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return (Err(err), buf),
+                }
+            }
+            (Ok(()), buf)
+        }
+    }
+}
+
+impl OwnedRead for &[u8] {
+    async fn read_owned(&mut self, mut buf: Vec<u8>, max: usize) -> (io::Result<usize>, Vec<u8>) {
+        let len = max.min(self.len());
+        buf.extend_from_slice(&self[..len]);
+        *self = &self[len..];
+        (Ok(len), buf)
+    }
+}
+
+macro_rules! impl_owned_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc " through the owned `buf`, returning it alongside the result"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`OwnedRead::read_exact_owned`] errors"]
+            fn [<read_ $order _ $type _owned>](&mut self, buf: Vec<u8>) -> impl Future<Output = (io::Result<$type>, Vec<u8>)> {
+                async move {
+                    const SIZE: usize = std::mem::size_of::<$type>();
+                    let (result, buf) = self.read_exact_owned(buf, SIZE).await;
+                    let result = result.map(|()| {
+                        let mut bytes = [0u8; SIZE];
+                        bytes.copy_from_slice(&buf[..SIZE]);
+                        $type::[<from_ $order _bytes>](bytes)
+                    });
+                    (result, buf)
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_owned_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to read " $type " from an [`OwnedRead`]."]
+                pub trait [<Read $type:camel>]: OwnedRead {
+                    impl_owned_methods!($type, ne, "native byte order");
+                    impl_owned_methods!($type, le, "little endian byte order");
+                    impl_owned_methods!($type, be, "big endian byte order");
+                }
+                impl<T: OwnedRead> [<Read $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+impl_owned_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+#[cfg(test)]
+mod test {
+    use crate::owned::*;
+
+    /// Hands out at most one byte per read, like a slow completion queue
+    struct Trickle<'a>(&'a [u8]);
+
+    impl OwnedRead for Trickle<'_> {
+        async fn read_owned(&mut self, buf: Vec<u8>, max: usize) -> (io::Result<usize>, Vec<u8>) {
+            let mut limited = &self.0[..self.0.len().min(1)];
+            let (result, buf) = limited.read_owned(buf, max).await;
+            self.0 = &self.0[self.0.len().min(1)..];
+            (result, buf)
+        }
+    }
+
+    #[test]
+    fn read_owned_reuses_buffer() {
+        futures::executor::block_on(async {
+            let mut source = Trickle(&[1, 0, 0, 2, 3]);
+            let (value, buf) = source.read_le_u16_owned(Vec::with_capacity(16)).await;
+            assert_eq!(1, value.unwrap());
+            assert_eq!(16, buf.capacity());
+            let (value, buf) = source.read_be_u16_owned(buf).await;
+            assert_eq!(2, value.unwrap());
+            let (value, _) = source.read_le_u32_owned(buf).await;
+            assert_eq!(io::ErrorKind::UnexpectedEof, value.unwrap_err().kind());
+        });
+    }
+}
@@ -0,0 +1,263 @@
+//! Free functions that parse a primitive from the start of a byte slice
+//!
+//! Each function returns the value together with the rest of the input, so they compose with
+//! hand-rolled parsers that thread the remaining slice through.
+//!
+//! ```
+//! use read_primitives::parse::{parse_be_u16, parse_u8};
+//! let (tag, rest) = parse_u8(&[7, 0, 42]).unwrap();
+//! let (len, rest) = parse_be_u16(rest).unwrap();
+//! assert_eq!((7, 42), (tag, len));
+//! assert!(rest.is_empty());
+//! ```
+//!
+//! Offsets in the returned errors are relative to the input that was passed in.
+//!
+//! All parse functions are `const fn`, so baked-in byte arrays can be decoded at compile time:
+//!
+//! ```
+//! use read_primitives::parse::parse_le_u32;
+//! const BLOB: &[u8] = &[3, 0, 1, 0, 0xff];
+//! const VERSION: u32 = match parse_le_u32(BLOB) {
+//!     Ok((version, _)) => version,
+//!     Err(_) => panic!("blob too short"),
+//! };
+//! assert_eq!(0x0001_0003, VERSION);
+//! ```
+
+use crate::ParseError;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+const fn chunk<const N: usize>(input: &[u8]) -> Result<(&[u8; N], &[u8]), ParseError> {
+    match input.split_first_chunk::<N>() {
+        Some(split) => Ok(split),
+        None => Err(ParseError::UnexpectedEnd {
+            needed: N,
+            remaining: input.len(),
+            offset: 0,
+        }),
+    }
+}
+
+macro_rules! impl_parse {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Parse " $type " in " $order_doc " from the start of `input`"]
+            #[doc = "# Errors"]
+            #[doc = "errors if `input` is shorter than " $type "'s size in bytes"]
+            pub const fn [<parse_ $order _ $type>](input: &[u8]) -> Result<($type, &[u8]), ParseError> {
+                match chunk(input) {
+                    Ok((bytes, rest)) => Ok(($type::[<from_ $order _bytes>](*bytes), rest)),
+                    Err(err) => Err(err),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_parse_all {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Parse all of `data` as consecutive " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`ParseError::TrailingBytes`] if the length of `data` is not a multiple of " $type "'s size"]
+            pub fn [<parse_all_ $order _ $type>](data: impl AsRef<[u8]>) -> Result<Vec<$type>, ParseError> {
+                const SIZE: usize = core::mem::size_of::<$type>();
+                let data = data.as_ref();
+                let chunks = data.chunks_exact(SIZE);
+                if !chunks.remainder().is_empty() {
+                    return Err(ParseError::TrailingBytes {
+                        count: chunks.remainder().len(),
+                        offset: data.len() - chunks.remainder().len(),
+                    });
+                }
+                Ok(chunks
+                    .map(|chunk| {
+                        let mut bytes = [0u8; SIZE];
+                        bytes.copy_from_slice(chunk);
+                        $type::[<from_ $order _bytes>](bytes)
+                    })
+                    .collect())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rayon")]
+macro_rules! impl_par_parse_all {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Parse all of `data` as consecutive " $type " in " $order_doc ", converting chunks of it in parallel with rayon"]
+            #[doc = "Worth it over [`parse_all_" $order "_" $type "`] for inputs of megabytes or more."]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`ParseError::TrailingBytes`] if the length of `data` is not a multiple of " $type "'s size"]
+            pub fn [<par_parse_all_ $order _ $type>](data: impl AsRef<[u8]>) -> Result<Vec<$type>, ParseError> {
+                use rayon::prelude::*;
+                const SIZE: usize = core::mem::size_of::<$type>();
+                let data = data.as_ref();
+                let remainder = data.len() % SIZE;
+                if remainder != 0 {
+                    return Err(ParseError::TrailingBytes {
+                        count: remainder,
+                        offset: data.len() - remainder,
+                    });
+                }
+                Ok(data
+                    .par_chunks_exact(SIZE)
+                    .with_min_len(PAR_MIN_LEN)
+                    .map(|chunk| {
+                        let mut bytes = [0u8; SIZE];
+                        bytes.copy_from_slice(chunk);
+                        $type::[<from_ $order _bytes>](bytes)
+                    })
+                    .collect())
+            }
+        }
+    };
+}
+
+/// Fewest elements converted by one rayon task, so small inputs are not split into tiny jobs
+#[cfg(feature = "rayon")]
+const PAR_MIN_LEN: usize = 16 * 1024;
+
+macro_rules! impl_parse_fns {
+    ($($type:ty),+) => {
+        $(
+            impl_parse!($type, ne, "native byte order");
+            impl_parse!($type, le, "little endian byte order");
+            impl_parse!($type, be, "big endian byte order");
+            #[cfg(feature = "alloc")]
+            impl_parse_all!($type, ne, "native byte order");
+            #[cfg(feature = "alloc")]
+            impl_parse_all!($type, le, "little endian byte order");
+            #[cfg(feature = "alloc")]
+            impl_parse_all!($type, be, "big endian byte order");
+            #[cfg(feature = "rayon")]
+            impl_par_parse_all!($type, ne, "native byte order");
+            #[cfg(feature = "rayon")]
+            impl_par_parse_all!($type, le, "little endian byte order");
+            #[cfg(feature = "rayon")]
+            impl_par_parse_all!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+impl_parse_fns!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// Parse a u8 from the start of `input`
+/// # Errors
+/// errors if `input` is empty
+pub const fn parse_u8(input: &[u8]) -> Result<(u8, &[u8]), ParseError> {
+    match chunk::<1>(input) {
+        Ok((bytes, rest)) => Ok((bytes[0], rest)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parse a bool from the start of `input`
+/// # Errors
+/// errors if `input` is empty
+pub const fn parse_bool(input: &[u8]) -> Result<(bool, &[u8]), ParseError> {
+    match parse_u8(input) {
+        Ok((byte, rest)) => Ok((byte != 0, rest)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parse a char from the start of `input`
+///  It is a assumed that the char is represented in native byte order
+/// # Errors
+/// errors if `input` is shorter than 4 bytes
+pub const fn parse_char(input: &[u8]) -> Result<(Option<char>, &[u8]), ParseError> {
+    match chunk(input) {
+        Ok((bytes, rest)) => Ok((char::from_u32(u32::from_ne_bytes(*bytes)), rest)),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::*;
+
+    macro_rules! impl_tests {
+        ($($type:ty),+) => {
+            $(
+                ::paste::paste! {
+                    #[test]
+                    fn [<parse_le_ $type>]() {
+                        let bytes = [[<37 $type>].to_le_bytes().as_slice(), &[1]].concat();
+                        assert_eq!(([<37 $type>], [1].as_slice()), super::[<parse_le_ $type>](&bytes).unwrap());
+                    }
+                    #[test]
+                    fn [<parse_be_ $type>]() {
+                        let bytes = [<37 $type>].to_be_bytes();
+                        assert_eq!(([<37 $type>], [].as_slice()), super::[<parse_be_ $type>](&bytes).unwrap());
+                    }
+                }
+            )+
+        };
+    }
+    impl_tests!(u16, i32, u64, i128, f32, f64);
+
+    #[test]
+    fn parse_const() {
+        const VALUE: (f32, u8) = match super::parse_be_f32(&[0x40, 0x49, 0x0f, 0xdb, 9]) {
+            Ok((value, rest)) => (value, rest[0]),
+            Err(_) => panic!(),
+        };
+        assert_eq!((core::f32::consts::PI, 9), VALUE);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn parse_all() {
+        let data = [1u8, 0, 2, 0, 3, 0];
+        assert_eq!(alloc::vec![1, 2, 3], parse_all_le_u16(data).unwrap());
+        assert_eq!(
+            ParseError::TrailingBytes {
+                count: 2,
+                offset: 4
+            },
+            parse_all_be_u32(data.as_slice()).unwrap_err()
+        );
+        assert!(parse_all_ne_u64([]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_parse_all() {
+        let values: alloc::vec::Vec<f64> = (0..100_000).map(f64::from).collect();
+        let data: alloc::vec::Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        assert_eq!(values, par_parse_all_be_f64(&data).unwrap());
+        assert_eq!(
+            parse_all_le_u32(&data).unwrap(),
+            par_parse_all_le_u32(&data).unwrap()
+        );
+        assert_eq!(
+            ParseError::TrailingBytes {
+                count: 3,
+                offset: 16
+            },
+            par_parse_all_ne_u64(&data[..19]).unwrap_err()
+        );
+        assert!(par_parse_all_le_i16([]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_short_input() {
+        assert_eq!(
+            ParseError::UnexpectedEnd {
+                needed: 8,
+                remaining: 3,
+                offset: 0
+            },
+            parse_ne_u64(&[1, 2, 3]).unwrap_err()
+        );
+        assert!(parse_u8(&[]).is_err());
+    }
+}
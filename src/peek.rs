@@ -0,0 +1,267 @@
+use crate::{Endian, Primitive};
+use std::io::{self, BufRead, Read};
+
+/// Capacity that [`BufRead::fill_buf`] reads at a time when nothing has been peeked
+const FILL_LEN: usize = 8 * 1024;
+
+macro_rules! impl_peek_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Peek at the next " $type " in " $order_doc " without consuming it"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`PeekReader::peek_bytes`] errors"]
+            pub fn [<peek_ $order _ $type>](&mut self) -> io::Result<$type> {
+                const SIZE: usize = std::mem::size_of::<$type>();
+                let mut bytes = [0u8; SIZE];
+                bytes.copy_from_slice(self.peek_bytes(SIZE)?);
+                Ok($type::[<from_ $order _bytes>](bytes))
+            }
+        }
+    };
+}
+
+macro_rules! impl_peek_reads {
+    ($($type:ty),+) => {
+        $(
+            impl_peek_methods!($type, ne, "native byte order");
+            impl_peek_methods!($type, le, "little endian byte order");
+            impl_peek_methods!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+/// Reader with a lookahead buffer, for peeking at any [`Read`] before consuming it
+///
+/// Unlike [`BufRead`] sources, which expose whatever happens to be buffered, peeks here read as
+/// much as they ask for, so tag-dispatch parsers can drive unbuffered sockets and pipes. Peeked
+/// bytes are returned again by the next reads. The reader is also a [`BufRead`] itself.
+///
+/// ```
+/// use read_primitives::{PeekReader, ReadU16};
+/// let mut reader = PeekReader::new(&[0x01, 0x02, 3][..]);
+/// assert_eq!(0x0102, reader.peek_be_u16().unwrap());
+/// assert_eq!([1, 2, 3], reader.peek_bytes(3).unwrap());
+/// assert_eq!(0x0201, reader.read_le_u16().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct PeekReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R> PeekReader<R> {
+    /// Wrap `inner`
+    pub fn new(inner: R) -> Self {
+        PeekReader {
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Bytes that were peeked at and not yet consumed
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped reader
+    ///
+    /// Reading from it directly skips over the bytes in [`PeekReader::buffer`].
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader, together with the bytes that were peeked at and not yet consumed
+    pub fn into_parts(mut self) -> (R, Vec<u8>) {
+        self.buf.drain(..self.pos);
+        (self.inner, self.buf)
+    }
+}
+
+impl<R: Read> PeekReader<R> {
+    /// Peek at the next `len` bytes without consuming them
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends first, keeping the bytes
+    /// read so far for the next reads, or when reading errors
+    pub fn peek_bytes(&mut self, len: usize) -> io::Result<&[u8]> {
+        if self.buf.len() - self.pos < len {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+            let mut filled = self.buf.len();
+            while filled < len {
+                // grow as data arrives rather than trusting `len` up front
+                if self.buf.len() == filled {
+                    self.buf.resize(len.min(filled + crate::PREALLOC_LIMIT), 0);
+                }
+                match self.inner.read(&mut self.buf[filled..]) {
+                    Ok(0) => {
+                        self.buf.truncate(filled);
+                        return Err(io::ErrorKind::UnexpectedEof.into());
+                    }
+                    Ok(read) => filled += read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        self.buf.truncate(filled);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(&self.buf[self.pos..self.pos + len])
+    }
+
+    /// Peek at the next u8 without consuming it
+    /// # Errors
+    /// errors exactly when [`PeekReader::peek_bytes`] errors
+    pub fn peek_u8(&mut self) -> io::Result<u8> {
+        Ok(self.peek_bytes(1)?[0])
+    }
+
+    /// Peek at the next i8 without consuming it
+    /// # Errors
+    /// errors exactly when [`PeekReader::peek_bytes`] errors
+    pub fn peek_i8(&mut self) -> io::Result<i8> {
+        self.peek_u8().map(|byte| byte as i8)
+    }
+
+    /// Peek at the next `T` in `endian` byte order without consuming it
+    /// # Errors
+    /// errors exactly when [`PeekReader::peek_bytes`] errors
+    pub fn peek_endian<T: Primitive>(&mut self, endian: Endian) -> io::Result<T> {
+        let mut bytes = T::Bytes::default();
+        bytes.as_mut().copy_from_slice(self.peek_bytes(T::SIZE)?);
+        Ok(T::from_endian_bytes(bytes, endian))
+    }
+
+    impl_peek_reads!(u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
+    /// Byte order in which the 4 byte `magic` is written at the start of the next bytes, without
+    /// consuming them, as [`detect_endianness`](crate::detect_endianness) tells it
+    /// # Errors
+    /// errors exactly when [`PeekReader::peek_bytes`] errors
+    pub fn peek_magic_endian(&mut self, magic: u32) -> io::Result<Option<Endian>> {
+        Ok(crate::detect_endianness(self.peek_bytes(4)?, magic))
+    }
+}
+
+impl<R: Read> Read for PeekReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() {
+            return self.inner.read(out);
+        }
+        let len = self.buffer().len().min(out.len());
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: Read> BufRead for PeekReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buf.len() {
+            self.buf.resize(FILL_LEN, 0);
+            self.pos = 0;
+            loop {
+                match self.inner.read(&mut self.buf) {
+                    Ok(read) => {
+                        self.buf.truncate(read);
+                        break;
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        self.buf.clear();
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(self.buffer())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.buf.len());
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, BufRead, Read};
+
+    /// Reader that returns one byte per call, like a slow pipe
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn peek_then_read() {
+        let mut reader = PeekReader::new(Trickle(&[7, 0, 0, 0, 1, 2]));
+        assert_eq!(7, reader.peek_u8().unwrap());
+        assert_eq!(7, reader.peek_le_u32().unwrap());
+        assert_eq!(7u32, reader.peek_endian(Endian::Little).unwrap());
+        assert_eq!(7, reader.read_le_u32().unwrap());
+        assert_eq!(0x0102, reader.peek_be_u16().unwrap());
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!([1, 2], *rest);
+    }
+
+    #[test]
+    fn peek_past_end() {
+        let mut reader = PeekReader::new(Trickle(&[1, 2, 3]));
+        reader.read_u8().unwrap();
+        let err = reader.peek_be_u32().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!([2, 3], reader.buffer());
+        let err = reader.peek_bytes(1 << 40).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!([2, 3], reader.buffer());
+        assert_eq!(0x0203, reader.read_be_u16().unwrap());
+    }
+
+    #[test]
+    fn magic_endian() {
+        let mut reader = PeekReader::new(Trickle(b"\x4d\x3c\xb2\xa1\x02\x00"));
+        assert_eq!(
+            Some(Endian::Big),
+            reader.peek_magic_endian(0x4d3c_b2a1).unwrap()
+        );
+        assert_eq!(
+            Some(Endian::Little),
+            reader.peek_magic_endian(0xa1b2_3c4d).unwrap()
+        );
+        assert_eq!(None, reader.peek_magic_endian(0xa1b2_c3d4).unwrap());
+        assert_eq!(0x4d, reader.read_u8().unwrap());
+        assert_eq!(None, detect_endianness(&[0xa1, 0xb2], 0xa1b2_c3d4));
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut reader = PeekReader::new(&b"ab\ncd"[..]);
+        assert_eq!(b'a', reader.peek_u8().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!("ab\n", line);
+        assert_eq!(b"cd", reader.fill_buf().unwrap());
+        reader.consume(1);
+        let (_, rest) = reader.into_parts();
+        assert_eq!(b"d", rest.as_slice());
+    }
+}
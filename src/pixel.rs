@@ -0,0 +1,266 @@
+//! Reads of common packed pixel formats
+//!
+//! Packed formats keep their raw component values; convert to [`Rgba8888`] with [`From`] to
+//! scale them to 8 bits.
+//!
+//! ```
+//! use read_primitives::pixel::{ReadPixel, Rgb565, Rgba8888};
+//! let mut bytes: &[u8] = &[0x1f, 0xf8];
+//! let pixel = bytes.read_le_rgb565().unwrap();
+//! assert_eq!(Rgb565 { r: 31, g: 0, b: 31 }, pixel);
+//! assert_eq!(Rgba8888 { r: 255, g: 0, b: 255, a: 255 }, pixel.into());
+//! ```
+
+use std::io::{self, Read};
+
+/// 8 bits per channel RGBA, stored as the bytes r, g, b, a
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgba8888 {
+    /// Red
+    pub r: u8,
+    /// Green
+    pub g: u8,
+    /// Blue
+    pub b: u8,
+    /// Alpha
+    pub a: u8,
+}
+
+/// 16-bit RGB with 5 bits red, 6 bits green and 5 bits blue, red in the high bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb565 {
+    /// Red, 0 to 31
+    pub r: u8,
+    /// Green, 0 to 63
+    pub g: u8,
+    /// Blue, 0 to 31
+    pub b: u8,
+}
+
+/// 16-bit ARGB with a 1-bit alpha in the high bit followed by 5 bits per color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Argb1555 {
+    /// Whether the pixel is opaque
+    pub a: bool,
+    /// Red, 0 to 31
+    pub r: u8,
+    /// Green, 0 to 31
+    pub g: u8,
+    /// Blue, 0 to 31
+    pub b: u8,
+}
+
+impl Rgb565 {
+    /// Unpack from a 16-bit value
+    pub fn from_u16(value: u16) -> Self {
+        Rgb565 {
+            r: (value >> 11) as u8,
+            g: (value >> 5 & 0x3f) as u8,
+            b: (value & 0x1f) as u8,
+        }
+    }
+}
+
+impl Argb1555 {
+    /// Unpack from a 16-bit value
+    pub fn from_u16(value: u16) -> Self {
+        Argb1555 {
+            a: value & 0x8000 != 0,
+            r: (value >> 10 & 0x1f) as u8,
+            g: (value >> 5 & 0x1f) as u8,
+            b: (value & 0x1f) as u8,
+        }
+    }
+}
+
+fn scale5(value: u8) -> u8 {
+    value << 3 | value >> 2
+}
+
+fn scale6(value: u8) -> u8 {
+    value << 2 | value >> 4
+}
+
+impl From<Rgb565> for Rgba8888 {
+    fn from(pixel: Rgb565) -> Self {
+        Rgba8888 {
+            r: scale5(pixel.r),
+            g: scale6(pixel.g),
+            b: scale5(pixel.b),
+            a: 0xff,
+        }
+    }
+}
+
+impl From<Argb1555> for Rgba8888 {
+    fn from(pixel: Argb1555) -> Self {
+        Rgba8888 {
+            r: scale5(pixel.r),
+            g: scale5(pixel.g),
+            b: scale5(pixel.b),
+            a: if pixel.a { 0xff } else { 0 },
+        }
+    }
+}
+
+macro_rules! impl_row {
+    ($name:ident, $pixel:ty, $size:literal, $decode:expr) => {
+        ::paste::paste! {
+            #[doc = "Read a row of `width` [`" $pixel "`] pixels"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidInput`] if the row is longer than `usize::MAX` bytes,"]
+            #[doc = "or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early"]
+            fn $name(&mut self, width: usize) -> io::Result<Vec<$pixel>> {
+                let len = width.checked_mul($size).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "row length overflows usize")
+                })?;
+                let bytes = crate::string::read_exact_vec(self, len)?;
+                Ok(bytes
+                    .chunks_exact($size)
+                    .map(|chunk| {
+                        let mut array = [0u8; $size];
+                        array.copy_from_slice(chunk);
+                        ($decode)(array)
+                    })
+                    .collect())
+            }
+        }
+    };
+}
+
+fn rgba8888([r, g, b, a]: [u8; 4]) -> Rgba8888 {
+    Rgba8888 { r, g, b, a }
+}
+
+/// Trait to read packed pixels
+pub trait ReadPixel: Read {
+    /// Read an [`Rgba8888`] pixel
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_rgba8888(&mut self) -> io::Result<Rgba8888> {
+        let mut bytes = [0u8; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(rgba8888(bytes))
+    }
+
+    /// Read an [`Rgb565`] pixel stored as a little endian u16
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_le_rgb565(&mut self) -> io::Result<Rgb565> {
+        let mut bytes = [0u8; 2];
+        self.read_exact(&mut bytes)?;
+        Ok(Rgb565::from_u16(u16::from_le_bytes(bytes)))
+    }
+
+    /// Read an [`Rgb565`] pixel stored as a big endian u16
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_be_rgb565(&mut self) -> io::Result<Rgb565> {
+        let mut bytes = [0u8; 2];
+        self.read_exact(&mut bytes)?;
+        Ok(Rgb565::from_u16(u16::from_be_bytes(bytes)))
+    }
+
+    /// Read an [`Argb1555`] pixel stored as a little endian u16
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_le_argb1555(&mut self) -> io::Result<Argb1555> {
+        let mut bytes = [0u8; 2];
+        self.read_exact(&mut bytes)?;
+        Ok(Argb1555::from_u16(u16::from_le_bytes(bytes)))
+    }
+
+    /// Read an [`Argb1555`] pixel stored as a big endian u16
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_be_argb1555(&mut self) -> io::Result<Argb1555> {
+        let mut bytes = [0u8; 2];
+        self.read_exact(&mut bytes)?;
+        Ok(Argb1555::from_u16(u16::from_be_bytes(bytes)))
+    }
+
+    impl_row!(read_rgba8888_row, Rgba8888, 4, rgba8888);
+    impl_row!(read_le_rgb565_row, Rgb565, 2, |bytes| Rgb565::from_u16(
+        u16::from_le_bytes(bytes)
+    ));
+    impl_row!(read_be_rgb565_row, Rgb565, 2, |bytes| Rgb565::from_u16(
+        u16::from_be_bytes(bytes)
+    ));
+    impl_row!(read_le_argb1555_row, Argb1555, 2, |bytes| {
+        Argb1555::from_u16(u16::from_le_bytes(bytes))
+    });
+    impl_row!(read_be_argb1555_row, Argb1555, 2, |bytes| {
+        Argb1555::from_u16(u16::from_be_bytes(bytes))
+    });
+}
+impl<R> ReadPixel for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::pixel::*;
+
+    #[test]
+    fn read_rgba8888() {
+        let mut bytes: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(
+            Rgba8888 {
+                r: 1,
+                g: 2,
+                b: 3,
+                a: 4
+            },
+            bytes.read_rgba8888().unwrap()
+        );
+        assert_eq!(
+            vec![Rgba8888 {
+                r: 5,
+                g: 6,
+                b: 7,
+                a: 8
+            }],
+            bytes.read_rgba8888_row(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn read_argb1555() {
+        // opaque, r = 1, g = 2, b = 3
+        let mut bytes: &[u8] = &[0x84, 0x43, 0x43, 0x84];
+        let expected = Argb1555 {
+            a: true,
+            r: 1,
+            g: 2,
+            b: 3,
+        };
+        assert_eq!(expected, bytes.read_be_argb1555().unwrap());
+        assert_eq!(expected, bytes.read_le_argb1555().unwrap());
+    }
+
+    #[test]
+    fn rgb565_rows() {
+        let mut bytes: &[u8] = &[0xff, 0xff, 0x07, 0xe0];
+        let row = bytes.read_be_rgb565_row(2).unwrap();
+        assert_eq!(
+            Rgb565 {
+                r: 31,
+                g: 63,
+                b: 31
+            },
+            row[0]
+        );
+        assert_eq!(Rgb565 { r: 0, g: 63, b: 0 }, row[1]);
+        assert_eq!(
+            Rgba8888 {
+                r: 0,
+                g: 255,
+                b: 0,
+                a: 255
+            },
+            row[1].into()
+        );
+        let err = bytes.read_rgba8888_row(usize::MAX).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let err = (&[0u8; 6][..]).read_rgba8888_row(1 << 40).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
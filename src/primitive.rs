@@ -0,0 +1,357 @@
+use crate::Endian;
+#[cfg(feature = "std")]
+use crate::{ConversionError, SaturatingFrom, WrappingFrom};
+#[cfg(feature = "std")]
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+#[cfg(feature = "std")]
+use std::marker::PhantomData;
+
+/// A fixed-size type that can be decoded from bytes in either byte order
+///
+/// Implemented for all integer and float types and bool. Downstream crates can implement it for
+/// their own fixed-size types to use them with the generic reads of [`ReadPrimitive`].
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{Primitive, ReadPrimitive};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Fixed16(i16);
+///
+/// impl Primitive for Fixed16 {
+///     const SIZE: usize = 2;
+///     type Bytes = [u8; 2];
+///     fn from_le_bytes(bytes: [u8; 2]) -> Self {
+///         Fixed16(i16::from_le_bytes(bytes))
+///     }
+///     fn from_be_bytes(bytes: [u8; 2]) -> Self {
+///         Fixed16(i16::from_be_bytes(bytes))
+///     }
+/// }
+///
+/// let mut bytes: &[u8] = &[0, 1, 0, 2];
+/// assert_eq!(vec![Fixed16(256), Fixed16(512)], bytes.read_le_vec::<Fixed16>(2).unwrap());
+/// ```
+pub trait Primitive: Sized {
+    /// Size of an encoded value in bytes
+    const SIZE: usize;
+    /// Byte array holding an encoded value, `[u8; SIZE]`
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
+
+    /// Decode from little endian bytes
+    fn from_le_bytes(bytes: Self::Bytes) -> Self;
+
+    /// Decode from big endian bytes
+    fn from_be_bytes(bytes: Self::Bytes) -> Self;
+
+    /// Decode from native endian bytes
+    fn from_ne_bytes(bytes: Self::Bytes) -> Self {
+        if cfg!(target_endian = "little") {
+            Self::from_le_bytes(bytes)
+        } else {
+            Self::from_be_bytes(bytes)
+        }
+    }
+
+    /// Decode from bytes in `endian` byte order
+    fn from_endian_bytes(bytes: Self::Bytes, endian: Endian) -> Self {
+        match endian {
+            Endian::Little => Self::from_le_bytes(bytes),
+            Endian::Big => Self::from_be_bytes(bytes),
+        }
+    }
+}
+
+macro_rules! impl_primitive {
+    ($($type:ty),+) => {
+        $(
+            impl Primitive for $type {
+                const SIZE: usize = core::mem::size_of::<$type>();
+                type Bytes = [u8; core::mem::size_of::<$type>()];
+
+                fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                    <$type>::from_le_bytes(bytes)
+                }
+
+                fn from_be_bytes(bytes: Self::Bytes) -> Self {
+                    <$type>::from_be_bytes(bytes)
+                }
+
+                fn from_ne_bytes(bytes: Self::Bytes) -> Self {
+                    <$type>::from_ne_bytes(bytes)
+                }
+            }
+        )+
+    };
+}
+
+impl_primitive!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+impl Primitive for bool {
+    const SIZE: usize = 1;
+    type Bytes = [u8; 1];
+
+    fn from_le_bytes([byte]: [u8; 1]) -> Self {
+        byte != 0
+    }
+
+    fn from_be_bytes([byte]: [u8; 1]) -> Self {
+        byte != 0
+    }
+}
+
+/// Iterator over consecutive primitives, returned by the `iter_*` methods of [`ReadPrimitive`]
+///
+/// Ends when the reader ends on an element boundary, and yields an
+/// [`io::ErrorKind::UnexpectedEof`] error if it ends inside an element.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct PrimitiveIter<'r, R: ?Sized, T: Primitive> {
+    reader: &'r mut R,
+    decode: fn(T::Bytes) -> T,
+    marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + ?Sized, T: Primitive> Iterator for PrimitiveIter<'_, R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = T::Bytes::default();
+        let buf = bytes.as_mut();
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return None,
+                Ok(0) => return Some(Err(io::ErrorKind::UnexpectedEof.into())),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        Some(Ok((self.decode)(bytes)))
+    }
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_generic_methods {
+    ($order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read a `T` in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order>]<T: Primitive>(&mut self) -> io::Result<T> {
+                let mut bytes = T::Bytes::default();
+                self.read_exact(bytes.as_mut())
+                    .map_err(|err| crate::error::context(err, core::any::type_name::<T>(), stringify!($order)))?;
+                Ok(T::[<from_ $order _bytes>](bytes))
+            }
+            #[doc = "Read an `S` in " $order_doc " and convert it to a `T` with [`TryFrom`]"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidData`] carrying a [`ConversionError`] with the stored value if"]
+            #[doc = "it doesn't convert, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _as>]<S, T>(&mut self) -> io::Result<T>
+            where
+                S: Primitive + Copy + fmt::Debug + Send + Sync + 'static,
+                T: TryFrom<S>,
+            {
+                let value = self.[<read_ $order>]::<S>()?;
+                T::try_from(value).map_err(|_| ConversionError::new::<T>(value).into())
+            }
+            #[doc = "Read an `S` in " $order_doc " and convert it to a `T`, clamping values out of range"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _saturating>]<S: Primitive, T: SaturatingFrom<S>>(&mut self) -> io::Result<T> {
+                self.[<read_ $order>]::<S>().map(T::saturating_from)
+            }
+            #[doc = "Read an `S` in " $order_doc " and convert it to a `T`, wrapping values out of range"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _wrapping>]<S: Primitive, T: WrappingFrom<S>>(&mut self) -> io::Result<T> {
+                self.[<read_ $order>]::<S>().map(T::wrapping_from)
+            }
+            #[doc = "Read `N` consecutive `T` in " $order_doc " into an array"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _array>]<T: Primitive, const N: usize>(&mut self) -> io::Result<[T; N]> {
+                match self.[<read_ $order _vec>](N)?.try_into() {
+                    Ok(array) => Ok(array),
+                    Err(_) => unreachable!("read_vec returns exactly N elements"),
+                }
+            }
+            #[doc = "Read `count` consecutive `T` in " $order_doc " into a `Vec`"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _vec>]<T: Primitive>(&mut self, count: usize) -> io::Result<Vec<T>> {
+                let mut reader = self;
+                crate::outline::read_vec(&mut reader, count, T::[<from_ $order _bytes>])
+            }
+            #[doc = "Iterate over consecutive `T` in " $order_doc " until the reader ends"]
+            fn [<iter_ $order>]<T: Primitive>(&mut self) -> PrimitiveIter<'_, Self, T> {
+                PrimitiveIter {
+                    reader: self,
+                    decode: T::[<from_ $order _bytes>],
+                    marker: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+/// Trait to read any [`Primitive`] through generic methods
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::ReadPrimitive;
+/// let mut bytes: &[u8] = &[1, 0, 2, 0, 3, 0];
+/// let first: u16 = bytes.read_le().unwrap();
+/// let rest = bytes.iter_le::<u16>().collect::<std::io::Result<Vec<_>>>().unwrap();
+/// assert_eq!((1, vec![2, 3]), (first, rest));
+/// ```
+#[cfg(feature = "std")]
+pub trait ReadPrimitive: Read {
+    impl_generic_methods!(ne, "native byte order");
+    impl_generic_methods!(le, "little endian byte order");
+    impl_generic_methods!(be, "big endian byte order");
+
+    /// Read a `T` in `endian` byte order
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_endian<T: Primitive>(&mut self, endian: Endian) -> io::Result<T> {
+        let mut bytes = T::Bytes::default();
+        let order = match endian {
+            Endian::Little => "le",
+            Endian::Big => "be",
+        };
+        self.read_exact(bytes.as_mut())
+            .map_err(|err| crate::error::context(err, core::any::type_name::<T>(), order))?;
+        Ok(T::from_endian_bytes(bytes, endian))
+    }
+
+    /// Read an `S` in `endian` byte order and convert it to a `T` with [`TryFrom`]
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] carrying a [`ConversionError`] with the stored
+    /// value if it doesn't convert, or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_endian_as<S, T>(&mut self, endian: Endian) -> io::Result<T>
+    where
+        S: Primitive + Copy + fmt::Debug + Send + Sync + 'static,
+        T: TryFrom<S>,
+    {
+        let value = self.read_endian::<S>(endian)?;
+        T::try_from(value).map_err(|_| ConversionError::new::<T>(value).into())
+    }
+
+    /// Read an `S` in `endian` byte order and convert it to a `T`, clamping values out of range
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_endian_saturating<S: Primitive, T: SaturatingFrom<S>>(
+        &mut self,
+        endian: Endian,
+    ) -> io::Result<T> {
+        self.read_endian::<S>(endian).map(T::saturating_from)
+    }
+
+    /// Read an `S` in `endian` byte order and convert it to a `T`, wrapping values out of range
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_endian_wrapping<S: Primitive, T: WrappingFrom<S>>(
+        &mut self,
+        endian: Endian,
+    ) -> io::Result<T> {
+        self.read_endian::<S>(endian).map(T::wrapping_from)
+    }
+}
+#[cfg(feature = "std")]
+impl<R> ReadPrimitive for R where R: Read {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn read_generic() {
+        let mut bytes: &[u8] = &[1, 0, 0, 0, 0x3f, 0x80, 0, 0, 1];
+        assert_eq!(1u32, bytes.read_le().unwrap());
+        assert_eq!(1.0f32, bytes.read_be().unwrap());
+        assert!(bytes.read_ne::<bool>().unwrap());
+    }
+
+    #[test]
+    fn read_endian() {
+        let mut bytes: &[u8] = &[1, 0, 0, 1];
+        assert_eq!(1u16, bytes.read_endian(Endian::Little).unwrap());
+        assert_eq!(1u16, bytes.read_endian(Endian::Big).unwrap());
+        assert_eq!(
+            u32::from_ne_bytes([1, 2, 3, 4]),
+            (&[1u8, 2, 3, 4][..]).read_endian(Endian::NATIVE).unwrap()
+        );
+    }
+
+    #[test]
+    fn read_as() {
+        #[derive(Debug, PartialEq)]
+        struct Even(u8);
+        impl TryFrom<i16> for Even {
+            type Error = ();
+            fn try_from(value: i16) -> Result<Self, ()> {
+                match u8::try_from(value) {
+                    Ok(value) if value % 2 == 0 => Ok(Even(value)),
+                    _ => Err(()),
+                }
+            }
+        }
+        let mut bytes: &[u8] = &[0, 4, 3, 0, 0xff, 0xff];
+        assert_eq!(Even(4), bytes.read_be_as::<i16, Even>().unwrap());
+        let err = bytes.read_le_as::<i16, Even>().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = bytes.read_endian_as::<i16, u8>(Endian::Big).unwrap_err();
+        assert_eq!("stored value -1 is out of range for u8", err.to_string());
+        let inner = err.into_inner().unwrap();
+        let inner = inner.downcast_ref::<ConversionError<i16>>().unwrap();
+        assert_eq!((&-1, "u8"), (inner.value(), inner.target()));
+        assert_eq!(7u64, (&[7u8][..]).read_ne_as::<u8, u64>().unwrap());
+    }
+
+    #[test]
+    fn read_saturating_and_wrapping() {
+        let mut bytes: &[u8] = &[0x70, 0x11, 1, 0, 0, 1, 0x11, 0x70, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(u16::MAX, bytes.read_le_saturating::<u32, u16>().unwrap());
+        assert_eq!(0x1170u16, bytes.read_be_wrapping::<u32, u16>().unwrap());
+        assert_eq!(
+            0u8,
+            bytes
+                .read_endian_saturating::<i16, u8>(Endian::Big)
+                .unwrap()
+        );
+        assert_eq!(
+            255u8,
+            bytes
+                .read_endian_wrapping::<i16, u8>(Endian::Little)
+                .unwrap()
+        );
+        assert!(bytes.read_ne_wrapping::<u8, i8>().is_err());
+    }
+
+    #[test]
+    fn read_array() {
+        let mut bytes: &[u8] = &[0, 1, 0, 2, 0, 3];
+        assert_eq!([1i16, 2, 3], bytes.read_be_array().unwrap());
+        assert!(bytes.read_be_array::<i16, 1>().is_err());
+    }
+
+    #[test]
+    fn iter() {
+        let mut bytes: &[u8] = &[1, 0, 2, 0, 3];
+        let mut iter = bytes.iter_le::<u16>();
+        assert_eq!(1, iter.next().unwrap().unwrap());
+        assert_eq!(2, iter.next().unwrap().unwrap());
+        let err = iter.next().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert!(iter.next().is_none());
+    }
+}
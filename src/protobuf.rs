@@ -0,0 +1,303 @@
+//! Schema-less reads of the Protocol Buffers wire format
+//!
+//! A message is a sequence of fields, each a varint key holding the field number and
+//! [`WireType`], then a payload whose length the wire type gives. [`Fields`] iterates over the
+//! fields of a buffer, borrowing length-delimited payloads, and [`FieldReader`] over those of a
+//! stream. Neither knows what the fields mean: a length-delimited payload may be a string, bytes,
+//! a nested message or a packed repeated field, and a varint may be signed, zigzag encoded or a
+//! bool.
+//!
+//! ```
+//! use read_primitives::protobuf::{Fields, Payload, WireType};
+//! // field 1 is the varint 150, field 2 the string "hi"
+//! let message = [0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i'];
+//! let fields = Fields::new(&message).collect::<std::io::Result<Vec<_>>>().unwrap();
+//! assert_eq!((1, WireType::Varint, Payload::Varint(150)), fields[0]);
+//! assert_eq!((2, WireType::Len, Payload::Len(&b"hi"[..])), fields[1]);
+//! ```
+
+use crate::{Endian, ReadFrom, ReadVarint};
+use std::io::{self, Read};
+
+/// Largest field number
+const MAX_FIELD_NUMBER: u64 = (1 << 29) - 1;
+
+/// Encoding of a field's payload, the low 3 bits of its key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireType {
+    /// A varint: `int32`, `int64`, `uint32`, `uint64`, `sint32`, `sint64`, `bool` and `enum`
+    Varint,
+    /// 8 little endian bytes: `fixed64`, `sfixed64` and `double`
+    I64,
+    /// A varint length, then that many bytes: `string`, `bytes`, messages and packed fields
+    Len,
+    /// Start of a deprecated group
+    StartGroup,
+    /// End of a deprecated group
+    EndGroup,
+    /// 4 little endian bytes: `fixed32`, `sfixed32` and `float`
+    I32,
+}
+
+impl WireType {
+    /// Wire type of the low 3 bits of a key, `None` for the unassigned 6 and 7
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        Some(match bits {
+            0 => WireType::Varint,
+            1 => WireType::I64,
+            2 => WireType::Len,
+            3 => WireType::StartGroup,
+            4 => WireType::EndGroup,
+            5 => WireType::I32,
+            _ => return None,
+        })
+    }
+}
+
+/// Payload of a field, with length-delimited bytes held in `B`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Payload<B> {
+    /// Value of a [`WireType::Varint`] field
+    Varint(u64),
+    /// Value of a [`WireType::I64`] field
+    I64(u64),
+    /// Bytes of a [`WireType::Len`] field
+    Len(B),
+    /// A [`WireType::StartGroup`] field, which has no payload
+    StartGroup,
+    /// A [`WireType::EndGroup`] field, which has no payload
+    EndGroup,
+    /// Value of a [`WireType::I32`] field
+    I32(u32),
+}
+
+/// A field: its number, wire type and payload
+pub type Field<B> = (u32, WireType, Payload<B>);
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Split a key into the field number and wire type
+fn split_key(key: u64) -> io::Result<(u32, WireType)> {
+    let number = key >> 3;
+    if number == 0 || number > MAX_FIELD_NUMBER {
+        return Err(invalid_data(format!("invalid field number {number}")));
+    }
+    let bits = (key & 7) as u8;
+    let wire_type = WireType::from_bits(bits)
+        .ok_or_else(|| invalid_data(format!("invalid wire type {bits}")))?;
+    Ok((number as u32, wire_type))
+}
+
+/// Read the payload of a field of `wire_type`, reading length-delimited bytes with `read_len`
+fn read_payload<R: Read, B>(
+    reader: &mut R,
+    wire_type: WireType,
+    read_len: impl FnOnce(&mut R, usize) -> io::Result<B>,
+) -> io::Result<Payload<B>> {
+    Ok(match wire_type {
+        WireType::Varint => Payload::Varint(reader.read_varint_u64()?),
+        WireType::I64 => Payload::I64(u64::read_from(reader, Endian::Little)?),
+        WireType::Len => {
+            let len = reader.read_varint_u64()?;
+            let len = usize::try_from(len)
+                .map_err(|_| invalid_data(format!("length {len} overflows usize")))?;
+            Payload::Len(read_len(reader, len)?)
+        }
+        WireType::StartGroup => Payload::StartGroup,
+        WireType::EndGroup => Payload::EndGroup,
+        WireType::I32 => Payload::I32(u32::read_from(reader, Endian::Little)?),
+    })
+}
+
+/// Iterator over the fields of an encoded message in a buffer, borrowing length-delimited
+/// payloads from it
+///
+/// Ends after the first error.
+#[derive(Debug, Clone)]
+pub struct Fields<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    /// Fields of the message `input`
+    pub fn new(input: &'a [u8]) -> Self {
+        Fields { input }
+    }
+
+    /// The bytes after the fields read so far
+    pub fn remaining(&self) -> &'a [u8] {
+        self.input
+    }
+
+    fn read_field(&mut self) -> io::Result<Field<&'a [u8]>> {
+        let (number, wire_type) = split_key(self.input.read_varint_u64()?)?;
+        let payload = read_payload(&mut self.input, wire_type, |input, len| {
+            if len > input.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("field {number} of {len} bytes overruns the message"),
+                ));
+            }
+            let (bytes, rest) = input.split_at(len);
+            *input = rest;
+            Ok(bytes)
+        })?;
+        Ok((number, wire_type, payload))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = io::Result<Field<&'a [u8]>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let field = self.read_field();
+        if field.is_err() {
+            self.input = &[];
+        }
+        Some(field)
+    }
+}
+
+/// Iterator over the fields of an encoded message read from a stream, until its end
+///
+/// Length-delimited payloads are read into a `Vec`, which grows as the bytes arrive, and longer
+/// ones than the [`Limits`](crate::Limits) allow are an [`io::ErrorKind::InvalidData`] error.
+/// Ends after the first error.
+#[derive(Debug)]
+pub struct FieldReader<R> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> FieldReader<R> {
+    /// Fields of the message read from `reader`
+    pub fn new(reader: R) -> Self {
+        FieldReader {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_field(&mut self) -> io::Result<Option<Field<Vec<u8>>>> {
+        // the end of the stream is only the end of the message before a key's first byte
+        let Some(first) = u8::read_from_optional(&mut self.reader, Endian::Little)? else {
+            return Ok(None);
+        };
+        let key = (&[first][..]).chain(&mut self.reader).read_varint_u64()?;
+        let (number, wire_type) = split_key(key)?;
+        let payload = read_payload(&mut self.reader, wire_type, |reader, len| {
+            crate::limits::check_bytes(len)?;
+            crate::string::read_exact_vec(reader, len)
+        })?;
+        Ok(Some((number, wire_type, payload)))
+    }
+}
+
+impl<R: Read> Iterator for FieldReader<R> {
+    type Item = io::Result<Field<Vec<u8>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let field = self.read_field().transpose();
+        if !matches!(field, Some(Ok(_))) {
+            self.done = true;
+        }
+        field
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::protobuf::*;
+    use std::io;
+
+    const MESSAGE: &[u8] = &[
+        0x08, 0xac, 0x02, // 1: varint 300
+        0x11, 1, 0, 0, 0, 0, 0, 0, 0, // 2: i64 1
+        0x1a, 0x03, 0x0a, 0x01, 0x78, // 3: len, a nested message
+        0x1b, 0x1c, // 3: group start and end
+        0xfd, 0xff, 0xff, 0xff, 0x0f, 0, 0, 0x80, 0x3f, // 536870911: i32 of 1.0f32
+    ];
+
+    #[test]
+    fn fields() {
+        let fields = Fields::new(MESSAGE)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (1, WireType::Varint, Payload::Varint(300)),
+                (2, WireType::I64, Payload::I64(1)),
+                (3, WireType::Len, Payload::Len(&[0x0a, 0x01, 0x78][..])),
+                (3, WireType::StartGroup, Payload::StartGroup),
+                (3, WireType::EndGroup, Payload::EndGroup),
+                (
+                    MAX_FIELD_NUMBER as u32,
+                    WireType::I32,
+                    Payload::I32(0x3f80_0000)
+                ),
+            ],
+            fields
+        );
+        let Payload::Len(nested) = fields[2].2 else {
+            unreachable!()
+        };
+        let nested: Vec<_> = Fields::new(nested).map(Result::unwrap).collect();
+        assert_eq!(vec![(1, WireType::Len, Payload::Len(&b"x"[..]))], nested);
+    }
+
+    #[test]
+    fn stream() {
+        let fields: Vec<_> = FieldReader::new(MESSAGE).map(Result::unwrap).collect();
+        assert_eq!(6, fields.len());
+        assert_eq!((1, WireType::Varint, Payload::Varint(300)), fields[0]);
+        assert_eq!(
+            (3, WireType::Len, Payload::Len(vec![0x0a, 0x01, 0x78])),
+            fields[2]
+        );
+        assert_eq!((3, WireType::EndGroup, Payload::EndGroup), fields[4]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut fields = Fields::new(&[0x0e, 0]);
+        let err = fields.next().unwrap().unwrap_err();
+        assert_eq!("invalid wire type 6", err.to_string());
+        assert!(fields.next().is_none());
+        let err = Fields::new(&[0x00]).next().unwrap().unwrap_err();
+        assert_eq!("invalid field number 0", err.to_string());
+        let err = Fields::new(&[0x0a, 0x05, 1]).next().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!("field 1 of 5 bytes overruns the message", err.to_string());
+
+        let mut truncated = FieldReader::new(&[0x08, 0x80][..]);
+        let err = truncated.next().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert!(truncated.next().is_none());
+        let err = FieldReader::new(&[0x0a, 0x05, 1][..])
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let limits = crate::Limits {
+            max_bytes_per_field: 1,
+            ..crate::Limits::DEFAULT
+        };
+        let err = limits
+            .apply(|| FieldReader::new(&[0x0a, 0x02, 1, 2][..]).next().unwrap())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
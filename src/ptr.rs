@@ -0,0 +1,124 @@
+use crate::{Endian, Primitive};
+use std::io::{self, Read};
+
+/// Pointer size of a target, which can differ from the host's `usize`
+///
+/// For core dumps, remote process memory and debug info, where the pointer size comes from the
+/// file header rather than from the platform the tool runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PtrWidth {
+    /// 32-bit pointers
+    U32,
+    /// 64-bit pointers
+    U64,
+}
+
+impl PtrWidth {
+    /// Pointer size of the host, if it is 32 or 64 bits
+    pub const NATIVE: Option<PtrWidth> = match usize::BITS {
+        32 => Some(PtrWidth::U32),
+        64 => Some(PtrWidth::U64),
+        _ => None,
+    };
+
+    /// Size of a pointer in bytes
+    pub fn size(self) -> usize {
+        match self {
+            PtrWidth::U32 => 4,
+            PtrWidth::U64 => 8,
+        }
+    }
+
+    /// Largest address a pointer can hold
+    pub fn max(self) -> u64 {
+        match self {
+            PtrWidth::U32 => u32::MAX.into(),
+            PtrWidth::U64 => u64::MAX,
+        }
+    }
+
+    /// Narrow `address` to this width
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `address` doesn't fit
+    pub fn narrow(self, address: u64) -> io::Result<u64> {
+        if address > self.max() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "address {address:#x} does not fit in {} bits",
+                    self.size() * 8
+                ),
+            ));
+        }
+        Ok(address)
+    }
+}
+
+/// Trait to read pointers of a target's width
+///
+/// ```
+/// use read_primitives::{Endian, PtrWidth, ReadPtr};
+/// let mut bytes: &[u8] = &[0x00, 0x10, 0x40, 0x00];
+/// assert_eq!(0x40_1000, bytes.read_ptr(PtrWidth::U32, Endian::Little).unwrap());
+/// ```
+pub trait ReadPtr: Read {
+    /// Read a pointer of `width` in `endian` byte order, widened to u64
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_ptr(&mut self, width: PtrWidth, endian: Endian) -> io::Result<u64> {
+        Ok(match width {
+            PtrWidth::U32 => {
+                let mut bytes = [0u8; 4];
+                self.read_exact(&mut bytes)?;
+                u32::from_endian_bytes(bytes, endian).into()
+            }
+            PtrWidth::U64 => {
+                let mut bytes = [0u8; 8];
+                self.read_exact(&mut bytes)?;
+                u64::from_endian_bytes(bytes, endian)
+            }
+        })
+    }
+
+    /// Read a pointer of `width` in `endian` byte order as a host `usize`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the address doesn't fit in a `usize`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_ptr_usize(&mut self, width: PtrWidth, endian: Endian) -> io::Result<usize> {
+        let address = self.read_ptr(width, endian)?;
+        usize::try_from(address).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("address {address:#x} does not fit in usize"),
+            )
+        })
+    }
+}
+impl<R> ReadPtr for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn read_ptr() {
+        let mut bytes: &[u8] = &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2];
+        assert_eq!(1, bytes.read_ptr(PtrWidth::U32, Endian::Big).unwrap());
+        assert_eq!(2, bytes.read_ptr_usize(PtrWidth::U64, Endian::Big).unwrap());
+        assert!(bytes.read_ptr(PtrWidth::U32, Endian::Big).is_err());
+        assert_eq!(size_of::<usize>(), PtrWidth::NATIVE.unwrap().size());
+    }
+
+    #[test]
+    fn narrow() {
+        assert_eq!(0xffff_ffff, PtrWidth::U32.narrow(0xffff_ffff).unwrap());
+        let err = PtrWidth::U32.narrow(1 << 32).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "address 0x100000000 does not fit in 32 bits",
+            err.to_string()
+        );
+        assert!(PtrWidth::U64.narrow(u64::MAX).is_ok());
+    }
+}
@@ -0,0 +1,63 @@
+use num_rational::Ratio;
+use std::io::{self, Read};
+
+macro_rules! impl_ratio_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read a [`Ratio`] of " $type " stored as numerator then denominator in " $order_doc]
+            #[doc = ""]
+            #[doc = "The ratio is kept as stored, not reduced."]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`io::ErrorKind::InvalidData`] if the denominator is zero,"]
+            #[doc = "or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+            fn [<read_ $order _ratio_ $type>](&mut self) -> io::Result<Ratio<$type>> {
+                let mut bytes = [[0u8; size_of::<$type>()]; 2];
+                self.read_exact(bytes.as_flattened_mut())?;
+                let [numer, denom] = bytes.map($type::[<from_ $order _bytes>]);
+                if denom == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("ratio {numer}/0 has a zero denominator"),
+                    ));
+                }
+                Ok(Ratio::new_raw(numer, denom))
+            }
+        }
+    };
+}
+
+macro_rules! impl_ratio_reads {
+    ($($type:ty),+) => {
+        $(
+            impl_ratio_methods!($type, ne, "native byte order");
+            impl_ratio_methods!($type, le, "little endian byte order");
+            impl_ratio_methods!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+/// Trait to read [`num_rational::Ratio`] values, like the rationals of TIFF and EXIF
+pub trait ReadRatio: Read {
+    impl_ratio_reads!(u16, i16, u32, i32, u64, i64);
+}
+impl<R> ReadRatio for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use num_rational::Ratio;
+    use std::io;
+
+    #[test]
+    fn ratio() {
+        let mut bytes: &[u8] = &[0, 0, 0, 72, 0, 0, 0, 2, 0xff, 0xff, 0xff, 0xfd, 0, 0, 0, 4];
+        let resolution = bytes.read_be_ratio_u32().unwrap();
+        assert_eq!((72, 2), (*resolution.numer(), *resolution.denom()));
+        assert_eq!(Ratio::new(-3, 4), bytes.read_be_ratio_i32().unwrap());
+
+        let mut zero: &[u8] = &[1, 0, 0, 0, 0, 0, 0, 0];
+        let err = zero.read_le_ratio_u32().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("ratio 1/0 has a zero denominator", err.to_string());
+    }
+}
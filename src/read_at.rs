@@ -0,0 +1,316 @@
+use crate::{Endian, Primitive};
+#[cfg(feature = "rayon")]
+use crate::{FixedSize, ReadFrom};
+use std::fs::File;
+use std::io;
+#[cfg(feature = "rayon")]
+use std::ops::Range;
+
+macro_rules! impl_at_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc " at `offset`"]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`ReadAt::read_exact_at`] errors"]
+            fn [<read_ $order _ $type _at>](&self, offset: u64) -> io::Result<$type> {
+                let mut bytes = [0u8; std::mem::size_of::<$type>()];
+                self.read_exact_at(&mut bytes, offset)?;
+                Ok($type::[<from_ $order _bytes>](bytes))
+            }
+        }
+    };
+}
+
+macro_rules! impl_at_reads {
+    ($($type:ty),+) => {
+        $(
+            impl_at_methods!($type, ne, "native byte order");
+            impl_at_methods!($type, le, "little endian byte order");
+            impl_at_methods!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+/// Trait for positional reads that take `&self` and leave the file cursor alone
+///
+/// Reads at independent offsets can run concurrently from several threads sharing one handle,
+/// as for fetching records through an index. Implemented for [`File`] with `pread` on Unix and
+/// `ReadFile` with an offset on Windows; on Windows the cursor does move, so don't mix these
+/// with cursor-based reads of the same handle there.
+///
+/// ```no_run
+/// use read_primitives::ReadAt;
+/// let file = std::fs::File::open("index.bin").unwrap();
+/// let record = file.read_le_u64_at(16).unwrap();
+/// let len = file.read_le_u32_at(record).unwrap();
+/// ```
+pub trait ReadAt {
+    /// Fill `buf` with the bytes starting at `offset`
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the source ends before `buf` is full,
+    /// or when the underlying read errors
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+
+    /// Read u8 at `offset`
+    /// # Errors
+    /// errors exactly when [`ReadAt::read_exact_at`] errors
+    fn read_u8_at(&self, offset: u64) -> io::Result<u8> {
+        let mut byte = [0u8; 1];
+        self.read_exact_at(&mut byte, offset)?;
+        Ok(byte[0])
+    }
+
+    /// Read i8 at `offset`
+    /// # Errors
+    /// errors exactly when [`ReadAt::read_exact_at`] errors
+    fn read_i8_at(&self, offset: u64) -> io::Result<i8> {
+        self.read_u8_at(offset).map(|byte| byte as i8)
+    }
+
+    /// Read a `T` in `endian` byte order at `offset`
+    /// # Errors
+    /// errors exactly when [`ReadAt::read_exact_at`] errors
+    fn read_endian_at<T: Primitive>(&self, offset: u64, endian: Endian) -> io::Result<T> {
+        let mut bytes = T::Bytes::default();
+        self.read_exact_at(bytes.as_mut(), offset)?;
+        Ok(T::from_endian_bytes(bytes, endian))
+    }
+
+    impl_at_reads!(u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+}
+
+impl ReadAt for File {
+    #[cfg(unix)]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => {
+                    buf = &mut buf[read..];
+                    offset += read as u64;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+}
+
+/// Bytes read by one rayon task of [`par_read_records`]
+#[cfg(feature = "rayon")]
+const PAR_CHUNK_LEN: usize = 1 << 20;
+
+/// Read `count` records of `T` starting at `offset` of `source`, splitting them into chunks read
+/// with positional reads and decoded in parallel with rayon, in order
+///
+/// Worth it over reading the records in sequence for tables of megabytes or more, especially
+/// when they are not yet in the page cache.
+///
+/// ```no_run
+/// use read_primitives::{par_read_records, Endian};
+/// let file = std::fs::File::open("samples.bin").unwrap();
+/// let samples: Vec<(u64, f32)> = par_read_records(&file, 64, 1_000_000, Endian::Little).unwrap();
+/// ```
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidInput`] if the records' size overflows `usize` or their
+/// offsets overflow `u64`, or when reading or decoding a chunk errors
+#[cfg(feature = "rayon")]
+pub fn par_read_records<T, S>(
+    source: &S,
+    offset: u64,
+    count: usize,
+    endian: Endian,
+) -> io::Result<Vec<T>>
+where
+    T: FixedSize + Send,
+    S: ReadAt + Sync + ?Sized,
+{
+    use rayon::prelude::*;
+    let len = crate::fixed::batch_len::<T>(count)?;
+    u64::try_from(len)
+        .ok()
+        .and_then(|len| offset.checked_add(len))
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "record offset overflows u64")
+        })?;
+    let chunk_records = (PAR_CHUNK_LEN / T::SIZE.max(1)).max(1);
+    let chunks = (0..count.div_ceil(chunk_records))
+        .into_par_iter()
+        .map(|chunk| {
+            let start = chunk * chunk_records;
+            let records = chunk_records.min(count - start);
+            let mut bytes = vec![0; records * T::SIZE];
+            source.read_exact_at(&mut bytes, offset + (start * T::SIZE) as u64)?;
+            crate::fixed::decode_batch(&bytes, endian, records)
+        })
+        .collect::<io::Result<Vec<Vec<T>>>>()?;
+    let mut records = Vec::with_capacity(count);
+    chunks.into_iter().for_each(|chunk| records.extend(chunk));
+    Ok(records)
+}
+
+/// Read a `T` from each byte range of `source` in `ranges`, as listed by an offset index, with
+/// positional reads decoded in parallel with rayon, in order
+///
+/// Each value is decoded from the bytes of its range, so it may leave the end of the range
+/// unread but not read past it. The decodes run with the caller's [`Limits`](crate::Limits),
+/// and a range's buffer grows by at most [`PREALLOC_LIMIT`](crate::PREALLOC_LIMIT) bytes per
+/// positional read, so a corrupt index allocates at most about as much as the source holds.
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidInput`] if a range is reversed or longer than `usize`,
+/// with [`io::ErrorKind::InvalidData`] if a range exceeds the limits,
+/// or when reading or decoding a value errors
+#[cfg(feature = "rayon")]
+pub fn par_read_ranges<T, S>(
+    source: &S,
+    ranges: &[Range<u64>],
+    endian: Endian,
+) -> io::Result<Vec<T>>
+where
+    T: ReadFrom + Send,
+    S: ReadAt + Sync + ?Sized,
+{
+    use rayon::prelude::*;
+    let limits = crate::Limits::current();
+    ranges
+        .par_iter()
+        .map_init(Vec::new, |bytes, range| {
+            limits.apply(|| {
+                let len = range
+                    .end
+                    .checked_sub(range.start)
+                    .and_then(|len| usize::try_from(len).ok())
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("invalid byte range {range:?}"),
+                        )
+                    })?;
+                crate::limits::check_bytes(len)?;
+                bytes.clear();
+                while bytes.len() < len {
+                    let filled = bytes.len();
+                    bytes.resize(filled + (len - filled).min(crate::PREALLOC_LIMIT), 0);
+                    source.read_exact_at(&mut bytes[filled..], range.start + filled as u64)?;
+                }
+                T::read_from(&mut bytes.as_slice(), endian)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Seek, SeekFrom, Write};
+
+    #[test]
+    fn read_at_keeps_cursor() {
+        let path = std::env::temp_dir().join(format!("read_at_{}.bin", std::process::id()));
+        let mut file = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(&[1, 0, 0, 0, 0, 0, 0, 0, 2, 3]).unwrap();
+        file.seek(SeekFrom::Start(4)).unwrap();
+        assert_eq!(1, file.read_le_u64_at(0).unwrap());
+        assert_eq!(0x0203, file.read_be_u16_at(8).unwrap());
+        assert_eq!(3u8, file.read_endian_at(9, Endian::Big).unwrap());
+        let err = file.read_le_u32_at(8).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        #[cfg(unix)]
+        assert_eq!(4, file.stream_position().unwrap());
+        std::thread::scope(|scope| {
+            let file = &file;
+            let first = scope.spawn(move || file.read_u8_at(0).unwrap());
+            let last = scope.spawn(move || file.read_i8_at(9).unwrap());
+            assert_eq!((1, 3), (first.join().unwrap(), last.join().unwrap()));
+        });
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    struct Memory(Vec<u8>);
+
+    #[cfg(feature = "rayon")]
+    impl ReadAt for Memory {
+        fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+            let start = offset as usize;
+            let bytes = self
+                .0
+                .get(start..start + buf.len())
+                .ok_or(io::ErrorKind::UnexpectedEof)?;
+            buf.copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_read_records() {
+        let count = 300_000u32;
+        let mut data = vec![0xee; 3];
+        for i in 0..count {
+            data.extend_from_slice(&i.to_be_bytes());
+            data.push(i as u8);
+        }
+        let source = Memory(data);
+        let records: Vec<(u32, u8)> =
+            crate::par_read_records(&source, 3, count as usize, Endian::Big).unwrap();
+        assert_eq!(count as usize, records.len());
+        assert!(records
+            .iter()
+            .enumerate()
+            .all(|(i, &(key, low))| key == i as u32 && low == i as u8));
+        let err = crate::par_read_records::<u64, _>(&source, 3, 200_000, Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let err =
+            crate::par_read_records::<u64, _>(&source, u64::MAX - 8, 2, Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_read_ranges() {
+        let source = Memory(vec![0, 1, 0, 2, 0, 0, 3]);
+        let values: Vec<u16> =
+            crate::par_read_ranges(&source, &[4..7, 0..2, 2..4], Endian::Little).unwrap();
+        assert_eq!(vec![0, 0x100, 0x200], values);
+        let err =
+            crate::par_read_ranges::<u16, _>(&source, &[0..2, 6..8], Endian::Little).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        #[allow(clippy::reversed_empty_ranges)]
+        let err =
+            crate::par_read_ranges::<u8, _>(&source, &[0..1, 3..2], Endian::Little).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let err =
+            crate::par_read_ranges::<u8, _>(&source, &[0..1, 0..u64::MAX >> 8], Endian::Little)
+                .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let limits = Limits {
+            max_bytes_per_field: 4,
+            ..Limits::DEFAULT
+        };
+        let err = limits
+            .apply(|| crate::par_read_ranges::<u8, _>(&source, &[0..1, 0..7], Endian::Little))
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,663 @@
+use crate::{Endian, Limits, Primitive};
+use std::io::{self, Read};
+
+/// A type that can be read from a byte stream
+///
+/// Implemented for the integer and float types, bool, arrays and tuples. With the `derive` feature,
+/// `#[derive(ReadFrom)]` implements it for structs by reading their fields in order.
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom};
+/// let mut bytes: &[u8] = &[1, 0, 2, 0];
+/// let pair = <[u16; 2]>::read_from(&mut bytes, Endian::Little).unwrap();
+/// assert_eq!([1, 2], pair);
+/// ```
+pub trait ReadFrom: Sized {
+    /// Read a value, using `endian` for the parts that do not specify their own byte order
+    /// # Errors
+    /// errors when reading from `reader` errors, or if the data is invalid for the type
+    fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self>;
+
+    /// [`ReadFrom::read_from`] with `limits` in effect for the whole value, see [`Limits`]
+    /// # Errors
+    /// errors like [`ReadFrom::read_from`], or with a [`LimitExceeded`](crate::LimitExceeded)
+    /// if a derived read exceeds `limits`
+    fn read_from_with_limits<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        limits: Limits,
+    ) -> io::Result<Self> {
+        limits.apply(|| Self::read_from(reader, endian))
+    }
+
+    /// [`ReadFrom::read_from`], or `None` if the reader is at its end, for trailing values that
+    /// older producers leave out
+    ///
+    /// Only an end before the value's first byte gives `None`; a reader ending inside the value
+    /// is still an error.
+    ///
+    /// ```
+    /// use read_primitives::{Endian, ReadFrom};
+    /// let mut bytes: &[u8] = &[1, 0];
+    /// assert_eq!(Some(1), u16::read_from_optional(&mut bytes, Endian::Little).unwrap());
+    /// assert_eq!(None, u16::read_from_optional(&mut bytes, Endian::Little).unwrap());
+    /// assert!(u16::read_from_optional(&mut &[1u8][..], Endian::Little).is_err());
+    /// ```
+    /// # Errors
+    /// errors like [`ReadFrom::read_from`], except at the end of the reader
+    fn read_from_optional<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> io::Result<Option<Self>> {
+        // read one byte ahead to tell the end from the start of a value
+        let mut first = [0u8; 1];
+        let read = loop {
+            match reader.read(&mut first) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                read => break read?,
+            }
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        Self::read_from(&mut (&first[..]).chain(reader), endian).map(Some)
+    }
+}
+
+/// A type that can be read from a byte slice, borrowing from it for `'a`
+///
+/// Implemented for every [`ReadFrom`] type. With the `derive` feature, `#[derive(ReadFrom)]` on a
+/// struct with a lifetime parameter implements this trait instead, so that `&'a str` and
+/// `&'a [u8]` fields can borrow from the input without copying.
+pub trait ReadFromSlice<'a>: Sized {
+    /// Read a value from the start of `input` and advance it past the value, using `endian` for
+    /// the parts that do not specify their own byte order
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if `input` ends early, or if the data is
+    /// invalid for the type
+    fn read_from_slice(input: &mut &'a [u8], endian: Endian) -> io::Result<Self>;
+}
+
+impl<'a, T: ReadFrom> ReadFromSlice<'a> for T {
+    fn read_from_slice(input: &mut &'a [u8], endian: Endian) -> io::Result<Self> {
+        T::read_from(input, endian)
+    }
+}
+
+/// Error of a derived read, naming the field that failed and where it starts
+///
+/// Returned inside an [`io::Error`] of the same kind as the underlying error. The path names the
+/// innermost struct and field, `Header.section_count`, and the offset is counted from the start of
+/// the outermost value being read.
+///
+/// ```
+/// use read_primitives::FieldError;
+/// # let err = std::io::Error::other("");
+/// if let Some(field) = err.get_ref().and_then(|err| err.downcast_ref::<FieldError>()) {
+///     eprintln!("{} failed at {:#x}", field.path(), field.offset());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FieldError {
+    path: String,
+    offset: u64,
+    source: io::Error,
+}
+
+impl FieldError {
+    /// Struct and field that failed to read
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Offset of the start of the field
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl core::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} at offset {:#x}: {}",
+            self.path, self.offset, self.source
+        )
+    }
+}
+
+impl core::error::Error for FieldError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// An enum whose variant is selected by a tag read before it
+///
+/// With the `derive` feature, `#[derive(ReadTagged)]` implements it for enums whose variants
+/// declare their tag values, and structs deriving [`ReadFrom`] pass a tag read earlier to
+/// `read_tagged` with `#[read(tag = "...")]`.
+pub trait ReadTagged: Sized {
+    /// Type of the tag
+    type Tag;
+
+    /// Read the variant selected by `tag`, using `endian` for the parts that do not specify their
+    /// own byte order
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if no variant has the tag, when reading from
+    /// `reader` errors, or if the data is invalid for the variant
+    fn read_tagged<R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        tag: Self::Tag,
+    ) -> io::Result<Self>;
+}
+
+macro_rules! impl_read_from {
+    ($($type:ty),+) => {
+        $(
+            impl ReadFrom for $type {
+                fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+                    let mut bytes = <$type as Primitive>::Bytes::default();
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$type as Primitive>::from_endian_bytes(bytes, endian))
+                }
+            }
+        )+
+    };
+}
+
+impl_read_from!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool);
+
+impl<T: ReadFrom, const N: usize> ReadFrom for [T; N] {
+    fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+        let mut vec = Vec::with_capacity(N);
+        for _ in 0..N {
+            vec.push(T::read_from(reader, endian)?);
+        }
+        match vec.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("exactly N elements were read"),
+        }
+    }
+}
+
+macro_rules! impl_read_from_tuple {
+    ($($type:ident),+) => {
+        /// Reads the elements in order, handing the byte order down to each
+        impl<$($type: ReadFrom),+> ReadFrom for ($($type,)+) {
+            fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+                Ok(($($type::read_from(reader, endian)?,)+))
+            }
+        }
+    };
+}
+
+impl_read_from_tuple!(A);
+impl_read_from_tuple!(A, B);
+impl_read_from_tuple!(A, B, C);
+impl_read_from_tuple!(A, B, C, D);
+impl_read_from_tuple!(A, B, C, D, E);
+impl_read_from_tuple!(A, B, C, D, E, F);
+impl_read_from_tuple!(A, B, C, D, E, F, G);
+impl_read_from_tuple!(A, B, C, D, E, F, G, H);
+
+/// Helpers called by the code generated by the derive, not public API
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive_support {
+    use super::{FieldError, ReadFrom, ReadFromSlice};
+    use crate::limits;
+    use crate::DepthGuard;
+    use crate::{Endian, ReadBorrowed, WriteTo};
+    use std::io::{self, Read, Write};
+
+    /// Reader or writer counting the bytes passed through it, for the offsets of [`FieldError`]
+    /// and for alignment
+    pub struct Counter<'r, R: ?Sized> {
+        inner: &'r mut R,
+        position: u64,
+    }
+
+    impl<'r, R: ?Sized> Counter<'r, R> {
+        pub fn new(inner: &'r mut R) -> Self {
+            Counter { inner, position: 0 }
+        }
+
+        /// Counter resuming a value `position` bytes after its start
+        pub fn at(inner: &'r mut R, position: u64) -> Self {
+            Counter { inner, position }
+        }
+
+        pub fn position(&self) -> u64 {
+            self.position
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for Counter<'_, R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.position += read as u64;
+            Ok(read)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for Counter<'_, W> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.inner.write(buf)?;
+            self.position += written as u64;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    /// Attribute an error to the field starting at `offset`
+    ///
+    /// Errors of nested fields keep their path and have their offset moved by `offset`.
+    pub fn field_error(err: io::Error, path: &str, offset: u64) -> io::Error {
+        let err = match shift_offset(err, offset) {
+            Ok(shifted) => return shifted,
+            Err(err) => err,
+        };
+        let kind = err.kind();
+        io::Error::new(
+            kind,
+            FieldError {
+                path: path.into(),
+                offset,
+                source: err,
+            },
+        )
+    }
+
+    /// Move the offset of an error from an element read on its own by `start`
+    pub fn shift_element_error(err: io::Error, start: u64) -> io::Error {
+        shift_offset(err, start).unwrap_or_else(|err| err)
+    }
+
+    /// Move the offset of a [`FieldError`] by `offset`, or return the error unchanged as `Err`
+    fn shift_offset(mut err: io::Error, offset: u64) -> Result<io::Error, io::Error> {
+        match err
+            .get_mut()
+            .and_then(|inner| inner.downcast_mut::<FieldError>())
+        {
+            Some(field) => {
+                field.offset += offset;
+                Ok(err)
+            }
+            None => Err(err),
+        }
+    }
+
+    pub fn with_field<T>(result: io::Result<T>, path: &str, offset: u64) -> io::Result<T> {
+        result.map_err(|err| field_error(err, path, offset))
+    }
+
+    /// Value of a `count` or `len` expression, which reads evaluate over the fields and writes
+    /// over references to them
+    pub trait ToSize {
+        fn to_size(self) -> Option<usize>;
+    }
+
+    macro_rules! impl_to_size {
+        ($($type:ty),+) => {
+            $(
+                impl ToSize for $type {
+                    fn to_size(self) -> Option<usize> {
+                        usize::try_from(self).ok()
+                    }
+                }
+                impl ToSize for &$type {
+                    fn to_size(self) -> Option<usize> {
+                        (*self).to_size()
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_to_size!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+    /// Convert the value of a `count` or `len` expression
+    pub fn size(value: impl ToSize) -> io::Result<usize> {
+        value
+            .to_size()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "size out of range"))
+    }
+
+    /// Check that a collection has the length its `count` or `len` expression gives
+    pub fn check_size(context: &str, expected: usize, len: usize) -> io::Result<()> {
+        if expected != len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{context}: length {len} does not match the declared size {expected}"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that a field with an `if` condition is present exactly when the condition holds
+    pub fn check_present(context: &str, condition: bool, present: bool) -> io::Result<()> {
+        if condition != present {
+            let (is, holds) = if present {
+                ("Some", "false")
+            } else {
+                ("None", "true")
+            };
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{context}: value is {is} but its condition is {holds}"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Skip `len` bytes of padding
+    pub fn skip<R: Read + ?Sized>(reader: &mut R, len: usize) -> io::Result<()> {
+        let skipped = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+        if skipped < len as u64 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    pub fn write_zeros<W: Write + ?Sized>(writer: &mut W, len: usize) -> io::Result<()> {
+        io::copy(&mut io::repeat(0).take(len as u64), writer).map(drop)
+    }
+
+    /// Number of bytes from `position` to the next multiple of `align`
+    pub fn align(position: u64, align: usize) -> io::Result<usize> {
+        if align == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "alignment must not be zero",
+            ));
+        }
+        Ok(((align as u64 - position % align as u64) % align as u64) as usize)
+    }
+
+    /// Number of padding bytes after a field of `used` bytes padded to `size`
+    pub fn pad_to(used: u64, size: usize) -> io::Result<usize> {
+        (size as u64)
+            .checked_sub(used)
+            .map(|len| len as usize)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("field of {used} bytes is longer than its padded size {size}"),
+                )
+            })
+    }
+
+    /// Check before writing that a field of `used` bytes fits in its padded size
+    pub fn check_pad_to(context: &str, used: u64, size: usize) -> io::Result<usize> {
+        pad_to(used, size)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, format!("{context}: {err}")))
+    }
+
+    pub fn write_slice<T: WriteTo, W: Write + ?Sized>(
+        writer: &mut W,
+        endian: Endian,
+        values: &[T],
+    ) -> io::Result<()> {
+        values.write_to(writer, endian)
+    }
+
+    pub fn read_vec<T: ReadFrom, R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Vec<T>> {
+        limits::check_elements(count)?;
+        let mut vec = Vec::with_capacity(count.min(crate::PREALLOC_LIMIT / size_of::<T>().max(1)));
+        let mut counter = Counter::new(reader);
+        for _ in 0..count {
+            let start = counter.position();
+            let element = T::read_from(&mut counter, endian)
+                .map_err(|err| shift_element_error(err, start))?;
+            vec.push(element);
+        }
+        Ok(vec)
+    }
+
+    pub fn read_until_eof<T: ReadFrom, R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> io::Result<Vec<T>> {
+        let mut vec = Vec::new();
+        let mut counter = Counter::new(reader);
+        loop {
+            let start = counter.position();
+            // read one byte ahead to tell the end from the start of another element
+            let mut first = [0u8; 1];
+            let read = loop {
+                match counter.read(&mut first) {
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read?,
+                }
+            };
+            if read == 0 {
+                return Ok(vec);
+            }
+            limits::check_elements(vec.len() + 1)?;
+            let mut head = &first[..];
+            let element = T::read_from(&mut (&mut head).chain(&mut counter), endian)
+                .map_err(|err| shift_element_error(err, start))?;
+            if !head.is_empty() {
+                return Err(empty_element::<T>());
+            }
+            vec.push(element);
+        }
+    }
+
+    /// Error for an element read until the end that consumes no bytes, and would repeat forever
+    fn empty_element<T>() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} read until the end consumes no bytes",
+                core::any::type_name::<T>()
+            ),
+        )
+    }
+
+    pub fn slice_position(base: usize, input: &[u8]) -> u64 {
+        (base - input.len()) as u64
+    }
+
+    pub fn read_vec_from_slice<'a, T: ReadFromSlice<'a>>(
+        input: &mut &'a [u8],
+        endian: Endian,
+        count: usize,
+    ) -> io::Result<Vec<T>> {
+        limits::check_elements(count)?;
+        let mut vec = Vec::with_capacity(count.min(crate::PREALLOC_LIMIT / size_of::<T>().max(1)));
+        let base = input.len();
+        for _ in 0..count {
+            let start = slice_position(base, input);
+            let element =
+                T::read_from_slice(input, endian).map_err(|err| shift_element_error(err, start))?;
+            vec.push(element);
+        }
+        Ok(vec)
+    }
+
+    pub fn read_optional<T: ReadFrom + Default, R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> io::Result<T> {
+        T::read_from_optional(reader, endian).map(Option::unwrap_or_default)
+    }
+
+    pub fn read_optional_from_slice<'a, T: ReadFromSlice<'a> + Default>(
+        input: &mut &'a [u8],
+        endian: Endian,
+    ) -> io::Result<T> {
+        if input.is_empty() {
+            return Ok(T::default());
+        }
+        T::read_from_slice(input, endian)
+    }
+
+    pub fn read_until_eof_from_slice<'a, T: ReadFromSlice<'a>>(
+        input: &mut &'a [u8],
+        endian: Endian,
+    ) -> io::Result<Vec<T>> {
+        let mut vec = Vec::new();
+        let base = input.len();
+        while !input.is_empty() {
+            limits::check_elements(vec.len() + 1)?;
+            let start = slice_position(base, input);
+            let element =
+                T::read_from_slice(input, endian).map_err(|err| shift_element_error(err, start))?;
+            if slice_position(base, input) == start {
+                return Err(empty_element::<T>());
+            }
+            vec.push(element);
+        }
+        Ok(vec)
+    }
+
+    /// Type of a `len` field read from a slice
+    pub trait ReadLen<'a>: Sized {
+        fn read_len(input: &mut &'a [u8], len: usize) -> io::Result<Self>;
+    }
+
+    impl<'a> ReadLen<'a> for &'a [u8] {
+        fn read_len(input: &mut &'a [u8], len: usize) -> io::Result<Self> {
+            limits::check_bytes(len)?;
+            input.read_borrowed_bytes(len)
+        }
+    }
+
+    impl<'a> ReadLen<'a> for &'a str {
+        fn read_len(input: &mut &'a [u8], len: usize) -> io::Result<Self> {
+            limits::check_bytes(len)?;
+            input.read_borrowed_str(len)
+        }
+    }
+
+    impl<'a> ReadLen<'a> for String {
+        fn read_len(input: &mut &'a [u8], len: usize) -> io::Result<Self> {
+            read_string(input, len)
+        }
+    }
+
+    /// Read `expected.len()` bytes and compare them with `expected`
+    pub fn check_magic<R: Read + ?Sized>(reader: &mut R, expected: &[u8]) -> io::Result<()> {
+        compare_magic(
+            expected,
+            &crate::string::read_exact_vec(reader, expected.len())?,
+        )
+    }
+
+    pub fn compare_magic(expected: &[u8], found: &[u8]) -> io::Result<()> {
+        if found != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected magic b\"{}\", found b\"{}\"",
+                    expected.escape_ascii(),
+                    found.escape_ascii()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn assertion_failed(condition: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("assertion `{condition}` failed"),
+        )
+    }
+
+    pub fn check_range<T: PartialOrd + core::fmt::Debug>(
+        value: &T,
+        range: impl core::ops::RangeBounds<T>,
+        text: &str,
+    ) -> io::Result<()> {
+        if range.contains(value) {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{value:?} is out of range {text}"),
+        ))
+    }
+
+    pub fn check_fn<E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+        result: Result<(), E>,
+    ) -> io::Result<()> {
+        result.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn unknown_tag(tag: &dyn core::fmt::Debug) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("unknown tag {tag:?}"))
+    }
+
+    pub fn unsupported_version(version: &dyn core::fmt::Debug) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported version {version:?}"),
+        )
+    }
+
+    pub fn read_string<R: Read + ?Sized>(reader: &mut R, len: usize) -> io::Result<String> {
+        limits::check_bytes(len)?;
+        String::from_utf8(crate::string::read_exact_vec(reader, len)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    #[cfg(feature = "bumpalo")]
+    pub use crate::bump::{ReadCountIn, ReadLenIn};
+    pub use crate::trace::{DescribeDebug, DescribeOther, TraceValue};
+    #[cfg(feature = "bumpalo")]
+    pub use bumpalo;
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    /// Mark the start of a derived field, for [`Trace`](crate::Trace)
+    pub fn trace_mark() -> crate::TraceMark {
+        crate::TraceMark::start()
+    }
+
+    /// Error for an incremental state whose decoded fields don't match its step
+    pub fn corrupted_state() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "incremental state is corrupted")
+    }
+
+    /// Enter a derived value, for [`Limits::max_depth`](crate::Limits::max_depth)
+    pub fn nested() -> io::Result<DepthGuard> {
+        DepthGuard::enter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn read_from() {
+        let mut bytes: &[u8] = &[0, 1, 1, 0, 0x3f, 0x80, 0, 0];
+        assert_eq!(1, u16::read_from(&mut bytes, Endian::Big).unwrap());
+        assert_eq!(1, i16::read_from(&mut bytes, Endian::Little).unwrap());
+        assert_eq!(1.0, f32::read_from(&mut bytes, Endian::Big).unwrap());
+        let err = u8::read_from(&mut bytes, Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_array() {
+        let mut bytes: &[u8] = &[1, 2, 3, 4, 5];
+        let array = <[[u8; 2]; 2]>::read_from(&mut bytes, Endian::Little).unwrap();
+        assert_eq!([[1, 2], [3, 4]], array);
+        assert!(<[u8; 2]>::read_from(&mut bytes, Endian::Little).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use std::io::{self, Read};
+
+/// Reader that can return to a marked position, like Java's `InputStream::mark`
+///
+/// After [`RewindReader::mark`], the bytes read are kept so that [`RewindReader::reset`] can
+/// replay them, for probing a few bytes to detect a format variant and then parsing again from
+/// the mark. At most `limit` bytes are kept; reading past that drops the mark.
+///
+/// ```
+/// use read_primitives::{ReadU32, RewindReader};
+/// let mut reader = RewindReader::new(&[0xfe, 0xff, 0, 1][..], 16);
+/// reader.mark();
+/// let little = reader.read_le_u32().unwrap();
+/// reader.reset().unwrap();
+/// let big = reader.read_be_u32().unwrap();
+/// assert_eq!((0x0100_fffe, 0xfeff_0001), (little, big));
+/// ```
+#[derive(Debug)]
+pub struct RewindReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    limit: usize,
+    marked: bool,
+}
+
+impl<R> RewindReader<R> {
+    /// Wrap `inner`, keeping at most `limit` bytes after a mark
+    pub fn new(inner: R, limit: usize) -> Self {
+        RewindReader {
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+            limit,
+            marked: false,
+        }
+    }
+
+    /// Mark the current position, replacing any earlier mark
+    pub fn mark(&mut self) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        self.marked = true;
+    }
+
+    /// Return to the marked position
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if there is no mark, or it was dropped because
+    /// more than `limit` bytes were read after it
+    pub fn reset(&mut self) -> io::Result<()> {
+        if !self.marked {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no mark to reset to",
+            ));
+        }
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// Drop the mark, so that the bytes read after it are no longer kept
+    pub fn unmark(&mut self) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        self.marked = false;
+    }
+
+    /// Run `parse`, and if it errors, return to where the reader was before it
+    ///
+    /// The bytes `parse` consumed stay consumed only if it succeeds, so that alternative formats
+    /// can be tried in turn. Transactions nest, also inside a [`RewindReader::mark`], but `parse`
+    /// must not set or drop marks itself.
+    ///
+    /// ```
+    /// use read_primitives::{ReadU8, RewindReader};
+    /// use std::io;
+    /// let mut reader = RewindReader::new(&[2, 7][..], 16);
+    /// let magic = reader.transaction(|reader| match reader.read_u8()? {
+    ///     1 => Ok("v1"),
+    ///     _ => Err(io::Error::new(io::ErrorKind::InvalidData, "not v1")),
+    /// });
+    /// assert!(magic.is_err());
+    /// assert_eq!(2, reader.read_u8().unwrap());
+    /// ```
+    /// # Errors
+    /// errors when `parse` errors; if it read more than `limit` bytes first, the reader can't go
+    /// back and the error says so
+    pub fn transaction<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let outer = self.marked;
+        if !outer {
+            self.mark();
+        }
+        let start = self.pos;
+        let result = parse(self);
+        let kept = self.marked;
+        if kept && result.is_err() {
+            self.pos = start;
+        }
+        if kept && !outer {
+            self.unmark();
+        }
+        match result {
+            Err(err) if !kept => Err(io::Error::new(
+                err.kind(),
+                format!(
+                    "{err}, and could not roll back past the rewind limit of {} bytes",
+                    self.limit
+                ),
+            )),
+            result => result,
+        }
+    }
+
+    /// Whether there is a mark to reset to
+    pub fn is_marked(&self) -> bool {
+        self.marked
+    }
+
+    /// Number of bytes read since the mark
+    pub fn marked_len(&self) -> usize {
+        if self.marked {
+            self.pos
+        } else {
+            0
+        }
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped reader
+    ///
+    /// Reading from it directly bypasses the replay buffer.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the reader, together with the bytes after a reset that have not been read again
+    pub fn into_parts(mut self) -> (R, Vec<u8>) {
+        self.buf.drain(..self.pos);
+        (self.inner, self.buf)
+    }
+}
+
+impl<R: Read> Read for RewindReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos < self.buf.len() {
+            let len = out.len().min(self.buf.len() - self.pos);
+            out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+            self.pos += len;
+            if !self.marked && self.pos == self.buf.len() {
+                self.buf.clear();
+                self.pos = 0;
+            }
+            return Ok(len);
+        }
+        let read = self.inner.read(out)?;
+        if self.marked {
+            if self.buf.len() + read > self.limit {
+                self.unmark();
+            } else {
+                self.buf.extend_from_slice(&out[..read]);
+                self.pos = self.buf.len();
+            }
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Read};
+
+    #[test]
+    fn reset_replays() {
+        let mut reader = RewindReader::new(&[1, 2, 3, 4, 5][..], 4);
+        assert_eq!(1, reader.read_u8().unwrap());
+        reader.mark();
+        assert_eq!(0x0203, reader.read_be_u16().unwrap());
+        assert_eq!(2, reader.marked_len());
+        reader.reset().unwrap();
+        assert_eq!(2, reader.read_u8().unwrap());
+        reader.mark();
+        reader.read_u8().unwrap();
+        reader.reset().unwrap();
+        reader.unmark();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!([3, 4, 5], *rest);
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            reader.reset().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn transaction() {
+        let mut reader = RewindReader::new(&[1, 2, 3, 4][..], 8);
+        let failed: io::Result<()> = reader.transaction(|reader| {
+            reader.read_u8()?;
+            let inner = reader.transaction(|reader| reader.read_be_u16());
+            assert_eq!(0x0203, inner.unwrap());
+            reader
+                .transaction(|reader| reader.read_be_u32())
+                .unwrap_err();
+            assert_eq!(4, reader.read_u8().unwrap());
+            Err(io::ErrorKind::InvalidData.into())
+        });
+        assert!(failed.is_err());
+        assert!(!reader.is_marked());
+        assert_eq!(1, reader.transaction(|reader| reader.read_u8()).unwrap());
+        assert_eq!(2, reader.read_u8().unwrap());
+    }
+
+    #[test]
+    fn transaction_past_limit() {
+        let mut reader = RewindReader::new(&[1, 2, 3, 4][..], 2);
+        let err = reader
+            .transaction(|reader| {
+                reader.read_be_u32()?;
+                Err::<(), _>(io::Error::new(io::ErrorKind::InvalidData, "bad record"))
+            })
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "bad record, and could not roll back past the rewind limit of 2 bytes",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn limit_drops_mark() {
+        let mut reader = RewindReader::new(&[1, 2, 3, 4, 5][..], 2);
+        reader.mark();
+        reader.read_be_u16().unwrap();
+        assert!(reader.is_marked());
+        reader.read_u8().unwrap();
+        assert!(!reader.is_marked());
+        assert!(reader.reset().is_err());
+        assert_eq!(0x0405, reader.read_be_u16().unwrap());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{Endian, FixedSize, Needed, ReadFrom, ScatterReader};
+use std::collections::VecDeque;
+use std::io;
+use std::num::NonZeroUsize;
+
+/// Trait to decode values from the front of a buffer that accumulates input, such as the
+/// [`VecDeque`] a poll-based connection fills
+///
+/// A decode that runs out of bytes returns the [`Needed`] bytes instead of an error and leaves
+/// the buffer untouched, so it can be retried when more input arrived. Types that read until the
+/// end of their input, like `until_eof` fields, are decoded from whatever is buffered.
+///
+/// ```
+/// use read_primitives::{Endian, Needed, RingBuffer};
+/// use std::collections::VecDeque;
+/// let mut buffer = VecDeque::from([0, 0]);
+/// assert_eq!(Err(Needed::Size(2.try_into().unwrap())), buffer.try_decode_fixed::<u32>(Endian::Big).unwrap());
+/// buffer.extend([0, 7, 1]);
+/// assert_eq!(Ok(7), buffer.try_decode_fixed::<u32>(Endian::Big).unwrap());
+/// assert_eq!([1], *buffer.make_contiguous());
+/// ```
+pub trait RingBuffer {
+    /// The buffered bytes in order, as the two parts they may be split in
+    fn as_slices(&self) -> (&[u8], &[u8]);
+
+    /// Remove `len` bytes from the front
+    fn discard(&mut self, len: usize);
+
+    /// Decode a `T` from the front, removing its bytes, or tell that more bytes are needed if the
+    /// buffer ended before the value
+    /// # Errors
+    /// errors when decoding errors for a reason other than the end of the buffer
+    fn try_decode<T: ReadFrom>(&mut self, endian: Endian) -> io::Result<Result<T, Needed>> {
+        let (front, back) = self.as_slices();
+        let buffers = [front, back];
+        let mut reader = ScatterReader::new(&buffers);
+        match T::read_from(&mut reader, endian) {
+            Ok(value) => {
+                let len = reader.position() as usize;
+                self.discard(len);
+                Ok(Ok(value))
+            }
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(Err(Needed::Unknown)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Decode a `T` from the front like [`RingBuffer::try_decode`], without trying before its
+    /// [`FixedSize::SIZE`] bytes are buffered, and telling how many more are needed
+    /// # Errors
+    /// errors when decoding errors
+    fn try_decode_fixed<T: FixedSize>(&mut self, endian: Endian) -> io::Result<Result<T, Needed>> {
+        let (front, back) = self.as_slices();
+        match NonZeroUsize::new(T::SIZE.saturating_sub(front.len() + back.len())) {
+            Some(missing) => Ok(Err(Needed::Size(missing))),
+            None => self.try_decode(endian),
+        }
+    }
+}
+
+impl RingBuffer for VecDeque<u8> {
+    fn as_slices(&self) -> (&[u8], &[u8]) {
+        VecDeque::as_slices(self)
+    }
+
+    fn discard(&mut self, len: usize) {
+        self.drain(..len);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::collections::VecDeque;
+    use std::io;
+
+    /// Buffer whose bytes wrap around the end of its storage
+    fn wrapped(bytes: &[u8]) -> VecDeque<u8> {
+        let mut buffer = VecDeque::with_capacity(8);
+        let capacity = buffer.capacity();
+        buffer.extend(vec![0xff; capacity - 1]);
+        buffer.drain(..capacity - 2);
+        buffer.extend(bytes);
+        buffer.pop_front();
+        buffer
+    }
+
+    #[derive(Debug)]
+    struct Invalid;
+
+    impl ReadFrom for Invalid {
+        fn read_from<R: io::Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+            u8::read_from(reader, endian)?;
+            Err(io::Error::new(io::ErrorKind::InvalidData, "invalid"))
+        }
+    }
+
+    #[test]
+    fn incomplete_then_complete() {
+        let mut buffer = wrapped(&[3, 0]);
+        let needed = buffer.try_decode::<(u16, [u8; 2])>(Endian::Little).unwrap();
+        assert_eq!(Err(Needed::Unknown), needed);
+        assert_eq!(2, buffer.len());
+        buffer.extend([1, 2, 9]);
+        assert!(!buffer.as_slices().0.is_empty() && !buffer.as_slices().1.is_empty());
+        let value = buffer.try_decode::<(u16, [u8; 2])>(Endian::Little).unwrap();
+        assert_eq!(Ok((3, [1, 2])), value);
+        assert_eq!([9], *buffer.make_contiguous());
+    }
+
+    #[test]
+    fn fixed_and_errors() {
+        let mut buffer = wrapped(&[2]);
+        let needed = buffer.try_decode_fixed::<[u16; 2]>(Endian::Big).unwrap();
+        assert_eq!(Err(Needed::Size(3.try_into().unwrap())), needed);
+        let mut invalid = wrapped(&[2]);
+        let err = invalid.try_decode::<Invalid>(Endian::Big).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(1, invalid.len());
+    }
+}
@@ -0,0 +1,64 @@
+//! Decoding from the consumer half of a [`ringbuf`](::ringbuf) ring buffer
+//!
+//! Values are decoded from both parts of the buffered bytes, so they may span the wrap point, and
+//! their bytes are only removed once a value is complete: a producer, such as an interrupt
+//! handler, can keep pushing bytes while the main loop retries.
+//!
+//! ```
+//! use read_primitives::ringbuf::ReadConsumer;
+//! use read_primitives::{Endian, Needed};
+//! use ringbuf::traits::{Observer, Producer, SplitRef};
+//! use ringbuf::StaticRb;
+//!
+//! let mut ring = StaticRb::<u8, 8>::default();
+//! let (mut producer, mut consumer) = ring.split_ref();
+//! producer.push_slice(&[0x12]);
+//! assert_eq!(Err(Needed::Unknown), consumer.try_decode::<u16>(Endian::Big).unwrap());
+//! producer.push_slice(&[0x34, 0x56]);
+//! assert_eq!(Ok(0x1234), consumer.try_decode::<u16>(Endian::Big).unwrap());
+//! assert_eq!(1, consumer.occupied_len());
+//! ```
+
+use crate::embedded::{decode_split, DecodeError, ReadFrom};
+use crate::{Endian, Needed};
+use ::ringbuf::traits::Consumer;
+use core::convert::Infallible;
+
+/// Trait to decode [`embedded::ReadFrom`](ReadFrom) values from a byte ring buffer's consumer
+pub trait ReadConsumer: Consumer<Item = u8> {
+    /// Decode a `T` from the front, removing its bytes, or tell that more bytes are needed and
+    /// leave the buffer as it is
+    /// # Errors
+    /// errors if the data is invalid for the type
+    fn try_decode<T: ReadFrom>(
+        &mut self,
+        endian: Endian,
+    ) -> Result<Result<T, Needed>, DecodeError<Infallible>> {
+        let (front, back) = self.as_slices();
+        Ok(decode_split(front, back, endian)?.map(|(value, len)| {
+            self.skip(len);
+            value
+        }))
+    }
+}
+impl<C> ReadConsumer for C where C: Consumer<Item = u8> {}
+
+#[cfg(test)]
+mod test {
+    use crate::ringbuf::*;
+    use ::ringbuf::traits::{Observer, Producer, SplitRef};
+    use ::ringbuf::StaticRb;
+
+    #[test]
+    fn across_wrap_point() {
+        let mut ring = StaticRb::<u8, 4>::default();
+        let (mut producer, mut consumer) = ring.split_ref();
+        producer.push_slice(&[0, 0, 0]);
+        consumer.skip(3);
+        producer.push_slice(&[1, 2, 3]);
+        assert!(!consumer.as_slices().1.is_empty());
+        let value = consumer.try_decode::<[u8; 3]>(Endian::Little).unwrap();
+        assert_eq!(Ok([1, 2, 3]), value);
+        assert!(consumer.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{self, BufRead, Read};
+use std::ops::Deref;
+
+/// Reader presenting a sequence of buffers as one stream, without copying them together
+///
+/// The buffers can be anything that derefs to bytes, such as the [`io::IoSlice`]s of a vectored
+/// receive, `Vec<u8>`s or `bytes::Bytes`. Values straddling the end of a buffer are read from
+/// both sides of it, and empty buffers are skipped.
+///
+/// ```
+/// use read_primitives::{ReadU16, ReadU32, ScatterReader};
+/// use std::io::IoSlice;
+/// let (first, second) = ([0, 1, 0], [0, 0, 2]);
+/// let buffers = [IoSlice::new(&first), IoSlice::new(&[]), IoSlice::new(&second)];
+/// let mut reader = ScatterReader::new(&buffers);
+/// assert_eq!(1, reader.read_be_u16().unwrap());
+/// assert_eq!(2, reader.read_be_u32().unwrap());
+/// assert_eq!(6, reader.position());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScatterReader<'b, S> {
+    buffers: &'b [S],
+    /// Index of the current buffer
+    index: usize,
+    /// Offset in the current buffer
+    offset: usize,
+    position: u64,
+}
+
+impl<'b, S: Deref<Target = [u8]>> ScatterReader<'b, S> {
+    /// Read the bytes of `buffers` in order
+    pub fn new(buffers: &'b [S]) -> Self {
+        ScatterReader {
+            buffers,
+            index: 0,
+            offset: 0,
+            position: 0,
+        }
+    }
+
+    /// Number of bytes read so far
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Number of bytes left in the buffers
+    pub fn remaining(&self) -> usize {
+        let rest = self.buffers.get(self.index..).unwrap_or_default();
+        rest.iter().map(|buffer| buffer.len()).sum::<usize>() - self.offset
+    }
+
+    /// The unread part of the current buffer, empty only at the end of the last one
+    fn current(&mut self) -> &'b [u8] {
+        while let Some(buffer) = self.buffers.get(self.index) {
+            if self.offset < buffer.len() {
+                return &buffer[self.offset..];
+            }
+            self.index += 1;
+            self.offset = 0;
+        }
+        &[]
+    }
+}
+
+impl<S: Deref<Target = [u8]>> Read for ScatterReader<'_, S> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < out.len() {
+            let current = self.current();
+            if current.is_empty() {
+                break;
+            }
+            let len = current.len().min(out.len() - written);
+            out[written..written + len].copy_from_slice(&current[..len]);
+            self.consume(len);
+            written += len;
+        }
+        Ok(written)
+    }
+}
+
+impl<S: Deref<Target = [u8]>> BufRead for ScatterReader<'_, S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.current())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.offset += amt;
+        self.position += amt as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, BufRead, Read};
+
+    #[test]
+    fn straddling_values() {
+        let buffers = vec![vec![1], vec![], vec![2, 3], vec![4, 5, 6, 7, 8, 9]];
+        let mut reader = ScatterReader::new(&buffers);
+        assert_eq!(9, reader.remaining());
+        assert_eq!(0x0102_0304_0506_0708, reader.read_be_u64().unwrap());
+        assert_eq!(1, reader.remaining());
+        assert_eq!(b"\x09", reader.fill_buf().unwrap());
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!([9], *rest);
+        assert_eq!(0, reader.remaining());
+        let err = reader.read_u8().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn composite_reads() {
+        let buffers: [&[u8]; 3] = [&[0, 0], &[0, 5, 0xaa, 0xbb], &[0xcc]];
+        let mut reader = ScatterReader::new(&buffers);
+        let (len, bytes) = <(u32, [u8; 3])>::read_from(&mut reader, Endian::Big).unwrap();
+        assert_eq!((5, [0xaa, 0xbb, 0xcc]), (len, bytes));
+        assert_eq!(7, reader.position());
+    }
+}
@@ -0,0 +1,376 @@
+//! Reads of records whose layout is described at runtime by named, typed fields
+//!
+//! Tools that take layout descriptions from their users, like hex editor templates or test
+//! harnesses, build a [`Layout`] from them and read [`Record`]s of dynamic [`Value`]s. Unlike
+//! [`format`](crate::format) and [`layout`](crate::layout), fields are named, nest, and may take
+//! their length from an earlier field.
+//!
+//! ```
+//! use read_primitives::schema::{Count, Layout, Type, Value};
+//! use read_primitives::Endian;
+//! let layout = Layout::new(Endian::Little)
+//!     .field("magic", Type::Bytes(Count::Fixed(2)))
+//!     .field_with_endian("len", Type::U16, Endian::Big)
+//!     .field("samples", Type::Array(Box::new(Type::I16), Count::Field("len".into())));
+//! let mut bytes: &[u8] = &[b'S', b'M', 0, 2, 0xff, 0xff, 7, 0];
+//! let record = layout.read(&mut bytes).unwrap();
+//! assert_eq!(Some(&Value::UInt(2)), record.get("len"));
+//! assert_eq!(
+//!     Some(&Value::Array(vec![Value::Int(-1), Value::Int(7)])),
+//!     record.get("samples")
+//! );
+//! ```
+
+use crate::limits::{check_bytes, check_elements};
+use crate::string::read_exact_vec;
+use crate::{Endian, ReadExpect, ReadPrimitive};
+use std::io::{self, Read};
+
+/// Number of elements of an array or bytes of a byte string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Count {
+    /// Fixed in the layout
+    Fixed(usize),
+    /// The value of the named unsigned integer field read before, in this record or an enclosing
+    /// one
+    Field(String),
+}
+
+/// Type of a field
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// [`Value::Bool`] of 1 byte
+    Bool,
+    /// [`Value::UInt`] of 1 byte
+    U8,
+    /// [`Value::Int`] of 1 byte
+    I8,
+    /// [`Value::UInt`] of 2 bytes
+    U16,
+    /// [`Value::Int`] of 2 bytes
+    I16,
+    /// [`Value::UInt`] of 4 bytes
+    U32,
+    /// [`Value::Int`] of 4 bytes
+    I32,
+    /// [`Value::UInt`] of 8 bytes
+    U64,
+    /// [`Value::Int`] of 8 bytes
+    I64,
+    /// [`Value::Float`] of 4 bytes
+    F32,
+    /// [`Value::Float`] of 8 bytes
+    F64,
+    /// [`Value::Bytes`]
+    Bytes(Count),
+    /// [`Value::Bytes`] that must equal the given bytes, like a magic number
+    Contents(Vec<u8>),
+    /// [`Value::Array`] of elements of a type
+    Array(Box<Type>, Count),
+    /// [`Value::Record`] of a nested layout, read in its own byte order
+    Record(Layout),
+}
+
+/// A named field of a [`Layout`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Field {
+    /// Name, looked up by [`Record::get`] and [`Count::Field`]
+    pub name: String,
+    /// Type
+    pub ty: Type,
+    /// Byte order, overriding the one of the layout
+    pub endian: Option<Endian>,
+}
+
+/// Fields read one after the other, without padding
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Layout {
+    endian: Endian,
+    fields: Vec<Field>,
+}
+
+/// A value decoded by a [`Layout`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// [`Type::Bool`]
+    Bool(bool),
+    /// Signed integer types
+    Int(i64),
+    /// Unsigned integer types
+    UInt(u64),
+    /// [`Type::F32`], [`Type::F64`]
+    Float(f64),
+    /// [`Type::Bytes`]
+    Bytes(Vec<u8>),
+    /// [`Type::Array`]
+    Array(Vec<Value>),
+    /// [`Type::Record`]
+    Record(Record),
+}
+
+/// Named values read by a [`Layout`], in field order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    fields: Vec<(String, Value)>,
+}
+
+impl Record {
+    /// Value of the first field named `name`
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Names and values of the fields, in field order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Number of fields
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether the record has no fields
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Unwrap the names and values of the fields
+    pub fn into_fields(self) -> Vec<(String, Value)> {
+        self.fields
+    }
+}
+
+/// Look up the count named `name` in the records being read, innermost first
+fn field_count(scopes: &[Vec<(String, Value)>], name: &str) -> io::Result<usize> {
+    let value = scopes
+        .iter()
+        .rev()
+        .flat_map(|scope| scope.iter().rev())
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("count field {name:?} is not read before its use"),
+            )
+        })?;
+    match *value {
+        Value::UInt(count) => usize::try_from(count).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("count {count} of field {name:?} overflows usize"),
+            )
+        }),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("count field {name:?} is not an unsigned integer"),
+        )),
+    }
+}
+
+fn resolve(count: &Count, scopes: &[Vec<(String, Value)>]) -> io::Result<usize> {
+    match count {
+        Count::Fixed(count) => Ok(*count),
+        Count::Field(name) => field_count(scopes, name),
+    }
+}
+
+fn read_type<R: Read + ?Sized>(
+    mut reader: &mut R,
+    ty: &Type,
+    endian: Endian,
+    scopes: &mut Vec<Vec<(String, Value)>>,
+) -> io::Result<Value> {
+    Ok(match ty {
+        Type::Bool => Value::Bool(reader.read_endian(endian)?),
+        Type::U8 => Value::UInt(reader.read_endian::<u8>(endian)?.into()),
+        Type::I8 => Value::Int(reader.read_endian::<i8>(endian)?.into()),
+        Type::U16 => Value::UInt(reader.read_endian::<u16>(endian)?.into()),
+        Type::I16 => Value::Int(reader.read_endian::<i16>(endian)?.into()),
+        Type::U32 => Value::UInt(reader.read_endian::<u32>(endian)?.into()),
+        Type::I32 => Value::Int(reader.read_endian::<i32>(endian)?.into()),
+        Type::U64 => Value::UInt(reader.read_endian(endian)?),
+        Type::I64 => Value::Int(reader.read_endian(endian)?),
+        Type::F32 => Value::Float(reader.read_endian::<f32>(endian)?.into()),
+        Type::F64 => Value::Float(reader.read_endian(endian)?),
+        Type::Bytes(count) => {
+            let len = resolve(count, scopes)?;
+            check_bytes(len)?;
+            Value::Bytes(read_exact_vec(reader, len)?)
+        }
+        Type::Contents(expected) => {
+            reader.expect_bytes(expected)?;
+            Value::Bytes(expected.clone())
+        }
+        Type::Array(element, count) => {
+            let count = resolve(count, scopes)?;
+            check_elements(count)?;
+            let mut values =
+                Vec::with_capacity(count.min(crate::PREALLOC_LIMIT / std::mem::size_of::<Value>()));
+            for _ in 0..count {
+                values.push(read_type(reader, element, endian, scopes)?);
+            }
+            Value::Array(values)
+        }
+        Type::Record(layout) => Value::Record(layout.read_nested(reader, scopes)?),
+    })
+}
+
+impl Layout {
+    /// Layout without fields, whose fields are read in `endian` byte order unless they set their
+    /// own
+    pub fn new(endian: Endian) -> Self {
+        Layout {
+            endian,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a field of type `ty`
+    pub fn field(self, name: impl Into<String>, ty: Type) -> Self {
+        self.push(Field {
+            name: name.into(),
+            ty,
+            endian: None,
+        })
+    }
+
+    /// Add a field of type `ty` in `endian` byte order
+    pub fn field_with_endian(self, name: impl Into<String>, ty: Type, endian: Endian) -> Self {
+        self.push(Field {
+            name: name.into(),
+            ty,
+            endian: Some(endian),
+        })
+    }
+
+    /// Add `field`
+    pub fn push(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Byte order of fields that don't set their own
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// The fields
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Read a record
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if a [`Count::Field`] names no unsigned integer
+    /// field read before it, with [`io::ErrorKind::InvalidData`] if a count exceeds the
+    /// [`Limits`](crate::Limits) or a [`Type::Contents`] doesn't match, or when reading a field
+    /// errors
+    pub fn read<R: Read + ?Sized>(&self, reader: &mut R) -> io::Result<Record> {
+        self.read_nested(reader, &mut Vec::new())
+    }
+
+    fn read_nested<R: Read + ?Sized>(
+        &self,
+        reader: &mut R,
+        scopes: &mut Vec<Vec<(String, Value)>>,
+    ) -> io::Result<Record> {
+        scopes.push(Vec::with_capacity(self.fields.len()));
+        for field in &self.fields {
+            let endian = field.endian.unwrap_or(self.endian);
+            let value = read_type(reader, &field.ty, endian, scopes);
+            let scope = scopes.last_mut().expect("scope pushed above");
+            match value {
+                Ok(value) => scope.push((field.name.clone(), value)),
+                Err(err) => {
+                    scopes.pop();
+                    return Err(err);
+                }
+            }
+        }
+        let fields = scopes.pop().expect("scope pushed above");
+        Ok(Record { fields })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schema::*;
+    use crate::Limits;
+
+    #[test]
+    fn nested_counts() {
+        let entry = Layout::new(Endian::Big)
+            .field("tag", Type::U8)
+            .field("data", Type::Bytes(Count::Field("width".into())));
+        let layout = Layout::new(Endian::Little)
+            .field("width", Type::U8)
+            .field("count", Type::U32)
+            .field(
+                "entries",
+                Type::Array(Box::new(Type::Record(entry)), Count::Field("count".into())),
+            )
+            .field("scale", Type::F32)
+            .field("valid", Type::Bool);
+        let mut bytes: &[u8] = &[2, 2, 0, 0, 0, 1, 9, 9, 2, 8, 8, 0, 0, 0x80, 0x3f, 1];
+        let record = layout.read(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(5, record.len());
+        let Some(Value::Array(entries)) = record.get("entries") else {
+            panic!("entries is not an array");
+        };
+        let Value::Record(second) = &entries[1] else {
+            panic!("entry is not a record");
+        };
+        let fields: Vec<_> = second.iter().collect();
+        assert_eq!(
+            vec![
+                ("tag", &Value::UInt(2)),
+                ("data", &Value::Bytes(vec![8, 8]))
+            ],
+            fields
+        );
+        assert_eq!(Some(&Value::Float(1.0)), record.get("scale"));
+        assert_eq!(Some(&Value::Bool(true)), record.get("valid"));
+        assert_eq!(None, record.get("missing"));
+    }
+
+    #[test]
+    fn invalid_counts() {
+        let layout = Layout::new(Endian::Little)
+            .field("len", Type::I8)
+            .field("data", Type::Bytes(Count::Field("len".into())));
+        let err = layout.read(&mut &[1, 0][..]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let layout =
+            Layout::new(Endian::Little).field("data", Type::Bytes(Count::Field("len".into())));
+        let err = layout.read(&mut &[1, 0][..]).unwrap_err();
+        assert_eq!(
+            "count field \"len\" is not read before its use",
+            err.to_string()
+        );
+        let layout = Layout::new(Endian::Little).field("count", Type::U64).field(
+            "values",
+            Type::Array(Box::new(Type::U8), Count::Field("count".into())),
+        );
+        let limits = Limits {
+            max_elements: 4,
+            ..Limits::default()
+        };
+        let err = limits
+            .apply(|| layout.read(&mut &[5, 0, 0, 0, 0, 0, 0, 0][..]))
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = Layout::new(Endian::Big)
+            .field("value", Type::U16)
+            .read(&mut &[1][..])
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,216 @@
+use std::io::{self, Read};
+
+/// Trait to read a region of known length through a length-limited sub-reader
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{ReadU16, ReadU8, ReadWithin};
+/// // a chunk of 4 bytes of which only the first 2 are understood
+/// let mut bytes: &[u8] = &[4, 42, 0, 0xff, 0xff, 7];
+/// let len = bytes.read_u8().unwrap();
+/// let value = bytes.read_within(len.into(), |chunk| chunk.read_le_u16()).unwrap();
+/// assert_eq!(42, value);
+/// assert_eq!(7, bytes.read_u8().unwrap());
+/// ```
+pub trait ReadWithin: Read {
+    /// Run `f` on a sub-reader limited to the next `len` bytes, then skip whatever `f` left unread
+    /// # Errors
+    /// errors when `f` errors, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends before `len` bytes
+    fn read_within<T, F>(&mut self, len: u64, f: F) -> io::Result<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut io::Take<&mut Self>) -> io::Result<T>,
+    {
+        let mut scope = self.take(len);
+        let value = f(&mut scope)?;
+        let remaining = scope.limit();
+        if io::copy(&mut scope, &mut io::sink())? < remaining {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(value)
+    }
+
+    /// Run `f` on a sub-reader limited to the next `len` bytes, which `f` has to consume completely
+    /// # Errors
+    /// errors when `f` errors, or with [`io::ErrorKind::InvalidData`] if `f` left bytes unread
+    fn read_within_exact<T, F>(&mut self, len: u64, f: F) -> io::Result<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut io::Take<&mut Self>) -> io::Result<T>,
+    {
+        let mut scope = self.take(len);
+        let value = f(&mut scope)?;
+        match scope.limit() {
+            0 => Ok(value),
+            unread => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{unread} unread bytes in scope of {len} bytes"),
+            )),
+        }
+    }
+}
+impl<R> ReadWithin for R where R: Read {}
+
+/// A reader with a byte budget, for decoding nested length-prefixed blocks
+///
+/// [`Scope::read_block`] checks every length prefix against the bytes left in the enclosing scope,
+/// so a nested block can never claim more bytes than its parent.
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{ReadU8, Scope};
+/// let mut bytes: &[u8] = &[2, 5, 0xff, 0xff];
+/// let mut scope = Scope::unbounded(&mut bytes);
+/// let err = scope
+///     .read_block(|outer| outer.read_u8(), |outer| {
+///         outer.read_block(|inner| inner.read_u8(), |inner| inner.read_u8())
+///     })
+///     .unwrap_err();
+/// assert_eq!("block of 5 bytes exceeds the 1 bytes left in its scope", err.to_string());
+/// ```
+#[derive(Debug)]
+pub struct Scope<'r, R: ?Sized> {
+    reader: &'r mut R,
+    remaining: u64,
+}
+
+impl<'r, R: Read + ?Sized> Scope<'r, R> {
+    /// Create a scope over the next `len` bytes of `reader`
+    pub fn new(reader: &'r mut R, len: u64) -> Self {
+        Scope {
+            reader,
+            remaining: len,
+        }
+    }
+
+    /// Create a scope without a byte budget, for the outermost level of a file or stream
+    pub fn unbounded(reader: &'r mut R) -> Self {
+        Scope::new(reader, u64::MAX)
+    }
+
+    /// Number of bytes left in this scope
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Read a length prefix with `read_len`, then run `f` on a nested scope over that many bytes,
+    /// skipping whatever `f` left unread
+    /// # Errors
+    /// errors when `read_len` or `f` error, with [`io::ErrorKind::InvalidData`] if the length exceeds
+    /// the bytes left in this scope, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    pub fn read_block<L, T>(
+        &mut self,
+        read_len: impl FnOnce(&mut Self) -> io::Result<L>,
+        f: impl FnOnce(&mut Scope<'_, R>) -> io::Result<T>,
+    ) -> io::Result<T>
+    where
+        L: Into<u64>,
+    {
+        let len = read_len(self)?.into();
+        if len > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "block of {len} bytes exceeds the {} bytes left in its scope",
+                    self.remaining
+                ),
+            ));
+        }
+        let mut block = Scope::new(&mut *self.reader, len);
+        let value = f(&mut block)?;
+        let unread = block.remaining;
+        if io::copy(&mut block, &mut io::sink())? < unread {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= len;
+        Ok(value)
+    }
+}
+
+impl<R: Read + ?Sized> Read for Scope<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = buf
+            .len()
+            .min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let read = self.reader.read(&mut buf[..max])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn read_within_skips_remainder() {
+        let mut bytes: &[u8] = &[1, 2, 3, 4, 5];
+        let first = bytes.read_within(3, |scope| scope.read_u8()).unwrap();
+        assert_eq!(1, first);
+        assert_eq!([4, 5], bytes);
+    }
+
+    #[test]
+    fn read_within_limits_closure() {
+        let mut bytes: &[u8] = &[1, 2, 3, 4, 5];
+        let err = bytes
+            .read_within(3, |scope| scope.read_le_u32())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_within_short_reader() {
+        let mut bytes: &[u8] = &[1, 2];
+        let err = bytes.read_within(3, |scope| scope.read_u8()).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let mut bytes: &[u8] = &[5, 2, 7, 8, 1, 9, 0xaa];
+        let mut scope = Scope::unbounded(&mut bytes);
+        let values = scope
+            .read_block(
+                |outer| outer.read_u8(),
+                |outer| {
+                    let first =
+                        outer.read_block(|inner| inner.read_u8(), |inner| inner.read_u8())?;
+                    assert_eq!(2, outer.remaining());
+                    let second =
+                        outer.read_block(|inner| inner.read_u8(), |inner| inner.read_u8())?;
+                    Ok((first, second))
+                },
+            )
+            .unwrap();
+        assert_eq!((7, 9), values);
+        assert_eq!(0xaa, scope.read_u8().unwrap());
+    }
+
+    #[test]
+    fn block_exceeding_parent() {
+        let mut bytes: &[u8] = &[3, 3, 1, 2, 3];
+        let mut scope = Scope::new(&mut bytes, 5);
+        let err = scope
+            .read_block(
+                |outer| outer.read_u8(),
+                |outer| outer.read_block(|inner| inner.read_u8(), |inner| inner.read_u8()),
+            )
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_within_exact() {
+        let mut bytes: &[u8] = &[1, 2, 3];
+        let err = bytes
+            .read_within_exact(2, |scope| scope.read_u8())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let value = bytes.read_within_exact(2, |scope| scope.read_be_u16());
+        assert_eq!(0x0203, value.unwrap());
+    }
+}
@@ -0,0 +1,204 @@
+use std::io::{self, BufRead};
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Trait to read records terminated by a multi-byte delimiter, and to skip to the next one
+pub trait ReadUntilSentinel: BufRead {
+    /// Read the bytes before the next occurrence of `sentinel`, consuming the sentinel as well
+    ///
+    /// The search works on the internal buffer and handles sentinels split across refills.
+    /// Bytes are consumed even if an error occurs.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `sentinel` is empty,
+    /// with [`io::ErrorKind::InvalidData`] if no sentinel follows within `max_len` bytes,
+    /// with [`io::ErrorKind::UnexpectedEof`] if the reader ends before the sentinel,
+    /// or when [`BufRead::fill_buf`](https://doc.rust-lang.org/std/io/trait.BufRead.html#tymethod.fill_buf) errors
+    fn read_until_sentinel(&mut self, sentinel: &[u8], max_len: usize) -> io::Result<Vec<u8>> {
+        if sentinel.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sentinel must not be empty",
+            ));
+        }
+        let limit = max_len.saturating_add(sentinel.len());
+        let mut record = Vec::new();
+        loop {
+            let buf = self.fill_buf()?;
+            if buf.is_empty() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let start = record.len().saturating_sub(sentinel.len() - 1);
+            let take = buf.len().min(limit - record.len());
+            record.extend_from_slice(&buf[..take]);
+            if let Some(pos) = find(&record[start..], sentinel) {
+                let end = start + pos + sentinel.len();
+                self.consume(take - (record.len() - end));
+                record.truncate(start + pos);
+                return Ok(record);
+            }
+            self.consume(take);
+            if record.len() == limit {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no sentinel within {max_len} bytes"),
+                ));
+            }
+        }
+    }
+
+    /// Skip to just past the next occurrence of `pattern`, returning the number of bytes skipped
+    /// before it
+    ///
+    /// For resynchronizing a stream to the sync marker or magic of its next record after a
+    /// corrupt one, instead of aborting. Only the last `pattern.len() - 1` bytes scanned are kept
+    /// between refills, so `max_scan` may be large.
+    ///
+    /// ```
+    /// use read_primitives::{ReadU8, ReadUntilSentinel};
+    /// // a damaged record, then one after the sync marker 0x47
+    /// let mut stream: &[u8] = b"\x01\x02\x03\x47\x10";
+    /// assert_eq!(3, stream.resync_to(b"\x47", 188).unwrap());
+    /// assert_eq!(0x10, stream.read_u8().unwrap());
+    /// ```
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `pattern` is empty,
+    /// with [`io::ErrorKind::InvalidData`] if the pattern doesn't start within `max_scan` bytes,
+    /// with [`io::ErrorKind::UnexpectedEof`] if the reader ends before the pattern,
+    /// or when [`BufRead::fill_buf`](https://doc.rust-lang.org/std/io/trait.BufRead.html#tymethod.fill_buf) errors
+    fn resync_to(&mut self, pattern: &[u8], max_scan: usize) -> io::Result<u64> {
+        if pattern.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sync pattern must not be empty",
+            ));
+        }
+        let limit = (max_scan as u64).saturating_add(pattern.len() as u64);
+        // the bytes scanned that could still begin the pattern
+        let mut window = Vec::with_capacity(pattern.len() * 2);
+        let mut skipped = 0u64;
+        loop {
+            let buf = self.fill_buf()?;
+            if buf.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "no sync pattern in the {} bytes before the end",
+                        skipped + window.len() as u64
+                    ),
+                ));
+            }
+            let scanned = skipped + window.len() as u64;
+            let take = buf
+                .len()
+                .min(usize::try_from(limit - scanned).unwrap_or(usize::MAX));
+            // compare across the previous refill, then within the buffer
+            let head = take.min(pattern.len() - 1);
+            window.extend_from_slice(&buf[..head]);
+            let found = find(&window, pattern)
+                .map(|pos| (pos, pos + pattern.len() - (window.len() - head)))
+                .or_else(|| {
+                    find(&buf[..take], pattern)
+                        .map(|pos| (window.len() - head + pos, pos + pattern.len()))
+                });
+            if let Some((pos, used)) = found {
+                self.consume(used);
+                return Ok(skipped + pos as u64);
+            }
+            let old = window.len() - head;
+            let keep = (old + take).min(pattern.len() - 1);
+            let from_buf = keep.min(take);
+            window.truncate(old);
+            window.drain(..old - (keep - from_buf));
+            window.extend_from_slice(&buf[take - from_buf..take]);
+            self.consume(take);
+            skipped += (old + take - keep) as u64;
+            if skipped + window.len() as u64 == limit {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no sync pattern within {max_scan} bytes"),
+                ));
+            }
+        }
+    }
+}
+impl<R> ReadUntilSentinel for R where R: BufRead {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, BufReader, Read};
+
+    #[test]
+    fn read_until_sentinel() {
+        let mut bytes: &[u8] = b"GET /\r\nHost: x\r\n\x01";
+        assert_eq!(b"GET /", &*bytes.read_until_sentinel(b"\r\n", 64).unwrap());
+        assert_eq!(
+            b"Host: x",
+            &*bytes.read_until_sentinel(b"\r\n", 64).unwrap()
+        );
+        assert_eq!(1, bytes.read_u8().unwrap());
+    }
+
+    #[test]
+    fn sentinel_across_refills() {
+        let data = b"abcdef\xff\xfeg";
+        let mut reader = BufReader::with_capacity(3, data.as_slice());
+        let record = reader.read_until_sentinel(b"\xff\xfe", 16).unwrap();
+        assert_eq!(b"abcdef", &*record);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(b"g", &*rest);
+    }
+
+    #[test]
+    fn sentinel_errors() {
+        let mut bytes: &[u8] = b"abcdef\r\n";
+        let err = bytes.read_until_sentinel(b"\r\n", 5).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        let mut bytes: &[u8] = b"abc";
+        let err = bytes.read_until_sentinel(b"\r\n", 5).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+
+        let mut bytes: &[u8] = b"abcde\r\n";
+        assert_eq!(b"abcde", &*bytes.read_until_sentinel(b"\r\n", 5).unwrap());
+    }
+
+    #[test]
+    fn resync_across_refills() {
+        let mut data = vec![0xaa; 1000];
+        data.extend_from_slice(b"SYNCrest");
+        let mut reader = BufReader::with_capacity(3, data.as_slice());
+        assert_eq!(1000, reader.resync_to(b"SYNC", 1000).unwrap());
+        assert_eq!(b"rest", &*reader.read_bytes(4).unwrap());
+
+        // a partial match before the real one
+        let mut reader = BufReader::with_capacity(2, &b"SYNSYNCx"[..]);
+        assert_eq!(3, reader.resync_to(b"SYNC", 8).unwrap());
+        assert_eq!(b'x', reader.read_u8().unwrap());
+        let mut bytes: &[u8] = b"SYNC";
+        assert_eq!(0, bytes.resync_to(b"SYNC", 0).unwrap());
+    }
+
+    #[test]
+    fn resync_errors() {
+        let mut reader = BufReader::with_capacity(4, &b"abcdefSYNC"[..]);
+        let err = reader.resync_to(b"SYNC", 5).unwrap_err();
+        assert_eq!("no sync pattern within 5 bytes", err.to_string());
+        let mut reader = BufReader::with_capacity(4, &b"abcdefSYNC"[..]);
+        assert_eq!(6, reader.resync_to(b"SYNC", 6).unwrap());
+
+        let mut bytes: &[u8] = b"abcSYN";
+        let err = bytes.resync_to(b"SYNC", 64).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            "no sync pattern in the 6 bytes before the end",
+            err.to_string()
+        );
+        assert!(bytes.resync_to(b"", 64).is_err());
+    }
+}
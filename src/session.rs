@@ -0,0 +1,303 @@
+use crate::{Endian, ReadFrom};
+use std::fmt;
+use std::io::{self, Read};
+
+/// A frame carrying its position in a stream, for [`Session`]
+pub trait Sequenced {
+    /// Sequence number or byte offset of the frame
+    fn sequence(&self) -> u64;
+
+    /// How far the frame moves the stream on: 1 for numbered frames, the default, or the payload
+    /// length for frames that carry byte offsets
+    fn advance(&self) -> u64 {
+        1
+    }
+}
+
+/// How a frame's sequence number disagreed with the [`Session`], inside an
+/// [`io::ErrorKind::InvalidData`] error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SequenceError {
+    /// The frame is ahead of the expected one, so frames were lost
+    Gap {
+        /// Sequence number the session expected
+        expected: u64,
+        /// Sequence number of the frame
+        found: u64,
+    },
+    /// The frame repeats the last accepted one
+    Duplicate {
+        /// Sequence number of the frame
+        found: u64,
+    },
+    /// The frame is older than the last accepted one
+    OutOfOrder {
+        /// Sequence number the session expected
+        expected: u64,
+        /// Sequence number of the frame
+        found: u64,
+    },
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Gap { expected, found } => {
+                write!(f, "gap in sequence: expected {expected}, found {found}")
+            }
+            SequenceError::Duplicate { found } => write!(f, "duplicate frame {found}"),
+            SequenceError::OutOfOrder { expected, found } => {
+                write!(f, "frame {found} out of order, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+impl From<SequenceError> for io::Error {
+    fn from(err: SequenceError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Reader of a stream of frames that checks each one continues where the last left off
+///
+/// A rejected frame leaves the session unchanged, so the caller may skip duplicates and keep
+/// reading, or [`Session::resync`] after a gap. Sequence numbers don't wrap unless the session
+/// is created with [`Session::wrapping`].
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom, SequenceError, Sequenced, Session};
+/// use std::io::{self, Read};
+///
+/// struct Frame {
+///     seq: u16,
+///     value: u8,
+/// }
+///
+/// impl ReadFrom for Frame {
+///     fn read_from<R: Read + ?Sized>(reader: &mut R, endian: Endian) -> io::Result<Self> {
+///         Ok(Frame { seq: u16::read_from(reader, endian)?, value: u8::read_from(reader, endian)? })
+///     }
+/// }
+///
+/// impl Sequenced for Frame {
+///     fn sequence(&self) -> u64 {
+///         self.seq.into()
+///     }
+/// }
+///
+/// let stream: &[u8] = &[0, 1, 10, 0, 2, 20, 0, 2, 20, 0, 4, 40];
+/// let mut session = Session::new(stream, 1);
+/// assert_eq!(10, session.read_frame::<Frame>(Endian::Big).unwrap().value);
+/// assert_eq!(20, session.read_frame::<Frame>(Endian::Big).unwrap().value);
+/// let err = session.read_frame::<Frame>(Endian::Big).err().unwrap();
+/// let err = err.get_ref().unwrap().downcast_ref::<SequenceError>().unwrap();
+/// assert_eq!(SequenceError::Duplicate { found: 2 }, *err);
+/// let err = session.read_frame::<Frame>(Endian::Big).err().unwrap();
+/// assert_eq!("gap in sequence: expected 3, found 4", err.to_string());
+/// ```
+#[derive(Debug)]
+pub struct Session<R> {
+    reader: R,
+    expected: u64,
+    last: Option<u64>,
+    /// Mask of the sequence number bits, if they wrap
+    mask: Option<u64>,
+}
+
+impl<R> Session<R> {
+    /// Session over `reader` whose first frame is `first`
+    pub fn new(reader: R, first: u64) -> Self {
+        Session {
+            reader,
+            expected: first,
+            last: None,
+            mask: None,
+        }
+    }
+
+    /// Session over `reader` whose first frame is `first`, with sequence numbers of `bits` bits
+    /// that wrap to 0
+    ///
+    /// Frames are compared with the serial number arithmetic of RFC 1982: a frame less than half
+    /// the sequence space ahead of the expected one is a gap, and one further ahead is behind it.
+    /// # Panics
+    /// panics if `bits` is 0 or more than 64
+    pub fn wrapping(reader: R, first: u64, bits: u32) -> Self {
+        assert!((1..=64).contains(&bits), "{bits} sequence number bits");
+        let mask = u64::MAX >> (64 - bits);
+        Session {
+            reader,
+            expected: first & mask,
+            last: None,
+            mask: Some(mask),
+        }
+    }
+
+    /// Sequence number the next frame must have
+    pub fn expected(&self) -> u64 {
+        self.expected
+    }
+
+    /// Expect `next` for the next frame, to carry on after a gap
+    pub fn resync(&mut self, next: u64) {
+        self.expected = next & self.mask.unwrap_or(u64::MAX);
+        self.last = None;
+    }
+
+    /// Record a frame at `sequence` that moves the stream on by `advance`, for frames read
+    /// without [`Session::read_frame`]
+    /// # Errors
+    /// errors if `sequence` is not the expected one, leaving the session unchanged
+    pub fn accept(&mut self, sequence: u64, advance: u64) -> Result<(), SequenceError> {
+        let expected = self.expected;
+        let ahead = match self.mask {
+            // ahead by less than half the sequence space
+            Some(mask) => {
+                let distance = sequence.wrapping_sub(expected) & mask;
+                distance != 0 && distance <= mask >> 1
+            }
+            None => sequence > expected,
+        };
+        if sequence == expected {
+            self.last = Some(sequence);
+            self.expected = expected.wrapping_add(advance) & self.mask.unwrap_or(u64::MAX);
+            Ok(())
+        } else if ahead {
+            Err(SequenceError::Gap {
+                expected,
+                found: sequence,
+            })
+        } else if self.last == Some(sequence) {
+            Err(SequenceError::Duplicate { found: sequence })
+        } else {
+            Err(SequenceError::OutOfOrder {
+                expected,
+                found: sequence,
+            })
+        }
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Mutably borrow the wrapped reader, to read data between frames
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Session<R> {
+    /// Read the next frame and check its sequence number
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] carrying a [`SequenceError`] if the frame is not
+    /// the expected one, after consuming it, or when reading the frame errors
+    pub fn read_frame<T: ReadFrom + Sequenced>(&mut self, endian: Endian) -> io::Result<T> {
+        let frame = T::read_from(&mut self.reader, endian)?;
+        self.accept(frame.sequence(), frame.advance())?;
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn offsets() {
+        let mut session = Session::new((), 100);
+        session.accept(100, 20).unwrap();
+        session.accept(120, 5).unwrap();
+        assert_eq!(125, session.expected());
+        assert_eq!(
+            Err(SequenceError::Duplicate { found: 120 }),
+            session.accept(120, 5)
+        );
+        assert_eq!(
+            Err(SequenceError::OutOfOrder {
+                expected: 125,
+                found: 100
+            }),
+            session.accept(100, 20)
+        );
+        assert_eq!(
+            Err(SequenceError::Gap {
+                expected: 125,
+                found: 130
+            }),
+            session.accept(130, 1)
+        );
+        session.resync(130);
+        session.accept(130, 1).unwrap();
+        assert_eq!(131, session.expected());
+    }
+
+    #[test]
+    fn wrapping() {
+        let mut session = Session::wrapping((), 65534, 16);
+        session.accept(65534, 1).unwrap();
+        session.accept(65535, 1).unwrap();
+        assert_eq!(0, session.expected());
+        session.accept(0, 1).unwrap();
+        assert_eq!(
+            Err(SequenceError::Duplicate { found: 0 }),
+            session.accept(0, 1)
+        );
+        assert_eq!(
+            Err(SequenceError::OutOfOrder {
+                expected: 1,
+                found: 65535
+            }),
+            session.accept(65535, 1)
+        );
+        assert_eq!(
+            Err(SequenceError::Gap {
+                expected: 1,
+                found: 0x8000
+            }),
+            session.accept(0x8000, 1)
+        );
+        assert!(matches!(
+            session.accept(0x8001, 1),
+            Err(SequenceError::OutOfOrder { .. })
+        ));
+        let mut session = Session::wrapping((), u64::MAX, 64);
+        session.accept(u64::MAX, 1).unwrap();
+        session.accept(0, 1).unwrap();
+    }
+
+    #[test]
+    fn read_frame() {
+        struct Numbered(u8);
+        impl ReadFrom for Numbered {
+            fn read_from<R: std::io::Read + ?Sized>(
+                reader: &mut R,
+                endian: Endian,
+            ) -> std::io::Result<Self> {
+                u8::read_from(reader, endian).map(Numbered)
+            }
+        }
+        impl Sequenced for Numbered {
+            fn sequence(&self) -> u64 {
+                self.0.into()
+            }
+        }
+        let mut session = Session::new(&[0u8, 1, 3][..], 0);
+        assert_eq!(0, session.read_frame::<Numbered>(Endian::Big).unwrap().0);
+        assert_eq!(1, session.read_frame::<Numbered>(Endian::Big).unwrap().0);
+        let err = session.read_frame::<Numbered>(Endian::Big).err().unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert!(session.get_ref().is_empty());
+        assert!(session.read_frame::<Numbered>(Endian::Big).is_err());
+    }
+}
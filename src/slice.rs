@@ -0,0 +1,593 @@
+#[cfg(feature = "std")]
+use crate::ReadBorrowed;
+use crate::{Endian, ParseError, Primitive};
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+/// A cursor over a byte slice that reads primitives without going through `std::io`
+///
+/// Errors report how many bytes were needed and where the read started.
+/// Readers created by [`SliceReader::sub_reader`] continue counting positions from their parent.
+/// Readers created by [`SliceReader::streaming`] report the end of the data as
+/// [`ParseError::Incomplete`] instead.
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::SliceReader;
+/// let mut reader = SliceReader::new(&[1, 0, 0, 0, 2]);
+/// assert_eq!(1, reader.read_le_u32().unwrap());
+/// let err = reader.read_le_u32().unwrap_err();
+/// assert_eq!("needed 4 bytes, 1 remaining at offset 0x4", err.to_string());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SliceReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    streaming: bool,
+}
+
+macro_rules! impl_slice_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Read " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors if fewer than " $type "'s size in bytes remain"]
+            pub fn [<read_ $order _ $type>](&mut self) -> Result<$type, ParseError> {
+                self.read_byte_array().map($type::[<from_ $order _bytes>])
+            }
+            #[cfg(feature = "unchecked")]
+            #[doc = "Read " $type " in " $order_doc " without checking the length"]
+            #[doc = "# Safety"]
+            #[doc = "at least " $type "'s size in bytes must remain"]
+            pub unsafe fn [<read_ $order _ $type _unchecked>](&mut self) -> $type {
+                // SAFETY: forwarded to the caller
+                $type::[<from_ $order _bytes>](unsafe { self.read_byte_array_unchecked() })
+            }
+            #[cfg(feature = "heapless")]
+            #[doc = "Read `count` " $type " in " $order_doc " into a [`heapless::Vec`] with capacity `N`"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`ParseError::CapacityExceeded`] if `count` exceeds `N`, or if fewer than `count` " $type " remain"]
+            pub fn [<read_ $order _ $type _heapless>]<const N: usize>(&mut self, count: usize) -> Result<heapless::Vec<$type, N>, ParseError> {
+                if count > N {
+                    return Err(ParseError::CapacityExceeded { count, capacity: N });
+                }
+                let mut values = self.sub_reader(count * core::mem::size_of::<$type>())?;
+                let mut vec = heapless::Vec::new();
+                for _ in 0..count {
+                    // count was checked against the capacity, so the pushes can't fail
+                    let _ = vec.push(values.[<read_ $order _ $type>]()?);
+                }
+                Ok(vec)
+            }
+            #[cfg(feature = "arrayvec")]
+            #[doc = "Read `count` " $type " in " $order_doc " into an [`arrayvec::ArrayVec`] with capacity `CAP`"]
+            #[doc = "# Errors"]
+            #[doc = "errors with [`ParseError::CapacityExceeded`] if `count` exceeds `CAP`, or if fewer than `count` " $type " remain"]
+            pub fn [<read_ $order _ $type _arrayvec>]<const CAP: usize>(&mut self, count: usize) -> Result<arrayvec::ArrayVec<$type, CAP>, ParseError> {
+                if count > CAP {
+                    return Err(ParseError::CapacityExceeded { count, capacity: CAP });
+                }
+                let mut values = self.sub_reader(count * core::mem::size_of::<$type>())?;
+                let mut vec = arrayvec::ArrayVec::new();
+                for _ in 0..count {
+                    vec.push(values.[<read_ $order _ $type>]()?);
+                }
+                Ok(vec)
+            }
+        }
+    };
+}
+
+macro_rules! impl_slice_reads {
+    ($($type:ty),+) => {
+        $(
+            impl_slice_methods!($type, ne, "native byte order");
+            impl_slice_methods!($type, le, "little endian byte order");
+            impl_slice_methods!($type, be, "big endian byte order");
+        )+
+    };
+}
+
+impl<'a> SliceReader<'a> {
+    /// Create a reader positioned at the start of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        SliceReader {
+            data,
+            position: 0,
+            streaming: false,
+        }
+    }
+
+    /// Create a reader over the part of a stream received so far
+    ///
+    /// Reads past the end fail with [`ParseError::Incomplete`], saying how many more bytes they
+    /// need, so that a network parser can wait for that much more data and parse again.
+    ///
+    /// ```
+    /// use read_primitives::{Needed, ParseError, SliceReader};
+    /// let mut reader = SliceReader::streaming(&[0, 5, 1, 2]);
+    /// let len = reader.read_be_u16().unwrap();
+    /// let err = reader.read_slice(len.into()).unwrap_err();
+    /// assert_eq!(ParseError::Incomplete(Needed::Size(3.try_into().unwrap())), err);
+    /// ```
+    pub fn streaming(data: &'a [u8]) -> Self {
+        SliceReader {
+            streaming: true,
+            ..SliceReader::new(data)
+        }
+    }
+
+    /// Create a reader over the `len` bytes at `ptr`, as C code hands them over, without copying
+    ///
+    /// A null `ptr` with a `len` of 0, which C callers pass for no data, is an empty reader.
+    /// # Safety
+    /// unless `len` is 0, `ptr` must be valid for reads of `len` initialized bytes that are not
+    /// written to for `'a`, and `len` must be at most `isize::MAX`, as for
+    /// [`core::slice::from_raw_parts`]
+    /// # Panics
+    /// panics if `ptr` is null and `len` is not 0
+    pub unsafe fn from_raw_parts(ptr: *const u8, len: usize) -> Self {
+        if len == 0 {
+            return SliceReader::new(&[]);
+        }
+        assert!(!ptr.is_null(), "null pointer to {len} bytes");
+        // SAFETY: forwarded to the caller, and `ptr` is not null
+        SliceReader::new(unsafe { core::slice::from_raw_parts(ptr, len) })
+    }
+
+    /// Run `f` on a reader over the `len` bytes at `ptr`, for the buffers that C callbacks lend for
+    /// the duration of the call
+    ///
+    /// The reader can't outlive `f`, so neither it nor bytes borrowed from it are kept once the
+    /// callback returned and the buffer may be reused.
+    ///
+    /// ```
+    /// use read_primitives::SliceReader;
+    ///
+    /// extern "C" fn on_packet(data: *const u8, len: usize) -> u16 {
+    ///     // SAFETY: the caller lends `len` readable bytes at `data` for the call
+    ///     unsafe { SliceReader::with_raw_parts(data, len, |reader| reader.read_be_u16()) }
+    ///         .unwrap_or(0)
+    /// }
+    ///
+    /// let packet = [0x12, 0x34, 0xff];
+    /// assert_eq!(0x1234, on_packet(packet.as_ptr(), packet.len()));
+    /// assert_eq!(0, on_packet(core::ptr::null(), 0));
+    /// ```
+    /// # Safety
+    /// as for [`SliceReader::from_raw_parts`], for the duration of the call
+    /// # Panics
+    /// panics if `ptr` is null and `len` is not 0
+    pub unsafe fn with_raw_parts<T>(
+        ptr: *const u8,
+        len: usize,
+        f: impl FnOnce(&mut SliceReader<'_>) -> T,
+    ) -> T {
+        // SAFETY: forwarded to the caller
+        f(&mut unsafe { SliceReader::from_raw_parts(ptr, len) })
+    }
+
+    /// Number of bytes consumed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether all bytes have been consumed
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The bytes left to read
+    pub fn remaining_slice(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Check that all bytes have been consumed
+    /// # Errors
+    /// errors with [`ParseError::TrailingBytes`] if any bytes remain
+    pub fn finish(self) -> Result<(), ParseError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::TrailingBytes {
+                count: self.data.len(),
+                offset: self.position,
+            })
+        }
+    }
+
+    fn unexpected_end(&self, needed: usize) -> ParseError {
+        let err = ParseError::UnexpectedEnd {
+            needed,
+            remaining: self.data.len(),
+            offset: self.position,
+        };
+        if self.streaming {
+            err.into_streaming()
+        } else {
+            err
+        }
+    }
+
+    /// Read `len` bytes, borrowing them from the underlying slice
+    /// # Errors
+    /// errors if fewer than `len` bytes remain
+    pub fn read_slice(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        if len > self.data.len() {
+            return Err(self.unexpected_end(len));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Split off a reader over the next `len` bytes and advance past them
+    ///
+    /// The bytes of the sub reader are all there, so it is never streaming.
+    /// # Errors
+    /// errors if fewer than `len` bytes remain
+    pub fn sub_reader(&mut self, len: usize) -> Result<SliceReader<'a>, ParseError> {
+        let position = self.position;
+        let data = self.read_slice(len)?;
+        Ok(SliceReader {
+            data,
+            position,
+            streaming: false,
+        })
+    }
+
+    /// Advance past `len` bytes
+    /// # Errors
+    /// errors if fewer than `len` bytes remain
+    pub fn skip(&mut self, len: usize) -> Result<(), ParseError> {
+        self.read_slice(len).map(|_| ())
+    }
+
+    /// Read `N` bytes into an array
+    /// # Errors
+    /// errors if fewer than `N` bytes remain
+    pub fn read_byte_array<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        match self.data.split_first_chunk::<N>() {
+            Some((chunk, rest)) => {
+                self.data = rest;
+                self.position += N;
+                Ok(*chunk)
+            }
+            None => Err(self.unexpected_end(N)),
+        }
+    }
+
+    /// Read `N` bytes into an array without checking the length
+    ///
+    /// For hot loops over data whose length was validated up front, such as records read after
+    /// one check of [`SliceReader::remaining`]. Debug builds still check.
+    /// # Safety
+    /// at least `N` bytes must remain
+    #[cfg(feature = "unchecked")]
+    pub unsafe fn read_byte_array_unchecked<const N: usize>(&mut self) -> [u8; N] {
+        debug_assert!(self.data.len() >= N, "unchecked read past the end");
+        // SAFETY: the caller guarantees that `N` bytes remain, and `[u8; N]` has alignment 1
+        let (chunk, rest) = unsafe { self.data.split_at_unchecked(N) };
+        self.data = rest;
+        self.position += N;
+        // SAFETY: `chunk` is `N` bytes long
+        unsafe { chunk.as_ptr().cast::<[u8; N]>().read() }
+    }
+
+    /// Iterate over `count` `T` in `endian` byte order from the starts of records `stride` bytes
+    /// apart, without copying the records out, and advance past the last `T`
+    ///
+    /// ```
+    /// use read_primitives::{Endian, SliceReader};
+    /// // RGB pixels, of which only green is wanted
+    /// let mut reader = SliceReader::new(&[10, 20, 30, 11, 21, 31]);
+    /// reader.skip(1).unwrap();
+    /// let green: Vec<u8> = reader.strided(2, 3, Endian::Little).unwrap().collect();
+    /// assert_eq!([20, 21], *green);
+    /// assert_eq!([31], reader.remaining_slice());
+    /// ```
+    /// # Errors
+    /// errors if fewer than `(count - 1) * stride` bytes and a `T` remain
+    /// # Panics
+    /// panics if `stride` is shorter than a `T`
+    pub fn strided<T: Primitive>(
+        &mut self,
+        count: usize,
+        stride: usize,
+        endian: Endian,
+    ) -> Result<Strided<'a, T>, ParseError> {
+        assert!(
+            stride >= T::SIZE,
+            "stride of {stride} bytes is shorter than a {}",
+            core::any::type_name::<T>()
+        );
+        let len = match count {
+            0 => 0,
+            _ => (count - 1)
+                .checked_mul(stride)
+                .and_then(|len| len.checked_add(T::SIZE))
+                .ok_or_else(|| self.unexpected_end(usize::MAX))?,
+        };
+        Ok(Strided {
+            data: self.read_slice(len)?,
+            stride,
+            count,
+            decode: match endian {
+                Endian::Little => T::from_le_bytes,
+                Endian::Big => T::from_be_bytes,
+            },
+        })
+    }
+
+    /// Read a u8
+    /// # Errors
+    /// errors if no bytes remain
+    pub fn read_u8(&mut self) -> Result<u8, ParseError> {
+        self.read_byte_array().map(u8::from_ne_bytes)
+    }
+
+    /// Read a u8 without checking the length
+    /// # Safety
+    /// at least one byte must remain
+    #[cfg(feature = "unchecked")]
+    pub unsafe fn read_u8_unchecked(&mut self) -> u8 {
+        // SAFETY: forwarded to the caller
+        u8::from_ne_bytes(unsafe { self.read_byte_array_unchecked() })
+    }
+
+    /// Read a bool
+    /// # Errors
+    /// errors if no bytes remain
+    pub fn read_bool(&mut self) -> Result<bool, ParseError> {
+        self.read_u8().map(|byte| byte != 0)
+    }
+
+    /// Read a char
+    ///  It is a assumed that the char is represented in native byte order
+    /// # Errors
+    /// errors if fewer than 4 bytes remain
+    pub fn read_char(&mut self) -> Result<Option<char>, ParseError> {
+        self.read_byte_array()
+            .map(|bytes| char::from_u32(u32::from_ne_bytes(bytes)))
+    }
+
+    impl_slice_reads!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+}
+
+/// Iterator over values spaced out in a slice, from [`SliceReader::strided`]
+#[derive(Debug, Clone)]
+pub struct Strided<'a, T: Primitive> {
+    data: &'a [u8],
+    stride: usize,
+    count: usize,
+    decode: fn(T::Bytes) -> T,
+}
+
+impl<T: Primitive> Iterator for Strided<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        let mut bytes = T::Bytes::default();
+        bytes.as_mut().copy_from_slice(&self.data[..T::SIZE]);
+        self.data = &self.data[self.stride.min(self.data.len())..];
+        Some((self.decode)(bytes))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count, Some(self.count))
+    }
+}
+
+impl<T: Primitive> ExactSizeIterator for Strided<'_, T> {}
+
+impl<'a> From<&'a [u8]> for SliceReader<'a> {
+    fn from(data: &'a [u8]) -> Self {
+        SliceReader::new(data)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Read for SliceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.data.len());
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> ReadBorrowed<'a> for SliceReader<'a> {
+    fn read_borrowed_bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        Ok(self.read_slice(len)?)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn position_and_remaining() {
+        let mut reader = SliceReader::new(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(0x0201, reader.read_le_u16().unwrap());
+        assert_eq!(2, reader.position());
+        assert_eq!(4, reader.remaining());
+        assert_eq!(0x0304, reader.read_be_u16().unwrap());
+        assert_eq!([5, 6], reader.remaining_slice());
+    }
+
+    #[test]
+    fn strided() {
+        let data = [0, 1, 0xaa, 0, 2, 0xaa, 0, 3];
+        let mut reader = SliceReader::new(&data);
+        let values = reader.strided::<u16>(3, 3, Endian::Big).unwrap();
+        assert_eq!(3, values.len());
+        assert_eq!([1, 2, 3], *values.collect::<Vec<_>>());
+        assert!(reader.is_empty());
+        let mut reader = SliceReader::new(&data);
+        let err = reader.strided::<u16>(3, 4, Endian::Big).unwrap_err();
+        assert_eq!(
+            ParseError::UnexpectedEnd {
+                needed: 10,
+                remaining: 8,
+                offset: 0
+            },
+            err
+        );
+        assert_eq!(0, reader.strided::<u64>(0, 9, Endian::Big).unwrap().count());
+        assert_eq!(0, reader.position());
+    }
+
+    #[cfg(all(feature = "heapless", feature = "arrayvec"))]
+    #[test]
+    fn fixed_capacity() {
+        let mut reader = SliceReader::new(&[0, 1, 0, 2, 0, 3]);
+        assert_eq!([1, 2], *reader.read_be_u16_heapless::<2>(2).unwrap());
+        assert_eq!(
+            ParseError::CapacityExceeded {
+                count: 2,
+                capacity: 1
+            },
+            reader.read_be_u16_arrayvec::<1>(2).unwrap_err()
+        );
+        assert!(reader.read_be_u16_arrayvec::<4>(2).is_err());
+        assert_eq!(4, reader.position());
+        assert_eq!([3], *reader.read_be_u16_arrayvec::<4>(1).unwrap());
+    }
+
+    #[test]
+    fn sub_reader() {
+        let mut reader = SliceReader::new(&[1, 2, 3, 4, 5]);
+        reader.skip(1).unwrap();
+        let mut sub = reader.sub_reader(2).unwrap();
+        assert_eq!(3, reader.position());
+        assert_eq!(2, sub.read_u8().unwrap());
+        assert_eq!(
+            ParseError::UnexpectedEnd {
+                needed: 2,
+                remaining: 1,
+                offset: 2
+            },
+            sub.read_le_u16().unwrap_err()
+        );
+        assert_eq!(3, sub.read_u8().unwrap());
+        assert!(sub.is_empty());
+        assert!(reader.sub_reader(3).is_err());
+    }
+
+    #[test]
+    fn streaming() {
+        let mut reader = SliceReader::streaming(&[1, 2, 3]);
+        assert_eq!(
+            ParseError::Incomplete(Needed::Size(1.try_into().unwrap())),
+            reader.read_le_u32().unwrap_err()
+        );
+        let mut sub = reader.sub_reader(2).unwrap();
+        assert_eq!(
+            Some(Needed::Size(2.try_into().unwrap())),
+            sub.read_le_u32().unwrap_err().needed()
+        );
+        assert!(matches!(
+            sub.read_le_u32(),
+            Err(ParseError::UnexpectedEnd { .. })
+        ));
+        let err = std::io::Error::from(reader.read_le_u16().unwrap_err());
+        assert_eq!(std::io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!("incomplete input, needed 1 more bytes", err.to_string());
+    }
+
+    #[test]
+    fn finish() {
+        let mut reader = SliceReader::new(&[1, 2, 3]);
+        reader.skip(1).unwrap();
+        assert_eq!(
+            ParseError::TrailingBytes {
+                count: 2,
+                offset: 1
+            },
+            reader.clone().finish().unwrap_err()
+        );
+        reader.skip(2).unwrap();
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn error_message() {
+        let mut reader = SliceReader::new(&[0; 0x1E]);
+        reader.skip(0x1C).unwrap();
+        let err = reader.read_le_f32().unwrap_err();
+        assert_eq!(
+            "needed 4 bytes, 2 remaining at offset 0x1c",
+            err.to_string()
+        );
+        let err = std::io::Error::from(err);
+        assert_eq!(std::io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_trait() {
+        let mut reader = SliceReader::new(&[1, 2, 3]);
+        assert_eq!(1, ReadU8::read_u8(&mut reader).unwrap());
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!([2, 3], *rest);
+        assert_eq!(3, reader.position());
+    }
+
+    #[test]
+    fn raw_parts() {
+        let data = [1u8, 2, 3];
+        // SAFETY: `data` outlives the reader
+        let mut reader = unsafe { SliceReader::from_raw_parts(data.as_ptr(), 2) };
+        assert_eq!(0x0201, reader.read_le_u16().unwrap());
+        assert!(reader.is_empty());
+        // SAFETY: a null pointer to no bytes is allowed
+        let empty = unsafe { SliceReader::from_raw_parts(core::ptr::null(), 0) };
+        assert!(empty.is_empty());
+        // SAFETY: `data` outlives the call
+        let sum = unsafe {
+            SliceReader::with_raw_parts(data.as_ptr(), 3, |reader| {
+                reader.read_slice(3).map(|bytes| bytes.iter().sum::<u8>())
+            })
+        };
+        assert_eq!(Ok(6), sum);
+    }
+
+    #[test]
+    #[should_panic = "null pointer to 4 bytes"]
+    fn raw_parts_null() {
+        // SAFETY: panics before reading
+        let _ = unsafe { SliceReader::from_raw_parts(core::ptr::null(), 4) };
+    }
+
+    #[cfg(feature = "unchecked")]
+    #[test]
+    fn unchecked() {
+        let mut reader = SliceReader::new(&[1, 0, 0, 0, 2, 3]);
+        assert!(reader.remaining() >= 6);
+        // SAFETY: checked above that 6 bytes remain
+        let (value, byte, array) = unsafe {
+            (
+                reader.read_le_u32_unchecked(),
+                reader.read_u8_unchecked(),
+                reader.read_byte_array_unchecked::<1>(),
+            )
+        };
+        assert_eq!((1, 2, [3]), (value, byte, array));
+        assert_eq!(6, reader.position());
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{Endian, Primitive};
+use std::io::{self, Read, Seek};
+
+fn check_stride<T: Primitive>(stride: usize) -> io::Result<()> {
+    if stride < T::SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "stride of {stride} bytes is shorter than the {} bytes of {}",
+                T::SIZE,
+                core::any::type_name::<T>()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn decoder<T: Primitive>(endian: Endian) -> fn(T::Bytes) -> T {
+    match endian {
+        Endian::Little => T::from_le_bytes,
+        Endian::Big => T::from_be_bytes,
+    }
+}
+
+/// Trait to read one field out of interleaved records, without decoding the rest of them
+///
+/// Each `T` is read from the start of its record and the `stride - T::SIZE` bytes after it are
+/// skipped, up to the last `T`, after which the reader is left. To pick a field other than the
+/// first, skip to it before reading.
+///
+/// ```
+/// use read_primitives::{Endian, ReadStrided, ReadU8};
+/// // stereo samples, left then right, as i16 in little endian byte order
+/// let mut frames: &[u8] = &[1, 0, 0xff, 0xff, 2, 0, 0xfe, 0xff, 3, 0, 0xfd, 0xff];
+/// frames.read_bytes(2).unwrap();
+/// let right: Vec<i16> = frames.read_strided(3, 4, Endian::Little).unwrap();
+/// assert_eq!([-1, -2, -3], *right);
+/// assert!(frames.is_empty());
+/// ```
+pub trait ReadStrided: Read {
+    /// Read `count` `T` in `endian` byte order from records `stride` bytes apart
+    ///
+    /// The gaps are read and discarded in chunks of whole records, or one gap at a time for
+    /// records longer than [`PREALLOC_LIMIT`](crate::PREALLOC_LIMIT).
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `stride` is shorter than a `T`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_strided<T: Primitive>(
+        &mut self,
+        count: usize,
+        stride: usize,
+        endian: Endian,
+    ) -> io::Result<Vec<T>> {
+        check_stride::<T>(stride)?;
+        let mut reader = self;
+        crate::outline::read_strided_vec(&mut reader, count, stride, decoder(endian))
+    }
+
+    /// Read `count` `T` in `endian` byte order from records `stride` bytes apart, seeking over
+    /// the gaps
+    ///
+    /// For records much longer than a `T`, as when picking one column out of a large table.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `stride` is shorter than a `T`,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact)
+    /// or seeking errors
+    fn read_strided_seek<T: Primitive>(
+        &mut self,
+        count: usize,
+        stride: usize,
+        endian: Endian,
+    ) -> io::Result<Vec<T>>
+    where
+        Self: Seek,
+    {
+        check_stride::<T>(stride)?;
+        let gap = i64::try_from(stride - T::SIZE)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "stride overflows i64"))?;
+        let decode = decoder(endian);
+        let mut vec = Vec::with_capacity(count.min(crate::PREALLOC_LIMIT / T::SIZE.max(1)));
+        for index in 0..count {
+            if index > 0 && gap > 0 {
+                self.seek_relative(gap)?;
+            }
+            let mut bytes = T::Bytes::default();
+            self.read_exact(bytes.as_mut())?;
+            vec.push(decode(bytes));
+        }
+        Ok(vec)
+    }
+}
+impl<R> ReadStrided for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Cursor, Read};
+
+    fn records(count: u32) -> Vec<u8> {
+        // records of a u32 key and 3 bytes of payload
+        let mut data = Vec::new();
+        for key in 0..count {
+            data.extend_from_slice(&key.to_be_bytes());
+            data.extend_from_slice(&[0xee; 3]);
+        }
+        data
+    }
+
+    #[test]
+    fn across_chunks() {
+        let data = records(20_000);
+        let mut reader = data.as_slice();
+        let keys: Vec<u32> = reader.read_strided(20_000, 7, Endian::Big).unwrap();
+        assert_eq!((0..20_000).collect::<Vec<_>>(), keys);
+        assert_eq!([0xee; 3], reader);
+        let mut cursor = Cursor::new(&data);
+        let keys: Vec<u32> = cursor.read_strided_seek(20_000, 7, Endian::Big).unwrap();
+        assert_eq!(19_999, keys[19_999]);
+        assert_eq!(data.len() as u64 - 3, cursor.position());
+    }
+
+    #[test]
+    fn invalid() {
+        let data = records(2);
+        let err = (&data[..])
+            .read_strided::<u32>(1, 3, Endian::Big)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!(
+            "stride of 3 bytes is shorter than the 4 bytes of u32",
+            err.to_string()
+        );
+        let err = (&data[..10])
+            .read_strided::<u32>(2, 7, Endian::Big)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut cursor = Cursor::new(&data[..10]);
+        assert!(cursor.read_strided_seek::<u32>(2, 7, Endian::Big).is_err());
+        let mut empty = &data[..];
+        assert!(empty
+            .read_strided::<u16>(0, 7, Endian::Big)
+            .unwrap()
+            .is_empty());
+        assert_eq!(14, empty.bytes().count());
+    }
+
+    #[test]
+    fn huge_stride() {
+        let mut data = vec![0u8; 1 << 17];
+        data[..2].copy_from_slice(&[0, 1]);
+        data[(1 << 17) - 2..].copy_from_slice(&[0, 2]);
+        let mut reader = &data[..];
+        let values: Vec<u16> = reader.read_strided(2, (1 << 17) - 2, Endian::Big).unwrap();
+        assert_eq!([1, 2], *values);
+        assert!(reader.is_empty());
+        // the stride is never allocated, so this reaches the end of the data instead of aborting
+        let err = (&data[..])
+            .read_strided::<u16>(2, 1 << 40, Endian::Big)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            vec![1u16],
+            (&data[..]).read_strided(1, 1 << 40, Endian::Big).unwrap()
+        );
+    }
+}
@@ -0,0 +1,453 @@
+use std::ffi::{CStr, CString};
+use std::io::{self, Read, Write};
+
+/// Read exactly `len` bytes into a new `Vec`, growing it as data arrives instead of trusting `len` up front
+pub(crate) fn read_exact_vec<R: Read + ?Sized>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    read_exact_into(reader, len, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// [`read_exact_vec`] into `bytes`, replacing its contents but reusing its capacity
+pub(crate) fn read_exact_into<R: Read + ?Sized>(
+    reader: &mut R,
+    len: usize,
+    bytes: &mut Vec<u8>,
+) -> io::Result<()> {
+    bytes.clear();
+    bytes.reserve(len.min(crate::PREALLOC_LIMIT));
+    reader.take(len as u64).read_to_end(bytes)?;
+    if bytes.len() < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// Find the terminator of a NUL-padded C string field, checking that only NULs follow it
+fn terminator(field: &[u8]) -> io::Result<usize> {
+    let nul = field.iter().position(|&byte| byte == 0).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "C string field has no terminator",
+        )
+    })?;
+    if let Some(after) = field[nul..].iter().position(|&byte| byte != 0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "interior nul byte at position {nul}, before data at {}",
+                nul + after
+            ),
+        ));
+    }
+    Ok(nul)
+}
+
+/// Borrow the C string in a NUL-padded field
+pub(crate) fn cstr_from_field(field: &[u8]) -> io::Result<&CStr> {
+    let nul = terminator(field)?;
+    Ok(CStr::from_bytes_with_nul(&field[..=nul]).expect("terminator is the first nul"))
+}
+
+/// Translate each byte of `bytes` through `table`
+fn translate(bytes: &mut [u8], table: &[u8; 256]) {
+    for byte in bytes {
+        *byte = table[usize::from(*byte)];
+    }
+}
+
+/// The name in a fixed-size name field, ending at the first NUL or filling the field
+fn name_from_field(mut bytes: Vec<u8>, padding: NamePadding) -> io::Result<String> {
+    if let Some(nul) = bytes.iter().position(|&byte| byte == 0) {
+        if padding == NamePadding::Nul {
+            terminator(&bytes)?;
+        }
+        bytes.truncate(nul);
+    }
+    String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// What may follow the terminator of a fixed-size name field, for [`ReadString::read_name_field_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NamePadding {
+    /// Only NUL bytes
+    #[default]
+    Nul,
+    /// Anything, as left behind by writers that don't clear their buffers
+    Any,
+}
+
+/// Trait to read UTF-8 strings of known length
+pub trait ReadString: Read {
+    /// Read a UTF-8 string of `len` bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_string(&mut self, len: usize) -> io::Result<String> {
+        String::from_utf8(read_exact_vec(self, len)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Read a UTF-8 string of `len` bytes into `string`, replacing its contents
+    ///
+    /// Reuses the capacity of `string`, so that decoding many strings through one buffer doesn't
+    /// allocate for each. On error `string` is left empty.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_string_into(&mut self, len: usize, string: &mut String) -> io::Result<()> {
+        let mut bytes = std::mem::take(string).into_bytes();
+        let result = read_exact_into(self, len, &mut bytes).and_then(|()| {
+            std::str::from_utf8(&bytes)
+                .map(drop)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        });
+        if result.is_err() {
+            bytes.clear();
+        }
+        *string = String::from_utf8(bytes).expect("bytes are empty or were checked to be UTF-8");
+        result
+    }
+
+    /// Read a string of `len` bytes, replacing invalid UTF-8 with U+FFFD
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_string_lossy(&mut self, len: usize) -> io::Result<String> {
+        let bytes = read_exact_vec(self, len)?;
+        Ok(match String::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        })
+    }
+
+    /// Read a UTF-8 string prefixed with its byte length as a little endian u32
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_le_string_u32(&mut self) -> io::Result<String> {
+        self.read_le_string_u32_max(usize::MAX)
+    }
+
+    /// Read a UTF-8 string prefixed with its byte length as a little endian u32, rejecting lengths
+    /// over `max_len` before reading the string
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length exceeds `max_len` or the bytes are
+    /// not valid UTF-8, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_le_string_u32_max(&mut self, max_len: usize) -> io::Result<String> {
+        let mut len = [0u8; 4];
+        self.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        crate::check_max_len(len, max_len)?;
+        self.read_string(len)
+    }
+
+    /// Read a UTF-8 string prefixed with its byte length as a big endian u32
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the bytes are not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_be_string_u32(&mut self) -> io::Result<String> {
+        self.read_be_string_u32_max(usize::MAX)
+    }
+
+    /// Read a UTF-8 string prefixed with its byte length as a big endian u32, rejecting lengths
+    /// over `max_len` before reading the string
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the length exceeds `max_len` or the bytes are
+    /// not valid UTF-8, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_be_string_u32_max(&mut self, max_len: usize) -> io::Result<String> {
+        let mut len = [0u8; 4];
+        self.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        crate::check_max_len(len, max_len)?;
+        self.read_string(len)
+    }
+
+    /// Read a fixed-size name field of `len` bytes, ending at the first NUL or filling the field
+    ///
+    /// Only NUL bytes may follow the terminator, see [`ReadString::read_name_field_with`].
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the name is not valid UTF-8 or other bytes
+    /// follow the terminator, or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_name_field(&mut self, len: usize) -> io::Result<String> {
+        self.read_name_field_with(len, NamePadding::Nul)
+    }
+
+    /// [`ReadString::read_name_field`] with the bytes allowed after the terminator set by `padding`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the name is not valid UTF-8 or bytes not
+    /// allowed by `padding` follow the terminator, or with [`io::ErrorKind::UnexpectedEof`] if the
+    /// reader ends early
+    fn read_name_field_with(&mut self, len: usize, padding: NamePadding) -> io::Result<String> {
+        name_from_field(read_exact_vec(self, len)?, padding)
+    }
+
+    /// Read a string of `len` bytes, translating each byte through `table` before checking that
+    /// the result is UTF-8
+    ///
+    /// For text in single-byte encodings that map onto ASCII, like the EBCDIC of mainframe files,
+    /// decoded in the same pass as the read.
+    ///
+    /// ```
+    /// use read_primitives::ReadString;
+    /// // EBCDIC letters A to I and the space
+    /// let mut table = [b'?'; 256];
+    /// table[0x40] = b' ';
+    /// for (ebcdic, ascii) in (0xc1..=0xc9).zip(b'A'..=b'I') {
+    ///     table[ebcdic] = ascii;
+    /// }
+    /// let mut record: &[u8] = &[0xc2, 0xc1, 0xc4, 0x40, 0xc6, 0xc5, 0xc5];
+    /// assert_eq!("BAD FEE", record.read_string_mapped(7, &table).unwrap());
+    /// ```
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the translated bytes are not valid UTF-8,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_string_mapped(&mut self, len: usize, table: &[u8; 256]) -> io::Result<String> {
+        let mut bytes = read_exact_vec(self, len)?;
+        translate(&mut bytes, table);
+        String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// [`ReadString::read_name_field_with`] with each byte translated through `table` first, so
+    /// the terminator is the byte `table` maps to NUL
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the translated name is not valid UTF-8 or
+    /// bytes not allowed by `padding` follow the terminator, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_name_field_mapped(
+        &mut self,
+        len: usize,
+        padding: NamePadding,
+        table: &[u8; 256],
+    ) -> io::Result<String> {
+        let mut bytes = read_exact_vec(self, len)?;
+        translate(&mut bytes, table);
+        name_from_field(bytes, padding)
+    }
+
+    /// Read a fixed-size, NUL-terminated field of `len` bytes into a [`CString`]
+    ///
+    /// The terminator may be followed by NUL padding up to `len`, but not by other bytes.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the field has no terminator or an interior NUL,
+    /// or with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_cstring_exact(&mut self, len: usize) -> io::Result<CString> {
+        let mut bytes = read_exact_vec(self, len)?;
+        bytes.truncate(terminator(&bytes)? + 1);
+        Ok(CString::from_vec_with_nul(bytes).expect("terminator is the first nul"))
+    }
+}
+impl<R> ReadString for R where R: Read {}
+
+fn check_no_nul(value: &str) -> io::Result<()> {
+    match value.bytes().position(|byte| byte == 0) {
+        Some(nul) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("interior nul byte at position {nul}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn prefix_u32(value: &str) -> io::Result<u32> {
+    u32::try_from(value.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "string of {} bytes is too long for a u32 length",
+                value.len()
+            ),
+        )
+    })
+}
+
+/// Trait to write strings in the layouts read by [`ReadString`]
+pub trait WriteString: Write {
+    /// Write a string followed by a NUL terminator
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `value` contains a NUL,
+    /// or when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_cstring(&mut self, value: &str) -> io::Result<()> {
+        check_no_nul(value)?;
+        self.write_all(value.as_bytes())?;
+        self.write_all(&[0])
+    }
+
+    /// Write a string prefixed with its byte length as a little endian u32
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if the length does not fit in a u32,
+    /// or when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_le_string_u32(&mut self, value: &str) -> io::Result<()> {
+        self.write_all(&prefix_u32(value)?.to_le_bytes())?;
+        self.write_all(value.as_bytes())
+    }
+
+    /// Write a string prefixed with its byte length as a big endian u32
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if the length does not fit in a u32,
+    /// or when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_be_string_u32(&mut self, value: &str) -> io::Result<()> {
+        self.write_all(&prefix_u32(value)?.to_be_bytes())?;
+        self.write_all(value.as_bytes())
+    }
+
+    /// Write a fixed-size field of `len` bytes: the string, a NUL terminator and NUL padding
+    ///
+    /// This is the layout read by [`ReadString::read_cstring_exact`].
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `value` contains a NUL or does not fit
+    /// in `len` bytes with its terminator,
+    /// or when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_cstring_exact(&mut self, value: &str, len: usize) -> io::Result<()> {
+        check_no_nul(value)?;
+        if value.len() >= len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "string of {} bytes does not fit in a field of {len} bytes with its terminator",
+                    value.len()
+                ),
+            ));
+        }
+        self.write_all(value.as_bytes())?;
+        io::copy(&mut io::repeat(0).take((len - value.len()) as u64), self).map(drop)
+    }
+}
+impl<W> WriteString for W where W: Write {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn read_string() {
+        let mut bytes: &[u8] = b"hello\xffworld";
+        assert_eq!("hello", bytes.read_string(5).unwrap());
+        let err = bytes.read_string(6).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&b"ab"[..]).read_string(3).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn read_string_into() {
+        let mut bytes: &[u8] = b"abcde\xff";
+        let mut string = String::with_capacity(16);
+        bytes.read_string_into(3, &mut string).unwrap();
+        assert_eq!("abc", string);
+        bytes.read_string_into(2, &mut string).unwrap();
+        assert_eq!("de", string);
+        assert!(string.capacity() >= 16);
+        let err = bytes.read_string_into(1, &mut string).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(string.is_empty() && string.capacity() >= 16);
+    }
+
+    #[test]
+    fn read_string_lossy() {
+        let mut bytes: &[u8] = b"na\xffme";
+        assert_eq!("na\u{fffd}me", bytes.read_string_lossy(5).unwrap());
+    }
+
+    #[test]
+    fn read_name_field() {
+        let mut bytes: &[u8] = b"ab\0\0full\0x\0y";
+        assert_eq!("ab", bytes.read_name_field(4).unwrap());
+        assert_eq!("full", bytes.read_name_field(4).unwrap());
+        let err = (&bytes[..]).read_name_field(4).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("", bytes.read_name_field_with(4, NamePadding::Any).unwrap());
+        let err = (&b"\xff\0"[..]).read_name_field(2).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_mapped() {
+        // upper case from lower case and NUL from 0xff, leaving 0x80 invalid UTF-8
+        let mut table = [0u8; 256];
+        for (byte, entry) in table.iter_mut().enumerate() {
+            *entry = (byte as u8).to_ascii_uppercase();
+        }
+        table[0xff] = 0;
+        let mut bytes: &[u8] = b"abc";
+        assert_eq!("ABC", bytes.read_string_mapped(3, &table).unwrap());
+        let mut bytes: &[u8] = b"ab\xff\xffcd\xffx";
+        let name = bytes.read_name_field_mapped(4, NamePadding::Nul, &table);
+        assert_eq!("AB", name.unwrap());
+        let err = bytes
+            .read_name_field_mapped(4, NamePadding::Nul, &table)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&b"a\x80"[..]).read_string_mapped(2, &table).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_cstring_exact() {
+        let mut bytes: &[u8] = b"abc\0\0\0xy\0";
+        assert_eq!(c"abc", bytes.read_cstring_exact(6).unwrap().as_c_str());
+        assert_eq!(c"xy", bytes.read_cstring_exact(3).unwrap().as_c_str());
+        for field in [&b"ab\0c\0"[..], b"abc"] {
+            let err = (&field[..]).read_cstring_exact(field.len()).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        }
+    }
+
+    #[test]
+    fn read_string_u32() {
+        let mut bytes: &[u8] = b"\x02\0\0\0hi\0\0\0\x03abc";
+        assert_eq!("hi", bytes.read_le_string_u32().unwrap());
+        assert_eq!("abc", bytes.read_be_string_u32().unwrap());
+        let err = (&b"\xff\xff\xff\xffab"[..])
+            .read_le_string_u32()
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn write_strings() {
+        let mut bytes = Vec::new();
+        bytes.write_cstring("ab").unwrap();
+        bytes.write_le_string_u32("hi").unwrap();
+        bytes.write_be_string_u32("abc").unwrap();
+        bytes.write_cstring_exact("xy", 5).unwrap();
+        assert_eq!(b"ab\0\x02\0\0\0hi\0\0\0\x03abcxy\0\0\0", bytes.as_slice());
+        let mut reader = &bytes[3..];
+        assert_eq!("hi", reader.read_le_string_u32().unwrap());
+        assert_eq!("abc", reader.read_be_string_u32().unwrap());
+        assert_eq!(c"xy", reader.read_cstring_exact(5).unwrap().as_c_str());
+    }
+
+    #[test]
+    fn write_strings_invalid() {
+        let mut bytes = Vec::new();
+        let err = bytes.write_cstring("a\0b").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("interior nul byte at position 1", err.to_string());
+        let err = bytes.write_cstring_exact("a\0", 4).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let err = bytes.write_cstring_exact("abc", 3).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn read_string_u32_max() {
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, b'a'];
+        let err = bytes.read_le_string_u32_max(1024).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "length 4294967295 exceeds the maximum of 1024",
+            err.to_string()
+        );
+        let mut bytes: &[u8] = &[0, 0, 0, 2, b'h', b'i'];
+        assert_eq!("hi", bytes.read_be_string_u32_max(2).unwrap());
+    }
+
+    #[test]
+    fn read_huge_len() {
+        let mut bytes: &[u8] = b"short";
+        let err = bytes.read_string(usize::MAX).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+
+fn out_of_bounds(offset: u64, len: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("string table offset {offset:#x} is out of bounds for a table of {len} bytes"),
+    )
+}
+
+fn unterminated(offset: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("string at table offset {offset:#x} has no terminator"),
+    )
+}
+
+fn to_str(bytes: &[u8], offset: u64) -> io::Result<&str> {
+    std::str::from_utf8(bytes).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("string at table offset {offset:#x} is not valid UTF-8: {err}"),
+        )
+    })
+}
+
+/// Blob of NUL-terminated strings referred to by offset, like ELF's `.strtab`
+///
+/// Offsets may point into the middle of a string, to share its suffix, as linkers do.
+///
+/// ```
+/// use read_primitives::StringTable;
+/// let table = StringTable::new(b"\0main\0.text\0".to_vec());
+/// assert_eq!("main", table.get(1).unwrap());
+/// assert_eq!("text", table.get(7).unwrap());
+/// assert!(table.get(64).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StringTable {
+    data: Vec<u8>,
+}
+
+impl StringTable {
+    /// Table over `data`
+    pub fn new(data: Vec<u8>) -> Self {
+        StringTable { data }
+    }
+
+    /// Read a table of `len` bytes
+    /// # Errors
+    /// errors with [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    pub fn read_from<R: Read + ?Sized>(reader: &mut R, len: usize) -> io::Result<Self> {
+        crate::string::read_exact_vec(reader, len).map(StringTable::new)
+    }
+
+    /// The bytes of the table
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Bytes of the string at `offset`, without the terminator
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `offset` is outside the table or the string
+    /// has no terminator before the end
+    pub fn get_bytes(&self, offset: usize) -> io::Result<&[u8]> {
+        let rest = self
+            .data
+            .get(offset..)
+            .filter(|rest| !rest.is_empty())
+            .ok_or_else(|| out_of_bounds(offset as u64, self.data.len() as u64))?;
+        let nul = rest
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or_else(|| unterminated(offset as u64))?;
+        Ok(&rest[..nul])
+    }
+
+    /// String at `offset`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `offset` is outside the table, or the string
+    /// has no terminator before the end or is not valid UTF-8
+    pub fn get(&self, offset: usize) -> io::Result<&str> {
+        to_str(self.get_bytes(offset)?, offset as u64)
+    }
+}
+
+/// [`StringTable`] read from a seekable reader string by string, for tables too large to load
+#[derive(Debug)]
+pub struct LazyStringTable<R> {
+    reader: R,
+    base: u64,
+    len: u64,
+}
+
+impl<R: Read + Seek> LazyStringTable<R> {
+    /// Table of `len` bytes at offset `base` of `reader`
+    pub fn new(reader: R, base: u64, len: u64) -> Self {
+        LazyStringTable { reader, base, len }
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Bytes of the string at `offset`, without the terminator
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `offset` is outside the table or the string
+    /// has no terminator before the end of the table, or when seeking or reading errors
+    pub fn get_bytes(&mut self, offset: u64) -> io::Result<Vec<u8>> {
+        if offset >= self.len {
+            return Err(out_of_bounds(offset, self.len));
+        }
+        let start = self
+            .base
+            .checked_add(offset)
+            .ok_or_else(|| out_of_bounds(offset, self.len))?;
+        self.reader.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        BufReader::new((&mut self.reader).take(self.len - offset)).read_until(0, &mut bytes)?;
+        if bytes.pop() != Some(0) {
+            return Err(unterminated(offset));
+        }
+        Ok(bytes)
+    }
+
+    /// String at `offset`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if `offset` is outside the table, or the string
+    /// has no terminator before the end of the table or is not valid UTF-8, or when seeking or
+    /// reading errors
+    pub fn get(&mut self, offset: u64) -> io::Result<String> {
+        let bytes = self.get_bytes(offset)?;
+        to_str(&bytes, offset)?;
+        Ok(String::from_utf8(bytes).expect("bytes were checked to be UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Cursor};
+
+    const TABLE: &[u8] = b"\0abc\0\xff\0de";
+
+    #[test]
+    fn string_table() {
+        let table = StringTable::read_from(&mut &TABLE[..], TABLE.len()).unwrap();
+        assert_eq!("", table.get(0).unwrap());
+        assert_eq!("bc", table.get(2).unwrap());
+        let err = table.get(5).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err
+            .to_string()
+            .starts_with("string at table offset 0x5 is not valid UTF-8"));
+        let err = table.get(7).unwrap_err();
+        assert_eq!(
+            "string at table offset 0x7 has no terminator",
+            err.to_string()
+        );
+        let err = table.get(9).unwrap_err();
+        assert_eq!(
+            "string table offset 0x9 is out of bounds for a table of 9 bytes",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn lazy_string_table() {
+        let mut data = b"header".to_vec();
+        data.extend_from_slice(TABLE);
+        data.extend_from_slice(b"f\0");
+        let mut table = LazyStringTable::new(Cursor::new(data), 6, TABLE.len() as u64);
+        assert_eq!("abc", table.get(1).unwrap());
+        assert_eq!(b"\xff", &*table.get_bytes(5).unwrap());
+        assert!(table.get(5).is_err());
+        assert!(table.get(7).is_err());
+        assert!(table.get(9).is_err());
+        let mut table = LazyStringTable::new(Cursor::new(Vec::new()), u64::MAX, 16);
+        let err = table.get(8).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
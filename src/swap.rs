@@ -0,0 +1,155 @@
+use crate::{Endian, Primitive};
+use std::io::{self, Read};
+
+/// Size of the blocks read from the inner reader, rounded down to whole elements
+const CHUNK_LEN: usize = 4096;
+
+/// Reader reversing the bytes of each fixed-size element read from the inner reader
+///
+/// Presents a stream of values in one byte order as the same values in the other, for
+/// consumers that only accept one, without decoding or buffering the whole stream.
+///
+/// ```
+/// use read_primitives::{ByteSwapReader, Endian, ReadF32};
+/// let big: &[u8] = &[0x3f, 0x80, 0, 0, 0x40, 0, 0, 0];
+/// let mut little = ByteSwapReader::transcode::<f32>(big, Endian::Big, Endian::Little);
+/// assert_eq!(1.0, little.read_le_f32().unwrap());
+/// assert_eq!(2.0, little.read_le_f32().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ByteSwapReader<R> {
+    inner: R,
+    element_size: usize,
+    swap: bool,
+    buf: Vec<u8>,
+    /// `buf[pos..ready]` is swapped and ready, `buf[ready..filled]` the start of an element
+    pos: usize,
+    ready: usize,
+    filled: usize,
+}
+
+impl<R> ByteSwapReader<R> {
+    /// Reverse the bytes of each element of `element_size` bytes of `inner`
+    /// # Panics
+    /// panics if `element_size` is 0
+    pub fn new(inner: R, element_size: usize) -> Self {
+        assert!(element_size > 0, "element size is 0");
+        ByteSwapReader::with_swap(inner, element_size, true)
+    }
+
+    /// Present `inner`, a stream of `T` in `from` byte order, as one in `to` byte order
+    pub fn transcode<T: Primitive>(inner: R, from: Endian, to: Endian) -> Self {
+        ByteSwapReader::with_swap(inner, T::SIZE.max(1), from != to)
+    }
+
+    fn with_swap(inner: R, element_size: usize, swap: bool) -> Self {
+        ByteSwapReader {
+            inner,
+            element_size,
+            swap,
+            buf: vec![0; (CHUNK_LEN / element_size).max(1) * element_size],
+            pos: 0,
+            ready: 0,
+            filled: 0,
+        }
+    }
+
+    /// Size of the elements in bytes
+    pub fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Borrow the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the reader, dropping bytes that were read from it but not yet returned
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ByteSwapReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        while self.pos == self.ready {
+            self.buf.copy_within(self.ready..self.filled, 0);
+            self.filled -= self.ready;
+            self.pos = 0;
+            self.ready = 0;
+            let read = self.inner.read(&mut self.buf[self.filled..])?;
+            if read == 0 {
+                if self.filled > 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!(
+                            "stream ends after {} of the {} bytes of an element",
+                            self.filled, self.element_size
+                        ),
+                    ));
+                }
+                return Ok(0);
+            }
+            self.filled += read;
+            self.ready = self.filled - self.filled % self.element_size;
+            if self.swap {
+                for element in self.buf[..self.ready].chunks_exact_mut(self.element_size) {
+                    element.reverse();
+                }
+            }
+        }
+        let len = out.len().min(self.ready - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Read};
+
+    /// Reader returning at most `n` bytes per read, to split elements across reads
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.1).min(self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn split_elements() {
+        let data: Vec<u8> = (0..3000u32).flat_map(u32::to_be_bytes).collect();
+        let mut swapped = Vec::new();
+        ByteSwapReader::new(Trickle(&data, 7), 4)
+            .read_to_end(&mut swapped)
+            .unwrap();
+        let expected: Vec<u8> = (0..3000u32).flat_map(u32::to_le_bytes).collect();
+        assert_eq!(expected, swapped);
+    }
+
+    #[test]
+    fn same_order_and_errors() {
+        let mut same =
+            ByteSwapReader::transcode::<u16>(&[1, 2, 3, 4][..], Endian::Big, Endian::Big);
+        assert_eq!(0x0102, same.read_be_u16().unwrap());
+        assert_eq!(0x0304, same.read_be_u16().unwrap());
+
+        let mut partial = ByteSwapReader::new(&[1, 2, 3][..], 2);
+        assert_eq!(0x0201, partial.read_be_u16().unwrap());
+        let err = partial.read(&mut [0; 4]).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            "stream ends after 1 of the 2 bytes of an element",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Endian, FixedSize, ReadFrom};
+use std::cmp::Ordering;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// Table of fixed-size records in a seekable reader, read one at a time as they are needed
+///
+/// Each record is read as a whole and decoded with [`ReadFrom::read_from`] from its
+/// `record_size` bytes, so a `T` may leave the end of its record unread but not read past it.
+///
+/// ```
+/// use read_primitives::{Endian, RecordTable};
+/// use std::io::Cursor;
+/// // a 2 byte header, then records of a u16 key and a u16 value
+/// let file = Cursor::new([0xff, 0xff, 0, 1, 0, 10, 0, 5, 0, 50, 0, 9, 0, 90]);
+/// let mut table = RecordTable::<_, [u16; 2]>::new(file, 2, 4, 3, Endian::Big);
+/// assert_eq!([5, 50], table.get(1).unwrap());
+/// assert_eq!(Ok(2), table.binary_search_by_key(&9, |record| record[0]).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct RecordTable<R, T> {
+    reader: R,
+    base: u64,
+    record_size: usize,
+    len: u64,
+    endian: Endian,
+    buf: Vec<u8>,
+    records: PhantomData<fn() -> T>,
+}
+
+impl<R, T> RecordTable<R, T> {
+    /// Table of `len` records of `record_size` bytes starting at offset `base` of `reader`,
+    /// decoded in `endian` byte order
+    pub fn new(reader: R, base: u64, record_size: usize, len: u64, endian: Endian) -> Self {
+        RecordTable {
+            reader,
+            base,
+            record_size,
+            len,
+            endian,
+            buf: vec![0; record_size],
+            records: PhantomData,
+        }
+    }
+
+    /// Table of `len` records of [`FixedSize::SIZE`] bytes starting at offset `base` of `reader`,
+    /// decoded in `endian` byte order
+    pub fn fixed(reader: R, base: u64, len: u64, endian: Endian) -> Self
+    where
+        T: FixedSize,
+    {
+        RecordTable::new(reader, base, T::SIZE, len, endian)
+    }
+
+    /// Number of records
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether there are no records
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Unwrap the reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read + Seek, T: ReadFrom> RecordTable<R, T> {
+    fn seek_to(&mut self, index: u64) -> io::Result<()> {
+        let offset = (self.record_size as u64)
+            .checked_mul(index)
+            .and_then(|offset| offset.checked_add(self.base))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "record offset overflows u64")
+            })?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    fn read_next(&mut self) -> io::Result<T> {
+        self.reader.read_exact(&mut self.buf)?;
+        T::read_from(&mut self.buf.as_slice(), self.endian)
+    }
+
+    /// Read record `index`
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidInput`] if `index` is out of range,
+    /// or when seeking, reading or decoding the record errors
+    pub fn get(&mut self, index: u64) -> io::Result<T> {
+        if index >= self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("record {index} out of range for table of {}", self.len),
+            ));
+        }
+        self.seek_to(index)?;
+        self.read_next()
+    }
+
+    /// Iterate over the records in `range`, seeking once and then reading them in order
+    ///
+    /// The range is clamped to the table.
+    pub fn range(&mut self, range: Range<u64>) -> Records<'_, R, T> {
+        let end = range.end.min(self.len);
+        Records {
+            next: range.start.min(end),
+            end,
+            seeked: false,
+            table: self,
+        }
+    }
+
+    /// Binary search for a record by the key `key_of` extracts, for tables sorted by that key
+    ///
+    /// Returns the index of a matching record, or where one would be inserted, like
+    /// [`slice::binary_search_by_key`].
+    /// # Errors
+    /// errors when reading a record errors
+    pub fn binary_search_by_key<K: Ord>(
+        &mut self,
+        key: &K,
+        mut key_of: impl FnMut(&T) -> K,
+    ) -> io::Result<Result<u64, u64>> {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match key_of(&self.get(mid)?).cmp(key) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+}
+
+/// Iterator over a range of a [`RecordTable`], from [`RecordTable::range`]
+#[derive(Debug)]
+pub struct Records<'t, R, T> {
+    table: &'t mut RecordTable<R, T>,
+    next: u64,
+    end: u64,
+    seeked: bool,
+}
+
+impl<R: Read + Seek, T: ReadFrom> Iterator for Records<'_, R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        if !self.seeked {
+            if let Err(err) = self.table.seek_to(self.next) {
+                self.next = self.end;
+                return Some(Err(err));
+            }
+            self.seeked = true;
+        }
+        self.next += 1;
+        let record = self.table.read_next();
+        if record.is_err() {
+            self.seeked = false;
+        }
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = usize::try_from(self.end - self.next).unwrap_or(usize::MAX);
+        (len, Some(len))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io::{self, Cursor};
+
+    fn table() -> RecordTable<Cursor<Vec<u8>>, u32> {
+        // records of a u32 and 2 bytes of padding, after a 3 byte header
+        let mut data = vec![9, 9, 9];
+        for key in [2u32, 4, 6, 8] {
+            data.extend_from_slice(&key.to_le_bytes());
+            data.extend_from_slice(&[0xee, 0xee]);
+        }
+        RecordTable::new(Cursor::new(data), 3, 6, 4, Endian::Little)
+    }
+
+    #[test]
+    fn get_and_range() {
+        let mut table = table();
+        assert_eq!(4, table.len());
+        assert_eq!(6, table.get(2).unwrap());
+        assert_eq!(2, table.get(0).unwrap());
+        let err = table.get(4).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        let keys: Vec<_> = table.range(1..10).map(Result::unwrap).collect();
+        assert_eq!([4, 6, 8], *keys);
+        assert_eq!(0, table.range(5..7).count());
+    }
+
+    #[test]
+    fn binary_search() {
+        let mut table = table();
+        assert_eq!(Ok(3), table.binary_search_by_key(&8, |&key| key).unwrap());
+        assert_eq!(Err(2), table.binary_search_by_key(&5, |&key| key).unwrap());
+        assert_eq!(Err(0), table.binary_search_by_key(&1, |&key| key).unwrap());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut table = RecordTable::<_, u32>::new(Cursor::new([1, 0, 0]), 0, 4, 2, Endian::Little);
+        let err = table.get(0).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut short = RecordTable::<_, u32>::new(Cursor::new([1, 0, 0]), 0, 2, 1, Endian::Little);
+        assert!(short.get(0).is_err());
+    }
+
+    #[test]
+    fn fixed() {
+        let data = Cursor::new([0xaa, 1, 0, 2, 2, 0, 3]);
+        let mut table = RecordTable::<_, (u8, u16)>::fixed(data, 1, 2, Endian::Big);
+        assert_eq!((2, 3), table.get(1).unwrap());
+    }
+}
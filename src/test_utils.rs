@@ -0,0 +1,137 @@
+//! Property testing helpers for layouts built on this crate
+//!
+//! The strategies generate primitives together with their encoding, and the round-trip helpers
+//! check that a [`ReadFrom`] type reads back exactly what its [`WriteTo`] implementation wrote.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use read_primitives::test_utils::{endian, prop_assert_round_trip};
+//!
+//! proptest!(|(value: [u32; 4], endian in endian())| {
+//!     prop_assert_round_trip(&value, endian)?;
+//! });
+//! ```
+
+use crate::{Endian, ReadFrom, WriteTo};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use std::fmt::Debug;
+use std::io;
+
+macro_rules! impl_encoded {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste! {
+                #[doc = "Any " $type " together with its encoding in `endian` byte order"]
+                pub fn [<encoded_ $type>](endian: Endian) -> impl Strategy<Value = ($type, Vec<u8>)> {
+                    any::<$type>().prop_map(move |value| {
+                        let bytes = match endian {
+                            Endian::Little => value.to_le_bytes(),
+                            Endian::Big => value.to_be_bytes(),
+                        };
+                        (value, bytes.to_vec())
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_encoded!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
+/// Either byte order
+pub fn endian() -> impl Strategy<Value = Endian> {
+    prop_oneof![Just(Endian::Little), Just(Endian::Big)]
+}
+
+/// Write `value` in `endian` byte order and read it back
+/// # Errors
+/// errors with [`io::ErrorKind::InvalidData`] if the read leaves bytes of the encoding unread,
+/// or when writing or reading errors
+pub fn round_trip<T: ReadFrom + WriteTo>(value: &T, endian: Endian) -> io::Result<T> {
+    let mut bytes = Vec::new();
+    value.write_to(&mut bytes, endian)?;
+    let mut reader = bytes.as_slice();
+    let read = T::read_from(&mut reader, endian)?;
+    if !reader.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "read {} of {} written bytes",
+                bytes.len() - reader.len(),
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(read)
+}
+
+/// Assert that `value` survives [`round_trip`] unchanged
+/// # Panics
+/// panics if the round trip errors or reads a different value
+pub fn assert_round_trip<T: ReadFrom + WriteTo + PartialEq + Debug>(value: &T, endian: Endian) {
+    match round_trip(value, endian) {
+        Ok(read) => assert_eq!(*value, read, "round trip in {endian:?} byte order"),
+        Err(err) => panic!("round trip of {value:?} in {endian:?} byte order failed: {err}"),
+    }
+}
+
+/// [`assert_round_trip`] for use inside `proptest!`, failing the test case instead of panicking
+/// # Errors
+/// errors if the round trip errors or reads a different value
+pub fn prop_assert_round_trip<T: ReadFrom + WriteTo + PartialEq + Debug>(
+    value: &T,
+    endian: Endian,
+) -> Result<(), TestCaseError> {
+    let read = round_trip(value, endian).map_err(|err| TestCaseError::fail(err.to_string()))?;
+    prop_assert_eq!(value, &read, "round trip in {:?} byte order", endian);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use crate::*;
+
+    proptest! {
+        #[test]
+        fn encoded_values_read_back((endian, (value, bytes)) in endian().prop_flat_map(|endian| (Just(endian), encoded_u64(endian)))) {
+            prop_assert_eq!(value, bytes.as_slice().read_endian::<u64>(endian).unwrap());
+        }
+
+        #[test]
+        fn encoded_floats_read_back((value, bytes) in encoded_f32(Endian::Big)) {
+            prop_assert_eq!(value.to_bits(), bytes.as_slice().read_be_f32().unwrap().to_bits());
+        }
+
+        #[test]
+        fn arrays_round_trip(value: [i16; 3], endian in endian()) {
+            prop_assert_round_trip(&value, endian)?;
+        }
+    }
+
+    #[test]
+    fn round_trip_reports_unread_bytes() {
+        struct Short(u16);
+        impl ReadFrom for Short {
+            fn read_from<R: std::io::Read + ?Sized>(
+                reader: &mut R,
+                endian: Endian,
+            ) -> std::io::Result<Self> {
+                u16::read_from(reader, endian).map(Short)
+            }
+        }
+        impl WriteTo for Short {
+            fn write_to<W: std::io::Write + ?Sized>(
+                &self,
+                writer: &mut W,
+                endian: Endian,
+            ) -> std::io::Result<()> {
+                u32::from(self.0).write_to(writer, endian)
+            }
+        }
+        let err = round_trip(&Short(1), Endian::Little).err().unwrap();
+        assert_eq!("read 2 of 4 written bytes", err.to_string());
+        assert_round_trip(&[1u8, 2], Endian::Big);
+    }
+}
@@ -0,0 +1,89 @@
+//! Async primitive writes for [`tokio::io::AsyncWrite`], cancellation safe reads for
+//! [`tokio::io::AsyncRead`] and peeks for [`tokio::io::AsyncBufRead`]
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> std::io::Result<()> {
+//! use read_primitives::tokio::AsyncWriteU32;
+//! let mut bytes = Vec::new();
+//! bytes.write_be_u32(0xCAFE_BABE).await?;
+//! assert_eq!([0xCA, 0xFE, 0xBA, 0xBE], *bytes);
+//! # Ok(())
+//! # }
+//! ```
+
+impl_async_write!(::tokio::io::AsyncWrite);
+impl_async_read!(
+    ::tokio::io::AsyncBufRead,
+    ::tokio::io::AsyncRead,
+    poll_read_slice
+);
+
+fn poll_read_slice<R: AsyncRead + Unpin + ?Sized>(
+    reader: &mut R,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+) -> Poll<io::Result<usize>> {
+    let mut buf = ::tokio::io::ReadBuf::new(buf);
+    ready!(Pin::new(reader).poll_read(cx, &mut buf))?;
+    Poll::Ready(Ok(buf.filled().len()))
+}
+
+impl_async_read_from!(tokio, ::tokio::io::AsyncRead, poll_read_slice);
+
+#[cfg(feature = "derive")]
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for derive_support::Counter<'_, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ::tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let read = ready!(self.poll_read_slice(cx, buf.initialize_unfilled()))?;
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tokio::*;
+    use crate::{ReadBool, ReadF64, ReadU16, ReadU8};
+
+    #[::tokio::test]
+    async fn write_primitives() {
+        let mut bytes = Vec::new();
+        bytes.write_le_u16(37).await.unwrap();
+        bytes.write_be_f64(1.5).await.unwrap();
+        bytes.write_u8(1).await.unwrap();
+        bytes.write_bool(true).await.unwrap();
+        let mut reader = bytes.as_slice();
+        assert_eq!(37, reader.read_le_u16().unwrap());
+        assert_eq!(1.5, reader.read_be_f64().unwrap());
+        assert_eq!(1, reader.read_u8().unwrap());
+        assert!(reader.read_bool().unwrap());
+    }
+
+    #[::tokio::test]
+    async fn read_primitives() {
+        let mut reader = AsyncPrimitiveReader::new(&[0x3f, 0x80, 0, 0, 7][..]);
+        assert_eq!(1.0f32, reader.read_be().await.unwrap());
+        assert_eq!(7u8, reader.read_le().await.unwrap());
+        let err = reader.read_ne::<u8>().await.unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert!(reader.pending().is_empty());
+    }
+
+    #[::tokio::test]
+    async fn peek_primitives() {
+        let mut reader: &[u8] = &[1, 2, 3];
+        assert_eq!(0x0201u16, reader.peek_le().await.unwrap());
+        assert_eq!(
+            0x0102u16,
+            reader.peek_endian(crate::Endian::Big).await.unwrap()
+        );
+        assert_eq!(1u8, reader.peek_ne().await.unwrap());
+        assert_eq!(3, reader.len());
+        let err = reader.peek_be::<u32>().await.unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
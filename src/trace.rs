@@ -0,0 +1,347 @@
+use crate::{Endian, ReadFrom};
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Write as _};
+use std::io::{self, Read};
+use std::ops::Range;
+
+/// Bytes shown per line of the hexdump
+const LINE_LEN: usize = 16;
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+/// The trace being recorded on this thread
+#[derive(Default)]
+struct Recording {
+    bytes: Vec<u8>,
+    entries: Vec<TraceEntry>,
+    open: usize,
+}
+
+/// Reader copying what passes through it into the recording
+struct Recorder<'r, R: ?Sized>(&'r mut R);
+
+impl<R: Read + ?Sized> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.0.read(buf)?;
+        RECORDING.with(|recording| {
+            if let Some(recording) = recording.borrow_mut().as_mut() {
+                recording.bytes.extend_from_slice(&buf[..read]);
+            }
+        });
+        Ok(read)
+    }
+}
+
+/// Field decoded during a [`Trace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    path: String,
+    range: Range<u64>,
+    depth: usize,
+    value: Option<String>,
+}
+
+impl TraceEntry {
+    /// Path of the field, like the one of a [`FieldError`](crate::FieldError)
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Offsets of the bytes the field consumed, from the start of the trace
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    /// Number of fields the field is nested in
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The decoded value formatted with [`Debug`], if its type implements it
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// Record of the bytes a decode consumed and of the fields they were decoded into
+///
+/// Derived [`ReadFrom`] implementations record each of their fields. Implementations written by
+/// hand record theirs with [`TraceMark`]. [`Display`](fmt::Display) renders an offset-annotated
+/// hexdump, for finding where a parser and a file disagree.
+///
+/// ```
+/// use read_primitives::{Endian, ReadFrom, Trace, TraceMark};
+/// // a hand-written read of a u16 tag and a u8 length
+/// let file: &[u8] = &[0x12, 0x34, 3];
+/// let (read, trace) = Trace::read(&mut &file[..], Endian::Big, |reader, endian| {
+///     let mark = TraceMark::start();
+///     let tag = u16::read_from(reader, endian)?;
+///     mark.finish("tag", &tag);
+///     let mark = TraceMark::start();
+///     let len = u8::read_from(reader, endian)?;
+///     mark.finish("len", &len);
+///     Ok((tag, len))
+/// });
+/// assert_eq!((0x1234, 3), read.unwrap());
+/// assert_eq!(2..3, trace.entries()[1].range());
+/// assert_eq!(
+///     "00000000  12 34                                            tag = 4660\n\
+///      00000002  03                                               len = 3\n",
+///     trace.to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    bytes: Vec<u8>,
+    entries: Vec<TraceEntry>,
+}
+
+impl Trace {
+    /// Trace `read` reading from `reader`, returning its result and the trace
+    ///
+    /// Only what `read` reads through the reader it is given is recorded. A trace started
+    /// within `read` records separately and leaves this one untouched.
+    pub fn read<T, R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+        read: impl FnOnce(&mut dyn Read, Endian) -> io::Result<T>,
+    ) -> (io::Result<T>, Trace) {
+        let outer = RECORDING.with(|recording| recording.replace(Some(Recording::default())));
+        let result = read(&mut Recorder(reader), endian);
+        let recording = RECORDING
+            .with(|recording| recording.replace(outer))
+            .expect("the recording was installed above");
+        let mut entries = recording.entries;
+        // fields finish after the fields nested in them
+        entries.sort_by_key(|entry| (entry.range.start, entry.depth));
+        let trace = Trace {
+            bytes: recording.bytes,
+            entries,
+        };
+        (result, trace)
+    }
+
+    /// Trace reading a `T` with [`ReadFrom::read_from`]
+    pub fn read_from<T: ReadFrom, R: Read + ?Sized>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> (io::Result<T>, Trace) {
+        Trace::read(reader, endian, |reader, endian| {
+            T::read_from(reader, endian)
+        })
+    }
+
+    /// The bytes consumed, including those of a decode that failed
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The fields decoded, by offset and with fields before the fields nested in them
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+}
+
+/// Write one line per up to [`LINE_LEN`] bytes of `range`, annotating the first with `label`
+fn dump(f: &mut fmt::Formatter<'_>, bytes: &[u8], range: Range<u64>, label: &str) -> fmt::Result {
+    let start = range.start as usize;
+    let end = (range.end as usize).min(bytes.len());
+    let mut offset = start;
+    loop {
+        let line_end = (offset + LINE_LEN).min(end);
+        let mut hex = String::new();
+        for byte in &bytes[offset.min(end)..line_end] {
+            write!(hex, "{byte:02x} ").expect("writing to a String can't fail");
+        }
+        let label = if offset == start { label } else { "" };
+        writeln!(f, "{offset:08x}  {hex:<w$} {label}", w = LINE_LEN * 3)?;
+        offset = line_end;
+        if offset >= end {
+            return Ok(());
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // offset up to which bytes were shown or are covered by a field being shown
+        let mut shown = 0;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.range.start > shown {
+                dump(f, &self.bytes, shown..entry.range.start, "")?;
+            }
+            let indent = "  ".repeat(entry.depth);
+            let parent = self
+                .entries
+                .get(index + 1)
+                .is_some_and(|next| next.depth > entry.depth && next.range.start < entry.range.end);
+            if parent {
+                let len = entry.range.end - entry.range.start;
+                writeln!(
+                    f,
+                    "{:08x}  {:<w$} {indent}{} ({len} bytes)",
+                    entry.range.start,
+                    "",
+                    entry.path,
+                    w = LINE_LEN * 3
+                )?;
+                shown = shown.max(entry.range.start);
+                continue;
+            }
+            let label = match &entry.value {
+                Some(value) => format!("{indent}{} = {value}", entry.path),
+                None => format!("{indent}{}", entry.path),
+            };
+            dump(f, &self.bytes, entry.range.clone(), &label)?;
+            shown = shown.max(entry.range.end);
+        }
+        if (shown as usize) < self.bytes.len() {
+            dump(f, &self.bytes, shown..self.bytes.len() as u64, "")?;
+        }
+        Ok(())
+    }
+}
+
+/// Start of a field in a [`Trace`], finished once the field is decoded
+///
+/// Does nothing unless a trace is being recorded on this thread. Dropping the mark, as when the
+/// read fails, leaves the field out of the trace.
+#[derive(Debug)]
+#[must_use = "the field is recorded when the mark is finished"]
+pub struct TraceMark(Option<(u64, usize)>);
+
+impl TraceMark {
+    /// Mark the start of a field at the next byte read
+    pub fn start() -> TraceMark {
+        TraceMark(RECORDING.with(|recording| {
+            recording.borrow_mut().as_mut().map(|recording| {
+                recording.open += 1;
+                (recording.bytes.len() as u64, recording.open - 1)
+            })
+        }))
+    }
+
+    /// Record the field `path` decoded into `value`, ending at the last byte read
+    pub fn finish<T: Debug + ?Sized>(self, path: &str, value: &T) {
+        self.finish_with(path, || Some(format!("{value:?}")));
+    }
+
+    /// Record the field `path`, formatting its value with `value` only if a trace is recorded
+    #[doc(hidden)]
+    pub fn finish_with(mut self, path: &str, value: impl FnOnce() -> Option<String>) {
+        let Some((start, depth)) = self.0.take() else {
+            return;
+        };
+        let value = value();
+        RECORDING.with(|recording| {
+            if let Some(recording) = recording.borrow_mut().as_mut() {
+                recording.open -= 1;
+                let end = recording.bytes.len() as u64;
+                recording.entries.push(TraceEntry {
+                    path: path.to_owned(),
+                    range: start..end,
+                    depth,
+                    value,
+                });
+            }
+        });
+    }
+}
+
+impl Drop for TraceMark {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            RECORDING.with(|recording| {
+                if let Some(recording) = recording.borrow_mut().as_mut() {
+                    recording.open -= 1;
+                }
+            });
+        }
+    }
+}
+
+/// Value of a derived field, formatted with [`Debug`] if its type implements it
+///
+/// Derived code calls `(&TraceValue(&value)).describe()` with both traits in scope: method
+/// resolution picks [`DescribeDebug`] where it applies and falls back to [`DescribeOther`].
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub struct TraceValue<'a, T: ?Sized>(pub &'a T);
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub trait DescribeDebug {
+    fn describe(&self) -> Option<String>;
+}
+
+#[cfg(feature = "derive")]
+impl<T: Debug + ?Sized> DescribeDebug for TraceValue<'_, T> {
+    fn describe(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub trait DescribeOther {
+    fn describe(&self) -> Option<String>;
+}
+
+#[cfg(feature = "derive")]
+impl<T: ?Sized> DescribeOther for &TraceValue<'_, T> {
+    fn describe(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    fn pair(reader: &mut dyn io::Read, endian: Endian) -> io::Result<(u8, [u8; 20])> {
+        let mark = TraceMark::start();
+        let outer = TraceMark::start();
+        let first = u8::read_from(reader, endian)?;
+        outer.finish("first", &first);
+        let long = <[u8; 20]>::read_from(reader, endian)?;
+        mark.finish("pair", &(first, long));
+        Ok((first, long))
+    }
+
+    #[test]
+    fn nested_and_long() {
+        let mut data = vec![7];
+        data.extend(0..20);
+        data.push(0xff);
+        let (read, trace) = Trace::read(&mut data.as_slice(), Endian::Little, pair);
+        assert_eq!(7, read.unwrap().0);
+        let entries = trace.entries();
+        assert_eq!(["pair", "first"], [entries[0].path(), entries[1].path()]);
+        assert_eq!((0..1, 1), (entries[1].range(), entries[1].depth()));
+        assert_eq!(Some("7"), entries[1].value());
+        let text = trace.to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[0].ends_with(" pair (21 bytes)"));
+        assert!(lines[1].starts_with("00000000  07 "));
+        assert!(lines[1].ends_with("  first = 7"));
+        assert!(lines[2].starts_with("00000001  00 01 02"));
+        assert!(lines[3].starts_with("00000011  10 11 12 13 "));
+    }
+
+    #[test]
+    fn failed_read() {
+        let (read, trace) = Trace::read(&mut &[1u8][..], Endian::Big, pair);
+        assert_eq!(io::ErrorKind::UnexpectedEof, read.unwrap_err().kind());
+        assert_eq!(1, trace.entries().len());
+        assert_eq!(&[1], trace.bytes());
+        // nothing is recorded outside a trace
+        assert!(pair(&mut &[0u8; 21][..], Endian::Big).is_ok());
+        let (_, empty) = Trace::read_from::<u32, _>(&mut &[0u8; 4][..], Endian::Big);
+        assert!(empty.entries().is_empty());
+        assert_eq!("00000000  00 00 00 00", empty.to_string().trim_end());
+    }
+}
@@ -0,0 +1,278 @@
+use std::io::{self, Read, Write};
+
+fn overflow() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "varint overflows 64 bits")
+}
+
+fn read_byte<R: Read + ?Sized>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn zigzag_decode(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Trait to read variable-length integers
+pub trait ReadVarint: Read {
+    /// Read an unsigned LEB128 varint, as used by Protocol Buffers: 7 bits per byte, least
+    /// significant first
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value overflows 64 bits,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_varint_u64(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..70).step_by(7) {
+            let byte = read_byte(self)?;
+            if shift == 63 && byte > 0x01 {
+                return Err(overflow());
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        unreachable!("the tenth byte has no continuation bit")
+    }
+
+    /// Read a zigzag encoded signed varint, as Protocol Buffers' `sint64`
+    /// # Errors
+    /// errors exactly when [`read_varint_u64`](ReadVarint::read_varint_u64) errors
+    fn read_varint_i64(&mut self) -> io::Result<i64> {
+        self.read_varint_u64().map(zigzag_decode)
+    }
+
+    /// Read a signed LEB128 varint, as used by DWARF and WebAssembly: two's complement, sign
+    /// extended from the last byte
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value overflows 64 bits,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_sleb128_i64(&mut self) -> io::Result<i64> {
+        let mut value = 0i64;
+        for shift in (0..70).step_by(7) {
+            let byte = read_byte(self)?;
+            // the tenth byte holds only the sign bit, which must match the bits above it
+            if shift == 63 && byte != 0x00 && byte != 0x7f {
+                return Err(overflow());
+            }
+            value |= i64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                if shift < 57 && byte & 0x40 != 0 {
+                    value |= -1 << (shift + 7);
+                }
+                return Ok(value);
+            }
+        }
+        unreachable!("the tenth byte has no continuation bit")
+    }
+
+    /// Read a VLQ, as used by MIDI: 7 bits per byte, most significant first
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the value overflows 64 bits,
+    /// or when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_vlq_u64(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        loop {
+            let byte = read_byte(self)?;
+            if value >> 57 != 0 {
+                return Err(overflow());
+            }
+            value = value << 7 | u64::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Read a SQLite varint: 1 to 9 bytes, most significant first, the first 8 with 7 bits and a
+    /// continuation bit and the ninth with all 8 bits
+    ///
+    /// SQLite stores rowids and record values as two's complement, so cast to i64 for those.
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_sqlite_varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for _ in 0..8 {
+            let byte = read_byte(self)?;
+            value = value << 7 | u64::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Ok(value << 8 | u64::from(read_byte(self)?))
+    }
+}
+impl<R> ReadVarint for R where R: Read {}
+
+/// Trait to write variable-length integers, matching [`ReadVarint`]
+pub trait WriteVarint: Write {
+    /// Write an unsigned LEB128 varint in the fewest bytes
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_varint_u64(&mut self, mut value: u64) -> io::Result<()> {
+        let mut bytes = [0u8; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes[len] = byte;
+                return self.write_all(&bytes[..=len]);
+            }
+            bytes[len] = byte | 0x80;
+            len += 1;
+        }
+    }
+
+    /// Write a zigzag encoded signed varint
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_varint_i64(&mut self, value: i64) -> io::Result<()> {
+        self.write_varint_u64(zigzag_encode(value))
+    }
+
+    /// Write a signed LEB128 varint in the fewest bytes
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_sleb128_i64(&mut self, mut value: i64) -> io::Result<()> {
+        let mut bytes = [0u8; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+                bytes[len] = byte;
+                return self.write_all(&bytes[..=len]);
+            }
+            bytes[len] = byte | 0x80;
+            len += 1;
+        }
+    }
+
+    /// Write a VLQ in the fewest bytes
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_vlq_u64(&mut self, value: u64) -> io::Result<()> {
+        let mut bytes = [0u8; 10];
+        let mut start = bytes.len() - 1;
+        bytes[start] = (value & 0x7f) as u8;
+        let mut rest = value >> 7;
+        while rest != 0 {
+            start -= 1;
+            bytes[start] = (rest & 0x7f) as u8 | 0x80;
+            rest >>= 7;
+        }
+        self.write_all(&bytes[start..])
+    }
+
+    /// Write a SQLite varint in the fewest bytes
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_sqlite_varint(&mut self, value: u64) -> io::Result<()> {
+        if value >> 56 != 0 {
+            let mut bytes = [0u8; 9];
+            bytes[8] = value as u8;
+            let mut rest = value >> 8;
+            for byte in bytes[..8].iter_mut().rev() {
+                *byte = (rest & 0x7f) as u8 | 0x80;
+                rest >>= 7;
+            }
+            return self.write_all(&bytes);
+        }
+        self.write_vlq_u64(value)
+    }
+}
+impl<W> WriteVarint for W where W: Write {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn varint_u64() {
+        let mut bytes = Vec::new();
+        for value in [0, 1, 127, 128, 300, u64::MAX] {
+            bytes.write_varint_u64(value).unwrap();
+        }
+        assert_eq!([0, 1, 0x7f, 0x80, 0x01, 0xac, 0x02], bytes[..7]);
+        let mut reader = bytes.as_slice();
+        for value in [0, 1, 127, 128, 300, u64::MAX] {
+            assert_eq!(value, reader.read_varint_u64().unwrap());
+        }
+        assert!(reader.is_empty());
+        let mut overflow: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        let err = overflow.read_varint_u64().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn varint_i64() {
+        let mut bytes = Vec::new();
+        for value in [0, -1, 1, -2, i64::MIN, i64::MAX] {
+            bytes.write_varint_i64(value).unwrap();
+        }
+        assert_eq!([0, 1, 2, 3], bytes[..4]);
+        let mut reader = bytes.as_slice();
+        for value in [0, -1, 1, -2, i64::MIN, i64::MAX] {
+            assert_eq!(value, reader.read_varint_i64().unwrap());
+        }
+    }
+
+    #[test]
+    fn sleb128_i64() {
+        let mut bytes = Vec::new();
+        for value in [2, -2, 127, -128, i64::MIN, i64::MAX] {
+            bytes.write_sleb128_i64(value).unwrap();
+        }
+        assert_eq!([0x02, 0x7e, 0xff, 0x00, 0x80, 0x7f], bytes[..6]);
+        let mut reader = bytes.as_slice();
+        for value in [2, -2, 127, -128, i64::MIN, i64::MAX] {
+            assert_eq!(value, reader.read_sleb128_i64().unwrap());
+        }
+        let mut overflow: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        let err = overflow.read_sleb128_i64().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn vlq_u64() {
+        let mut bytes = Vec::new();
+        for value in [0, 0x7f, 0x80, 0x0fff_ffff, u64::MAX] {
+            bytes.write_vlq_u64(value).unwrap();
+        }
+        assert_eq!([0x00, 0x7f, 0x81, 0x00, 0xff, 0xff, 0xff, 0x7f], bytes[..8]);
+        let mut reader = bytes.as_slice();
+        for value in [0, 0x7f, 0x80, 0x0fff_ffff, u64::MAX] {
+            assert_eq!(value, reader.read_vlq_u64().unwrap());
+        }
+        let mut overflow: &[u8] = &[0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        let err = overflow.read_vlq_u64().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn sqlite_varint() {
+        let values = [0, 240, 241, 0x00ff_ffff_ffff_ffff, 1 << 56, u64::MAX];
+        let mut bytes = Vec::new();
+        for value in values {
+            bytes.write_sqlite_varint(value).unwrap();
+        }
+        assert_eq!([0x00, 0x81, 0x70, 0x81, 0x71], bytes[..5]);
+        assert_eq!(5 + 8 + 9 + 9, bytes.len());
+        let mut reader = bytes.as_slice();
+        for value in values {
+            assert_eq!(value, reader.read_sqlite_varint().unwrap());
+        }
+        // the ninth byte's high bit is a value bit, not a continuation
+        let mut minus_one: &[u8] = &[0xff; 9];
+        assert_eq!(-1, minus_one.read_sqlite_varint().unwrap() as i64);
+        let err = (&[0x81][..]).read_sqlite_varint().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+}
@@ -0,0 +1,125 @@
+//! Reading a browser [`ReadableStream`](web_sys::ReadableStream) as a
+//! [`futures_io::AsyncRead`], for the async reads of the [`futures`](crate::futures) module
+//!
+//! The stream is read chunk by chunk as the reads need its bytes, so a `fetch` body or a `File`'s
+//! stream is parsed without loading it into memory first, with the same code that parses a
+//! native async reader.
+//!
+//! ```no_run
+//! use read_primitives::futures::AsyncPrimitiveReader;
+//! use read_primitives::wasm::StreamReader;
+//!
+//! async fn magic(stream: web_sys::ReadableStream) -> std::io::Result<u32> {
+//!     let mut reader = AsyncPrimitiveReader::new(StreamReader::new(&stream)?);
+//!     reader.read_be::<u32>().await
+//! }
+//! ```
+
+use futures_io::{AsyncBufRead, AsyncRead};
+use js_sys::{Reflect, Uint8Array};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ReadableStream, ReadableStreamDefaultReader};
+
+fn js_error(err: JsValue) -> io::Error {
+    io::Error::other(format!("{err:?}"))
+}
+
+/// Async reader over the bytes of a [`ReadableStream`] of `Uint8Array` chunks
+///
+/// It holds the stream's lock until it is dropped.
+pub struct StreamReader {
+    reader: ReadableStreamDefaultReader,
+    /// The read of the next chunk, while one is in flight
+    pending: Option<JsFuture>,
+    chunk: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl std::fmt::Debug for StreamReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("buffered", &(self.chunk.len() - self.pos))
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StreamReader {
+    /// Lock `stream` and read from it
+    /// # Errors
+    /// errors if the stream is already locked
+    pub fn new(stream: &ReadableStream) -> io::Result<Self> {
+        Ok(StreamReader {
+            reader: ReadableStreamDefaultReader::new(stream).map_err(js_error)?,
+            pending: None,
+            chunk: Vec::new(),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    /// Make sure the current chunk has unread bytes, unless the stream ended
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.pos == self.chunk.len() && !self.done {
+            let read = self
+                .pending
+                .get_or_insert_with(|| JsFuture::from(self.reader.read()));
+            let result = ready!(Pin::new(read).poll(cx));
+            self.pending = None;
+            let result = result.map_err(js_error)?;
+            let done = Reflect::get(&result, &JsValue::from_str("done")).map_err(js_error)?;
+            if done.as_bool().unwrap_or(false) {
+                self.done = true;
+                break;
+            }
+            let value = Reflect::get(&result, &JsValue::from_str("value")).map_err(js_error)?;
+            let chunk = value.dyn_into::<Uint8Array>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream chunk is not a Uint8Array",
+                )
+            })?;
+            self.chunk = chunk.to_vec();
+            self.pos = 0;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for StreamReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl AsyncBufRead for StreamReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        ready!(this.poll_chunk(cx))?;
+        Poll::Ready(Ok(&this.chunk[this.pos..]))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.pos = (self.pos + amt).min(self.chunk.len());
+    }
+}
+
+impl Drop for StreamReader {
+    fn drop(&mut self) {
+        self.reader.release_lock();
+    }
+}
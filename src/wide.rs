@@ -0,0 +1,500 @@
+//! Reads of UTF-16 and UTF-32 text whose byte order is given by a byte order mark
+//!
+//! ```
+//! use read_primitives::wide::{BomPolicy, WideEncoding, WideReader};
+//! let bytes: &[u8] = &[0xfe, 0xff, 0, b'h', 0, b'i'];
+//! let mut reader = WideReader::from_bom(bytes, BomPolicy::Fallback(WideEncoding::Utf16Le)).unwrap();
+//! assert_eq!(WideEncoding::Utf16Be, reader.encoding());
+//! assert_eq!("hi", reader.read_to_string().unwrap());
+//! ```
+//!
+//! [`ReadWchar`] reads the `wchar_t` strings of C and C++ programs, whose width differs between
+//! Windows and other platforms.
+
+use crate::limits::check_bytes;
+use crate::string::read_exact_vec;
+use crate::Endian;
+use std::io::{self, Read};
+
+/// Encoding of a wide-string payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WideEncoding {
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// UTF-32, little endian
+    Utf32Le,
+    /// UTF-32, big endian
+    Utf32Be,
+}
+
+/// Width of a C `wchar_t` code unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WcharWidth {
+    /// 16 bit units of UTF-16, as on Windows
+    U16,
+    /// 32 bit units of UTF-32, as on Linux and macOS
+    U32,
+}
+
+impl WcharWidth {
+    /// Width of `wchar_t` on the target platform
+    pub const NATIVE: WcharWidth = if cfg!(windows) {
+        WcharWidth::U16
+    } else {
+        WcharWidth::U32
+    };
+
+    /// Size of a code unit in bytes
+    pub const fn unit_size(self) -> usize {
+        match self {
+            WcharWidth::U16 => 2,
+            WcharWidth::U32 => 4,
+        }
+    }
+
+    /// Encoding of units of this width in `endian` byte order
+    pub const fn encoding(self, endian: Endian) -> WideEncoding {
+        match (self, endian) {
+            (WcharWidth::U16, Endian::Little) => WideEncoding::Utf16Le,
+            (WcharWidth::U16, Endian::Big) => WideEncoding::Utf16Be,
+            (WcharWidth::U32, Endian::Little) => WideEncoding::Utf32Le,
+            (WcharWidth::U32, Endian::Big) => WideEncoding::Utf32Be,
+        }
+    }
+}
+
+/// How [`WideReader::from_bom`] treats the byte order mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BomPolicy {
+    /// The text must start with a BOM
+    Require,
+    /// Use the BOM if there is one, otherwise fall back to the given encoding
+    Fallback(WideEncoding),
+    /// The text is in the given encoding; a BOM is optional but must agree with it
+    Expect(WideEncoding),
+}
+
+/// Reader of UTF-16 or UTF-32 characters
+#[derive(Debug)]
+pub struct WideReader<R> {
+    reader: R,
+    encoding: WideEncoding,
+    /// Bytes read while looking for a BOM that turned out to be text
+    pending: [u8; 4],
+    start: usize,
+    end: usize,
+}
+
+impl<R: Read> WideReader<R> {
+    /// Read text in `encoding` without looking for a BOM
+    pub fn new(reader: R, encoding: WideEncoding) -> Self {
+        WideReader {
+            reader,
+            encoding,
+            pending: [0; 4],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Read an optional BOM and configure the reader for the rest of the text according to `policy`
+    ///
+    /// `FF FE 00 00` is taken as a UTF-16 little endian BOM followed by U+0000 if the policy names
+    /// [`WideEncoding::Utf16Le`], and as a UTF-32 little endian BOM otherwise.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the BOM is absent under [`BomPolicy::Require`]
+    /// or conflicts with [`BomPolicy::Expect`], or when reading the BOM errors
+    pub fn from_bom(mut reader: R, policy: BomPolicy) -> io::Result<Self> {
+        let mut head = [0u8; 4];
+        let mut len = 0;
+        while len < head.len() {
+            match reader.read(&mut head[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let preferred = match policy {
+            BomPolicy::Require => None,
+            BomPolicy::Fallback(encoding) | BomPolicy::Expect(encoding) => Some(encoding),
+        };
+        let bom = match &head[..len] {
+            [0, 0, 0xfe, 0xff] => Some((WideEncoding::Utf32Be, 4)),
+            [0xff, 0xfe, 0, 0] if preferred != Some(WideEncoding::Utf16Le) => {
+                Some((WideEncoding::Utf32Le, 4))
+            }
+            [0xfe, 0xff, ..] => Some((WideEncoding::Utf16Be, 2)),
+            [0xff, 0xfe, ..] => Some((WideEncoding::Utf16Le, 2)),
+            _ => None,
+        };
+        let (encoding, start) = match (bom, policy) {
+            (Some((found, _)), BomPolicy::Expect(expected)) if found != expected => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("byte order mark indicates {found:?}, expected {expected:?}"),
+                ));
+            }
+            (Some(bom), _) => bom,
+            (None, BomPolicy::Fallback(encoding) | BomPolicy::Expect(encoding)) => (encoding, 0),
+            (None, BomPolicy::Require) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing byte order mark",
+                ));
+            }
+        };
+        Ok(WideReader {
+            reader,
+            encoding,
+            pending: head,
+            start,
+            end: len,
+        })
+    }
+
+    /// Encoding the reader decodes
+    pub fn encoding(&self) -> WideEncoding {
+        self.encoding
+    }
+
+    /// Unwrap the underlying reader
+    ///
+    /// Text bytes read while looking for the BOM and not yet decoded are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read one code unit, or `None` at a clean end of the text
+    fn read_unit<const N: usize>(&mut self) -> io::Result<Option<[u8; N]>> {
+        let mut unit = [0u8; N];
+        let pending = N.min(self.end - self.start);
+        unit[..pending].copy_from_slice(&self.pending[self.start..self.start + pending]);
+        self.start += pending;
+        let mut filled = pending;
+        while filled < N {
+            match self.reader.read(&mut unit[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        match filled {
+            0 => Ok(None),
+            _ if filled == N => Ok(Some(unit)),
+            _ => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    fn read_utf16_unit(&mut self) -> io::Result<Option<u16>> {
+        let from_bytes = match self.encoding {
+            WideEncoding::Utf16Be => u16::from_be_bytes,
+            _ => u16::from_le_bytes,
+        };
+        Ok(self.read_unit::<2>()?.map(from_bytes))
+    }
+
+    /// Read one character, or `None` at the end of the text
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] on unpaired surrogates or values outside the
+    /// Unicode range, with [`io::ErrorKind::UnexpectedEof`] if the text ends inside a character,
+    /// or when the underlying reader errors
+    pub fn read_char(&mut self) -> io::Result<Option<char>> {
+        let invalid = |value| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid character {value:#x}"),
+            )
+        };
+        let value = match self.encoding {
+            WideEncoding::Utf16Le | WideEncoding::Utf16Be => {
+                let Some(first) = self.read_utf16_unit()? else {
+                    return Ok(None);
+                };
+                match first {
+                    0xd800..0xdc00 => {
+                        let second = self
+                            .read_utf16_unit()?
+                            .ok_or(io::ErrorKind::UnexpectedEof)?;
+                        if !(0xdc00..0xe000).contains(&second) {
+                            return Err(invalid(u32::from(first)));
+                        }
+                        0x10000 + ((u32::from(first) - 0xd800) << 10) + (u32::from(second) - 0xdc00)
+                    }
+                    _ => u32::from(first),
+                }
+            }
+            WideEncoding::Utf32Le => match self.read_unit()? {
+                Some(unit) => u32::from_le_bytes(unit),
+                None => return Ok(None),
+            },
+            WideEncoding::Utf32Be => match self.read_unit()? {
+                Some(unit) => u32::from_be_bytes(unit),
+                None => return Ok(None),
+            },
+        };
+        char::from_u32(value)
+            .map(Some)
+            .ok_or_else(|| invalid(value))
+    }
+
+    /// Read the rest of the text into a `String`
+    /// # Errors
+    /// errors like [`WideReader::read_char`]
+    pub fn read_to_string(&mut self) -> io::Result<String> {
+        let mut string = String::new();
+        while let Some(char) = self.read_char()? {
+            string.push(char);
+        }
+        Ok(string)
+    }
+}
+
+/// Read the bytes of `units` code units of `width`
+fn read_units<R: Read + ?Sized>(
+    reader: &mut R,
+    units: usize,
+    width: WcharWidth,
+) -> io::Result<Vec<u8>> {
+    let len = units.checked_mul(width.unit_size()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{units} units of {width:?} overflow usize"),
+        )
+    })?;
+    check_bytes(len)?;
+    read_exact_vec(reader, len)
+}
+
+/// Decode a whole field, where a character cut off by the end of the field is invalid
+fn decode_units(bytes: &[u8], encoding: WideEncoding) -> io::Result<String> {
+    WideReader::new(bytes, encoding)
+        .read_to_string()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unpaired surrogate at the end of the string",
+            ),
+            _ => err,
+        })
+}
+
+/// Trait to read `wchar_t` strings, whose code units are UTF-16 on Windows and UTF-32 elsewhere
+///
+/// Pass [`WcharWidth::NATIVE`] and [`Endian::NATIVE`] for data written on the same platform,
+/// or the width and byte order of the platform that wrote it. Surrogates must be paired in
+/// UTF-16, and UTF-32 units must be Unicode scalar values.
+///
+/// ```
+/// use read_primitives::wide::{ReadWchar, WcharWidth};
+/// use read_primitives::Endian;
+/// // L"hi" from Linux, then from Windows
+/// let mut file: &[u8] = b"h\0\0\0i\0\0\0\0\0\0\0h\0i\0\0\0";
+/// assert_eq!("hi", file.read_wchar_cstring(WcharWidth::U32, Endian::Little, 64).unwrap());
+/// assert_eq!("hi", file.read_wchar_cstring(WcharWidth::U16, Endian::Little, 64).unwrap());
+/// ```
+pub trait ReadWchar: Read {
+    /// Read a string of exactly `units` code units
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if the units exceed the [`Limits`](crate::Limits),
+    /// on unpaired surrogates or values that are not Unicode scalar values, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends early
+    fn read_wchar_string(
+        &mut self,
+        units: usize,
+        width: WcharWidth,
+        endian: Endian,
+    ) -> io::Result<String> {
+        let bytes = read_units(self, units, width)?;
+        decode_units(&bytes, width.encoding(endian))
+    }
+
+    /// Read a fixed-size field of `units` code units, ending at the first NUL or filling the field
+    ///
+    /// The units after the terminator are not decoded.
+    /// # Errors
+    /// errors like [`ReadWchar::read_wchar_string`]
+    fn read_wchar_field(
+        &mut self,
+        units: usize,
+        width: WcharWidth,
+        endian: Endian,
+    ) -> io::Result<String> {
+        let bytes = read_units(self, units, width)?;
+        let end = bytes
+            .chunks_exact(width.unit_size())
+            .position(|unit| unit.iter().all(|&byte| byte == 0))
+            .map_or(bytes.len(), |nul| nul * width.unit_size());
+        decode_units(&bytes[..end], width.encoding(endian))
+    }
+
+    /// Read a NUL-terminated string, consuming the terminator
+    ///
+    /// The units are read one at a time; wrap the reader in a
+    /// [`BufReader`](https://doc.rust-lang.org/std/io/struct.BufReader.html) for unbuffered sources.
+    /// # Errors
+    /// errors with [`io::ErrorKind::InvalidData`] if no terminator follows within `max_units`
+    /// code units, on unpaired surrogates or values that are not Unicode scalar values, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the reader ends before the terminator
+    fn read_wchar_cstring(
+        &mut self,
+        width: WcharWidth,
+        endian: Endian,
+        max_units: usize,
+    ) -> io::Result<String> {
+        let mut reader = WideReader::new(self, width.encoding(endian));
+        let mut string = String::new();
+        let mut units = 0;
+        loop {
+            match reader.read_char()?.ok_or(io::ErrorKind::UnexpectedEof)? {
+                '\0' => return Ok(string),
+                char => {
+                    units += match width {
+                        WcharWidth::U16 => char.len_utf16(),
+                        WcharWidth::U32 => 1,
+                    };
+                    if units > max_units {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("no terminator within {max_units} units"),
+                        ));
+                    }
+                    string.push(char);
+                }
+            }
+        }
+    }
+}
+impl<R> ReadWchar for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::wide::*;
+
+    fn from_bom(bytes: &[u8], policy: BomPolicy) -> io::Result<(WideEncoding, String)> {
+        let mut reader = WideReader::from_bom(bytes, policy)?;
+        Ok((reader.encoding(), reader.read_to_string()?))
+    }
+
+    #[test]
+    fn detect_bom() {
+        use WideEncoding::*;
+        let policy = BomPolicy::Require;
+        let utf32be = [0, 0, 0xfe, 0xff, 0, 1, 0xf6, 0x00];
+        assert_eq!(
+            (Utf32Be, "\u{1f600}".into()),
+            from_bom(&utf32be, policy).unwrap()
+        );
+        let utf32le = [0xff, 0xfe, 0, 0, b'a', 0, 0, 0];
+        assert_eq!((Utf32Le, "a".into()), from_bom(&utf32le, policy).unwrap());
+        let utf16le = [0xff, 0xfe, 0x3d, 0xd8, 0x00, 0xde];
+        assert_eq!(
+            (Utf16Le, "\u{1f600}".into()),
+            from_bom(&utf16le, policy).unwrap()
+        );
+        let policy = BomPolicy::Expect(Utf16Le);
+        assert_eq!(
+            (Utf16Le, "\0a".into()),
+            from_bom(&[0xff, 0xfe, 0, 0, b'a', 0], policy).unwrap()
+        );
+    }
+
+    #[test]
+    fn without_bom() {
+        let policy = BomPolicy::Fallback(WideEncoding::Utf16Be);
+        assert_eq!(
+            (WideEncoding::Utf16Be, "ok".into()),
+            from_bom(&[0, b'o', 0, b'k'], policy).unwrap()
+        );
+        assert_eq!(
+            (WideEncoding::Utf16Be, "".into()),
+            from_bom(&[], policy).unwrap()
+        );
+        let err = from_bom(&[0, b'o'], BomPolicy::Require).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn conflicting_bom() {
+        let policy = BomPolicy::Expect(WideEncoding::Utf16Le);
+        let err = from_bom(&[0xfe, 0xff, 0, b'a'], policy).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn invalid_text() {
+        let mut reader = WideReader::new(&[0x00, 0xdc][..], WideEncoding::Utf16Le);
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            reader.read_char().unwrap_err().kind()
+        );
+        let mut reader = WideReader::new(&[0x3d, 0xd8][..], WideEncoding::Utf16Le);
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            reader.read_char().unwrap_err().kind()
+        );
+        let mut reader = WideReader::new(&[0, 0x11, 0, 0][..], WideEncoding::Utf32Be);
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            reader.read_char().unwrap_err().kind()
+        );
+        let mut reader = WideReader::new(&[b'a', 0, 0][..], WideEncoding::Utf32Le);
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            reader.read_char().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn wchar() {
+        use crate::Endian::{Big, Little};
+        let mut reader: &[u8] = &[0xd8, 0x3d, 0xde, 0x00, 0, b'a', 0, 0, 0xff, 0xff];
+        let string = reader.read_wchar_field(5, WcharWidth::U16, Big).unwrap();
+        assert_eq!("\u{1f600}a", string);
+        assert!(reader.is_empty());
+        let mut reader: &[u8] = &[0, 0xf6, 0x01, 0, b'a', 0, 0, 0];
+        let string = reader
+            .read_wchar_string(2, WcharWidth::U32, Little)
+            .unwrap();
+        assert_eq!("\u{1f600}a", string);
+        let err = (&[0x3d, 0xd8][..])
+            .read_wchar_string(1, WcharWidth::U16, Little)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = (&[0xd8, 0xd8, 0, 0][..])
+            .read_wchar_field(1, WcharWidth::U32, Little)
+            .unwrap_err();
+        assert_eq!("invalid character 0xd8d8", err.to_string());
+        let limits = crate::Limits {
+            max_bytes_per_field: 1,
+            ..crate::Limits::DEFAULT
+        };
+        let err = limits
+            .apply(|| (&[b'a', 0][..]).read_wchar_string(1, WcharWidth::U16, Little))
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn wchar_cstring() {
+        use crate::Endian::Little;
+        let text = [0x3d, 0xd8, 0x00, 0xde, b'a', 0, 0, 0];
+        let string = (&text[..]).read_wchar_cstring(WcharWidth::U16, Little, 3);
+        assert_eq!("\u{1f600}a", string.unwrap());
+        let err = (&text[..])
+            .read_wchar_cstring(WcharWidth::U16, Little, 2)
+            .unwrap_err();
+        assert_eq!("no terminator within 2 units", err.to_string());
+        let err = (&text[..6])
+            .read_wchar_cstring(WcharWidth::U16, Little, 8)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            WideEncoding::Utf32Be,
+            WcharWidth::U32.encoding(crate::Endian::Big)
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{Endian, MixedEndian, Primitive, WordOrder};
+use std::io::{self, Read};
+
+/// Read two u64 words in `endian` byte order and combine them in `order`
+fn read_words<R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    order: WordOrder,
+) -> io::Result<u128> {
+    let mut bytes = [[0u8; 8]; 2];
+    reader.read_exact(bytes.as_flattened_mut())?;
+    let [first, second] = bytes.map(|word| u64::from_endian_bytes(word, endian));
+    let (hi, lo) = match order {
+        WordOrder::HiLo => (first, second),
+        WordOrder::LoHi => (second, first),
+    };
+    Ok((u128::from(hi) << 64) | u128::from(lo))
+}
+
+/// Trait to read 128-bit integers stored as two 64-bit words, as some databases and protocols do
+///
+/// ```
+/// use read_primitives::{Endian, ReadWords, WordOrder};
+/// let mut bytes: &[u8] = &[2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+/// let value = bytes.read_u128_words(Endian::Little, WordOrder::LoHi).unwrap();
+/// assert_eq!((1 << 64) | 2, value);
+/// ```
+pub trait ReadWords: Read {
+    /// Read a u128 as two u64 words in `endian` byte order, the words in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_u128_words(&mut self, endian: Endian, order: WordOrder) -> io::Result<u128> {
+        read_words(self, endian, order)
+    }
+
+    /// Read an i128 as two u64 words in `endian` byte order, the words in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_i128_words(&mut self, endian: Endian, order: WordOrder) -> io::Result<i128> {
+        read_words(self, endian, order).map(|value| value as i128)
+    }
+}
+impl<R> ReadWords for R where R: Read {}
+
+/// Read two 16-bit registers in `endian` byte order and combine them in `order`
+fn read_registers<R: Read + ?Sized>(
+    reader: &mut R,
+    endian: Endian,
+    order: WordOrder,
+) -> io::Result<u32> {
+    let mut bytes = [[0u8; 2]; 2];
+    reader.read_exact(bytes.as_flattened_mut())?;
+    let [first, second] = bytes.map(|register| u16::from_endian_bytes(register, endian));
+    let (hi, lo) = match order {
+        WordOrder::HiLo => (first, second),
+        WordOrder::LoHi => (second, first),
+    };
+    Ok((u32::from(hi) << 16) | u32::from(lo))
+}
+
+/// Trait to read 32-bit values stored in two 16-bit Modbus registers
+///
+/// Devices differ in the order of the registers and of the bytes within each. With the bytes of
+/// the value named `ABCD` from most significant, the four layouts are:
+///
+/// | Layout | `endian`           | `order`             |
+/// |--------|--------------------|---------------------|
+/// | AB CD  | [`Endian::Big`]    | [`WordOrder::HiLo`] |
+/// | CD AB  | [`Endian::Big`]    | [`WordOrder::LoHi`] |
+/// | BA DC  | [`Endian::Little`] | [`WordOrder::HiLo`] |
+/// | DC BA  | [`Endian::Little`] | [`WordOrder::LoHi`] |
+///
+/// ```
+/// use read_primitives::{Endian, ReadModbus, WordOrder};
+/// // 1.0f32 from a device swapping the registers
+/// let mut registers: &[u8] = &[0x00, 0x00, 0x3f, 0x80];
+/// assert_eq!(1.0, registers.read_modbus_f32(Endian::Big, WordOrder::LoHi).unwrap());
+/// ```
+pub trait ReadModbus: Read {
+    /// Read a u32 from two registers in `endian` byte order, the registers in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_modbus_u32(&mut self, endian: Endian, order: WordOrder) -> io::Result<u32> {
+        read_registers(self, endian, order)
+    }
+
+    /// Read an i32 from two registers in `endian` byte order, the registers in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_modbus_i32(&mut self, endian: Endian, order: WordOrder) -> io::Result<i32> {
+        read_registers(self, endian, order).map(|value| value as i32)
+    }
+
+    /// Read an f32 from two registers in `endian` byte order, the registers in `order`
+    /// # Errors
+    /// errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors
+    fn read_modbus_f32(&mut self, endian: Endian, order: WordOrder) -> io::Result<f32> {
+        read_registers(self, endian, order).map(f32::from_bits)
+    }
+}
+impl<R> ReadModbus for R where R: Read {}
+
+macro_rules! impl_mixed_methods {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste! {
+                #[doc = "Read " $type " stored in `layout`"]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Read::read_exact`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact) errors"]
+                fn [<read_mixed_ $type>](&mut self, layout: MixedEndian) -> io::Result<$type> {
+                    let mut bytes = [0u8; size_of::<$type>()];
+                    self.read_exact(&mut bytes)?;
+                    Ok($type::from_be_bytes(layout.to_be_bytes(bytes)))
+                }
+            }
+        )+
+    };
+}
+
+/// Trait to read 32 and 64-bit values in a [`MixedEndian`] layout chosen at runtime
+pub trait ReadMixedEndian: Read {
+    impl_mixed_methods!(u32, i32, f32, u64, i64, f64);
+}
+impl<R> ReadMixedEndian for R where R: Read {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn word_orders() {
+        let value: u128 = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        let bytes = value.to_be_bytes();
+        let hi_lo = bytes
+            .as_slice()
+            .read_u128_words(Endian::Big, WordOrder::HiLo);
+        assert_eq!(value, hi_lo.unwrap());
+        let lo_hi = bytes
+            .as_slice()
+            .read_u128_words(Endian::Big, WordOrder::LoHi);
+        assert_eq!(0x090a_0b0c_0d0e_0f10_0102_0304_0506_0708, lo_hi.unwrap());
+        let bytes = (-2i128).to_le_bytes();
+        let signed = bytes
+            .as_slice()
+            .read_i128_words(Endian::Little, WordOrder::LoHi);
+        assert_eq!(-2, signed.unwrap());
+        assert!(bytes[..12]
+            .as_ref()
+            .read_u128_words(Endian::Little, WordOrder::LoHi)
+            .is_err());
+    }
+
+    #[test]
+    fn mixed_endian() {
+        let value: u64 = 0x0102_0304_0506_0708;
+        let layouts = [
+            (MixedEndian::ABCD, [1, 2, 3, 4, 5, 6, 7, 8]),
+            (MixedEndian::DCBA, [8, 7, 6, 5, 4, 3, 2, 1]),
+            (MixedEndian::CDAB, [7, 8, 5, 6, 3, 4, 1, 2]),
+            (MixedEndian::BADC, [2, 1, 4, 3, 6, 5, 8, 7]),
+            (
+                MixedEndian::new(4, Endian::Big, WordOrder::LoHi),
+                [5, 6, 7, 8, 1, 2, 3, 4],
+            ),
+            (
+                MixedEndian::new(8, Endian::Little, WordOrder::HiLo),
+                [8, 7, 6, 5, 4, 3, 2, 1],
+            ),
+        ];
+        for (layout, bytes) in layouts {
+            assert_eq!(value, bytes.as_slice().read_mixed_u64(layout).unwrap());
+        }
+        let bytes = [0x80, 0x3f, 0, 0];
+        assert_eq!(
+            1.0,
+            bytes.as_slice().read_mixed_f32(MixedEndian::BADC).unwrap()
+        );
+        let bytes = [0, 0, 0xf0, 0x3f, 0, 0, 0, 0];
+        let layout = MixedEndian::new(4, Endian::Little, WordOrder::HiLo);
+        assert_eq!(1.0, bytes.as_slice().read_mixed_f64(layout).unwrap());
+        assert!(bytes[..3].as_ref().read_mixed_i32(layout).is_err());
+    }
+
+    #[test]
+    #[should_panic = "word size is not 1, 2, 4 or 8"]
+    fn mixed_endian_word_size() {
+        MixedEndian::new(3, Endian::Big, WordOrder::HiLo);
+    }
+
+    #[test]
+    fn modbus() {
+        let layouts = [
+            ([0xaa, 0xbb, 0xcc, 0xdd], Endian::Big, WordOrder::HiLo),
+            ([0xcc, 0xdd, 0xaa, 0xbb], Endian::Big, WordOrder::LoHi),
+            ([0xbb, 0xaa, 0xdd, 0xcc], Endian::Little, WordOrder::HiLo),
+            ([0xdd, 0xcc, 0xbb, 0xaa], Endian::Little, WordOrder::LoHi),
+        ];
+        for (bytes, endian, order) in layouts {
+            let value = bytes.as_slice().read_modbus_u32(endian, order);
+            assert_eq!(0xaabb_ccdd, value.unwrap());
+        }
+        let bytes = [0xc0, 0x49, 0x0f, 0xdb];
+        let pi = bytes
+            .as_slice()
+            .read_modbus_f32(Endian::Big, WordOrder::HiLo);
+        assert_eq!(-std::f32::consts::PI, pi.unwrap());
+        let minus_two = [0xfe, 0xff, 0xff, 0xff];
+        let signed = minus_two
+            .as_slice()
+            .read_modbus_i32(Endian::Little, WordOrder::LoHi);
+        assert_eq!(-2, signed.unwrap());
+        assert!([0u8; 3]
+            .as_slice()
+            .read_modbus_u32(Endian::Big, WordOrder::HiLo)
+            .is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::outline;
+use std::io::{self, Write};
+
+macro_rules! impl_write_methods {
+    ($type:ty, $order:ident, $order_doc:literal) => {
+        ::paste::paste! {
+            #[doc = "Write " $type " in " $order_doc]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors"]
+            fn [<write_ $order _ $type>](&mut self, value: $type) -> io::Result<()> {
+                self.write_all(&value.[<to_ $order _bytes>]())
+            }
+            #[doc = "Write a slice of " $type " in " $order_doc]
+            #[doc = "The values are encoded into a buffer of 4096 bytes at a time, so large slices are written in few calls."]
+            #[doc = "# Errors"]
+            #[doc = "errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors"]
+            fn [<write_ $order _ $type _slice>](&mut self, values: &[$type]) -> io::Result<()> {
+                let mut writer = self;
+                outline::write_values(&mut writer, values, $type::[<to_ $order _bytes>])
+            }
+        }
+    };
+}
+
+macro_rules! impl_write_traits {
+    ($($type:ty),+) => {
+        $(
+            ::paste::paste!{
+                #[doc = "Trait to write " $type "."]
+                #[doc = "# Errors"]
+                #[doc = "errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors"]
+                pub trait [<Write $type:camel>]: Write {
+                    impl_write_methods!($type, ne, "native byte order");
+                    impl_write_methods!($type, le, "little endian byte order");
+                    impl_write_methods!($type, be, "big endian byte order");
+                }
+                impl<T: Write> [<Write $type:camel>] for T {}
+            }
+        )+
+    };
+}
+
+impl_write_traits!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// Trait to write u8
+pub trait WriteU8: Write {
+    /// Write a u8
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.write_all(&[value])
+    }
+}
+impl<W> WriteU8 for W where W: Write {}
+
+/// Trait to write char
+pub trait WriteChar: Write {
+    /// Write a char
+    ///  The char is written as a u32 in native byte order, matching [`ReadChar`](crate::ReadChar)
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_char(&mut self, value: char) -> io::Result<()> {
+        self.write_all(&u32::from(value).to_ne_bytes())
+    }
+}
+impl<W> WriteChar for W where W: Write {}
+
+/// Trait to write bool
+pub trait WriteBool: Write {
+    /// Write a bool as a single byte
+    /// # Errors
+    /// errors exactly when [`Write::write_all`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all) errors
+    fn write_bool(&mut self, value: bool) -> io::Result<()> {
+        self.write_all(&[u8::from(value)])
+    }
+}
+impl<W> WriteBool for W where W: Write {}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    macro_rules! impl_tests {
+        ($($type:ty),+) => {
+            $(
+                ::paste::paste! {
+                    #[test]
+                    fn [<write_ $type>]() {
+                        let mut bytes = Vec::new();
+                        bytes.[<write_ne_ $type>]([<37 $type>]).unwrap();
+                        bytes.[<write_le_ $type>]([<37 $type>]).unwrap();
+                        bytes.[<write_be_ $type>]([<37 $type>]).unwrap();
+                        let mut reader = bytes.as_slice();
+                        assert_eq!([<37 $type>], reader.[<read_ne_ $type>]().unwrap());
+                        assert_eq!([<37 $type>], reader.[<read_le_ $type>]().unwrap());
+                        assert_eq!([<37 $type>], reader.[<read_be_ $type>]().unwrap());
+                    }
+                }
+            )+
+        };
+    }
+    impl_tests!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+    macro_rules! impl_slice_tests {
+        ($($type:ty),+) => {
+            $(
+                ::paste::paste! {
+                    #[test]
+                    fn [<write_ $type _slice>]() {
+                        // longer than one chunk, and not a multiple of it
+                        let values: Vec<$type> = (0..3000u16).map(|value| value as $type).collect();
+                        let mut bytes = Vec::new();
+                        bytes.[<write_le_ $type _slice>](&values).unwrap();
+                        bytes.[<write_be_ $type _slice>](&values).unwrap();
+                        bytes.[<write_ne_ $type _slice>](&[]).unwrap();
+                        let mut reader = bytes.as_slice();
+                        assert_eq!(values, reader.[<read_le_ $type _vec>](values.len()).unwrap());
+                        assert_eq!(values, reader.[<read_be_ $type _vec>](values.len()).unwrap());
+                        assert!(reader.is_empty());
+                    }
+                }
+            )+
+        };
+    }
+    impl_slice_tests!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+    #[test]
+    fn write_u8_char_bool() {
+        let mut bytes = Vec::new();
+        bytes.write_u8(37).unwrap();
+        bytes.write_char('ß').unwrap();
+        bytes.write_bool(true).unwrap();
+        let mut reader = bytes.as_slice();
+        assert_eq!(37, reader.read_u8().unwrap());
+        assert_eq!(Some('ß'), reader.read_char().unwrap());
+        assert!(reader.read_bool().unwrap());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::Endian;
+use std::io::{self, Write};
+
+/// A type that can be written to a byte stream, the counterpart of [`ReadFrom`](crate::ReadFrom)
+///
+/// Implemented for the integer and float types, bool, arrays, slices and tuples. With the `derive`
+/// feature, `#[derive(WriteTo)]` implements it for structs and enums from the same `#[read(...)]`
+/// attributes as `#[derive(ReadFrom)]`, so that both stay in sync.
+///
+/// # Examples
+///
+/// ```
+/// use read_primitives::{Endian, WriteTo};
+/// let mut bytes = Vec::new();
+/// [1u16, 2].write_to(&mut bytes, Endian::Big).unwrap();
+/// assert_eq!([0, 1, 0, 2], bytes.as_slice());
+/// ```
+pub trait WriteTo {
+    /// Write the value, using `endian` for the parts that do not specify their own byte order
+    /// # Errors
+    /// errors when writing to `writer` errors, or if the value cannot be encoded
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian) -> io::Result<()>;
+}
+
+macro_rules! impl_write_to {
+    ($($type:ty),+) => {
+        $(
+            impl WriteTo for $type {
+                fn write_to<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian) -> io::Result<()> {
+                    writer.write_all(&match endian {
+                        Endian::Little => self.to_le_bytes(),
+                        Endian::Big => self.to_be_bytes(),
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_write_to!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+impl WriteTo for bool {
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W, _: Endian) -> io::Result<()> {
+        writer.write_all(&[u8::from(*self)])
+    }
+}
+
+impl<T: WriteTo> WriteTo for [T] {
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian) -> io::Result<()> {
+        self.iter()
+            .try_for_each(|value| value.write_to(writer, endian))
+    }
+}
+
+impl<T: WriteTo, const N: usize> WriteTo for [T; N] {
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian) -> io::Result<()> {
+        self.as_slice().write_to(writer, endian)
+    }
+}
+
+macro_rules! impl_write_to_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl<$($type: WriteTo),+> WriteTo for ($($type,)+) {
+            fn write_to<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian) -> io::Result<()> {
+                $(self.$index.write_to(writer, endian)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_write_to_tuple!(A 0);
+impl_write_to_tuple!(A 0, B 1);
+impl_write_to_tuple!(A 0, B 1, C 2);
+impl_write_to_tuple!(A 0, B 1, C 2, D 3);
+impl_write_to_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_write_to_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_write_to_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_write_to_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn write_to() {
+        let mut bytes = Vec::new();
+        1u16.write_to(&mut bytes, Endian::Big).unwrap();
+        (-1i16).write_to(&mut bytes, Endian::Little).unwrap();
+        true.write_to(&mut bytes, Endian::Big).unwrap();
+        [[1.0f32]].write_to(&mut bytes, Endian::Big).unwrap();
+        assert_eq!([0, 1, 0xff, 0xff, 1, 0x3f, 0x80, 0, 0], bytes.as_slice());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = Vec::new();
+        let values = [u64::MAX - 1, 7];
+        values.write_to(&mut bytes, Endian::Little).unwrap();
+        let read = <[u64; 2]>::read_from(&mut bytes.as_slice(), Endian::Little).unwrap();
+        assert_eq!(values, read);
+
+        let mut bytes = Vec::new();
+        let tuple = (1u8, -2i32, [3u16; 2]);
+        tuple.write_to(&mut bytes, Endian::Big).unwrap();
+        assert_eq!([1, 0xff, 0xff, 0xff, 0xfe, 0, 3, 0, 3], bytes.as_slice());
+        let read = <(u8, i32, [u16; 2])>::read_from(&mut bytes.as_slice(), Endian::Big).unwrap();
+        assert_eq!(tuple, read);
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "codegen")]
+
+use read_primitives::codegen::generate;
+use read_primitives::layout::{CType, ReadCLayout, Target};
+use read_primitives::{Endian, ReadFrom, WriteTo};
+
+mod wire {
+    include!("data/wire.rs");
+}
+
+#[test]
+fn generated_code_is_current() {
+    let code = generate(include_str!("data/wire.h"), &Target::X86_64).unwrap();
+    assert_eq!(include_str!("data/wire.rs"), code);
+}
+
+#[test]
+fn round_trip() {
+    let header = wire::PacketHeader {
+        magic: 0xcafe,
+        flags: 1,
+        timestamp: 2.5,
+        label: 0x7fff_0000_1000,
+        samples: [1, 2, 3, 4].map(|channel| wire::Sample {
+            channel,
+            value: -i32::from(channel),
+        }),
+        calib: [[1, 2, 3], [4, 5, 6]],
+    };
+    let mut bytes = Vec::new();
+    header.write_to(&mut bytes, Endian::Big).unwrap();
+    let sample = CType::Struct(vec![CType::U8, CType::I32]);
+    let layout = CType::Struct(vec![
+        CType::U16,
+        CType::U8,
+        CType::F64,
+        CType::Pointer,
+        CType::Array(Box::new(sample), 4),
+        CType::Array(Box::new(CType::Array(Box::new(CType::I16), 3)), 2),
+    ]);
+    assert_eq!(layout.size(&Target::X86_64), bytes.len());
+    let values = bytes
+        .as_slice()
+        .read_c_layout(&layout, &Target::X86_64)
+        .unwrap();
+    assert_eq!(read_primitives::format::Value::Float(2.5), values[2]);
+
+    let mut reader = bytes.as_slice();
+    let read = wire::PacketHeader::read_from(&mut reader, Endian::Big).unwrap();
+    assert_eq!(header, read);
+    assert!(reader.is_empty());
+}
@@ -0,0 +1,18 @@
+/* Records of a telemetry link, as sent by the firmware */
+#include <stdint.h>
+
+#define MAX_SAMPLES 4
+
+struct sample {
+    uint8_t channel;
+    int32_t value;
+};
+
+typedef struct packet_header {
+    uint16_t magic;
+    unsigned char flags; // bit 0: compressed
+    double timestamp;
+    const char *label;
+    struct sample samples[4];
+    short calib[2][3];
+} packet_header_t;
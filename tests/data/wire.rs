@@ -0,0 +1,145 @@
+// Generated by read_primitives::codegen, do not edit
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(non_snake_case)]
+pub struct Sample {
+    pub channel: u8,
+    pub value: i32,
+}
+
+impl ::read_primitives::ReadFrom for Sample {
+    fn read_from<R: ::std::io::Read + ?Sized>(
+        reader: &mut R,
+        _: ::read_primitives::Endian,
+    ) -> ::std::io::Result<Self> {
+        let channel = <u8 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Read::read_exact(reader, &mut [0u8; 3])?;
+        let value = <i32 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        Ok(Sample {
+            channel,
+            value,
+        })
+    }
+}
+
+impl ::read_primitives::WriteTo for Sample {
+    fn write_to<W: ::std::io::Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        _: ::read_primitives::Endian,
+    ) -> ::std::io::Result<()> {
+        ::read_primitives::WriteTo::write_to(
+            &self.channel,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Write::write_all(writer, &[0u8; 3])?;
+        ::read_primitives::WriteTo::write_to(
+            &self.value,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(non_snake_case)]
+pub struct PacketHeader {
+    pub magic: u16,
+    pub flags: u8,
+    pub timestamp: f64,
+    pub label: u64,
+    pub samples: [Sample; 4],
+    pub calib: [[i16; 3]; 2],
+}
+
+impl ::read_primitives::ReadFrom for PacketHeader {
+    fn read_from<R: ::std::io::Read + ?Sized>(
+        reader: &mut R,
+        _: ::read_primitives::Endian,
+    ) -> ::std::io::Result<Self> {
+        let magic = <u16 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        let flags = <u8 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Read::read_exact(reader, &mut [0u8; 5])?;
+        let timestamp = <f64 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        let label = <u64 as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        let samples = <[Sample; 4] as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        let calib = <[[i16; 3]; 2] as ::read_primitives::ReadFrom>::read_from(
+            reader,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Read::read_exact(reader, &mut [0u8; 4])?;
+        Ok(PacketHeader {
+            magic,
+            flags,
+            timestamp,
+            label,
+            samples,
+            calib,
+        })
+    }
+}
+
+impl ::read_primitives::WriteTo for PacketHeader {
+    fn write_to<W: ::std::io::Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        _: ::read_primitives::Endian,
+    ) -> ::std::io::Result<()> {
+        ::read_primitives::WriteTo::write_to(
+            &self.magic,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::read_primitives::WriteTo::write_to(
+            &self.flags,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Write::write_all(writer, &[0u8; 5])?;
+        ::read_primitives::WriteTo::write_to(
+            &self.timestamp,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::read_primitives::WriteTo::write_to(
+            &self.label,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::read_primitives::WriteTo::write_to(
+            &self.samples,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::read_primitives::WriteTo::write_to(
+            &self.calib,
+            writer,
+            ::read_primitives::Endian::Little,
+        )?;
+        ::std::io::Write::write_all(writer, &[0u8; 4])?;
+        Ok(())
+    }
+}